@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+  // The reader must return an error on malformed input instead of panicking or hanging.
+  let _ = embroidery_studio::fuzzing::parse_oxs(data);
+});
@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+
+use crate::core::fonts::FontInfo;
+use crate::error::{CommandError, CommandResult};
+use crate::state::FontsState;
+use crate::utils::path::app_fonts_dir;
+
+/// Returns the fonts that can be used to draw the symbols.
+#[tauri::command]
+pub fn get_symbol_fonts(fonts: tauri::State<FontsState>) -> CommandResult<Vec<FontInfo>> {
+  let fonts = fonts.read().unwrap();
+  Ok(fonts.fonts().iter().map(|font| font.info()).collect())
+}
+
+/// Returns the code points of the glyphs of the font, so they can be shown in the symbol picker.
+#[tauri::command]
+pub fn get_font_codepoints(font_name: String, fonts: tauri::State<FontsState>) -> CommandResult<Vec<u16>> {
+  let fonts = fonts.read().unwrap();
+  let font = fonts
+    .get(&font_name)
+    .ok_or_else(|| CommandError::font_not_found(&font_name))?;
+  Ok(font.codepoints())
+}
+
+/// Copies the font file to the fonts directory and loads it.
+#[tauri::command]
+pub fn install_font<R: tauri::Runtime>(
+  file_path: PathBuf,
+  app_handle: tauri::AppHandle<R>,
+  fonts: tauri::State<FontsState>,
+) -> CommandResult<FontInfo> {
+  log::trace!("Installing font {file_path:?}");
+  let fonts_dir = app_fonts_dir(&app_handle)?;
+  let file_name = file_path
+    .file_name()
+    .ok_or_else(|| anyhow::anyhow!("The font file path has no file name"))?;
+  let target_path = fonts_dir.join(file_name);
+
+  // The font is loaded before copying, so the invalid files don't get into the fonts directory.
+  let mut fonts = fonts.write().unwrap();
+  fonts
+    .load_font(&file_path)
+    .map_err(|error| CommandError::from(error).with_file_path(&file_path))?;
+  std::fs::create_dir_all(&fonts_dir)
+    .and_then(|_| std::fs::copy(&file_path, &target_path))
+    .map_err(|error| CommandError::from(error).with_file_path(&target_path))?;
+  let font = fonts.load_font(&target_path)?;
+
+  log::trace!("Font {} installed", font.name());
+  Ok(font.info())
+}
@@ -1,6 +1,6 @@
 use tauri::WebviewWindow;
 
-use crate::error::CommandResult;
+use crate::error::{CommandError, CommandResult};
 use crate::state::{HistoryState, PatternKey, PatternsState};
 
 #[tauri::command]
@@ -12,8 +12,11 @@ pub fn undo<R: tauri::Runtime>(
 ) -> CommandResult<()> {
   let mut history = history.write().unwrap();
   let mut patterns = patterns.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
   if let Some(action) = history.get_mut(&pattern_key).undo() {
-    action.revoke(&window, patterns.get_mut(&pattern_key).unwrap())?;
+    action.revoke(&window, patproj)?;
   }
   Ok(())
 }
@@ -27,8 +30,11 @@ pub fn redo<R: tauri::Runtime>(
 ) -> CommandResult<()> {
   let mut history = history.write().unwrap();
   let mut patterns = patterns.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
   if let Some(action) = history.get_mut(&pattern_key).redo() {
-    action.perform(&window, patterns.get_mut(&pattern_key).unwrap())?;
+    action.perform(&window, patproj)?;
   }
   Ok(())
 }
@@ -0,0 +1,145 @@
+use crate::core::actions::{
+  Action, AddLayerAction, MergeLayerDownAction, MoveLayerAction, RemoveLayerAction, SetActiveLayerAction,
+  UpdateLayerAction,
+};
+use crate::core::pattern::{LayerInfo, LayerProperties};
+use crate::error::{CommandError, CommandResult};
+use crate::state::{HistoryState, PatternKey, PatternsState};
+
+/// Returns the layers of the pattern, from the bottom to the top one.
+#[tauri::command]
+pub fn get_layers(pattern_key: PatternKey, patterns: tauri::State<PatternsState>) -> CommandResult<Vec<LayerInfo>> {
+  let patterns = patterns.read().unwrap();
+  let patproj = patterns
+    .get(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  Ok(patproj.pattern.layers.info())
+}
+
+/// Adds an empty layer above the active one.
+/// The layer gets the next free name like `Layer 2` if the name is not specified.
+#[tauri::command]
+pub fn add_layer<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  name: Option<String>,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut history = history.write().unwrap();
+  let mut patterns = patterns.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  let layers = &patproj.pattern.layers;
+  let name = name.unwrap_or_else(|| layers.next_name());
+  let action = AddLayerAction::new(layers.active() + 1, LayerProperties::new(name));
+  action.perform(&window, patproj)?;
+  history.get_mut(&pattern_key).push(Box::new(action));
+  Ok(())
+}
+
+/// Removes the layer with all its stitches.
+#[tauri::command]
+pub fn remove_layer<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  index: usize,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut history = history.write().unwrap();
+  let mut patterns = patterns.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  let action = RemoveLayerAction::new(index);
+  action.perform(&window, patproj)?;
+  history.get_mut(&pattern_key).push(Box::new(action));
+  Ok(())
+}
+
+/// Moves the layer to the new position in the stack.
+#[tauri::command]
+pub fn move_layer<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  from: usize,
+  to: usize,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut history = history.write().unwrap();
+  let mut patterns = patterns.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  let action = MoveLayerAction::new(from, to);
+  action.perform(&window, patproj)?;
+  history.get_mut(&pattern_key).push(Box::new(action));
+  Ok(())
+}
+
+/// Changes the name, visibility or locking of the layer.
+#[tauri::command]
+pub fn update_layer<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  index: usize,
+  properties: LayerProperties,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut history = history.write().unwrap();
+  let mut patterns = patterns.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  let action = UpdateLayerAction::new(index, properties);
+  action.perform(&window, patproj)?;
+  history.get_mut(&pattern_key).push(Box::new(action));
+  Ok(())
+}
+
+/// Makes the layer active, so the stitches are added to and removed from it.
+#[tauri::command]
+pub fn set_active_layer<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  index: usize,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut history = history.write().unwrap();
+  let mut patterns = patterns.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  if patproj.pattern.layers.active() == index {
+    return Ok(());
+  }
+  let action = SetActiveLayerAction::new(index);
+  action.perform(&window, patproj)?;
+  history.get_mut(&pattern_key).push(Box::new(action));
+  Ok(())
+}
+
+/// Merges the layer into the one below it.
+#[tauri::command]
+pub fn merge_layer_down<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  index: usize,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut history = history.write().unwrap();
+  let mut patterns = patterns.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  let action = MergeLayerDownAction::new(index);
+  action.perform(&window, patproj)?;
+  history.get_mut(&pattern_key).push(Box::new(action));
+  Ok(())
+}
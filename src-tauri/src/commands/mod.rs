@@ -1,5 +1,11 @@
+pub mod fonts;
 pub mod history;
+pub mod layers;
+pub mod motifs;
 pub mod palette;
 pub mod path;
 pub mod pattern;
+pub mod progress;
+pub mod render;
+pub mod settings;
 pub mod stitches;
@@ -0,0 +1,110 @@
+use super::palette::symbol_candidates;
+use crate::core::actions::{Action, InsertMotifAction};
+use crate::core::motifs::Motif;
+use crate::core::pattern::symbols::SymbolAllocator;
+use crate::core::pattern::Region;
+use crate::error::{CommandError, CommandResult};
+use crate::state::{FontsState, HistoryState, MotifsState, PatternKey, PatternsState};
+use crate::utils::path::app_motifs_dir;
+
+/// Returns the motifs whose name or tags contain the query and that have all the given tags.
+#[tauri::command]
+pub fn get_motifs(
+  query: Option<String>,
+  tags: Option<Vec<String>>,
+  motifs: tauri::State<MotifsState>,
+) -> CommandResult<Vec<Motif>> {
+  let motifs = motifs.read().unwrap();
+  let found = motifs.search(query.as_deref().unwrap_or_default(), &tags.unwrap_or_default());
+  Ok(found.into_iter().cloned().collect())
+}
+
+/// Returns all the tags used by the motifs in the library.
+#[tauri::command]
+pub fn get_motif_tags(motifs: tauri::State<MotifsState>) -> CommandResult<Vec<String>> {
+  Ok(motifs.read().unwrap().tags())
+}
+
+/// Saves the region of the pattern to the motif library.
+/// The motif with the same name is replaced.
+#[tauri::command]
+pub fn save_motif<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  region: Region,
+  name: String,
+  tags: Vec<String>,
+  app_handle: tauri::AppHandle<R>,
+  patterns: tauri::State<PatternsState>,
+  motifs: tauri::State<MotifsState>,
+) -> CommandResult<Motif> {
+  log::trace!("Saving motif {name}");
+  let patterns = patterns.read().unwrap();
+  let patproj = patterns
+    .get(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  let motif = Motif::from_region(&patproj.pattern, &region, name, tags)?;
+  let motifs_dir = app_motifs_dir(&app_handle)?;
+  let file_path = motifs.write().unwrap().save_motif(&motifs_dir, motif.clone())?;
+  log::trace!("Motif saved to {file_path:?}");
+  Ok(motif)
+}
+
+/// Removes the motif from the library.
+#[tauri::command]
+pub fn remove_motif<R: tauri::Runtime>(
+  name: String,
+  app_handle: tauri::AppHandle<R>,
+  motifs: tauri::State<MotifsState>,
+) -> CommandResult<()> {
+  log::trace!("Removing motif {name}");
+  let mut motifs = motifs.write().unwrap();
+  if motifs.get(&name).is_none() {
+    return Err(CommandError::motif_not_found(&name));
+  }
+  let motifs_dir = app_motifs_dir(&app_handle)?;
+  motifs.remove_motif(&motifs_dir, &name)?;
+  Ok(())
+}
+
+/// Inserts the motif into the pattern so its top-left corner lies at the given cell.
+/// The motif colours that are missing in the pattern palette are appended to it.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn insert_motif<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  name: String,
+  x: u16,
+  y: u16,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+  motifs: tauri::State<MotifsState>,
+  fonts: tauri::State<FontsState>,
+) -> CommandResult<()> {
+  let motifs = motifs.read().unwrap();
+  let motif = motifs.get(&name).ok_or_else(|| CommandError::motif_not_found(&name))?;
+
+  let mut patterns = patterns.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  patproj.pattern.layers.ensure_active_editable()?;
+  let (mapping, appended) = motif.map_palette(&patproj.pattern.palette);
+  if patproj.pattern.palette.len() + appended.len() > usize::from(u8::MAX) + 1 {
+    return Err(anyhow::anyhow!("The pattern palette can't fit the motif colours").into());
+  }
+
+  let candidates = symbol_candidates(&fonts, &patproj.display_settings.default_stitch_font);
+  let mut allocator = SymbolAllocator::new(candidates, &patproj.display_settings.symbols);
+  let palette_items = appended
+    .into_iter()
+    .map(|palitem| (palitem, allocator.allocate_symbols()))
+    .collect();
+  let stitches = motif.stitches_at(x, y, &mapping, &patproj.pattern);
+
+  let mut history = history.write().unwrap();
+  let action = InsertMotifAction::new(palette_items, stitches);
+  action.perform(&window, patproj)?;
+  history.get_mut(&pattern_key).push(Box::new(action));
+  Ok(())
+}
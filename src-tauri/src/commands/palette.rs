@@ -1,7 +1,11 @@
-use crate::core::actions::{Action, AddPaletteItemAction, RemovePaletteItemAction};
-use crate::core::pattern::PaletteItem;
-use crate::error::CommandResult;
-use crate::state::{HistoryState, PatternKey, PatternsState};
+use crate::core::actions::{
+  Action, AddPaletteItemAction, CleanupPaletteAction, MovePaletteItemAction, ReassignSymbolsAction,
+  RemovePaletteItemAction, ReplacePaletteIndexAction, ReplacementScope, UpdatePaletteItemAction,
+};
+use crate::core::pattern::symbols::{self, SymbolAllocator};
+use crate::core::pattern::{PaletteAnalysis, PaletteItem};
+use crate::error::{CommandError, CommandResult};
+use crate::state::{FontsState, HistoryState, PatternKey, PatternsState};
 
 #[tauri::command]
 pub fn add_palette_item<R: tauri::Runtime>(
@@ -10,12 +14,17 @@ pub fn add_palette_item<R: tauri::Runtime>(
   window: tauri::WebviewWindow<R>,
   history: tauri::State<HistoryState<R>>,
   patterns: tauri::State<PatternsState>,
+  fonts: tauri::State<FontsState>,
 ) -> CommandResult<()> {
   let mut patterns = patterns.write().unwrap();
-  let patproj = patterns.get_mut(&pattern_key).unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
   if !patproj.pattern.palette.contains(&palette_item) {
     let mut history = history.write().unwrap();
-    let action = AddPaletteItemAction::new(palette_item);
+    let candidates = symbol_candidates(&fonts, &patproj.display_settings.default_stitch_font);
+    let symbols = SymbolAllocator::new(candidates, &patproj.display_settings.symbols).allocate_symbols();
+    let action = AddPaletteItemAction::new(palette_item, symbols);
     action.perform(&window, patproj)?;
     history.get_mut(&pattern_key).push(Box::new(action));
   }
@@ -33,7 +42,143 @@ pub fn remove_palette_item<R: tauri::Runtime>(
   let mut patterns = patterns.write().unwrap();
   let mut history = history.write().unwrap();
   let action = RemovePaletteItemAction::new(palette_item);
-  action.perform(&window, patterns.get_mut(&pattern_key).unwrap())?;
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  action.perform(&window, patproj)?;
   history.get_mut(&pattern_key).push(Box::new(action));
   Ok(())
 }
+
+/// Replaces the palette item at the given index keeping its stitches.
+#[tauri::command]
+pub fn update_palette_item<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  palindex: usize,
+  palette_item: PaletteItem,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut patterns = patterns.write().unwrap();
+  let mut history = history.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  let action = UpdatePaletteItemAction::new(palindex, palette_item);
+  action.perform(&window, patproj)?;
+  history.get_mut(&pattern_key).push(Box::new(action));
+  Ok(())
+}
+
+/// Moves the palette item to the new position.
+#[tauri::command]
+pub fn move_palette_item<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  from: usize,
+  to: usize,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut patterns = patterns.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  if from != to {
+    let mut history = history.write().unwrap();
+    let action = MovePaletteItemAction::new(from, to);
+    action.perform(&window, patproj)?;
+    history.get_mut(&pattern_key).push(Box::new(action));
+  }
+  Ok(())
+}
+
+/// Makes the stitches of one palette item use another one, e.g. to merge similar colours.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn replace_palette_index<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  from: usize,
+  to: usize,
+  scope: ReplacementScope,
+  remove_unused: bool,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut patterns = patterns.write().unwrap();
+  let mut history = history.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  let action = ReplacePaletteIndexAction::new(from, to, scope, remove_unused);
+  action.perform(&window, patproj)?;
+  history.get_mut(&pattern_key).push(Box::new(action));
+  Ok(())
+}
+
+/// Finds the unused and duplicate palette items.
+#[tauri::command]
+pub fn analyze_palette(
+  pattern_key: PatternKey,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<PaletteAnalysis> {
+  let patterns = patterns.read().unwrap();
+  let patproj = patterns
+    .get(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  Ok(patproj.pattern.analyze_palette())
+}
+
+/// Merges the duplicate palette items and removes the unused ones.
+#[tauri::command]
+pub fn cleanup_palette<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  remove_unused: bool,
+  merge_duplicates: bool,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut patterns = patterns.write().unwrap();
+  let mut history = history.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  let action = CleanupPaletteAction::new(remove_unused, merge_duplicates);
+  action.perform(&window, patproj)?;
+  history.get_mut(&pattern_key).push(Box::new(action));
+  Ok(())
+}
+
+/// Assigns the new distinct symbols to all the palette items.
+#[tauri::command]
+pub fn reassign_symbols<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+  fonts: tauri::State<FontsState>,
+) -> CommandResult<()> {
+  let mut patterns = patterns.write().unwrap();
+  let mut history = history.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  let candidates = symbol_candidates(&fonts, &patproj.display_settings.default_stitch_font);
+  let action = ReassignSymbolsAction::new(symbols::reassign_symbols(patproj.pattern.palette.len(), candidates));
+  action.perform(&window, patproj)?;
+  history.get_mut(&pattern_key).push(Box::new(action));
+  Ok(())
+}
+
+/// Returns the symbols to assign automatically from the font that is used to draw them.
+pub(crate) fn symbol_candidates(fonts: &FontsState, font_name: &str) -> Vec<u16> {
+  fonts
+    .read()
+    .unwrap()
+    .get_or_default(font_name)
+    .symbol_candidates()
+    .to_vec()
+}
@@ -1,9 +1,12 @@
-use crate::core::parser::{self, PatternFormat};
+use crate::core::actions::{Action, UpdateFabricAction, UpdatePatternInfoAction, UpdatePatternPropertiesAction};
+use crate::core::parser::{self, PatternFormat, UnsupportedFormatError};
 use crate::core::pattern::display::DisplaySettings;
 use crate::core::pattern::print::PrintSettings;
-use crate::core::pattern::{Pattern, PatternProject};
-use crate::error::CommandResult;
-use crate::state::{PatternKey, PatternsState};
+use crate::core::pattern::progress::Progress;
+use crate::core::pattern::sessions::SessionLog;
+use crate::core::pattern::{Fabric, Pattern, PatternInfo, PatternProject, PatternProperties};
+use crate::error::{CommandError, CommandResult};
+use crate::state::{HistoryState, PatternKey, PatternsState};
 use crate::utils::path::app_document_dir;
 
 #[tauri::command]
@@ -12,23 +15,26 @@ pub fn load_pattern(file_path: std::path::PathBuf, patterns: tauri::State<Patter
   let mut patterns = patterns.write().unwrap();
 
   let pattern_key = PatternKey::from(&file_path);
+  // The frontend shows the stitches of all the visible layers, while only the active one is kept in the pattern.
   if let Some(pattern) = patterns.get(&pattern_key) {
     log::trace!("Pattern loaded");
-    return Ok(borsh::to_vec(&(pattern_key, pattern))?);
+    return Ok(borsh::to_vec(&(pattern_key, &*pattern.flattened()))?);
   }
 
   // Change the original file path with the path to `.embproj` file.
   let mut new_file_path = file_path.clone();
   new_file_path.set_extension(PatternFormat::default().to_string());
 
-  let mut pattern = match PatternFormat::try_from(file_path.extension())? {
-    PatternFormat::Xsd => parser::xsd::parse_pattern(file_path)?,
-    PatternFormat::Oxs => parser::oxs::parse_pattern(file_path)?,
-    PatternFormat::EmbProj => parser::embproj::parse_pattern(file_path)?,
-  };
+  let mut pattern = match PatternFormat::detect(&file_path) {
+    Ok(PatternFormat::Xsd) => parser::xsd::parse_pattern(file_path.clone()),
+    Ok(PatternFormat::Oxs) => parser::oxs::parse_pattern(file_path.clone()),
+    Ok(PatternFormat::EmbProj) => parser::embproj::parse_pattern(file_path.clone()),
+    Err(error) => Err(error),
+  }
+  .map_err(|error| CommandError::from(error).with_file_path(&file_path))?;
   pattern.file_path = new_file_path;
 
-  let result = borsh::to_vec(&(&pattern_key, &pattern))?;
+  let result = borsh::to_vec(&(&pattern_key, &*pattern.flattened()))?;
   patterns.insert(pattern_key, pattern);
 
   log::trace!("Pattern loaded");
@@ -49,6 +55,8 @@ pub fn create_pattern<R: tauri::Runtime>(
     pattern,
     display_settings: DisplaySettings::new(2),
     print_settings: PrintSettings::default(),
+    progress: Progress::default(),
+    sessions: SessionLog::default(),
   };
 
   let pattern_key = PatternKey::from(&patproj.file_path);
@@ -67,13 +75,17 @@ pub fn save_pattern(
 ) -> CommandResult<()> {
   log::trace!("Saving pattern");
   let mut patterns = patterns.write().unwrap();
-  let patproj = patterns.get_mut(&pattern_key).unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
   patproj.file_path = file_path;
-  match PatternFormat::try_from(patproj.file_path.extension())? {
-    PatternFormat::Xsd => Err(anyhow::anyhow!("The XSD format is not supported for saving.")),
-    PatternFormat::Oxs => parser::oxs::save_pattern(patproj),
-    PatternFormat::EmbProj => parser::embproj::save_pattern(patproj),
-  }?;
+  match PatternFormat::try_from(patproj.file_path.extension()) {
+    Ok(PatternFormat::Xsd) => Err(UnsupportedFormatError::read_only(PatternFormat::Xsd).into()),
+    Ok(PatternFormat::Oxs) => parser::oxs::save_pattern(patproj),
+    Ok(PatternFormat::EmbProj) => parser::embproj::save_pattern(patproj),
+    Err(error) => Err(error),
+  }
+  .map_err(|error| CommandError::from(error).with_file_path(&patproj.file_path))?;
   log::trace!("Pattern saved");
   Ok(())
 }
@@ -86,8 +98,70 @@ pub fn close_pattern(pattern_key: PatternKey, patterns: tauri::State<PatternsSta
 }
 
 #[tauri::command]
-pub fn get_pattern_file_path(pattern_key: PatternKey, patterns: tauri::State<PatternsState>) -> String {
+pub fn get_pattern_file_path(pattern_key: PatternKey, patterns: tauri::State<PatternsState>) -> CommandResult<String> {
   let patterns = patterns.read().unwrap();
-  let patproj = patterns.get(&pattern_key).unwrap();
-  patproj.file_path.to_string_lossy().to_string()
+  let patproj = patterns
+    .get(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  Ok(patproj.file_path.to_string_lossy().to_string())
+}
+
+/// Changes the title, author and other information about the pattern.
+#[tauri::command]
+pub fn update_pattern_info<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  info: PatternInfo,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut patterns = patterns.write().unwrap();
+  let mut history = history.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  let action = UpdatePatternInfoAction::new(info);
+  action.perform(&window, patproj)?;
+  history.get_mut(&pattern_key).push(Box::new(action));
+  Ok(())
+}
+
+/// Resizes the pattern. The stitches that don't fit the new size are removed.
+#[tauri::command]
+pub fn update_pattern_properties<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  properties: PatternProperties,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut patterns = patterns.write().unwrap();
+  let mut history = history.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  let action = UpdatePatternPropertiesAction::new(properties);
+  action.perform(&window, patproj)?;
+  history.get_mut(&pattern_key).push(Box::new(action));
+  Ok(())
+}
+
+/// Changes the fabric of the pattern.
+#[tauri::command]
+pub fn update_fabric<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  fabric: Fabric,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut patterns = patterns.write().unwrap();
+  let mut history = history.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  let action = UpdateFabricAction::new(fabric);
+  action.perform(&window, patproj)?;
+  history.get_mut(&pattern_key).push(Box::new(action));
+  Ok(())
 }
@@ -0,0 +1,102 @@
+use crate::core::actions::{Action, MarkProgressAction};
+use crate::core::pattern::progress::{timestamp, ProgressEntry, ProgressStats, ProgressTarget};
+use crate::core::pattern::sessions::{SessionStats, StitchingSession};
+use crate::error::{CommandError, CommandResult};
+use crate::state::{HistoryState, PatternKey, PatternsState};
+
+/// Returns the completed stitches of all the layers.
+#[tauri::command]
+pub fn get_progress(
+  pattern_key: PatternKey,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<Vec<ProgressEntry>> {
+  let patterns = patterns.read().unwrap();
+  let patproj = patterns
+    .get(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  Ok(patproj.progress.entries(&patproj.pattern))
+}
+
+/// Marks the stitches of all the layers as completed now or as not completed.
+/// The stitches that are already in the requested state are left as they are, so they keep their completion times.
+#[tauri::command]
+pub fn mark_progress<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  target: ProgressTarget,
+  completed: bool,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut history = history.write().unwrap();
+  let mut patterns = patterns.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  let stitches: Vec<_> = target
+    .stitches(&patproj.pattern)
+    .into_iter()
+    .filter(|stitch| patproj.progress.completed_at(stitch).is_some() != completed)
+    .collect();
+  if stitches.is_empty() {
+    return Ok(());
+  }
+  let action = MarkProgressAction::new(stitches, completed.then(timestamp));
+  action.perform(&window, patproj)?;
+  history.get_mut(&pattern_key).push(Box::new(action));
+  Ok(())
+}
+
+/// Returns the completion of the whole pattern, each palette item and each page of the given size in cells.
+#[tauri::command]
+pub fn get_progress_stats(
+  pattern_key: PatternKey,
+  page_width: u16,
+  page_height: u16,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<ProgressStats> {
+  let patterns = patterns.read().unwrap();
+  let patproj = patterns
+    .get(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  Ok(patproj.progress.stats(&patproj.pattern, page_width, page_height))
+}
+
+/// Starts a stitching session. Only one session can run at a time.
+#[tauri::command]
+pub fn start_stitching_session(
+  pattern_key: PatternKey,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<StitchingSession> {
+  let mut patterns = patterns.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  Ok(patproj.sessions.start(timestamp())?)
+}
+
+/// Stops the running stitching session.
+#[tauri::command]
+pub fn stop_stitching_session(
+  pattern_key: PatternKey,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<StitchingSession> {
+  let mut patterns = patterns.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  Ok(patproj.sessions.stop(timestamp())?)
+}
+
+/// Returns the statistics of the stitching sessions and the projected completion date of the pattern.
+#[tauri::command]
+pub fn get_session_stats(
+  pattern_key: PatternKey,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<SessionStats> {
+  let patterns = patterns.read().unwrap();
+  let patproj = patterns
+    .get(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  Ok(patproj.sessions.stats(&patproj.progress, &patproj.pattern, timestamp()))
+}
@@ -0,0 +1,88 @@
+use std::path::PathBuf;
+
+use crate::core::render::{self, Region, RenderOptions};
+use crate::error::{CommandError, CommandResult};
+use crate::state::{FontsState, PatternKey, PatternsState};
+
+/// Renders the pattern to a PNG image using its current display settings.
+/// The zoom defaults to the current one, and the whole pattern is rendered if the region is not specified.
+/// The image is sent as the raw bytes, since a JSON array of numbers is several times larger.
+#[tauri::command]
+pub fn render_pattern(
+  pattern_key: PatternKey,
+  zoom: Option<u16>,
+  region: Option<Region>,
+  patterns: tauri::State<PatternsState>,
+  fonts: tauri::State<FontsState>,
+) -> CommandResult<tauri::ipc::Response> {
+  log::trace!("Rendering pattern");
+  let patterns = patterns.read().unwrap();
+  let patproj = patterns
+    .get(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+
+  let mut options = RenderOptions::from_display_settings(patproj);
+  if let Some(zoom) = zoom {
+    options.zoom = zoom;
+  }
+  options.region = region;
+  let area = render::drawn_area(&options.region(patproj)?);
+
+  let png = render::render_png(&patproj.flattened_around(&area), &options, &fonts.read().unwrap())?;
+  log::trace!("Pattern rendered");
+  Ok(tauri::ipc::Response::new(png))
+}
+
+/// Renders the realistic preview of the stitched pattern to a PNG image.
+/// The zoom defaults to the current one, and the whole pattern is rendered if the region is not specified.
+#[tauri::command]
+pub fn render_pattern_preview(
+  pattern_key: PatternKey,
+  zoom: Option<u16>,
+  region: Option<Region>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<tauri::ipc::Response> {
+  log::trace!("Rendering pattern preview");
+  let patterns = patterns.read().unwrap();
+  let patproj = patterns
+    .get(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+
+  let mut options = RenderOptions::from_display_settings(patproj);
+  if let Some(zoom) = zoom {
+    options.zoom = zoom;
+  }
+  options.region = region;
+  let area = render::drawn_area(&options.region(patproj)?);
+
+  let png = render::render_preview_png(&patproj.flattened_around(&area), &options)?;
+  log::trace!("Pattern preview rendered");
+  Ok(tauri::ipc::Response::new(png))
+}
+
+/// Exports the pattern to an SVG file using its current display settings.
+#[tauri::command]
+pub fn export_pattern_svg(
+  pattern_key: PatternKey,
+  file_path: PathBuf,
+  region: Option<Region>,
+  patterns: tauri::State<PatternsState>,
+  fonts: tauri::State<FontsState>,
+) -> CommandResult<()> {
+  log::trace!("Exporting pattern to SVG");
+  let patterns = patterns.read().unwrap();
+  let patproj = patterns
+    .get(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+
+  let options = RenderOptions {
+    region,
+    ..RenderOptions::from_display_settings(patproj)
+  };
+  let area = render::drawn_area(&options.region(patproj)?);
+  let svg = render::render_svg(&patproj.flattened_around(&area), &options, &fonts.read().unwrap())?;
+  std::fs::write(&file_path, svg).map_err(|error| CommandError::from(error).with_file_path(&file_path))?;
+
+  log::trace!("Pattern exported to SVG");
+  Ok(())
+}
@@ -0,0 +1,45 @@
+use crate::core::actions::{
+  Action, DisplaySettingsUpdate, PrintSettingsUpdate, UpdateDisplaySettingsAction, UpdatePrintSettingsAction,
+};
+use crate::error::{CommandError, CommandResult};
+use crate::state::{HistoryState, PatternKey, PatternsState};
+
+/// Changes a single section of the display settings.
+#[tauri::command]
+pub fn update_display_settings<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  update: DisplaySettingsUpdate,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut patterns = patterns.write().unwrap();
+  let mut history = history.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  let action = UpdateDisplaySettingsAction::new(update);
+  action.perform(&window, patproj)?;
+  history.get_mut(&pattern_key).push(Box::new(action));
+  Ok(())
+}
+
+/// Changes a single section of the print settings.
+#[tauri::command]
+pub fn update_print_settings<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  update: PrintSettingsUpdate,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut patterns = patterns.write().unwrap();
+  let mut history = history.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  let action = UpdatePrintSettingsAction::new(update);
+  action.perform(&window, patproj)?;
+  history.get_mut(&pattern_key).push(Box::new(action));
+  Ok(())
+}
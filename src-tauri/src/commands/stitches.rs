@@ -1,39 +1,239 @@
-use crate::core::actions::{Action, AddStitchAction, RemoveStitchAction};
-use crate::core::pattern::Stitch;
-use crate::error::CommandResult;
-use crate::state::{HistoryState, PatternKey, PatternsState};
+use crate::core::actions::{Action, AddStitchAction, AddStitchesAction, RemoveStitchAction, RemoveStitchesAction};
+use crate::core::pattern::draw::Shape;
+use crate::core::pattern::fill::FillStitchKind;
+use crate::core::pattern::repeat::RepeatLayout;
+use crate::core::pattern::symmetry::Symmetry;
+use crate::core::pattern::text::TextOptions;
+use crate::core::pattern::{Region, Stitch};
+use crate::error::{CommandError, CommandResult};
+use crate::state::{FontsState, HistoryState, PatternKey, PatternsState};
 
+/// The distance in cells within which the lines and nodes are hit.
+const DEFAULT_HIT_TOLERANCE: f32 = 0.25;
+
+/// Adds the stitch to the pattern.
+/// If the symmetry is set, its symmetric copies are added too, all in one history entry.
 #[tauri::command]
 pub fn add_stitch<R: tauri::Runtime>(
   pattern_key: PatternKey,
   stitch: Stitch,
+  symmetry: Option<Symmetry>,
   window: tauri::WebviewWindow<R>,
   history: tauri::State<HistoryState<R>>,
   patterns: tauri::State<PatternsState>,
 ) -> CommandResult<()> {
   let mut patterns = patterns.write().unwrap();
-  let patproj = patterns.get_mut(&pattern_key).unwrap();
-  if !patproj.pattern.contains_stitch(&stitch) {
-    let mut history = history.write().unwrap();
-    let action = AddStitchAction::new(stitch);
-    action.perform(&window, patproj)?;
-    history.get_mut(&pattern_key).push(Box::new(action));
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  patproj.pattern.layers.ensure_active_editable()?;
+  let stitches: Vec<Stitch> = match symmetry {
+    Some(symmetry) => patproj.pattern.symmetric_stitches(stitch, symmetry),
+    None => vec![stitch],
   }
+  .into_iter()
+  .filter(|stitch| !patproj.pattern.contains_stitch(stitch))
+  .collect();
+  let action: Box<dyn Action<R>> = match stitches.as_slice() {
+    [] => return Ok(()),
+    [stitch] => Box::new(AddStitchAction::new(*stitch)),
+    _ => Box::new(AddStitchesAction::new(stitches)),
+  };
+  let mut history = history.write().unwrap();
+  action.perform(&window, patproj)?;
+  history.get_mut(&pattern_key).push(action);
   Ok(())
 }
 
+/// Removes the stitch from the pattern.
+/// If the symmetry is set, its symmetric copies are removed too, all in one history entry.
 #[tauri::command]
 pub fn remove_stitch<R: tauri::Runtime>(
   pattern_key: PatternKey,
   stitch: Stitch,
+  symmetry: Option<Symmetry>,
   window: tauri::WebviewWindow<R>,
   history: tauri::State<HistoryState<R>>,
   patterns: tauri::State<PatternsState>,
 ) -> CommandResult<()> {
   let mut history = history.write().unwrap();
   let mut patterns = patterns.write().unwrap();
-  let action = RemoveStitchAction::new(stitch);
-  action.perform(&window, patterns.get_mut(&pattern_key).unwrap())?;
-  history.get_mut(&pattern_key).push(Box::new(action));
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  patproj.pattern.layers.ensure_active_editable()?;
+  let action: Box<dyn Action<R>> = match symmetry {
+    Some(symmetry) => Box::new(RemoveStitchesAction::new(
+      patproj.pattern.symmetric_stitches(stitch, symmetry),
+    )),
+    None => Box::new(RemoveStitchAction::new(stitch)),
+  };
+  action.perform(&window, patproj)?;
+  history.get_mut(&pattern_key).push(action);
+  Ok(())
+}
+
+/// Fills the area connected to the cell with the stitches.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn fill_stitches<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  x: u16,
+  y: u16,
+  palindex: u8,
+  kind: FillStitchKind,
+  respect_lines: bool,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut patterns = patterns.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  patproj.pattern.layers.ensure_active_editable()?;
+  let stitches = patproj.pattern.flood_fill(x, y, palindex, kind, respect_lines);
+  if !stitches.is_empty() {
+    let mut history = history.write().unwrap();
+    let action = AddStitchesAction::new(stitches);
+    action.perform(&window, patproj)?;
+    history.get_mut(&pattern_key).push(Box::new(action));
+  }
+  Ok(())
+}
+
+/// Draws the shape with the stitches.
+#[tauri::command]
+pub fn draw_shape<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  shape: Shape,
+  palindex: u8,
+  kind: FillStitchKind,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut patterns = patterns.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  patproj.pattern.layers.ensure_active_editable()?;
+  let stitches = patproj.pattern.draw_shape(&shape, palindex, kind);
+  if !stitches.is_empty() {
+    let mut history = history.write().unwrap();
+    let action = AddStitchesAction::new(stitches);
+    action.perform(&window, patproj)?;
+    history.get_mut(&pattern_key).push(Box::new(action));
+  }
+  Ok(())
+}
+
+/// Repeats the stitches of the motif across the pattern or along its edges.
+#[tauri::command]
+pub fn repeat_motif<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  motif: Region,
+  layout: RepeatLayout,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<()> {
+  let mut patterns = patterns.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  patproj.pattern.layers.ensure_active_editable()?;
+  let stitches: Vec<Stitch> = patproj
+    .pattern
+    .repeat_motif(&motif, &layout)
+    .into_iter()
+    .filter(|stitch| !patproj.pattern.contains_stitch(stitch))
+    .collect();
+  if !stitches.is_empty() {
+    let mut history = history.write().unwrap();
+    let action = AddStitchesAction::new(stitches);
+    action.perform(&window, patproj)?;
+    history.get_mut(&pattern_key).push(Box::new(action));
+  }
+  Ok(())
+}
+
+/// Renders the text with the stitches.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn add_text<R: tauri::Runtime>(
+  pattern_key: PatternKey,
+  options: TextOptions,
+  palindex: u8,
+  kind: FillStitchKind,
+  window: tauri::WebviewWindow<R>,
+  history: tauri::State<HistoryState<R>>,
+  patterns: tauri::State<PatternsState>,
+  fonts: tauri::State<FontsState>,
+) -> CommandResult<()> {
+  let mut patterns = patterns.write().unwrap();
+  let patproj = patterns
+    .get_mut(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  patproj.pattern.layers.ensure_active_editable()?;
+  let stitches = patproj
+    .pattern
+    .render_text(&options, palindex, kind, &fonts.read().unwrap())?;
+  if !stitches.is_empty() {
+    let mut history = history.write().unwrap();
+    let action = AddStitchesAction::new(stitches);
+    action.perform(&window, patproj)?;
+    history.get_mut(&pattern_key).push(Box::new(action));
+  }
   Ok(())
 }
+
+/// Returns the stitches of the visible layers that should be drawn in the viewport.
+/// The stitches crossing the viewport border are included too.
+#[tauri::command]
+pub fn get_stitches_in_viewport(
+  pattern_key: PatternKey,
+  viewport: Region,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<Vec<Stitch>> {
+  let patterns = patterns.read().unwrap();
+  let patproj = patterns
+    .get(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  Ok(patproj.pattern.visible_stitches_intersecting(&viewport.bounds()))
+}
+
+/// Returns the stitches of the active layer lying inside the region.
+#[tauri::command]
+pub fn select_stitches(
+  pattern_key: PatternKey,
+  region: Region,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<Vec<Stitch>> {
+  let patterns = patterns.read().unwrap();
+  let patproj = patterns
+    .get(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  Ok(patproj.pattern.stitches_in(&region))
+}
+
+/// Returns the stitches of the active layer under the point, from the topmost to the bottommost one.
+/// The point is in cells, e.g. `(2.5, 3.5)` is the center of the cell at `(2, 3)`.
+#[tauri::command]
+pub fn hit_test_stitches(
+  pattern_key: PatternKey,
+  x: f32,
+  y: f32,
+  tolerance: Option<f32>,
+  patterns: tauri::State<PatternsState>,
+) -> CommandResult<Vec<Stitch>> {
+  let patterns = patterns.read().unwrap();
+  let patproj = patterns
+    .get(&pattern_key)
+    .ok_or_else(|| CommandError::pattern_not_found(&pattern_key))?;
+  Ok(
+    patproj
+      .pattern
+      .stitches_at_point(x, y, tolerance.unwrap_or(DEFAULT_HIT_TOLERANCE)),
+  )
+}
@@ -0,0 +1,311 @@
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use tauri::{Emitter, WebviewWindow};
+
+use super::Action;
+use crate::core::pattern::{Layer, LayerProperties, MergedLayers, PatternProject, SpecialStitch, Stitch};
+
+#[cfg(test)]
+#[path = "layers.test.rs"]
+mod tests;
+
+fn emit_layers<R: tauri::Runtime>(window: &WebviewWindow<R>, patproj: &PatternProject) -> Result<()> {
+  window.emit("layers:update", patproj.pattern.layers.info())?;
+  Ok(())
+}
+
+/// The stitches of the visible layers as they are shown, with the special stitches separately.
+type VisibleStitches = (Vec<Stitch>, Vec<SpecialStitch>);
+
+/// Emits the events to replace the shown stitches of the visible layers with the current ones.
+/// The layers are shown merged, so the stitches of one layer can cover the ones of the layers below it.
+fn emit_visible_stitches<R: tauri::Runtime>(
+  window: &WebviewWindow<R>,
+  patproj: &PatternProject,
+  previous: &VisibleStitches,
+) -> Result<()> {
+  let (stitches, specialstitches) = previous;
+  window.emit("stitches:remove_many", stitches)?;
+  window.emit("specialstitches:remove_many", specialstitches)?;
+  let (stitches, specialstitches) = patproj.pattern.visible_stitches();
+  window.emit("stitches:add_many", stitches)?;
+  window.emit("specialstitches:add_many", specialstitches)?;
+  Ok(())
+}
+
+#[derive(Clone)]
+pub struct AddLayerAction {
+  index: usize,
+  properties: LayerProperties,
+}
+
+impl AddLayerAction {
+  pub fn new(index: usize, properties: LayerProperties) -> Self {
+    Self { index, properties }
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for AddLayerAction {
+  /// Add an empty layer at the given position.
+  ///
+  /// **Emits:**
+  /// - `layers:update` with the information about all the layers
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    patproj
+      .pattern
+      .insert_layer(self.index, Layer::new(self.properties.clone()))?;
+    emit_layers(window, patproj)
+  }
+
+  /// Remove the added layer.
+  ///
+  /// **Emits:**
+  /// - `layers:update` with the information about all the layers
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    patproj.pattern.remove_layer(self.index)?;
+    emit_layers(window, patproj)
+  }
+}
+
+#[derive(Clone)]
+pub struct RemoveLayerAction {
+  index: usize,
+  /// The removed layer and the index of the layer that was active before the removal.
+  removed: OnceLock<(Layer, usize)>,
+}
+
+impl RemoveLayerAction {
+  pub fn new(index: usize) -> Self {
+    Self { index, removed: OnceLock::new() }
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for RemoveLayerAction {
+  /// Remove the layer with its stitches.
+  ///
+  /// **Emits:**
+  /// - `layers:update` with the information about all the layers
+  /// - `stitches:remove_many` and `specialstitches:remove_many` with the previously shown stitches
+  /// - `stitches:add_many` and `specialstitches:add_many` with the stitches of the visible layers
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let active = patproj.pattern.layers.active();
+    let previous = patproj.pattern.visible_stitches();
+    let layer = patproj.pattern.remove_layer(self.index)?;
+    emit_layers(window, patproj)?;
+    emit_visible_stitches(window, patproj, &previous)?;
+    if self.removed.get().is_none() {
+      self.removed.set((layer, active)).unwrap();
+    }
+    Ok(())
+  }
+
+  /// Restore the removed layer.
+  ///
+  /// **Emits:**
+  /// - `layers:update` with the information about all the layers
+  /// - `stitches:remove_many` and `specialstitches:remove_many` with the previously shown stitches
+  /// - `stitches:add_many` and `specialstitches:add_many` with the stitches of the visible layers
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let (layer, active) = self.removed.get().unwrap();
+    let previous = patproj.pattern.visible_stitches();
+    patproj.pattern.restore_layer(self.index, layer.clone(), *active)?;
+    emit_layers(window, patproj)?;
+    emit_visible_stitches(window, patproj, &previous)
+  }
+}
+
+#[derive(Clone)]
+pub struct MoveLayerAction {
+  from: usize,
+  to: usize,
+}
+
+impl MoveLayerAction {
+  pub fn new(from: usize, to: usize) -> Self {
+    Self { from, to }
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for MoveLayerAction {
+  /// Move the layer to the new position.
+  /// The order of the layers decides which stitches cover the others, so the visible stitches are sent again.
+  ///
+  /// **Emits:**
+  /// - `layers:update` with the information about all the layers
+  /// - `stitches:remove_many` and `specialstitches:remove_many` with the previously shown stitches
+  /// - `stitches:add_many` and `specialstitches:add_many` with the stitches of the visible layers
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let previous = patproj.pattern.visible_stitches();
+    patproj.pattern.move_layer(self.from, self.to)?;
+    emit_layers(window, patproj)?;
+    emit_visible_stitches(window, patproj, &previous)
+  }
+
+  /// Move the layer back to its previous position.
+  ///
+  /// **Emits:**
+  /// - `layers:update` with the information about all the layers
+  /// - `stitches:remove_many` and `specialstitches:remove_many` with the previously shown stitches
+  /// - `stitches:add_many` and `specialstitches:add_many` with the stitches of the visible layers
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let previous = patproj.pattern.visible_stitches();
+    patproj.pattern.move_layer(self.to, self.from)?;
+    emit_layers(window, patproj)?;
+    emit_visible_stitches(window, patproj, &previous)
+  }
+}
+
+#[derive(Clone)]
+pub struct UpdateLayerAction {
+  index: usize,
+  properties: LayerProperties,
+  old_properties: OnceLock<LayerProperties>,
+}
+
+impl UpdateLayerAction {
+  pub fn new(index: usize, properties: LayerProperties) -> Self {
+    Self {
+      index,
+      properties,
+      old_properties: OnceLock::new(),
+    }
+  }
+}
+
+impl UpdateLayerAction {
+  fn update_layer<R: tauri::Runtime>(
+    window: &WebviewWindow<R>,
+    patproj: &mut PatternProject,
+    index: usize,
+    properties: LayerProperties,
+  ) -> Result<LayerProperties> {
+    let visible = properties.visible;
+    let previous = patproj.pattern.visible_stitches();
+    let old_properties = patproj.pattern.update_layer(index, properties)?;
+    emit_layers(window, patproj)?;
+    if old_properties.visible != visible {
+      emit_visible_stitches(window, patproj, &previous)?;
+    }
+    Ok(old_properties)
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for UpdateLayerAction {
+  /// Update the name, visibility and locking of the layer.
+  ///
+  /// **Emits:**
+  /// - `layers:update` with the information about all the layers
+  /// - `stitches:remove_many` and `specialstitches:remove_many` with the previously shown stitches
+  ///   if the visibility is changed
+  /// - `stitches:add_many` and `specialstitches:add_many` with the stitches of the visible layers
+  ///   if the visibility is changed
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_properties = Self::update_layer(window, patproj, self.index, self.properties.clone())?;
+    if self.old_properties.get().is_none() {
+      self.old_properties.set(old_properties).unwrap();
+    }
+    Ok(())
+  }
+
+  /// Restore the previous properties of the layer.
+  ///
+  /// **Emits:**
+  /// - `layers:update` with the information about all the layers
+  /// - `stitches:remove_many` and `specialstitches:remove_many` with the previously shown stitches
+  ///   if the visibility is changed
+  /// - `stitches:add_many` and `specialstitches:add_many` with the stitches of the visible layers
+  ///   if the visibility is changed
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_properties = self.old_properties.get().unwrap();
+    Self::update_layer(window, patproj, self.index, old_properties.clone())?;
+    Ok(())
+  }
+}
+
+#[derive(Clone)]
+pub struct SetActiveLayerAction {
+  index: usize,
+  old_index: OnceLock<usize>,
+}
+
+impl SetActiveLayerAction {
+  pub fn new(index: usize) -> Self {
+    Self {
+      index,
+      old_index: OnceLock::new(),
+    }
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for SetActiveLayerAction {
+  /// Make the layer active, so the stitches are edited on it.
+  /// The visible stitches stay the same, since all the visible layers are shown regardless of the active one.
+  ///
+  /// **Emits:**
+  /// - `layers:update` with the information about all the layers
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_index = patproj.pattern.layers.active();
+    patproj.pattern.set_active_layer(self.index)?;
+    emit_layers(window, patproj)?;
+    if self.old_index.get().is_none() {
+      self.old_index.set(old_index).unwrap();
+    }
+    Ok(())
+  }
+
+  /// Make the previous layer active.
+  ///
+  /// **Emits:**
+  /// - `layers:update` with the information about all the layers
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    patproj.pattern.set_active_layer(*self.old_index.get().unwrap())?;
+    emit_layers(window, patproj)
+  }
+}
+
+#[derive(Clone)]
+pub struct MergeLayerDownAction {
+  index: usize,
+  merged: OnceLock<MergedLayers>,
+}
+
+impl MergeLayerDownAction {
+  pub fn new(index: usize) -> Self {
+    Self { index, merged: OnceLock::new() }
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for MergeLayerDownAction {
+  /// Merge the layer into the one below it.
+  ///
+  /// **Emits:**
+  /// - `layers:update` with the information about all the layers
+  /// - `stitches:remove_many` and `specialstitches:remove_many` with the previously shown stitches
+  /// - `stitches:add_many` and `specialstitches:add_many` with the stitches of the visible layers
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let previous = patproj.pattern.visible_stitches();
+    let merged = patproj.pattern.merge_layer_down(self.index)?;
+    emit_layers(window, patproj)?;
+    emit_visible_stitches(window, patproj, &previous)?;
+    if self.merged.get().is_none() {
+      self.merged.set(merged).unwrap();
+    }
+    Ok(())
+  }
+
+  /// Split the merged layers back.
+  ///
+  /// **Emits:**
+  /// - `layers:update` with the information about all the layers
+  /// - `stitches:remove_many` and `specialstitches:remove_many` with the previously shown stitches
+  /// - `stitches:add_many` and `specialstitches:add_many` with the stitches of the visible layers
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let previous = patproj.pattern.visible_stitches();
+    patproj
+      .pattern
+      .split_layer(self.index, self.merged.get().unwrap().clone())?;
+    emit_layers(window, patproj)?;
+    emit_visible_stitches(window, patproj, &previous)
+  }
+}
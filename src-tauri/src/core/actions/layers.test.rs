@@ -0,0 +1,168 @@
+use ordered_float::NotNan;
+use tauri::test::{mock_builder, MockRuntime};
+use tauri::{generate_context, App, Listener, WebviewUrl, WebviewWindowBuilder};
+
+use super::{Action, AddLayerAction, MergeLayerDownAction, SetActiveLayerAction, UpdateLayerAction};
+use crate::core::pattern::*;
+
+fn setup_app() -> App<MockRuntime> {
+  mock_builder().build(generate_context!()).unwrap()
+}
+
+fn full(x: f32) -> Stitch {
+  Stitch::Full(FullStitch {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(0.0).unwrap(),
+    palindex: 0,
+    kind: FullStitchKind::Full,
+  })
+}
+
+#[test]
+fn test_add_layer_and_set_active() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+  patproj.pattern.add_stitch(full(0.0));
+
+  let add_layer = AddLayerAction::new(1, LayerProperties::new(String::from("Layer 2")));
+  let set_active = SetActiveLayerAction::new(1);
+
+  // Test executing the commands.
+  {
+    let layers_event = window.listen("layers:update", |e| {
+      let layers: Vec<LayerInfo> = serde_json::from_str(e.payload()).unwrap();
+      assert_eq!(layers.len(), 2);
+    });
+    // The stitches of the inactive layers are still shown.
+    let remove_event = window.listen("stitches:remove_many", |_| panic!("The shown stitches must not change"));
+
+    add_layer.perform(&window, &mut patproj).unwrap();
+    set_active.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.layers.active(), 1);
+    assert!(patproj.pattern.all_stitches().is_empty());
+
+    patproj.pattern.add_stitch(full(1.0));
+    assert_eq!(patproj.pattern.flatten().all_stitches(), vec![full(0.0), full(1.0)]);
+
+    window.unlisten(layers_event);
+    window.unlisten(remove_event);
+  }
+
+  // Test revoking the commands.
+  {
+    patproj.pattern.remove_stitch(full(1.0));
+    set_active.revoke(&window, &mut patproj).unwrap();
+    add_layer.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.layers.len(), 1);
+    assert_eq!(patproj.pattern.all_stitches(), vec![full(0.0)]);
+  }
+}
+
+#[test]
+fn test_merge_layer_down() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+  patproj.pattern.add_stitch(full(0.0));
+  patproj
+    .pattern
+    .insert_layer(1, Layer::new(LayerProperties::new(String::from("Layer 2"))))
+    .unwrap();
+  patproj.pattern.set_active_layer(1).unwrap();
+  patproj.pattern.add_stitch(full(1.0));
+
+  let action = MergeLayerDownAction::new(1);
+
+  // Test executing the command.
+  {
+    let add_event = window.listen("stitches:add_many", |e| {
+      assert_eq!(
+        serde_json::from_str::<Vec<Stitch>>(e.payload()).unwrap(),
+        vec![full(0.0), full(1.0)]
+      );
+    });
+
+    action.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.layers.len(), 1);
+    assert_eq!(patproj.pattern.all_stitches(), vec![full(0.0), full(1.0)]);
+
+    window.unlisten(add_event);
+  }
+
+  // Test revoking the command.
+  {
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.layers.len(), 2);
+    assert_eq!(patproj.pattern.layers.active(), 1);
+    assert_eq!(patproj.pattern.all_stitches(), vec![full(1.0)]);
+  }
+}
+
+#[test]
+fn test_hide_layer() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+  patproj.pattern.add_stitch(full(0.0));
+  patproj
+    .pattern
+    .insert_layer(1, Layer::new(LayerProperties::new(String::from("Layer 2"))))
+    .unwrap();
+  patproj.pattern.set_active_layer(1).unwrap();
+  patproj.pattern.add_stitch(full(1.0));
+
+  let action = UpdateLayerAction::new(
+    0,
+    LayerProperties {
+      visible: false,
+      ..LayerProperties::new(String::from("Layer 1"))
+    },
+  );
+
+  // Test executing the command.
+  {
+    let remove_event = window.listen("stitches:remove_many", |e| {
+      assert_eq!(
+        serde_json::from_str::<Vec<Stitch>>(e.payload()).unwrap(),
+        vec![full(0.0), full(1.0)]
+      );
+    });
+    let add_event = window.listen("stitches:add_many", |e| {
+      assert_eq!(
+        serde_json::from_str::<Vec<Stitch>>(e.payload()).unwrap(),
+        vec![full(1.0)]
+      );
+    });
+
+    action.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.visible_stitches().0, vec![full(1.0)]);
+
+    window.unlisten(remove_event);
+    window.unlisten(add_event);
+  }
+
+  // Test revoking the command.
+  {
+    let add_event = window.listen("stitches:add_many", |e| {
+      assert_eq!(
+        serde_json::from_str::<Vec<Stitch>>(e.payload()).unwrap(),
+        vec![full(0.0), full(1.0)]
+      );
+    });
+
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.visible_stitches().0, vec![full(0.0), full(1.0)]);
+
+    window.unlisten(add_event);
+  }
+}
@@ -19,6 +19,21 @@ pub use stitches::*;
 mod palette;
 pub use palette::*;
 
+mod pattern;
+pub use pattern::*;
+
+mod settings;
+pub use settings::*;
+
+mod motifs;
+pub use motifs::*;
+
+mod layers;
+pub use layers::*;
+
+mod progress;
+pub use progress::*;
+
 /// An action that can be executed and revoked.
 pub trait Action<R: tauri::Runtime>: Send + Sync + dyn_clone::DynClone {
   /// Perform the action.
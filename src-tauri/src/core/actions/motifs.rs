@@ -0,0 +1,59 @@
+use anyhow::Result;
+use tauri::WebviewWindow;
+
+use super::{Action, AddPaletteItemAction, AddStitchesAction};
+use crate::core::pattern::display::Symbols;
+use crate::core::pattern::{PaletteItem, PatternProject, Stitch};
+
+#[cfg(test)]
+#[path = "motifs.test.rs"]
+mod tests;
+
+#[derive(Clone)]
+pub struct InsertMotifAction {
+  palette_items: Vec<AddPaletteItemAction>,
+  stitches: AddStitchesAction,
+}
+
+impl InsertMotifAction {
+  /// Creates an action that appends the motif palette items missing in the pattern and adds the motif stitches.
+  /// The stitches must already refer to the pattern palette, including the appended palette items.
+  pub fn new(palette_items: Vec<(PaletteItem, Symbols)>, stitches: Vec<Stitch>) -> Self {
+    Self {
+      palette_items: palette_items
+        .into_iter()
+        .map(|(palitem, symbols)| AddPaletteItemAction::new(palitem, symbols))
+        .collect(),
+      stitches: AddStitchesAction::new(stitches),
+    }
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for InsertMotifAction {
+  /// Add the palette items and then the stitches of the motif to the pattern.
+  ///
+  /// **Emits:**
+  /// - `palette:add_palette_item` for each added palette item
+  /// - `stitches:remove_many` with the removed stitches that conflict with the motif stitches
+  /// - `stitches:add_many` with the added stitches
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    for action in self.palette_items.iter() {
+      Action::<R>::perform(action, window, patproj)?;
+    }
+    Action::<R>::perform(&self.stitches, window, patproj)
+  }
+
+  /// Remove the stitches and then the palette items of the motif from the pattern.
+  ///
+  /// **Emits:**
+  /// - `stitches:remove_many` with the removed stitches
+  /// - `stitches:add_many` with the stitches that were removed when the motif was inserted
+  /// - `palette:remove_palette_item` for each removed palette item
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    Action::<R>::revoke(&self.stitches, window, patproj)?;
+    for action in self.palette_items.iter().rev() {
+      Action::<R>::revoke(action, window, patproj)?;
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,100 @@
+use ordered_float::NotNan;
+use tauri::test::{mock_builder, MockRuntime};
+use tauri::{generate_context, App, Listener, WebviewUrl, WebviewWindowBuilder};
+
+use super::{Action, InsertMotifAction};
+use crate::core::pattern::display::Symbols;
+use crate::core::pattern::*;
+
+fn setup_app() -> App<MockRuntime> {
+  mock_builder().build(generate_context!()).unwrap()
+}
+
+fn palitem(number: &str) -> PaletteItem {
+  PaletteItem {
+    brand: String::from("DMC"),
+    number: String::from(number),
+    name: String::new(),
+    color: String::from("FFFFFF"),
+    blends: None,
+    bead: None,
+    strands: None,
+  }
+}
+
+fn full(x: f32, palindex: u8) -> Stitch {
+  Stitch::Full(FullStitch {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(0.0).unwrap(),
+    palindex,
+    kind: FullStitchKind::Full,
+  })
+}
+
+#[test]
+fn test_insert_motif() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+  patproj.pattern.palette.push(palitem("310"));
+  patproj.display_settings.symbols.push(Symbols::default());
+  patproj.display_settings.formats.push(Default::default());
+  patproj.pattern.add_stitch(full(0.0, 0));
+
+  let stitches = vec![full(0.0, 1), full(1.0, 0)];
+  let action = InsertMotifAction::new(vec![(palitem("444"), Symbols::default())], stitches.clone());
+
+  // Test executing the command.
+  {
+    let add_palette_event = window.listen("palette:add_palette_item", |e| {
+      let payload: serde_json::Value = serde_json::from_str(e.payload()).unwrap();
+      assert_eq!(payload["palindex"], 1);
+    });
+    let remove_event = window.listen("stitches:remove_many", |e| {
+      assert_eq!(
+        serde_json::from_str::<Vec<Stitch>>(e.payload()).unwrap(),
+        vec![full(0.0, 0)]
+      );
+    });
+
+    action.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.palette, vec![palitem("310"), palitem("444")]);
+    assert_eq!(patproj.display_settings.symbols.len(), 2);
+    assert_eq!(
+      patproj
+        .pattern
+        .fullstitches
+        .iter()
+        .copied()
+        .map(Stitch::Full)
+        .collect::<Vec<_>>(),
+      stitches
+    );
+    window.unlisten(add_palette_event);
+    window.unlisten(remove_event);
+  }
+
+  // Test revoking the command.
+  {
+    window.listen("palette:remove_palette_item", |e| {
+      assert_eq!(serde_json::from_str::<usize>(e.payload()).unwrap(), 1);
+    });
+
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.palette, vec![palitem("310")]);
+    assert_eq!(patproj.display_settings.symbols.len(), 1);
+    assert_eq!(
+      patproj
+        .pattern
+        .fullstitches
+        .iter()
+        .copied()
+        .map(Stitch::Full)
+        .collect::<Vec<_>>(),
+      vec![full(0.0, 0)]
+    );
+  }
+}
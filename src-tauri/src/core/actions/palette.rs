@@ -1,12 +1,15 @@
 use std::sync::OnceLock;
 
-use anyhow::Result;
-use serde::Serialize;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use tauri::{Emitter, WebviewWindow};
 
 use super::Action;
 use crate::core::pattern::display::{Formats, Symbols};
-use crate::core::pattern::{PaletteItem, PatternProject, Stitch};
+use crate::core::pattern::progress::{ProgressEntry, ProgressStitch};
+use crate::core::pattern::{
+  moved_palindex, LayersDiff, PaletteItem, PatternProject, Region, SpecialStitch, Stitch, StitchKind,
+};
 
 #[cfg(test)]
 #[path = "palette.test.rs"]
@@ -21,10 +24,10 @@ pub struct AddPaletteItemAction {
 }
 
 impl AddPaletteItemAction {
-  pub fn new(palitem: PaletteItem) -> Self {
+  pub fn new(palitem: PaletteItem, symbols: Symbols) -> Self {
     Self {
       palitem,
-      symbols: Symbols::default(),
+      symbols,
       formats: Formats::default(),
     }
   }
@@ -76,6 +79,11 @@ struct RemovePaletteItemActionMetadata {
   symbols: Symbols,
   formats: Formats,
   conflicts: Vec<Stitch>,
+  specialconflicts: Vec<SpecialStitch>,
+  /// The removed stitches of the inactive layers.
+  layers: LayersDiff,
+  /// The progress of the removed stitches.
+  progress: Vec<ProgressEntry>,
 }
 
 impl RemovePaletteItemAction {
@@ -89,10 +97,12 @@ impl RemovePaletteItemAction {
 
 impl<R: tauri::Runtime> Action<R> for RemovePaletteItemAction {
   /// Remove the palette item from the pattern.
+  /// The palette item used by a locked layer can't be removed.
   ///
   /// **Emits:**
   /// - `palette:remove_palette_item` with the palette item index.
   /// - `stitches:remove_many` with the stitches that should be removed.
+  /// - `specialstitches:remove_many` with the special stitches that should be removed.
   fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
     let palindex = patproj
       .pattern
@@ -100,12 +110,15 @@ impl<R: tauri::Runtime> Action<R> for RemovePaletteItemAction {
       .iter()
       .position(|item| item == &self.palitem)
       .unwrap();
+    patproj.pattern.ensure_palindex_unlocked(palindex as u8)?;
     patproj.pattern.palette.remove(palindex);
     let symbols = patproj.display_settings.symbols.remove(palindex);
     let formats = patproj.display_settings.formats.remove(palindex);
-    let conflicts = patproj.pattern.remove_stitches_by_palindex(palindex as u8);
+    let (conflicts, specialconflicts, layers) = patproj.pattern.remove_stitches_by_palindex(palindex as u8);
+    let progress = remove_palette_item_progress(patproj, palindex as u8);
     window.emit("palette:remove_palette_item", palindex)?;
     window.emit("stitches:remove_many", &conflicts)?;
+    window.emit("specialstitches:remove_many", &specialconflicts)?;
     if self.metadata.get().is_none() {
       self
         .metadata
@@ -114,6 +127,9 @@ impl<R: tauri::Runtime> Action<R> for RemovePaletteItemAction {
           symbols,
           formats,
           conflicts,
+          specialconflicts,
+          layers,
+          progress,
         })
         .unwrap();
     }
@@ -125,6 +141,7 @@ impl<R: tauri::Runtime> Action<R> for RemovePaletteItemAction {
   /// **Emits:**
   /// - `palette:add_palette_item` with the added palette item and its related types.
   /// - `stitches:add_many` with the stitches that should be restored.
+  /// - `specialstitches:add_many` with the special stitches that should be restored.
   fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
     let metadata = self.metadata.get().unwrap();
     patproj.pattern.palette.insert(metadata.palindex, self.palitem.clone());
@@ -136,9 +153,13 @@ impl<R: tauri::Runtime> Action<R> for RemovePaletteItemAction {
       .display_settings
       .formats
       .insert(metadata.palindex, metadata.formats.clone());
-    patproj
-      .pattern
-      .restore_stitches(metadata.conflicts.clone(), metadata.palindex as u8);
+    patproj.pattern.restore_stitches(
+      &metadata.conflicts,
+      &metadata.specialconflicts,
+      &metadata.layers,
+      metadata.palindex as u8,
+    );
+    restore_palette_item_progress(patproj, metadata.palindex as u8, &metadata.progress);
     window.emit(
       "palette:add_palette_item",
       AddedPaletteItemData {
@@ -149,6 +170,539 @@ impl<R: tauri::Runtime> Action<R> for RemovePaletteItemAction {
       },
     )?;
     window.emit("stitches:add_many", &metadata.conflicts)?;
+    window.emit("specialstitches:add_many", &metadata.specialconflicts)?;
+    Ok(())
+  }
+}
+
+#[derive(Clone)]
+pub struct UpdatePaletteItemAction {
+  palindex: usize,
+  palitem: PaletteItem,
+  old_palitem: OnceLock<PaletteItem>,
+}
+
+impl UpdatePaletteItemAction {
+  pub fn new(palindex: usize, palitem: PaletteItem) -> Self {
+    Self {
+      palindex,
+      palitem,
+      old_palitem: OnceLock::new(),
+    }
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for UpdatePaletteItemAction {
+  /// Replace the palette item keeping its stitches.
+  ///
+  /// **Emits:**
+  /// - `palette:update_palette_item` with the palette item index and the new palette item.
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let palitem = patproj
+      .pattern
+      .palette
+      .get_mut(self.palindex)
+      .with_context(|| format!("There is no palette item with index {}", self.palindex))?;
+    let old_palitem = std::mem::replace(palitem, self.palitem.clone());
+    window.emit(
+      "palette:update_palette_item",
+      UpdatedPaletteItemData {
+        palindex: self.palindex,
+        palitem: self.palitem.clone(),
+      },
+    )?;
+    if self.old_palitem.get().is_none() {
+      self.old_palitem.set(old_palitem).unwrap();
+    }
+    Ok(())
+  }
+
+  /// Restore the previous palette item.
+  ///
+  /// **Emits:**
+  /// - `palette:update_palette_item` with the palette item index and the previous palette item.
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_palitem = self.old_palitem.get().unwrap();
+    patproj.pattern.palette[self.palindex] = old_palitem.clone();
+    window.emit(
+      "palette:update_palette_item",
+      UpdatedPaletteItemData {
+        palindex: self.palindex,
+        palitem: old_palitem.clone(),
+      },
+    )?;
+    Ok(())
+  }
+}
+
+#[derive(Clone)]
+pub struct MovePaletteItemAction {
+  from: usize,
+  to: usize,
+}
+
+impl MovePaletteItemAction {
+  pub fn new(from: usize, to: usize) -> Self {
+    Self { from, to }
+  }
+
+  fn move_palette_item<R: tauri::Runtime>(
+    window: &WebviewWindow<R>,
+    patproj: &mut PatternProject,
+    from: usize,
+    to: usize,
+  ) -> Result<()> {
+    let len = patproj.pattern.palette.len();
+    anyhow::ensure!(
+      from < len && to < len,
+      "There is no palette item with index {}",
+      from.max(to)
+    );
+
+    patproj.pattern.move_palette_item(from, to);
+    patproj
+      .progress
+      .remap_palindexes(|palindex| moved_palindex(palindex, from as u8, to as u8));
+    let symbols = patproj.display_settings.symbols.remove(from);
+    patproj.display_settings.symbols.insert(to, symbols);
+    let formats = patproj.display_settings.formats.remove(from);
+    patproj.display_settings.formats.insert(to, formats);
+
+    window.emit("palette:move_palette_item", MovedPaletteItemData { from, to })?;
+    Ok(())
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for MovePaletteItemAction {
+  /// Move the palette item to the new position.
+  /// The palette indices of all the stitches are updated accordingly.
+  ///
+  /// **Emits:**
+  /// - `palette:move_palette_item` with the old and new palette item indices.
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    Self::move_palette_item(window, patproj, self.from, self.to)
+  }
+
+  /// Move the palette item back to its previous position.
+  ///
+  /// **Emits:**
+  /// - `palette:move_palette_item` with the old and new palette item indices.
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    Self::move_palette_item(window, patproj, self.to, self.from)
+  }
+}
+
+/// The stitches affected by the palette index replacement.
+///
+/// It is serialized as `{ "scope": "stitch_kind", "value": "half" }`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "scope", content = "value", rename_all = "snake_case")]
+pub enum ReplacementScope {
+  All,
+  StitchKind(StitchKind),
+  Region(Region),
+}
+
+impl ReplacementScope {
+  fn contains(&self, stitch: &Stitch) -> bool {
+    match self {
+      Self::All => true,
+      Self::StitchKind(kind) => stitch.kind() == *kind,
+      Self::Region(region) => region.contains_stitch(stitch),
+    }
+  }
+
+  fn contains_special(&self, specialstitch: &SpecialStitch) -> bool {
+    match self {
+      Self::All => true,
+      Self::StitchKind(kind) => *kind == StitchKind::Special,
+      Self::Region(region) => region.contains_cell(specialstitch.x, specialstitch.y),
+    }
+  }
+}
+
+#[derive(Clone)]
+pub struct ReplacePaletteIndexAction {
+  from: usize,
+  to: usize,
+  scope: ReplacementScope,
+  remove_unused: bool,
+  metadata: OnceLock<ReplacePaletteIndexActionMetadata>,
+}
+
+#[derive(Debug, Clone)]
+struct ReplacePaletteIndexActionMetadata {
+  /// The replaced stitches as they were before the replacement.
+  stitches: Vec<Stitch>,
+  specialstitches: Vec<SpecialStitch>,
+  /// The replaced stitches of the inactive layers.
+  layers: LayersDiff,
+  /// The progress of the replaced stitches, which is moved to the replacement palette item.
+  progress: Vec<ProgressEntry>,
+  /// The palette item that was removed because it became unused, with the progress of its missing stitches.
+  removed: Option<(PaletteItem, Symbols, Formats, Vec<ProgressEntry>)>,
+}
+
+impl ReplacePaletteIndexAction {
+  /// Creates an action that makes the stitches of the `from` palette item use the `to` one.
+  /// If `remove_unused` is set and no stitches use the `from` palette item after that, it is removed from the palette.
+  pub fn new(from: usize, to: usize, scope: ReplacementScope, remove_unused: bool) -> Self {
+    Self {
+      from,
+      to,
+      scope,
+      remove_unused,
+      metadata: OnceLock::new(),
+    }
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for ReplacePaletteIndexAction {
+  /// Replace the palette index of the stitches in the scope.
+  /// The stitches of the locked layers are left as they are.
+  ///
+  /// **Emits:**
+  /// - `palette:remove_palette_item` with the palette item index if it was removed.
+  /// - `stitches:remove_many` with the stitches as they were before the replacement.
+  /// - `stitches:add_many` with the replaced stitches.
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let len = patproj.pattern.palette.len();
+    anyhow::ensure!(
+      self.from < len && self.to < len,
+      "There is no palette item with index {}",
+      self.from.max(self.to)
+    );
+    anyhow::ensure!(self.from != self.to, "The palette item can't be replaced with itself");
+
+    let pattern = &mut patproj.pattern;
+    let (from, to) = (self.from as u8, self.to as u8);
+    let (stitches, specialstitches, layers) = pattern.replace_palindex(
+      from,
+      to,
+      |stitch| self.scope.contains(stitch),
+      |specialstitch| self.scope.contains_special(specialstitch),
+    );
+
+    let progress = patproj
+      .progress
+      .recolor(replaced_stitches(&stitches, &specialstitches, &layers), to);
+
+    let mut removed = None;
+    if self.remove_unused && !pattern.is_palindex_used(from) {
+      let palitem = pattern.palette.remove(self.from);
+      pattern.remap_palindexes(|palindex| if palindex > from { palindex - 1 } else { palindex });
+      let removed_progress = remove_palette_item_progress(patproj, from);
+      let symbols = patproj.display_settings.symbols.remove(self.from);
+      let formats = patproj.display_settings.formats.remove(self.from);
+      window.emit("palette:remove_palette_item", self.from)?;
+      removed = Some((palitem, symbols, formats, removed_progress));
+    }
+
+    window.emit("stitches:remove_many", &stitches)?;
+    window.emit("stitches:add_many", patproj.pattern.stitches_at(&stitches))?;
+    if self.metadata.get().is_none() {
+      self
+        .metadata
+        .set(ReplacePaletteIndexActionMetadata {
+          stitches,
+          specialstitches,
+          layers,
+          progress,
+          removed,
+        })
+        .unwrap();
+    }
+    Ok(())
+  }
+
+  /// Restore the palette index of the replaced stitches and the removed palette item.
+  ///
+  /// **Emits:**
+  /// - `palette:add_palette_item` with the palette item and its related types if it was removed.
+  /// - `stitches:remove_many` with the replaced stitches.
+  /// - `stitches:add_many` with the restored stitches.
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let metadata = self.metadata.get().unwrap();
+    if let Some((palitem, symbols, formats, removed_progress)) = &metadata.removed {
+      let from = self.from as u8;
+      patproj
+        .pattern
+        .remap_palindexes(|palindex| if palindex >= from { palindex + 1 } else { palindex });
+      restore_palette_item_progress(patproj, from, removed_progress);
+      patproj.pattern.palette.insert(self.from, palitem.clone());
+      patproj.display_settings.symbols.insert(self.from, symbols.clone());
+      patproj.display_settings.formats.insert(self.from, formats.clone());
+      window.emit(
+        "palette:add_palette_item",
+        AddedPaletteItemData {
+          palitem: palitem.clone(),
+          palindex: self.from,
+          symbols: symbols.clone(),
+          formats: formats.clone(),
+        },
+      )?;
+    }
+
+    let replaced = patproj.pattern.stitches_at(&metadata.stitches);
+    patproj
+      .pattern
+      .put_back_stitches(&metadata.stitches, &metadata.specialstitches);
+    patproj.pattern.put_back_layers_stitches(&metadata.layers);
+    patproj.progress.unrecolor(&metadata.progress, self.to as u8);
+    window.emit("stitches:remove_many", replaced)?;
+    window.emit("stitches:add_many", &metadata.stitches)?;
+    Ok(())
+  }
+}
+
+#[derive(Clone)]
+pub struct CleanupPaletteAction {
+  remove_unused: bool,
+  merge_duplicates: bool,
+  metadata: OnceLock<CleanupPaletteActionMetadata>,
+}
+
+#[derive(Debug, Clone)]
+struct CleanupPaletteActionMetadata {
+  /// The previous indices of the palette items that were kept.
+  kept: Vec<usize>,
+  removed: Vec<RemovedPaletteItem>,
+  /// The stitches of the merged palette items as they were before the merge.
+  stitches: Vec<Stitch>,
+  specialstitches: Vec<SpecialStitch>,
+  /// The merged stitches of the inactive layers.
+  layers: LayersDiff,
+  /// The progress of the merged stitches with the palette indices it was moved to.
+  merged_progress: Vec<(u8, Vec<ProgressEntry>)>,
+  /// The progress of the missing stitches of the removed palette items.
+  removed_progress: Vec<ProgressEntry>,
+}
+
+#[derive(Debug, Clone)]
+struct RemovedPaletteItem {
+  palindex: usize,
+  palitem: PaletteItem,
+  symbols: Symbols,
+  formats: Formats,
+}
+
+impl CleanupPaletteAction {
+  /// Creates an action that merges the duplicate palette items into the first of them and removes the unused ones.
+  pub fn new(remove_unused: bool, merge_duplicates: bool) -> Self {
+    Self {
+      remove_unused,
+      merge_duplicates,
+      metadata: OnceLock::new(),
+    }
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for CleanupPaletteAction {
+  /// Merge the duplicate palette items and remove the unused ones.
+  /// The stitches of the locked layers are not merged, so the duplicates they use are kept.
+  ///
+  /// **Emits:**
+  /// - `palette:remove_palette_item` with the index of each removed palette item, from the last to the first.
+  /// - `stitches:remove_many` with the stitches of the merged palette items as they were before the merge.
+  /// - `stitches:add_many` with the merged stitches.
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let pattern = &mut patproj.pattern;
+    let mut is_removed = vec![false; pattern.palette.len()];
+
+    let mut stitches = Vec::new();
+    let mut specialstitches = Vec::new();
+    let mut layers = LayersDiff::default();
+    let mut merged_progress = Vec::new();
+    if self.merge_duplicates {
+      for group in pattern.analyze_palette().duplicates {
+        for &palindex in group[1..].iter() {
+          let (replaced, replaced_special, replaced_layers) =
+            pattern.replace_palindex(palindex as u8, group[0] as u8, |_| true, |_| true);
+          let moved = patproj.progress.recolor(
+            replaced_stitches(&replaced, &replaced_special, &replaced_layers),
+            group[0] as u8,
+          );
+          merged_progress.push((group[0] as u8, moved));
+          stitches.extend(replaced);
+          specialstitches.extend(replaced_special);
+          layers.append(replaced_layers);
+          // The duplicate is kept if the locked layers still use it.
+          is_removed[palindex] = !pattern.is_palindex_used(palindex as u8);
+        }
+      }
+    }
+    if self.remove_unused {
+      // The palette is analyzed after merging, so the duplicates that received the stitches are kept.
+      for palindex in pattern.analyze_palette().unused {
+        is_removed[palindex] = true;
+      }
+    }
+
+    let kept: Vec<usize> = (0..is_removed.len())
+      .filter(|&palindex| !is_removed[palindex])
+      .collect();
+    let mut new_palindexes = vec![0; is_removed.len()];
+    for (new_palindex, &palindex) in kept.iter().enumerate() {
+      new_palindexes[palindex] = new_palindex as u8;
+    }
+    pattern.remap_palindexes(|palindex| new_palindexes[palindex as usize]);
+    let removed_progress = patproj
+      .progress
+      .remove_palindexes(|palindex| is_removed[palindex as usize]);
+    patproj
+      .progress
+      .remap_palindexes(|palindex| new_palindexes[palindex as usize]);
+
+    let mut removed = Vec::new();
+    for palindex in (0..is_removed.len()).rev().filter(|&palindex| is_removed[palindex]) {
+      removed.push(RemovedPaletteItem {
+        palindex,
+        palitem: patproj.pattern.palette.remove(palindex),
+        symbols: patproj.display_settings.symbols.remove(palindex),
+        formats: patproj.display_settings.formats.remove(palindex),
+      });
+      window.emit("palette:remove_palette_item", palindex)?;
+    }
+    removed.reverse();
+
+    window.emit("stitches:remove_many", &stitches)?;
+    window.emit("stitches:add_many", patproj.pattern.stitches_at(&stitches))?;
+    if self.metadata.get().is_none() {
+      self
+        .metadata
+        .set(CleanupPaletteActionMetadata {
+          kept,
+          removed,
+          stitches,
+          specialstitches,
+          layers,
+          merged_progress,
+          removed_progress,
+        })
+        .unwrap();
+    }
+    Ok(())
+  }
+
+  /// Restore the removed palette items and the stitches of the merged ones.
+  ///
+  /// **Emits:**
+  /// - `palette:add_palette_item` with each restored palette item and its related types, from the first to the last.
+  /// - `stitches:remove_many` with the merged stitches.
+  /// - `stitches:add_many` with the restored stitches.
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let metadata = self.metadata.get().unwrap();
+    patproj
+      .pattern
+      .remap_palindexes(|palindex| metadata.kept[palindex as usize] as u8);
+    patproj
+      .progress
+      .remap_palindexes(|palindex| metadata.kept[palindex as usize] as u8);
+    patproj.progress.restore(&metadata.removed_progress);
+    for (palindex, moved) in metadata.merged_progress.iter().rev() {
+      patproj.progress.unrecolor(moved, *palindex);
+    }
+    for item in metadata.removed.iter() {
+      patproj.pattern.palette.insert(item.palindex, item.palitem.clone());
+      patproj
+        .display_settings
+        .symbols
+        .insert(item.palindex, item.symbols.clone());
+      patproj
+        .display_settings
+        .formats
+        .insert(item.palindex, item.formats.clone());
+      window.emit(
+        "palette:add_palette_item",
+        AddedPaletteItemData {
+          palitem: item.palitem.clone(),
+          palindex: item.palindex,
+          symbols: item.symbols.clone(),
+          formats: item.formats.clone(),
+        },
+      )?;
+    }
+
+    let merged = patproj.pattern.stitches_at(&metadata.stitches);
+    patproj
+      .pattern
+      .put_back_stitches(&metadata.stitches, &metadata.specialstitches);
+    patproj.pattern.put_back_layers_stitches(&metadata.layers);
+    window.emit("stitches:remove_many", merged)?;
+    window.emit("stitches:add_many", &metadata.stitches)?;
+    Ok(())
+  }
+}
+
+/// Returns the stitches of all the layers replaced by `Pattern::replace_palindex`.
+fn replaced_stitches<'a>(
+  stitches: &'a [Stitch],
+  specialstitches: &'a [SpecialStitch],
+  layers: &'a LayersDiff,
+) -> impl Iterator<Item = ProgressStitch> + 'a {
+  let stitches = stitches.iter().chain(layers.stitches()).map(|&stitch| stitch.into());
+  let specialstitches = specialstitches
+    .iter()
+    .chain(layers.specialstitches())
+    .map(|&specialstitch| specialstitch.into());
+  stitches.chain(specialstitches)
+}
+
+/// Removes the progress of the palette item and shifts the palette indices of the following ones.
+/// Only the marks of the missing stitches use the palette item, since its stitches are removed or replaced first.
+fn remove_palette_item_progress(patproj: &mut PatternProject, palindex: u8) -> Vec<ProgressEntry> {
+  let removed = patproj.progress.remove_palindexes(|index| index == palindex);
+  patproj
+    .progress
+    .remap_palindexes(|index| if index > palindex { index - 1 } else { index });
+  removed
+}
+
+/// Restores the progress removed by `remove_palette_item_progress`.
+fn restore_palette_item_progress(patproj: &mut PatternProject, palindex: u8, removed: &[ProgressEntry]) {
+  patproj
+    .progress
+    .remap_palindexes(|index| if index >= palindex { index + 1 } else { index });
+  patproj.progress.restore(removed);
+}
+
+#[derive(Clone)]
+pub struct ReassignSymbolsAction {
+  symbols: Vec<Symbols>,
+  old_symbols: OnceLock<Vec<Symbols>>,
+}
+
+impl ReassignSymbolsAction {
+  pub fn new(symbols: Vec<Symbols>) -> Self {
+    Self {
+      symbols,
+      old_symbols: OnceLock::new(),
+    }
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for ReassignSymbolsAction {
+  /// Replace the symbols of all the palette items.
+  ///
+  /// **Emits:**
+  /// - `palette:update_symbols` with the new symbols of all the palette items.
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_symbols = std::mem::replace(&mut patproj.display_settings.symbols, self.symbols.clone());
+    window.emit("palette:update_symbols", &self.symbols)?;
+    if self.old_symbols.get().is_none() {
+      self.old_symbols.set(old_symbols).unwrap();
+    }
+    Ok(())
+  }
+
+  /// Restore the previous symbols of all the palette items.
+  ///
+  /// **Emits:**
+  /// - `palette:update_symbols` with the previous symbols of all the palette items.
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_symbols = self.old_symbols.get().unwrap();
+    patproj.display_settings.symbols = old_symbols.clone();
+    window.emit("palette:update_symbols", old_symbols)?;
     Ok(())
   }
 }
@@ -162,3 +716,18 @@ struct AddedPaletteItemData {
   symbols: Symbols,
   formats: Formats,
 }
+
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(test, derive(PartialEq, serde::Deserialize))]
+struct UpdatedPaletteItemData {
+  palindex: usize,
+  #[serde(rename = "paletteItem")]
+  palitem: PaletteItem,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(test, derive(PartialEq, serde::Deserialize))]
+struct MovedPaletteItemData {
+  from: usize,
+  to: usize,
+}
@@ -1,8 +1,13 @@
 use display::{Formats, Symbols};
+use ordered_float::NotNan;
 use tauri::test::{mock_builder, MockRuntime};
 use tauri::{generate_context, App, Listener, WebviewUrl, WebviewWindowBuilder};
 
-use super::{Action, AddPaletteItemAction, AddedPaletteItemData, RemovePaletteItemAction};
+use super::{
+  Action, AddPaletteItemAction, AddedPaletteItemData, CleanupPaletteAction, MovePaletteItemAction,
+  MovedPaletteItemData, ReassignSymbolsAction, RemovePaletteItemAction, ReplacePaletteIndexAction, ReplacementScope,
+  UpdatePaletteItemAction, UpdatedPaletteItemData,
+};
 use crate::core::parser::oxs;
 use crate::core::pattern::*;
 
@@ -32,17 +37,20 @@ fn test_add_palette_item() {
     bead: None,
     strands: None,
   };
-  let action = AddPaletteItemAction::new(palitem.clone());
+  let symbols =
+    symbols::SymbolAllocator::new(vec![u16::from(b'X')], &patproj.display_settings.symbols).allocate_symbols();
+  let action = AddPaletteItemAction::new(palitem.clone(), symbols.clone());
 
   // Test executing the command.
   {
+    let expected_symbols = symbols.clone();
     window.listen("palette:add_palette_item", move |e| {
       assert_eq!(
         serde_json::from_str::<AddedPaletteItemData>(e.payload()).unwrap(),
         AddedPaletteItemData {
           palitem: palitem.clone(),
           palindex: 7,
-          symbols: Symbols::default(),
+          symbols: expected_symbols.clone(),
           formats: Formats::default(),
         }
       );
@@ -51,6 +59,7 @@ fn test_add_palette_item() {
     assert_eq!(patproj.pattern.palette.len(), 7);
     action.perform(&window, &mut patproj).unwrap();
     assert_eq!(patproj.pattern.palette.len(), 8);
+    assert_eq!(patproj.display_settings.symbols[7], symbols);
   }
 
   // Test revoking the command.
@@ -120,3 +129,491 @@ fn test_remove_palette_item() {
     assert_eq!(patproj.pattern.palette.len(), 7);
   }
 }
+
+#[test]
+fn test_reassign_symbols() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let old_symbols = patproj.display_settings.symbols.clone();
+  let new_symbols = symbols::reassign_symbols(patproj.pattern.palette.len(), vec![u16::from(b'X'), u16::from(b'O')]);
+  let action = ReassignSymbolsAction::new(new_symbols.clone());
+
+  // Test executing the command.
+  {
+    let expected_symbols = new_symbols.clone();
+    let event = window.listen("palette:update_symbols", move |e| {
+      assert_eq!(
+        serde_json::from_str::<Vec<Symbols>>(e.payload()).unwrap(),
+        expected_symbols
+      );
+    });
+
+    action.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.display_settings.symbols, new_symbols);
+    window.unlisten(event);
+  }
+
+  // Test revoking the command.
+  {
+    let expected_symbols = old_symbols.clone();
+    window.listen("palette:update_symbols", move |e| {
+      assert_eq!(
+        serde_json::from_str::<Vec<Symbols>>(e.payload()).unwrap(),
+        expected_symbols
+      );
+    });
+
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.display_settings.symbols, old_symbols);
+  }
+}
+
+#[test]
+fn test_update_palette_item() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let old_palitem = patproj.pattern.palette[2].clone();
+  let stitches_count = patproj.pattern.fullstitches.len();
+  let palitem = PaletteItem {
+    color: String::from("FF0000"),
+    strands: Some(PaletteItemStitchStrands {
+      full: Some(StitchStrands::new(3)),
+      ..Default::default()
+    }),
+    ..old_palitem.clone()
+  };
+  let action = UpdatePaletteItemAction::new(2, palitem.clone());
+
+  // Test executing the command.
+  {
+    let expected_palitem = palitem.clone();
+    let event = window.listen("palette:update_palette_item", move |e| {
+      assert_eq!(
+        serde_json::from_str::<UpdatedPaletteItemData>(e.payload()).unwrap(),
+        UpdatedPaletteItemData {
+          palindex: 2,
+          palitem: expected_palitem.clone(),
+        }
+      );
+    });
+
+    action.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.palette[2], palitem);
+    assert_eq!(patproj.pattern.fullstitches.len(), stitches_count);
+    window.unlisten(event);
+  }
+
+  // Test revoking the command.
+  {
+    let expected_palitem = old_palitem.clone();
+    window.listen("palette:update_palette_item", move |e| {
+      assert_eq!(
+        serde_json::from_str::<UpdatedPaletteItemData>(e.payload()).unwrap(),
+        UpdatedPaletteItemData {
+          palindex: 2,
+          palitem: expected_palitem.clone(),
+        }
+      );
+    });
+
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.palette[2], old_palitem);
+  }
+
+  // The palette item must exist.
+  let action = UpdatePaletteItemAction::new(100, palitem);
+  assert!(action.perform(&window, &mut patproj).is_err());
+}
+
+#[test]
+fn test_move_palette_item() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let original = patproj.clone();
+  let count_stitches = |pattern: &Pattern, palindex: u8| {
+    pattern
+      .fullstitches
+      .iter()
+      .filter(|stitch| stitch.palindex == palindex)
+      .count()
+  };
+  let action = MovePaletteItemAction::new(0, 3);
+
+  // Test executing the command.
+  {
+    let event = window.listen("palette:move_palette_item", move |e| {
+      assert_eq!(
+        serde_json::from_str::<MovedPaletteItemData>(e.payload()).unwrap(),
+        MovedPaletteItemData { from: 0, to: 3 }
+      );
+    });
+
+    action.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.palette[3], original.pattern.palette[0]);
+    assert_eq!(patproj.pattern.palette[0], original.pattern.palette[1]);
+    assert_eq!(
+      patproj.display_settings.symbols[3],
+      original.display_settings.symbols[0]
+    );
+    assert_eq!(
+      patproj.display_settings.formats[2],
+      original.display_settings.formats[3]
+    );
+    for (from, to) in [(0, 3), (1, 0), (3, 2), (4, 4)] {
+      assert_eq!(
+        count_stitches(&patproj.pattern, to),
+        count_stitches(&original.pattern, from)
+      );
+    }
+    window.unlisten(event);
+  }
+
+  // Test revoking the command.
+  {
+    window.listen("palette:move_palette_item", move |e| {
+      assert_eq!(
+        serde_json::from_str::<MovedPaletteItemData>(e.payload()).unwrap(),
+        MovedPaletteItemData { from: 3, to: 0 }
+      );
+    });
+
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.palette, original.pattern.palette);
+    assert_eq!(patproj.display_settings.symbols, original.display_settings.symbols);
+    assert!(patproj
+      .pattern
+      .fullstitches
+      .iter()
+      .eq(original.pattern.fullstitches.iter()));
+    assert!(patproj.pattern.lines.iter().eq(original.pattern.lines.iter()));
+  }
+}
+
+fn count_stitches_by_palindex(pattern: &Pattern, palindex: u8) -> usize {
+  pattern
+    .fullstitches
+    .iter()
+    .filter(|stitch| stitch.palindex == palindex)
+    .count()
+    + pattern
+      .partstitches
+      .iter()
+      .filter(|stitch| stitch.palindex == palindex)
+      .count()
+    + pattern
+      .lines
+      .iter()
+      .filter(|stitch| stitch.palindex == palindex)
+      .count()
+    + pattern
+      .nodes
+      .iter()
+      .filter(|stitch| stitch.palindex == palindex)
+      .count()
+}
+
+#[test]
+fn test_replace_palette_index() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let original = patproj.clone();
+  let (from_count, to_count) = (
+    count_stitches_by_palindex(&original.pattern, 1),
+    count_stitches_by_palindex(&original.pattern, 3),
+  );
+  assert!(from_count > 0);
+  let action = ReplacePaletteIndexAction::new(1, 3, ReplacementScope::All, true);
+
+  // Test executing the command.
+  {
+    let event = window.listen("palette:remove_palette_item", move |e| {
+      assert_eq!(serde_json::from_str::<usize>(e.payload()).unwrap(), 1);
+    });
+
+    action.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.palette.len(), original.pattern.palette.len() - 1);
+    assert_eq!(patproj.display_settings.symbols.len(), patproj.pattern.palette.len());
+    // The indices after the removed palette item are shifted.
+    assert_eq!(patproj.pattern.palette[2], original.pattern.palette[3]);
+    assert_eq!(count_stitches_by_palindex(&patproj.pattern, 2), from_count + to_count);
+    window.unlisten(event);
+  }
+
+  // Test revoking the command.
+  {
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.palette, original.pattern.palette);
+    assert_eq!(patproj.display_settings.symbols, original.display_settings.symbols);
+    assert!(patproj
+      .pattern
+      .fullstitches
+      .iter()
+      .eq(original.pattern.fullstitches.iter()));
+    assert!(patproj
+      .pattern
+      .partstitches
+      .iter()
+      .eq(original.pattern.partstitches.iter()));
+    assert!(patproj.pattern.lines.iter().eq(original.pattern.lines.iter()));
+    assert!(patproj.pattern.nodes.iter().eq(original.pattern.nodes.iter()));
+  }
+}
+
+/// Moves all the stitches of the pattern to the bottom inactive layer.
+fn move_stitches_to_inactive_layer(patproj: &mut PatternProject) {
+  patproj
+    .pattern
+    .insert_layer(1, Layer::new(LayerProperties::new(String::from("Layer 2"))))
+    .unwrap();
+  patproj.pattern.set_active_layer(1).unwrap();
+}
+
+#[test]
+fn test_replace_palette_index_on_inactive_layers() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let original = patproj.clone();
+  move_stitches_to_inactive_layer(&mut patproj);
+  let action = ReplacePaletteIndexAction::new(1, 3, ReplacementScope::All, true);
+
+  action.perform(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.pattern.palette.len(), original.pattern.palette.len() - 1);
+
+  action.revoke(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.pattern.palette, original.pattern.palette);
+  patproj.pattern.set_active_layer(0).unwrap();
+  assert!(patproj
+    .pattern
+    .fullstitches
+    .iter()
+    .eq(original.pattern.fullstitches.iter()));
+  assert!(patproj.pattern.lines.iter().eq(original.pattern.lines.iter()));
+}
+
+#[test]
+fn test_palette_changes_keep_locked_layers() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let original = patproj.clone();
+  move_stitches_to_inactive_layer(&mut patproj);
+  let mut properties = patproj.pattern.layers.info()[0].properties.clone();
+  properties.locked = true;
+  patproj.pattern.update_layer(0, properties).unwrap();
+
+  // The palette item used by the locked layer can't be removed.
+  let action = RemovePaletteItemAction::new(original.pattern.palette[1].clone());
+  assert!(action.perform(&window, &mut patproj).is_err());
+  assert_eq!(patproj.pattern.palette, original.pattern.palette);
+
+  // The stitches of the locked layer are not replaced, so the palette item stays.
+  let action = ReplacePaletteIndexAction::new(1, 3, ReplacementScope::All, true);
+  action.perform(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.pattern.palette, original.pattern.palette);
+  patproj.pattern.set_active_layer(0).unwrap();
+  assert!(patproj
+    .pattern
+    .fullstitches
+    .iter()
+    .eq(original.pattern.fullstitches.iter()));
+}
+
+#[test]
+fn test_replace_palette_index_in_scope() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let fullstitch = *patproj.pattern.fullstitches.iter().next().unwrap();
+  let palindex = fullstitch.palindex;
+  let other = if palindex == 0 { 1 } else { 0 };
+  let region = Region {
+    x: fullstitch.x.into_inner() as u16,
+    y: fullstitch.y.into_inner() as u16,
+    width: 1,
+    height: 1,
+  };
+
+  // Only the stitch in the region is replaced, so the palette item is still used and kept.
+  let action = ReplacePaletteIndexAction::new(
+    palindex as usize,
+    other as usize,
+    ReplacementScope::Region(region),
+    true,
+  );
+  action.perform(&window, &mut patproj).unwrap();
+  assert_eq!(patproj.pattern.palette.len(), 7);
+  assert_eq!(patproj.pattern.fullstitches.get(&fullstitch).unwrap().palindex, other);
+  action.revoke(&window, &mut patproj).unwrap();
+  assert_eq!(
+    patproj.pattern.fullstitches.get(&fullstitch).unwrap().palindex,
+    palindex
+  );
+
+  // The scope can be limited to a single stitch kind.
+  let scope = ReplacementScope::StitchKind(StitchKind::Back);
+  assert!(scope.contains(&Stitch::Line(Line {
+    x: (NotNan::new(0.0).unwrap(), NotNan::new(1.0).unwrap()),
+    y: (NotNan::new(0.0).unwrap(), NotNan::new(1.0).unwrap()),
+    palindex: 0,
+    kind: LineKind::Back,
+  })));
+  assert!(!scope.contains(&Stitch::Full(fullstitch)));
+}
+
+#[test]
+fn test_deserialize_replacement_scope() {
+  assert_eq!(
+    serde_json::from_str::<ReplacementScope>(r#"{"scope":"stitch_kind","value":"french_knot"}"#).unwrap(),
+    ReplacementScope::StitchKind(StitchKind::FrenchKnot)
+  );
+  assert_eq!(
+    serde_json::from_str::<ReplacementScope>(r#"{"scope":"all"}"#).unwrap(),
+    ReplacementScope::All
+  );
+}
+
+/// Appends the duplicate of the palette item with a single stitch and the unused palette item.
+fn create_pattern_project_with_redundant_palette() -> PatternProject {
+  let mut patproj = create_pattern_project();
+  let duplicate = patproj.pattern.palette[2].clone();
+  let unused = PaletteItem {
+    brand: String::from("DMC"),
+    number: String::from("3825"),
+    name: String::from("Pumpkin-Pale"),
+    color: String::from("F5BA82"),
+    blends: None,
+    bead: None,
+    strands: None,
+  };
+  for palitem in [duplicate, unused] {
+    patproj.pattern.palette.push(palitem);
+    patproj.display_settings.symbols.push(Symbols::default());
+    patproj.display_settings.formats.push(Formats::default());
+  }
+  patproj.pattern.fullstitches.insert(FullStitch {
+    x: NotNan::new(1000.0).unwrap(),
+    y: NotNan::new(1000.0).unwrap(),
+    palindex: 7,
+    kind: FullStitchKind::Full,
+  });
+  patproj
+}
+
+#[test]
+fn test_analyze_palette() {
+  let patproj = create_pattern_project_with_redundant_palette();
+  let analysis = patproj.pattern.analyze_palette();
+  assert!(analysis.unused.contains(&8));
+  assert!(!analysis.unused.contains(&7));
+  assert_eq!(analysis.duplicates, vec![vec![2, 7]]);
+}
+
+#[test]
+fn test_analyze_palette_without_thread_numbers() {
+  let mut patproj = create_pattern_project();
+  let custom = |color: &str| PaletteItem {
+    brand: String::new(),
+    number: String::new(),
+    name: String::new(),
+    color: String::from(color),
+    blends: None,
+    bead: None,
+    strands: None,
+  };
+  let start = patproj.pattern.palette.len();
+  patproj
+    .pattern
+    .palette
+    .extend([custom("FF0000"), custom("00FF00"), custom("ff0000")]);
+  let analysis = patproj.pattern.analyze_palette();
+  assert_eq!(analysis.duplicates, vec![vec![start, start + 2]]);
+}
+
+#[test]
+fn test_cleanup_palette() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project_with_redundant_palette();
+  let original = patproj.clone();
+  let stitch = FullStitch {
+    x: NotNan::new(1000.0).unwrap(),
+    y: NotNan::new(1000.0).unwrap(),
+    palindex: 7,
+    kind: FullStitchKind::Full,
+  };
+  let action = CleanupPaletteAction::new(true, true);
+
+  // Test executing the command.
+  {
+    action.perform(&window, &mut patproj).unwrap();
+    let analysis = patproj.pattern.analyze_palette();
+    assert!(analysis.unused.is_empty());
+    assert!(analysis.duplicates.is_empty());
+    assert_eq!(patproj.display_settings.symbols.len(), patproj.pattern.palette.len());
+    assert_eq!(patproj.display_settings.formats.len(), patproj.pattern.palette.len());
+
+    // The stitch of the duplicate uses the palette item it was merged into.
+    let merged = patproj.pattern.fullstitches.get(&stitch).unwrap();
+    assert_eq!(
+      patproj.pattern.palette[merged.palindex as usize],
+      original.pattern.palette[2]
+    );
+    // All the other stitches keep their threads.
+    for fullstitch in original
+      .pattern
+      .fullstitches
+      .iter()
+      .filter(|fullstitch| fullstitch.palindex != 7)
+    {
+      let current = patproj.pattern.fullstitches.get(fullstitch).unwrap();
+      assert_eq!(
+        patproj.pattern.palette[current.palindex as usize],
+        original.pattern.palette[fullstitch.palindex as usize]
+      );
+    }
+  }
+
+  // Test revoking the command.
+  {
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.palette, original.pattern.palette);
+    assert_eq!(patproj.display_settings.symbols, original.display_settings.symbols);
+    assert_eq!(patproj.display_settings.formats, original.display_settings.formats);
+    assert!(patproj
+      .pattern
+      .fullstitches
+      .iter()
+      .eq(original.pattern.fullstitches.iter()));
+    assert!(patproj.pattern.lines.iter().eq(original.pattern.lines.iter()));
+    assert!(patproj.pattern.nodes.iter().eq(original.pattern.nodes.iter()));
+  }
+}
@@ -0,0 +1,172 @@
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use tauri::{Emitter, WebviewWindow};
+
+use super::Action;
+use crate::core::pattern::{Fabric, LayersDiff, PatternInfo, PatternProject, PatternProperties, SpecialStitch, Stitch};
+
+#[cfg(test)]
+#[path = "pattern.test.rs"]
+mod tests;
+
+#[derive(Clone)]
+pub struct UpdatePatternInfoAction {
+  info: PatternInfo,
+  old_info: OnceLock<PatternInfo>,
+}
+
+impl UpdatePatternInfoAction {
+  pub fn new(info: PatternInfo) -> Self {
+    Self { info, old_info: OnceLock::new() }
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for UpdatePatternInfoAction {
+  /// Update the pattern information.
+  ///
+  /// **Emits:**
+  /// - `pattern:update_info` with the new pattern information
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_info = std::mem::replace(&mut patproj.pattern.info, self.info.clone());
+    window.emit("pattern:update_info", &self.info)?;
+    if self.old_info.get().is_none() {
+      self.old_info.set(old_info).unwrap();
+    }
+    Ok(())
+  }
+
+  /// Restore the previous pattern information.
+  ///
+  /// **Emits:**
+  /// - `pattern:update_info` with the previous pattern information
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_info = self.old_info.get().unwrap();
+    patproj.pattern.info = old_info.clone();
+    window.emit("pattern:update_info", old_info)?;
+    Ok(())
+  }
+}
+
+/// The state of the pattern before resizing.
+#[derive(Debug, Clone)]
+struct ResizedPatternData {
+  properties: PatternProperties,
+  /// The stitches that didn't fit the new size.
+  stitches: Vec<Stitch>,
+  specialstitches: Vec<SpecialStitch>,
+  /// The stitches of the inactive layers that didn't fit the new size.
+  layers: LayersDiff,
+}
+
+#[derive(Clone)]
+pub struct UpdatePatternPropertiesAction {
+  properties: PatternProperties,
+  old_data: OnceLock<ResizedPatternData>,
+}
+
+impl UpdatePatternPropertiesAction {
+  pub fn new(properties: PatternProperties) -> Self {
+    Self {
+      properties,
+      old_data: OnceLock::new(),
+    }
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for UpdatePatternPropertiesAction {
+  /// Update the pattern properties.
+  /// When the pattern is shrunk, the stitches of all the layers that don't fit the new size are removed.
+  /// The pattern must be at least one cell wide and high.
+  ///
+  /// **Emits:**
+  /// - `pattern:update_properties` with the new pattern properties
+  /// - `stitches:remove_many` with the removed stitches
+  /// - `specialstitches:remove_many` with the removed special stitches
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let PatternProperties { width, height } = self.properties;
+    anyhow::ensure!(
+      width >= 1 && height >= 1,
+      "The pattern size {width}x{height} is invalid"
+    );
+    let (stitches, specialstitches, layers) = patproj
+      .pattern
+      .remove_stitches_outside(self.properties.width, self.properties.height);
+    let properties = std::mem::replace(&mut patproj.pattern.properties, self.properties.clone());
+    window.emit("pattern:update_properties", &self.properties)?;
+    window.emit("stitches:remove_many", &stitches)?;
+    window.emit("specialstitches:remove_many", &specialstitches)?;
+    if self.old_data.get().is_none() {
+      let old_data = ResizedPatternData {
+        properties,
+        stitches,
+        specialstitches,
+        layers,
+      };
+      self.old_data.set(old_data).unwrap();
+    }
+    Ok(())
+  }
+
+  /// Restore the previous pattern properties and the removed stitches.
+  ///
+  /// **Emits:**
+  /// - `pattern:update_properties` with the previous pattern properties
+  /// - `stitches:add_many` with the restored stitches
+  /// - `specialstitches:add_many` with the restored special stitches
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_data = self.old_data.get().unwrap();
+    patproj.pattern.properties = old_data.properties.clone();
+    patproj.pattern.add_stitches(old_data.stitches.clone());
+    patproj
+      .pattern
+      .specialstitches
+      .extend(old_data.specialstitches.iter().copied().collect());
+    patproj.pattern.put_back_layers_stitches(&old_data.layers);
+    window.emit("pattern:update_properties", &old_data.properties)?;
+    window.emit("stitches:add_many", &old_data.stitches)?;
+    window.emit("specialstitches:add_many", &old_data.specialstitches)?;
+    Ok(())
+  }
+}
+
+#[derive(Clone)]
+pub struct UpdateFabricAction {
+  fabric: Fabric,
+  old_fabric: OnceLock<Fabric>,
+}
+
+impl UpdateFabricAction {
+  pub fn new(fabric: Fabric) -> Self {
+    Self {
+      fabric,
+      old_fabric: OnceLock::new(),
+    }
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for UpdateFabricAction {
+  /// Update the fabric.
+  ///
+  /// **Emits:**
+  /// - `pattern:update_fabric` with the new fabric
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_fabric = std::mem::replace(&mut patproj.pattern.fabric, self.fabric.clone());
+    window.emit("pattern:update_fabric", &self.fabric)?;
+    if self.old_fabric.get().is_none() {
+      self.old_fabric.set(old_fabric).unwrap();
+    }
+    Ok(())
+  }
+
+  /// Restore the previous fabric.
+  ///
+  /// **Emits:**
+  /// - `pattern:update_fabric` with the previous fabric
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_fabric = self.old_fabric.get().unwrap();
+    patproj.pattern.fabric = old_fabric.clone();
+    window.emit("pattern:update_fabric", old_fabric)?;
+    Ok(())
+  }
+}
@@ -0,0 +1,209 @@
+use ordered_float::NotNan;
+use tauri::test::{mock_builder, MockRuntime};
+use tauri::{generate_context, App, Listener, WebviewUrl, WebviewWindowBuilder};
+
+use super::{Action, UpdateFabricAction, UpdatePatternInfoAction, UpdatePatternPropertiesAction};
+use crate::core::pattern::*;
+
+fn setup_app() -> App<MockRuntime> {
+  mock_builder().build(generate_context!()).unwrap()
+}
+
+fn create_pattern_project() -> PatternProject {
+  let mut patproj = PatternProject::default();
+  patproj.pattern.properties = PatternProperties { width: 10, height: 10 };
+
+  // Inside the pattern.
+  patproj.pattern.fullstitches.insert(FullStitch {
+    x: NotNan::new(4.0).unwrap(),
+    y: NotNan::new(4.0).unwrap(),
+    palindex: 0,
+    kind: FullStitchKind::Full,
+  });
+  // On the edge of the shrunk pattern.
+  patproj.pattern.lines.insert(Line {
+    x: (NotNan::new(3.0).unwrap(), NotNan::new(5.0).unwrap()),
+    y: (NotNan::new(5.0).unwrap(), NotNan::new(5.0).unwrap()),
+    palindex: 0,
+    kind: LineKind::Back,
+  });
+  // Outside the shrunk pattern.
+  patproj.pattern.partstitches.insert(PartStitch {
+    x: NotNan::new(5.5).unwrap(),
+    y: NotNan::new(0.0).unwrap(),
+    palindex: 0,
+    kind: PartStitchKind::Quarter,
+    direction: PartStitchDirection::Forward,
+  });
+  patproj.pattern.nodes.insert(Node {
+    x: NotNan::new(2.0).unwrap(),
+    y: NotNan::new(7.0).unwrap(),
+    rotated: false,
+    palindex: 0,
+    kind: NodeKind::FrenchKnot,
+  });
+  patproj.pattern.specialstitches.insert(SpecialStitch {
+    x: NotNan::new(6.0).unwrap(),
+    y: NotNan::new(6.0).unwrap(),
+    rotation: Degree::new(0),
+    flip: (false, false),
+    palindex: 0,
+    modindex: 0,
+  });
+
+  patproj
+}
+
+#[test]
+fn test_update_pattern_info() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let old_info = patproj.pattern.info.clone();
+  let info = PatternInfo {
+    title: String::from("Piggies"),
+    author: String::from("Jane Doe"),
+    ..PatternInfo::default()
+  };
+  let action = UpdatePatternInfoAction::new(info.clone());
+
+  // Test executing the command.
+  {
+    let expected_info = info.clone();
+    let event = window.listen("pattern:update_info", move |e| {
+      assert_eq!(serde_json::from_str::<PatternInfo>(e.payload()).unwrap(), expected_info);
+    });
+
+    action.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.info, info);
+    window.unlisten(event);
+  }
+
+  // Test revoking the command.
+  {
+    let expected_info = old_info.clone();
+    window.listen("pattern:update_info", move |e| {
+      assert_eq!(serde_json::from_str::<PatternInfo>(e.payload()).unwrap(), expected_info);
+    });
+
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.info, old_info);
+  }
+}
+
+#[test]
+fn test_update_pattern_properties() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let properties = PatternProperties { width: 5, height: 5 };
+  let action = UpdatePatternPropertiesAction::new(properties.clone());
+
+  // Test executing the command.
+  {
+    let remove_event = window.listen("stitches:remove_many", move |e| {
+      assert_eq!(serde_json::from_str::<Vec<Stitch>>(e.payload()).unwrap().len(), 2);
+    });
+
+    let remove_special_event = window.listen("specialstitches:remove_many", move |e| {
+      assert_eq!(
+        serde_json::from_str::<Vec<SpecialStitch>>(e.payload()).unwrap().len(),
+        1
+      );
+    });
+
+    action.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.properties, properties);
+    assert_eq!(patproj.pattern.fullstitches.len(), 1);
+    assert_eq!(patproj.pattern.lines.len(), 1);
+    assert_eq!(patproj.pattern.partstitches.len(), 0);
+    assert_eq!(patproj.pattern.nodes.len(), 0);
+    assert_eq!(patproj.pattern.specialstitches.len(), 0);
+    window.unlisten(remove_event);
+    window.unlisten(remove_special_event);
+  }
+
+  // Test revoking the command.
+  {
+    window.listen("stitches:add_many", move |e| {
+      assert_eq!(serde_json::from_str::<Vec<Stitch>>(e.payload()).unwrap().len(), 2);
+    });
+
+    window.listen("specialstitches:add_many", move |e| {
+      assert_eq!(
+        serde_json::from_str::<Vec<SpecialStitch>>(e.payload()).unwrap().len(),
+        1
+      );
+    });
+
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.properties, PatternProperties { width: 10, height: 10 });
+    assert_eq!(patproj.pattern.partstitches.len(), 1);
+    assert_eq!(patproj.pattern.nodes.len(), 1);
+    assert_eq!(patproj.pattern.specialstitches.len(), 1);
+  }
+}
+
+#[test]
+fn test_update_pattern_properties_rejects_empty_size() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  for properties in [
+    PatternProperties { width: 0, height: 5 },
+    PatternProperties { width: 5, height: 0 },
+  ] {
+    let action = UpdatePatternPropertiesAction::new(properties);
+    assert!(action.perform(&window, &mut patproj).is_err());
+    assert_eq!(patproj.pattern.properties, PatternProperties { width: 10, height: 10 });
+    assert_eq!(patproj.pattern.partstitches.len(), 1);
+  }
+}
+
+#[test]
+fn test_update_fabric() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let fabric = Fabric {
+    spi: (28, 28),
+    kind: String::from("Evenweave"),
+    name: String::from("Antique White"),
+    color: String::from("FAEBD7"),
+  };
+  let action = UpdateFabricAction::new(fabric.clone());
+
+  // Test executing the command.
+  {
+    let expected_fabric = fabric.clone();
+    let event = window.listen("pattern:update_fabric", move |e| {
+      assert_eq!(serde_json::from_str::<Fabric>(e.payload()).unwrap(), expected_fabric);
+    });
+
+    action.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.fabric, fabric);
+    window.unlisten(event);
+  }
+
+  // Test revoking the command.
+  {
+    window.listen("pattern:update_fabric", move |e| {
+      assert_eq!(serde_json::from_str::<Fabric>(e.payload()).unwrap(), Fabric::default());
+    });
+
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.fabric, Fabric::default());
+  }
+}
@@ -0,0 +1,70 @@
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use tauri::{Emitter, WebviewWindow};
+
+use super::Action;
+use crate::core::pattern::progress::{ProgressEntry, ProgressStitch};
+use crate::core::pattern::PatternProject;
+
+#[cfg(test)]
+#[path = "progress.test.rs"]
+mod tests;
+
+#[derive(Clone)]
+pub struct MarkProgressAction {
+  stitches: Vec<ProgressStitch>,
+  /// The time the stitches are completed at or `None` to mark them as not completed.
+  completed_at: Option<u64>,
+  old_entries: OnceLock<Vec<ProgressEntry>>,
+}
+
+impl MarkProgressAction {
+  pub fn new(stitches: Vec<ProgressStitch>, completed_at: Option<u64>) -> Self {
+    Self {
+      stitches,
+      completed_at,
+      old_entries: OnceLock::new(),
+    }
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for MarkProgressAction {
+  /// Mark the stitches as completed or not completed.
+  ///
+  /// **Emits:**
+  /// - `progress:update` with the new completion state of the stitches
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_entries: Vec<ProgressEntry> = self
+      .stitches
+      .iter()
+      .map(|&stitch| {
+        let completed_at = patproj.progress.set_completed_at(stitch, self.completed_at);
+        ProgressEntry::new(stitch, completed_at)
+      })
+      .collect();
+    let entries: Vec<ProgressEntry> = self
+      .stitches
+      .iter()
+      .map(|&stitch| ProgressEntry::new(stitch, self.completed_at))
+      .collect();
+    window.emit("progress:update", entries)?;
+    if self.old_entries.get().is_none() {
+      self.old_entries.set(old_entries).unwrap();
+    }
+    Ok(())
+  }
+
+  /// Restore the previous completion state of the stitches.
+  ///
+  /// **Emits:**
+  /// - `progress:update` with the previous completion state of the stitches
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let old_entries = self.old_entries.get().unwrap();
+    for entry in old_entries.iter() {
+      patproj.progress.set_completed_at(entry.stitch, entry.completed_at);
+    }
+    window.emit("progress:update", old_entries)?;
+    Ok(())
+  }
+}
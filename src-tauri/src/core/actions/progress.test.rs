@@ -0,0 +1,73 @@
+use ordered_float::NotNan;
+use tauri::test::{mock_builder, MockRuntime};
+use tauri::{generate_context, App, Listener, WebviewUrl, WebviewWindowBuilder};
+
+use super::{Action, MarkProgressAction};
+use crate::core::pattern::progress::ProgressEntry;
+use crate::core::pattern::*;
+
+fn setup_app() -> App<MockRuntime> {
+  mock_builder().build(generate_context!()).unwrap()
+}
+
+fn full(x: f32) -> Stitch {
+  Stitch::Full(FullStitch {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(0.0).unwrap(),
+    palindex: 0,
+    kind: FullStitchKind::Full,
+  })
+}
+
+#[test]
+fn test_mark_progress() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = PatternProject::default();
+  patproj.pattern.add_stitches(vec![full(0.0), full(1.0)]);
+  patproj.progress.set_completed_at(full(0.0).into(), Some(10));
+
+  let action = MarkProgressAction::new(vec![full(0.0).into(), full(1.0).into()], None);
+
+  // Test executing the command.
+  {
+    let progress_event = window.listen("progress:update", |e| {
+      assert_eq!(
+        serde_json::from_str::<Vec<ProgressEntry>>(e.payload()).unwrap(),
+        vec![
+          ProgressEntry::new(full(0.0).into(), None),
+          ProgressEntry::new(full(1.0).into(), None)
+        ]
+      );
+    });
+
+    action.perform(&window, &mut patproj).unwrap();
+    assert!(patproj.progress.entries(&patproj.pattern).is_empty());
+
+    window.unlisten(progress_event);
+  }
+
+  // Test revoking the command.
+  {
+    let progress_event = window.listen("progress:update", |e| {
+      assert_eq!(
+        serde_json::from_str::<Vec<ProgressEntry>>(e.payload()).unwrap(),
+        vec![
+          ProgressEntry::new(full(0.0).into(), Some(10)),
+          ProgressEntry::new(full(1.0).into(), None)
+        ]
+      );
+    });
+
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(
+      patproj.progress.entries(&patproj.pattern),
+      vec![ProgressEntry::new(full(0.0).into(), Some(10))]
+    );
+
+    window.unlisten(progress_event);
+  }
+}
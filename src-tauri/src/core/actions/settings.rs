@@ -0,0 +1,212 @@
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, WebviewWindow};
+
+use super::Action;
+use crate::core::pattern::display::{
+  DisplaySettings, Formats, Grid, StitchOutline, StitchSettings, SymbolSettings, Symbols, View,
+};
+use crate::core::pattern::print::{Font, PageMargins, PrintSettings};
+use crate::core::pattern::PatternProject;
+
+#[cfg(test)]
+#[path = "settings.test.rs"]
+mod tests;
+
+/// A change of a single section of the display settings.
+///
+/// It is serialized as `{ "section": "grid", "value": { ... } }`.
+/// The symbols and formats are changed per palette item, so their values also contain the palette item index.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "section", content = "value", rename_all = "snake_case")]
+pub enum DisplaySettingsUpdate {
+  DefaultStitchFont(String),
+  Symbols { palindex: usize, symbols: Symbols },
+  SymbolSettings(SymbolSettings),
+  Formats { palindex: usize, formats: Formats },
+  Grid(Grid),
+  View(View),
+  Zoom(u16),
+  ShowGrid(bool),
+  ShowRulers(bool),
+  ShowCenteringMarks(bool),
+  ShowFabricColorsWithSymbols(bool),
+  GapsBetweenStitches(bool),
+  OutlinedStitches(bool),
+  StitchOutline(StitchOutline),
+  StitchSettings(StitchSettings),
+}
+
+impl DisplaySettingsUpdate {
+  /// Applies the change and returns the one that restores the previous value.
+  fn apply(&self, settings: &mut DisplaySettings) -> Result<Self> {
+    use std::mem::replace;
+
+    let previous = match self.clone() {
+      Self::DefaultStitchFont(value) => Self::DefaultStitchFont(replace(&mut settings.default_stitch_font, value)),
+      Self::Symbols { palindex, symbols } => {
+        let current = settings
+          .symbols
+          .get_mut(palindex)
+          .with_context(|| format!("There is no palette item with index {palindex}"))?;
+        Self::Symbols {
+          palindex,
+          symbols: replace(current, symbols),
+        }
+      }
+      Self::SymbolSettings(value) => Self::SymbolSettings(replace(&mut settings.symbol_settings, value)),
+      Self::Formats { palindex, formats } => {
+        let current = settings
+          .formats
+          .get_mut(palindex)
+          .with_context(|| format!("There is no palette item with index {palindex}"))?;
+        Self::Formats {
+          palindex,
+          formats: replace(current, formats),
+        }
+      }
+      Self::Grid(value) => Self::Grid(replace(&mut settings.grid, value)),
+      Self::View(value) => Self::View(replace(&mut settings.view, value)),
+      Self::Zoom(value) => Self::Zoom(replace(&mut settings.zoom, value)),
+      Self::ShowGrid(value) => Self::ShowGrid(replace(&mut settings.show_grid, value)),
+      Self::ShowRulers(value) => Self::ShowRulers(replace(&mut settings.show_rulers, value)),
+      Self::ShowCenteringMarks(value) => Self::ShowCenteringMarks(replace(&mut settings.show_centering_marks, value)),
+      Self::ShowFabricColorsWithSymbols(value) => {
+        Self::ShowFabricColorsWithSymbols(replace(&mut settings.show_fabric_colors_with_symbols, value))
+      }
+      Self::GapsBetweenStitches(value) => {
+        Self::GapsBetweenStitches(replace(&mut settings.gaps_between_stitches, value))
+      }
+      Self::OutlinedStitches(value) => Self::OutlinedStitches(replace(&mut settings.outlined_stitches, value)),
+      Self::StitchOutline(value) => Self::StitchOutline(replace(&mut settings.stitch_outline, value)),
+      Self::StitchSettings(value) => Self::StitchSettings(replace(&mut settings.stitch_settings, value)),
+    };
+    Ok(previous)
+  }
+}
+
+/// A change of a single section of the print settings.
+///
+/// It is serialized as `{ "section": "margins", "value": { ... } }`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "section", content = "value", rename_all = "snake_case")]
+pub enum PrintSettingsUpdate {
+  Font(Font),
+  Header(String),
+  Footer(String),
+  Margins(PageMargins),
+  ShowPageNumbers(bool),
+  ShowAdjacentPageNumbers(bool),
+  CenterChartOnPages(bool),
+}
+
+impl PrintSettingsUpdate {
+  /// Applies the change and returns the one that restores the previous value.
+  fn apply(&self, settings: &mut PrintSettings) -> Self {
+    use std::mem::replace;
+
+    match self.clone() {
+      Self::Font(value) => Self::Font(replace(&mut settings.font, value)),
+      Self::Header(value) => Self::Header(replace(&mut settings.header, value)),
+      Self::Footer(value) => Self::Footer(replace(&mut settings.footer, value)),
+      Self::Margins(value) => Self::Margins(replace(&mut settings.margins, value)),
+      Self::ShowPageNumbers(value) => Self::ShowPageNumbers(replace(&mut settings.show_page_numbers, value)),
+      Self::ShowAdjacentPageNumbers(value) => {
+        Self::ShowAdjacentPageNumbers(replace(&mut settings.show_adjacent_page_numbers, value))
+      }
+      Self::CenterChartOnPages(value) => Self::CenterChartOnPages(replace(&mut settings.center_chart_on_pages, value)),
+    }
+  }
+}
+
+/// Emits the event named after the changed section, e.g. `display_settings:update_grid`, with the new value.
+fn emit_update<R: tauri::Runtime, U: Serialize>(window: &WebviewWindow<R>, prefix: &str, update: &U) -> Result<()> {
+  let mut update = serde_json::to_value(update)?;
+  let section = update["section"].as_str().unwrap_or_default().to_owned();
+  window.emit(&format!("{prefix}:update_{section}"), update["value"].take())?;
+  Ok(())
+}
+
+#[derive(Clone)]
+pub struct UpdateDisplaySettingsAction {
+  update: DisplaySettingsUpdate,
+  previous: OnceLock<DisplaySettingsUpdate>,
+}
+
+impl UpdateDisplaySettingsAction {
+  pub fn new(update: DisplaySettingsUpdate) -> Self {
+    Self {
+      update,
+      previous: OnceLock::new(),
+    }
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for UpdateDisplaySettingsAction {
+  /// Change the section of the display settings.
+  ///
+  /// **Emits:**
+  /// - `display_settings:update_<section>` with the new value of the section.
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let previous = self.update.apply(&mut patproj.display_settings)?;
+    emit_update(window, "display_settings", &self.update)?;
+    if self.previous.get().is_none() {
+      self.previous.set(previous).unwrap();
+    }
+    Ok(())
+  }
+
+  /// Restore the previous value of the section.
+  ///
+  /// **Emits:**
+  /// - `display_settings:update_<section>` with the previous value of the section.
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let previous = self.previous.get().unwrap();
+    previous.apply(&mut patproj.display_settings)?;
+    emit_update(window, "display_settings", previous)?;
+    Ok(())
+  }
+}
+
+#[derive(Clone)]
+pub struct UpdatePrintSettingsAction {
+  update: PrintSettingsUpdate,
+  previous: OnceLock<PrintSettingsUpdate>,
+}
+
+impl UpdatePrintSettingsAction {
+  pub fn new(update: PrintSettingsUpdate) -> Self {
+    Self {
+      update,
+      previous: OnceLock::new(),
+    }
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for UpdatePrintSettingsAction {
+  /// Change the section of the print settings.
+  ///
+  /// **Emits:**
+  /// - `print_settings:update_<section>` with the new value of the section.
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let previous = self.update.apply(&mut patproj.print_settings);
+    emit_update(window, "print_settings", &self.update)?;
+    if self.previous.get().is_none() {
+      self.previous.set(previous).unwrap();
+    }
+    Ok(())
+  }
+
+  /// Restore the previous value of the section.
+  ///
+  /// **Emits:**
+  /// - `print_settings:update_<section>` with the previous value of the section.
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let previous = self.previous.get().unwrap();
+    previous.apply(&mut patproj.print_settings);
+    emit_update(window, "print_settings", previous)?;
+    Ok(())
+  }
+}
@@ -0,0 +1,124 @@
+use tauri::test::{mock_builder, MockRuntime};
+use tauri::{generate_context, App, Listener, WebviewUrl, WebviewWindowBuilder};
+
+use super::{
+  Action, DisplaySettingsUpdate, PrintSettingsUpdate, UpdateDisplaySettingsAction, UpdatePrintSettingsAction,
+};
+use crate::core::parser::oxs;
+use crate::core::pattern::display::Symbols;
+use crate::core::pattern::*;
+
+fn setup_app() -> App<MockRuntime> {
+  mock_builder().build(generate_context!()).unwrap()
+}
+
+fn create_pattern_project() -> PatternProject {
+  let file_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/patterns/piggies.oxs");
+  oxs::parse_pattern(file_path).unwrap()
+}
+
+#[test]
+fn test_serialize_update() {
+  let update = DisplaySettingsUpdate::ShowGrid(false);
+  let json = serde_json::to_string(&update).unwrap();
+  assert_eq!(json, r#"{"section":"show_grid","value":false}"#);
+  assert_eq!(serde_json::from_str::<DisplaySettingsUpdate>(&json).unwrap(), update);
+}
+
+#[test]
+fn test_apply_symbols_update() {
+  let mut patproj = create_pattern_project();
+  let old_symbols = patproj.display_settings.symbols[2].clone();
+  let new_symbols = Symbols {
+    full: Some(u16::from(b'@')),
+    ..Symbols::default()
+  };
+
+  let update = DisplaySettingsUpdate::Symbols {
+    palindex: 2,
+    symbols: new_symbols.clone(),
+  };
+  let previous = update.apply(&mut patproj.display_settings).unwrap();
+  assert_eq!(patproj.display_settings.symbols[2], new_symbols);
+  assert_eq!(
+    previous,
+    DisplaySettingsUpdate::Symbols {
+      palindex: 2,
+      symbols: old_symbols
+    }
+  );
+
+  // The palette item must exist.
+  let update = DisplaySettingsUpdate::Symbols {
+    palindex: 100,
+    symbols: new_symbols,
+  };
+  assert!(update.apply(&mut patproj.display_settings).is_err());
+}
+
+#[test]
+fn test_update_display_settings() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let old_zoom = patproj.display_settings.zoom;
+  let action = UpdateDisplaySettingsAction::new(DisplaySettingsUpdate::Zoom(250));
+
+  // Test executing the command.
+  {
+    let event = window.listen("display_settings:update_zoom", move |e| {
+      assert_eq!(serde_json::from_str::<u16>(e.payload()).unwrap(), 250);
+    });
+
+    action.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.display_settings.zoom, 250);
+    window.unlisten(event);
+  }
+
+  // Test revoking the command.
+  {
+    window.listen("display_settings:update_zoom", move |e| {
+      assert_eq!(serde_json::from_str::<u16>(e.payload()).unwrap(), old_zoom);
+    });
+
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.display_settings.zoom, old_zoom);
+  }
+}
+
+#[test]
+fn test_update_print_settings() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let old_header = patproj.print_settings.header.clone();
+  let action = UpdatePrintSettingsAction::new(PrintSettingsUpdate::Header(String::from("&[Title]")));
+
+  // Test executing the command.
+  {
+    let event = window.listen("print_settings:update_header", move |e| {
+      assert_eq!(serde_json::from_str::<String>(e.payload()).unwrap(), "&[Title]");
+    });
+
+    action.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.print_settings.header, "&[Title]");
+    window.unlisten(event);
+  }
+
+  // Test revoking the command.
+  {
+    let expected_header = old_header.clone();
+    window.listen("print_settings:update_header", move |e| {
+      assert_eq!(serde_json::from_str::<String>(e.payload()).unwrap(), expected_header);
+    });
+
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.print_settings.header, old_header);
+  }
+}
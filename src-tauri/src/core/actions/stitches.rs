@@ -57,6 +57,104 @@ impl<R: tauri::Runtime> Action<R> for AddStitchAction {
   }
 }
 
+#[derive(Clone)]
+pub struct AddStitchesAction {
+  stitches: Vec<Stitch>,
+  conflicts: OnceLock<Vec<Stitch>>,
+}
+
+impl AddStitchesAction {
+  /// Creates an action that adds many stitches at once, e.g. to fill an area.
+  /// The stitches must not conflict with each other.
+  pub fn new(stitches: Vec<Stitch>) -> Self {
+    Self {
+      stitches,
+      conflicts: OnceLock::new(),
+    }
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for AddStitchesAction {
+  /// Add the stitches to the pattern.
+  ///
+  /// **Emits:**
+  /// - `stitches:remove_many` with the removed stitches that conflict with the new stitches
+  /// - `stitches:add_many` with the added stitches
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let mut conflicts = Vec::new();
+    for &stitch in self.stitches.iter() {
+      conflicts.extend(patproj.pattern.add_stitch(stitch));
+    }
+    window.emit("stitches:remove_many", &conflicts)?;
+    window.emit("stitches:add_many", &self.stitches)?;
+    if self.conflicts.get().is_none() {
+      self.conflicts.set(conflicts).unwrap();
+    }
+    Ok(())
+  }
+
+  /// Remove the added stitches from the pattern.
+  ///
+  /// **Emits:**
+  /// - `stitches:remove_many` with the removed stitches
+  /// - `stitches:add_many` with the added stitches that were removed when the stitches were added
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let conflicts = self.conflicts.get().unwrap();
+    patproj.pattern.remove_stitches(self.stitches.clone());
+    patproj.pattern.add_stitches(conflicts.clone());
+    window.emit("stitches:remove_many", &self.stitches)?;
+    window.emit("stitches:add_many", conflicts)?;
+    Ok(())
+  }
+}
+
+#[derive(Clone)]
+pub struct RemoveStitchesAction {
+  stitches: Vec<Stitch>,
+  removed: OnceLock<Vec<Stitch>>,
+}
+
+impl RemoveStitchesAction {
+  /// Creates an action that removes many stitches at once, e.g. a stitch with its symmetric copies.
+  /// The stitches that are missing in the pattern are ignored.
+  pub fn new(stitches: Vec<Stitch>) -> Self {
+    Self {
+      stitches,
+      removed: OnceLock::new(),
+    }
+  }
+}
+
+impl<R: tauri::Runtime> Action<R> for RemoveStitchesAction {
+  /// Remove the stitches from the pattern.
+  ///
+  /// **Emits:**
+  /// - `stitches:remove_many` with the removed stitches
+  fn perform(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let removed: Vec<Stitch> = self
+      .stitches
+      .iter()
+      .filter_map(|&stitch| patproj.pattern.remove_stitch(stitch))
+      .collect();
+    window.emit("stitches:remove_many", &removed)?;
+    if self.removed.get().is_none() {
+      self.removed.set(removed).unwrap();
+    }
+    Ok(())
+  }
+
+  /// Add the removed stitches back to the pattern.
+  ///
+  /// **Emits:**
+  /// - `stitches:add_many` with the added stitches
+  fn revoke(&self, window: &WebviewWindow<R>, patproj: &mut PatternProject) -> Result<()> {
+    let removed = self.removed.get().unwrap();
+    patproj.pattern.add_stitches(removed.clone());
+    window.emit("stitches:add_many", removed)?;
+    Ok(())
+  }
+}
+
 #[derive(Clone)]
 pub struct RemoveStitchAction {
   stitch: Stitch,
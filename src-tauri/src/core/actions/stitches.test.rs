@@ -2,7 +2,7 @@ use ordered_float::NotNan;
 use tauri::test::{mock_builder, MockRuntime};
 use tauri::{generate_context, App, Listener, WebviewUrl, WebviewWindowBuilder};
 
-use super::{Action, AddStitchAction, RemoveStitchAction};
+use super::{Action, AddStitchAction, AddStitchesAction, RemoveStitchAction, RemoveStitchesAction};
 use crate::core::pattern::*;
 
 fn setup_app() -> App<MockRuntime> {
@@ -131,3 +131,125 @@ fn test_remove_stitch() {
     assert_eq!(patproj.pattern.partstitches.len(), 2);
   }
 }
+
+#[test]
+fn test_add_stitches() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let stitches: Vec<Stitch> = [0.0, 1.0]
+    .into_iter()
+    .map(|x| {
+      Stitch::Full(FullStitch {
+        x: NotNan::new(x).unwrap(),
+        y: NotNan::new(0.0).unwrap(),
+        palindex: 1,
+        kind: FullStitchKind::Full,
+      })
+    })
+    .collect();
+  let action = AddStitchesAction::new(stitches.clone());
+
+  // Test executing the command.
+  {
+    let expected_stitches = stitches.clone();
+    let add_event = window.listen("stitches:add_many", move |e| {
+      assert_eq!(
+        serde_json::from_str::<Vec<Stitch>>(e.payload()).unwrap(),
+        expected_stitches
+      );
+    });
+    let remove_event = window.listen("stitches:remove_many", |e| {
+      let conflicts: Vec<Stitch> = serde_json::from_str(e.payload()).unwrap();
+      assert_eq!(conflicts.len(), 4);
+    });
+
+    action.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.fullstitches.len(), 2);
+    assert_eq!(patproj.pattern.partstitches.len(), 0);
+    window.unlisten(add_event);
+    window.unlisten(remove_event);
+  }
+
+  // Test revoking the command.
+  {
+    let expected_stitches = stitches.clone();
+    window.listen("stitches:remove_many", move |e| {
+      assert_eq!(
+        serde_json::from_str::<Vec<Stitch>>(e.payload()).unwrap(),
+        expected_stitches
+      );
+    });
+
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.fullstitches.len(), 2);
+    assert_eq!(patproj.pattern.partstitches.len(), 2);
+    assert!(patproj
+      .pattern
+      .fullstitches
+      .iter()
+      .all(|stitch| stitch.kind == FullStitchKind::Petite));
+  }
+}
+
+#[test]
+fn test_remove_stitches() {
+  let app = setup_app();
+  let window = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+    .build()
+    .unwrap();
+
+  let mut patproj = create_pattern_project();
+  let stitches: Vec<Stitch> = [0.0, 0.5]
+    .into_iter()
+    .map(|y| {
+      Stitch::Full(FullStitch {
+        x: NotNan::new(0.0).unwrap(),
+        y: NotNan::new(y).unwrap(),
+        palindex: 0,
+        kind: FullStitchKind::Petite,
+      })
+    })
+    .chain([Stitch::Full(FullStitch {
+      x: NotNan::new(5.0).unwrap(),
+      y: NotNan::new(5.0).unwrap(),
+      palindex: 0,
+      kind: FullStitchKind::Full,
+    })])
+    .collect();
+  let action = RemoveStitchesAction::new(stitches.clone());
+
+  // Test executing the command.
+  {
+    let expected_stitches = stitches[..2].to_vec();
+    let remove_event = window.listen("stitches:remove_many", move |e| {
+      assert_eq!(
+        serde_json::from_str::<Vec<Stitch>>(e.payload()).unwrap(),
+        expected_stitches
+      );
+    });
+
+    action.perform(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.fullstitches.len(), 0);
+    assert_eq!(patproj.pattern.partstitches.len(), 2);
+    window.unlisten(remove_event);
+  }
+
+  // Test revoking the command.
+  {
+    let expected_stitches = stitches[..2].to_vec();
+    window.listen("stitches:add_many", move |e| {
+      assert_eq!(
+        serde_json::from_str::<Vec<Stitch>>(e.payload()).unwrap(),
+        expected_stitches
+      );
+    });
+
+    action.revoke(&window, &mut patproj).unwrap();
+    assert_eq!(patproj.pattern.fullstitches.len(), 2);
+    assert_eq!(patproj.pattern.partstitches.len(), 2);
+  }
+}
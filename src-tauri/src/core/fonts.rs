@@ -0,0 +1,410 @@
+//! The management of the fonts used to draw the chart symbols.
+//!
+//! The fonts are referenced by their family names in the display settings, so this module resolves these names to the actual font files.
+//! It loads the fonts bundled with the application and the ones installed by the user into the fonts directory.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use ab_glyph::{point, Font, FontArc, OutlineCurve, PxScale, ScaleFont};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use ttf_parser::name_id;
+
+#[cfg(test)]
+#[path = "fonts.test.rs"]
+mod tests;
+
+/// The fonts that are shipped with the application.
+/// The first one is used when the requested font is not available.
+/// It is DejaVu Sans, since it has the wide range of the geometric shapes, arrows and dingbats to use as the symbols.
+static BUNDLED_FONTS: &[&[u8]] = &[
+  include_bytes!("../../../src/assets/fonts/DejaVuSans.ttf"),
+  include_bytes!("../../../src/assets/fonts/NotoSans-VariableFont.ttf"),
+];
+
+/// The extensions of the font files that can be loaded.
+const FONT_EXTENSIONS: [&str; 2] = ["ttf", "otf"];
+
+/// The side of the bitmaps the glyphs are compared by, in pixels.
+const GLYPH_BITMAP_SIZE: usize = 16;
+
+/// The glyphs that cover fewer pixels of the bitmap are too light to be recognized in the charts.
+const MIN_GLYPH_INK: u32 = 12;
+
+/// The maximum number of the symbol candidates. It is enough for the largest palette.
+const MAX_SYMBOL_CANDIDATES: usize = u8::MAX as usize + 1;
+
+/// The Unicode blocks the symbols are picked from.
+/// The symbol fonts map their glyphs to the ASCII and Latin-1 ranges, while the other fonts have the shapes and the signs.
+const SYMBOL_RANGES: [std::ops::RangeInclusive<u16>; 7] = [
+  0x21..=0x7E,     // Basic Latin
+  0xA1..=0xFF,     // Latin-1 Supplement
+  0x2190..=0x21FF, // Arrows
+  0x2200..=0x22FF, // Mathematical Operators
+  0x25A0..=0x25FF, // Geometric Shapes
+  0x2600..=0x26FF, // Miscellaneous Symbols
+  0x2700..=0x27BF, // Dingbats
+];
+
+/// A glyph rasterized to compare it with the other ones. Each row is a bit mask.
+type GlyphBitmap = [u16; GLYPH_BITMAP_SIZE];
+
+/// A point of the glyph outline.
+pub type GlyphPoint = (f32, f32);
+
+/// A segment of the glyph outline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GlyphSegment {
+  MoveTo(GlyphPoint),
+  LineTo(GlyphPoint),
+  QuadTo(GlyphPoint, GlyphPoint),
+  CubicTo(GlyphPoint, GlyphPoint, GlyphPoint),
+  Close,
+}
+
+/// A glyph rasterized into the cells.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RasterGlyph {
+  /// The distance to the next glyph in cells.
+  pub advance: u16,
+  /// The filled cells relative to the top-left corner of the line.
+  pub cells: Vec<(i32, i32)>,
+}
+
+/// The brief information about the font for the frontend.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FontInfo {
+  pub name: String,
+  pub bundled: bool,
+}
+
+#[derive(Clone)]
+pub struct SymbolFont {
+  name: String,
+  font: FontArc,
+  /// The path to the font file if it was installed by the user.
+  file_path: Option<PathBuf>,
+  /// The symbol candidates are computed once, since it requires rasterizing all the glyphs.
+  symbol_candidates: OnceLock<Vec<u16>>,
+}
+
+impl SymbolFont {
+  fn new(name: String, font: FontArc, file_path: Option<PathBuf>) -> Self {
+    Self {
+      name,
+      font,
+      file_path,
+      symbol_candidates: OnceLock::new(),
+    }
+  }
+
+  /// Returns the family name of the font.
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn info(&self) -> FontInfo {
+    FontInfo {
+      name: self.name.clone(),
+      bundled: self.file_path.is_none(),
+    }
+  }
+
+  /// Checks if the font has a glyph for the symbol.
+  pub fn has_glyph(&self, symbol: char) -> bool {
+    self.font.glyph_id(symbol).0 != 0
+  }
+
+  /// Returns the code points of the glyphs that can be used as symbols.
+  /// The symbols are stored as 16-bit values, so only the Basic Multilingual Plane is considered.
+  pub fn codepoints(&self) -> Vec<u16> {
+    let mut codepoints: Vec<u16> = self
+      .font
+      .codepoint_ids()
+      .filter(|(glyph_id, symbol)| glyph_id.0 != 0 && !symbol.is_whitespace() && !symbol.is_control())
+      .filter_map(|(_, symbol)| u16::try_from(u32::from(symbol)).ok())
+      .collect();
+    codepoints.sort_unstable();
+    codepoints.dedup();
+    codepoints
+  }
+
+  /// Returns the code points of the glyphs to assign to the palette items automatically.
+  ///
+  /// The glyphs are ordered so that each next one differs the most from the ones before it.
+  /// The glyphs that are too light or that duplicate the previous ones are skipped.
+  pub fn symbol_candidates(&self) -> &[u16] {
+    self.symbol_candidates.get_or_init(|| {
+      let glyphs = self
+        .codepoints()
+        .into_iter()
+        .filter(|code| SYMBOL_RANGES.iter().any(|range| range.contains(code)))
+        .filter_map(|code| {
+          let bitmap = self.bitmap(char::from_u32(u32::from(code))?)?;
+          (ink(&bitmap) >= MIN_GLYPH_INK).then_some((code, bitmap))
+        })
+        .collect();
+      order_by_distinctness(glyphs, MAX_SYMBOL_CANDIDATES)
+    })
+  }
+
+  /// Rasterizes the glyph into the bitmap, centered like in the charts.
+  /// The glyphs keep their relative sizes, but the large ones are shrunk to fit the bitmap.
+  fn bitmap(&self, symbol: char) -> Option<GlyphBitmap> {
+    let size = GLYPH_BITMAP_SIZE as f32;
+    let outline = |scale: f32| {
+      self
+        .font
+        .outline_glyph(self.font.as_scaled(PxScale::from(scale)).scaled_glyph(symbol))
+    };
+    let mut outlined = outline(size)?;
+    let bounds = outlined.px_bounds();
+    let extent = bounds.width().max(bounds.height());
+    if extent > size {
+      outlined = outline(size * size / extent)?;
+    }
+
+    let bounds = outlined.px_bounds();
+    let (offset_x, offset_y) = ((size - bounds.width()) / 2.0, (size - bounds.height()) / 2.0);
+    let mut bitmap = [0; GLYPH_BITMAP_SIZE];
+    outlined.draw(|x, y, coverage| {
+      let column = (x as f32 + offset_x).floor();
+      let row = (y as f32 + offset_y).floor();
+      if coverage >= 0.5 && (0.0..size).contains(&column) && (0.0..size).contains(&row) {
+        bitmap[row as usize] |= 1 << column as usize;
+      }
+    });
+    Some(bitmap)
+  }
+
+  /// Returns the outline of the symbol glyph.
+  ///
+  /// The outline is centered at the origin, its Y axis goes downwards, and it fits the unit square.
+  /// The glyphs are scaled by the font height, so they keep their relative sizes, but the large ones are shrunk to fit the square.
+  pub fn glyph(&self, symbol: char) -> Option<Vec<GlyphSegment>> {
+    let glyph_id = self.font.glyph_id(symbol);
+    if glyph_id.0 == 0 {
+      return None;
+    }
+    let outline = self.font.outline(glyph_id)?;
+
+    let bounds = outline.bounds;
+    let extent = (bounds.max.x - bounds.min.x).max(bounds.max.y - bounds.min.y);
+    if extent <= 0.0 {
+      return None;
+    }
+    let scale = (1.0 / self.font.height_unscaled()).min(1.0 / extent);
+    let (center_x, center_y) = ((bounds.min.x + bounds.max.x) / 2.0, (bounds.min.y + bounds.max.y) / 2.0);
+    // The font units go upwards, while the image ones go downwards.
+    let map = |point: ab_glyph::Point| ((point.x - center_x) * scale, -(point.y - center_y) * scale);
+
+    let mut segments = Vec::new();
+    let mut last = None;
+    for curve in outline.curves.iter() {
+      let (start, end) = match *curve {
+        OutlineCurve::Line(p0, p1) => (p0, p1),
+        OutlineCurve::Quad(p0, _, p2) => (p0, p2),
+        OutlineCurve::Cubic(p0, _, _, p3) => (p0, p3),
+      };
+      if last != Some(start) {
+        if last.is_some() {
+          segments.push(GlyphSegment::Close);
+        }
+        segments.push(GlyphSegment::MoveTo(map(start)));
+      }
+      segments.push(match *curve {
+        OutlineCurve::Line(_, p1) => GlyphSegment::LineTo(map(p1)),
+        OutlineCurve::Quad(_, p1, p2) => GlyphSegment::QuadTo(map(p1), map(p2)),
+        OutlineCurve::Cubic(_, p1, p2, p3) => GlyphSegment::CubicTo(map(p1), map(p2), map(p3)),
+      });
+      last = Some(end);
+    }
+    segments.push(GlyphSegment::Close);
+    Some(segments)
+  }
+
+  /// Rasterizes the character so the line of text is `height` cells high.
+  /// The cell is filled when the glyph covers at least a half of it.
+  pub fn rasterize(&self, symbol: char, height: u16) -> RasterGlyph {
+    let font = self.font.as_scaled(PxScale::from(f32::from(height)));
+    let mut glyph = font.scaled_glyph(symbol);
+    glyph.position = point(0.0, font.ascent());
+    let advance = font.h_advance(glyph.id).round().max(0.0) as u16;
+
+    let mut cells = Vec::new();
+    if let Some(outlined) = self.font.outline_glyph(glyph) {
+      let bounds = outlined.px_bounds();
+      let (left, top) = (bounds.min.x as i32, bounds.min.y as i32);
+      outlined.draw(|x, y, coverage| {
+        if coverage >= 0.5 {
+          cells.push((left + x as i32, top + y as i32));
+        }
+      });
+    }
+    RasterGlyph { advance, cells }
+  }
+}
+
+impl std::fmt::Debug for SymbolFont {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("SymbolFont")
+      .field("name", &self.name)
+      .field("file_path", &self.file_path)
+      .finish()
+  }
+}
+
+/// The fonts available for drawing the symbols.
+#[derive(Debug, Clone)]
+pub struct FontRegistry {
+  fonts: Vec<SymbolFont>,
+}
+
+impl FontRegistry {
+  /// Creates a registry with the bundled fonts only.
+  pub fn new() -> Self {
+    let fonts = BUNDLED_FONTS
+      .iter()
+      .map(|data| {
+        let name = font_family_name(data).expect("The bundled fonts must have names");
+        let font = FontArc::try_from_slice(data).expect("The bundled fonts must be valid");
+        SymbolFont::new(name, font, None)
+      })
+      .collect();
+    Self { fonts }
+  }
+
+  /// Loads the font file and adds it to the registry.
+  /// The font replaces the previously loaded user font with the same name, but never the bundled one.
+  pub fn load_font<P: AsRef<Path>>(&mut self, file_path: P) -> Result<&SymbolFont> {
+    let file_path = file_path.as_ref();
+    let data = std::fs::read(file_path)?;
+    let name = font_family_name(&data)
+      .or_else(|| file_path.file_stem().map(|stem| stem.to_string_lossy().to_string()))
+      .context("The font has no name")?;
+    let font = FontArc::try_from_vec(data).context("The font file is invalid")?;
+    let font = SymbolFont::new(name, font, Some(file_path.to_path_buf()));
+
+    let index = match self
+      .fonts
+      .iter()
+      .position(|other| other.name.eq_ignore_ascii_case(&font.name))
+    {
+      Some(index) if self.fonts[index].file_path.is_some() => {
+        self.fonts[index] = font;
+        index
+      }
+      Some(_) => anyhow::bail!("The font {} is already bundled with the application", font.name),
+      None => {
+        self.fonts.push(font);
+        self.fonts.len() - 1
+      }
+    };
+    Ok(&self.fonts[index])
+  }
+
+  /// Loads all the font files from the directory.
+  /// The invalid font files are skipped, so a single broken file doesn't hide the others.
+  pub fn load_dir<P: AsRef<Path>>(&mut self, dir_path: P) -> Result<()> {
+    let dir_path = dir_path.as_ref();
+    if !dir_path.exists() {
+      return Ok(());
+    }
+    log::debug!("Loading fonts from {dir_path:?}");
+    for entry in std::fs::read_dir(dir_path)? {
+      let file_path = entry?.path();
+      let is_font = file_path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| FONT_EXTENSIONS.contains(&extension.to_lowercase().as_str()));
+      if !is_font {
+        continue;
+      }
+      if let Err(error) = self.load_font(&file_path) {
+        log::warn!("Failed to load the font {file_path:?}: {error:#}");
+      }
+    }
+    Ok(())
+  }
+
+  pub fn fonts(&self) -> &[SymbolFont] {
+    &self.fonts
+  }
+
+  /// Returns the font with the given family name.
+  pub fn get(&self, name: &str) -> Option<&SymbolFont> {
+    self.fonts.iter().find(|font| font.name.eq_ignore_ascii_case(name))
+  }
+
+  /// Returns the font with the given family name or the default one if it is not available.
+  pub fn get_or_default(&self, name: &str) -> &SymbolFont {
+    self.get(name).unwrap_or(&self.fonts[0])
+  }
+}
+
+impl Default for FontRegistry {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Returns the number of the filled pixels.
+fn ink(bitmap: &GlyphBitmap) -> u32 {
+  bitmap.iter().map(|row| row.count_ones()).sum()
+}
+
+/// Returns the number of the pixels that differ between the bitmaps.
+fn distance(a: &GlyphBitmap, b: &GlyphBitmap) -> u32 {
+  a.iter().zip(b.iter()).map(|(a, b)| (a ^ b).count_ones()).sum()
+}
+
+/// Orders the glyphs so that each next one is the farthest from the already picked ones.
+/// The first glyph covers about a half of the bitmap, so it is neither too light nor too heavy.
+/// The ties are resolved in favor of the lower code points, so the order is stable.
+fn order_by_distinctness(glyphs: Vec<(u16, GlyphBitmap)>, limit: usize) -> Vec<u16> {
+  let half = (GLYPH_BITMAP_SIZE * GLYPH_BITMAP_SIZE / 2) as u32;
+  let mut next = glyphs
+    .iter()
+    .enumerate()
+    .min_by_key(|(_, (_, bitmap))| ink(bitmap).abs_diff(half))
+    .map(|(index, _)| index);
+
+  // The distance from each glyph to the nearest picked one.
+  let mut distances = vec![u32::MAX; glyphs.len()];
+  let mut ordered = Vec::new();
+  while let Some(index) = next.take() {
+    ordered.push(glyphs[index].0);
+    distances[index] = 0;
+    if ordered.len() == limit {
+      break;
+    }
+
+    let picked = glyphs[index].1;
+    let mut farthest = 0;
+    for (index, (_, bitmap)) in glyphs.iter().enumerate() {
+      distances[index] = distances[index].min(distance(&picked, bitmap));
+      // The identical glyphs can't be told apart, so they are never picked.
+      if distances[index] > farthest {
+        farthest = distances[index];
+        next = Some(index);
+      }
+    }
+  }
+  ordered
+}
+
+/// Reads the family name of the font from its naming table.
+fn font_family_name(data: &[u8]) -> Option<String> {
+  let face = ttf_parser::Face::parse(data, 0).ok()?;
+  let names = face.names();
+  // The typographic family groups all the styles, so it is preferred.
+  [name_id::TYPOGRAPHIC_FAMILY, name_id::FAMILY]
+    .into_iter()
+    .find_map(|id| {
+      names
+        .into_iter()
+        .filter(|name| name.name_id == id && name.is_unicode())
+        .find_map(|name| name.to_string())
+    })
+}
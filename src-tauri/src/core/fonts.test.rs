@@ -0,0 +1,122 @@
+use std::collections::HashSet;
+
+use super::*;
+
+fn bundled_font_path() -> PathBuf {
+  Path::new(env!("CARGO_MANIFEST_DIR")).join("../src/assets/fonts/NotoSans-VariableFont.ttf")
+}
+
+#[test]
+fn loads_bundled_fonts() {
+  let fonts = FontRegistry::new();
+  assert_eq!(
+    fonts.fonts().iter().map(|font| font.info()).collect::<Vec<_>>(),
+    vec![
+      FontInfo {
+        name: String::from("DejaVu Sans"),
+        bundled: true
+      },
+      FontInfo {
+        name: String::from("Noto Sans"),
+        bundled: true
+      }
+    ]
+  );
+  assert!(fonts.get("noto sans").is_some());
+  assert!(fonts.get("CrossStitch3").is_none());
+  // The bundled symbol font is used instead of the missing ones.
+  assert_eq!(fonts.get_or_default("CrossStitch3").name(), "DejaVu Sans");
+}
+
+#[test]
+fn enumerates_codepoints() {
+  let fonts = FontRegistry::new();
+  let codepoints = fonts.get_or_default("").codepoints();
+  assert!(codepoints.contains(&u16::from(b'A')));
+  assert!(!codepoints.contains(&u16::from(b' ')));
+  assert!(codepoints.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn orders_symbol_candidates_by_distinctness() {
+  let font = FontRegistry::new().get_or_default("").clone();
+  let candidates = font.symbol_candidates();
+  // The bundled symbol font is enough for the largest palette.
+  assert_eq!(candidates.len(), MAX_SYMBOL_CANDIDATES);
+  assert_eq!(candidates.len(), candidates.iter().collect::<HashSet<_>>().len());
+
+  let bitmaps: Vec<_> = candidates
+    .iter()
+    .map(|&code| font.bitmap(char::from_u32(u32::from(code)).unwrap()).unwrap())
+    .collect();
+  assert!(bitmaps.iter().all(|bitmap| ink(bitmap) >= MIN_GLYPH_INK));
+  // The first symbols differ from each other more than the last ones.
+  let nearest = |index: usize| {
+    (0..index)
+      .map(|other| distance(&bitmaps[index], &bitmaps[other]))
+      .min()
+      .unwrap()
+  };
+  assert!(nearest(8) > nearest(candidates.len() - 1));
+  assert!(nearest(candidates.len() - 1) > 0);
+}
+
+#[test]
+fn builds_glyph_outlines() {
+  let font = FontRegistry::new().get_or_default("").clone();
+  assert!(font.has_glyph('A'));
+  assert!(font.glyph(' ').is_none());
+
+  let glyph = font.glyph('A').unwrap();
+  assert!(matches!(glyph.first(), Some(GlyphSegment::MoveTo(_))));
+  assert_eq!(glyph.last(), Some(&GlyphSegment::Close));
+
+  // The outline is centered and fits the unit square.
+  let points: Vec<GlyphPoint> = glyph
+    .iter()
+    .filter_map(|segment| match *segment {
+      GlyphSegment::MoveTo(point) | GlyphSegment::LineTo(point) => Some(point),
+      _ => None,
+    })
+    .collect();
+  assert!(points.iter().all(|(x, y)| x.abs() <= 0.5 && y.abs() <= 0.5));
+  let top = points.iter().map(|(_, y)| *y).fold(f32::MAX, f32::min);
+  let bottom = points.iter().map(|(_, y)| *y).fold(f32::MIN, f32::max);
+  assert!((top + bottom).abs() < 1e-3);
+}
+
+#[test]
+fn loads_user_fonts() {
+  let dir = std::env::temp_dir().join("embroidery-studio-fonts-test");
+  std::fs::create_dir_all(&dir).unwrap();
+  std::fs::write(dir.join("broken.ttf"), b"not a font").unwrap();
+  std::fs::write(dir.join("notes.txt"), b"not a font either").unwrap();
+
+  let mut fonts = FontRegistry::new();
+  fonts.load_dir(&dir).unwrap();
+  assert_eq!(fonts.fonts().len(), BUNDLED_FONTS.len());
+
+  // The bundled fonts can't be replaced by the user ones.
+  assert!(fonts.load_font(bundled_font_path()).is_err());
+  assert!(fonts.load_font(dir.join("broken.ttf")).is_err());
+
+  std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn rasterizes_glyphs() {
+  let font = FontRegistry::new().get_or_default("").clone();
+  let glyph = font.rasterize('I', 20);
+  assert!(glyph.advance > 0);
+  assert!(!glyph.cells.is_empty());
+  assert!(glyph.cells.iter().all(|&(x, y)| x >= 0 && (0..20).contains(&y)));
+
+  // The letter `I` is a vertical bar, so it is much taller than wide.
+  let width = glyph.cells.iter().map(|(x, _)| *x).max().unwrap() - glyph.cells.iter().map(|(x, _)| *x).min().unwrap();
+  let height = glyph.cells.iter().map(|(_, y)| *y).max().unwrap() - glyph.cells.iter().map(|(_, y)| *y).min().unwrap();
+  assert!(height > width * 3);
+
+  let space = font.rasterize(' ', 20);
+  assert!(space.advance > 0);
+  assert!(space.cells.is_empty());
+}
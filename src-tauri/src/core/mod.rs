@@ -1,4 +1,7 @@
 pub mod actions;
+pub mod fonts;
 pub mod history;
+pub mod motifs;
 pub mod parser;
 pub mod pattern;
+pub mod render;
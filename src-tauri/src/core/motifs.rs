@@ -0,0 +1,324 @@
+//! The library of the motifs, i.e. the pattern fragments that can be reused across the patterns.
+//!
+//! Each motif has its own palette, so it can be inserted into any pattern.
+//! The motifs are stored as JSON files in the motifs directory, one file per motif.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::pattern::{Coord, Line, Node, PaletteItem, Pattern, Region, Stitch};
+
+#[cfg(test)]
+#[path = "motifs.test.rs"]
+mod tests;
+
+/// A pattern fragment with its own palette.
+/// The stitches are placed relative to the top-left corner of the motif and refer to the motif palette.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Motif {
+  pub name: String,
+  pub tags: Vec<String>,
+  pub width: u16,
+  pub height: u16,
+  pub palette: Vec<PaletteItem>,
+  pub stitches: Vec<Stitch>,
+}
+
+impl Motif {
+  /// Creates a motif from the region of the pattern.
+  /// Only the palette items used by the stitches of the region get into the motif palette.
+  /// Fails if a stitch refers to a palette item that doesn't exist.
+  pub fn from_region(pattern: &Pattern, region: &Region, name: String, tags: Vec<String>) -> Result<Self> {
+    let mut palette_map = Vec::new();
+    let mut palette = Vec::new();
+    let stitches = pattern
+      .stitches_in(region)
+      .into_iter()
+      .map(|stitch| {
+        let palindex = stitch.palindex();
+        let index = match palette_map.iter().position(|&used| used == palindex) {
+          Some(index) => index,
+          None => {
+            let palitem = pattern
+              .palette
+              .get(palindex as usize)
+              .with_context(|| format!("The stitch refers to the missing palette item {palindex}"))?;
+            palette_map.push(palindex);
+            palette.push(palitem.clone());
+            palette.len() - 1
+          }
+        };
+        let mut stitch = translate(stitch, -f32::from(region.x), -f32::from(region.y));
+        stitch.set_palindex(index as u8);
+        Ok(stitch)
+      })
+      .collect::<Result<_>>()?;
+    Ok(Self {
+      name,
+      tags,
+      width: region.width,
+      height: region.height,
+      palette,
+      stitches,
+    })
+  }
+
+  /// Maps the motif palette onto the target one.
+  /// The motif palette items that list the same threads as the target ones are mapped to them, while the others are appended.
+  ///
+  /// Returns the target palette indices of the motif palette items and the palette items to append.
+  pub fn map_palette(&self, target: &[PaletteItem]) -> (Vec<u8>, Vec<PaletteItem>) {
+    let mut appended: Vec<PaletteItem> = Vec::new();
+    let mapping = self
+      .palette
+      .iter()
+      .map(|palitem| {
+        let existing = target
+          .iter()
+          .chain(appended.iter())
+          .position(|other| other.is_same_thread(palitem));
+        match existing {
+          Some(palindex) => palindex as u8,
+          None => {
+            appended.push(palitem.clone());
+            (target.len() + appended.len() - 1) as u8
+          }
+        }
+      })
+      .collect();
+    (mapping, appended)
+  }
+
+  /// Returns the motif stitches placed at the given cell of the pattern with the palette indices mapped by `map_palette`.
+  /// The stitches that don't fit the pattern are clipped.
+  pub fn stitches_at(&self, x: u16, y: u16, mapping: &[u8], pattern: &Pattern) -> Vec<Stitch> {
+    let bounds = Region {
+      x: 0,
+      y: 0,
+      width: pattern.properties.width,
+      height: pattern.properties.height,
+    };
+    self
+      .stitches
+      .iter()
+      .filter_map(|&stitch| {
+        // The stitches referring to the missing palette items are skipped.
+        let palindex = *mapping.get(stitch.palindex() as usize)?;
+        let mut stitch = translate(stitch, f32::from(x), f32::from(y));
+        stitch.set_palindex(palindex);
+        Some(stitch)
+      })
+      .filter(|stitch| bounds.contains_stitch(stitch))
+      .collect()
+  }
+
+  /// Checks that all the stitches refer to the palette items of the motif.
+  fn validate(&self) -> Result<()> {
+    let palette_len = self.palette.len();
+    if let Some(stitch) = self
+      .stitches
+      .iter()
+      .find(|stitch| stitch.palindex() as usize >= palette_len)
+    {
+      anyhow::bail!(
+        "The stitch refers to the missing palette item {}, while the motif has {palette_len} palette items",
+        stitch.palindex()
+      );
+    }
+    Ok(())
+  }
+
+  /// Checks whether the motif name or one of its tags contains the query.
+  fn matches(&self, query: &str) -> bool {
+    let query = query.trim().to_lowercase();
+    self.name.to_lowercase().contains(&query) || self.tags.iter().any(|tag| tag.to_lowercase().contains(&query))
+  }
+
+  fn has_tag(&self, tag: &str) -> bool {
+    self
+      .tags
+      .iter()
+      .any(|other| other.trim().eq_ignore_ascii_case(tag.trim()))
+  }
+}
+
+fn translate(stitch: Stitch, dx: f32, dy: f32) -> Stitch {
+  let shift = |coord: Coord, delta: f32| coord + delta;
+  match stitch {
+    Stitch::Full(mut fullstitch) => {
+      (fullstitch.x, fullstitch.y) = (shift(fullstitch.x, dx), shift(fullstitch.y, dy));
+      Stitch::Full(fullstitch)
+    }
+    Stitch::Part(mut partstitch) => {
+      (partstitch.x, partstitch.y) = (shift(partstitch.x, dx), shift(partstitch.y, dy));
+      Stitch::Part(partstitch)
+    }
+    Stitch::Line(line) => Stitch::Line(Line {
+      x: (shift(line.x.0, dx), shift(line.x.1, dx)),
+      y: (shift(line.y.0, dy), shift(line.y.1, dy)),
+      ..line
+    }),
+    Stitch::Node(node) => Stitch::Node(Node {
+      x: shift(node.x, dx),
+      y: shift(node.y, dy),
+      ..node
+    }),
+  }
+}
+
+/// Returns the name of the file the motif is stored in.
+/// The characters that can't be used in the file names are replaced with underscores.
+fn motif_file_name(name: &str) -> String {
+  let stem: String = name
+    .trim()
+    .chars()
+    .map(|ch| {
+      if ch.is_alphanumeric() || ch == '-' || ch == ' ' {
+        ch
+      } else {
+        '_'
+      }
+    })
+    .collect();
+  format!("{stem}.json")
+}
+
+#[derive(Debug, Default)]
+pub struct MotifLibrary {
+  motifs: Vec<Motif>,
+}
+
+impl MotifLibrary {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn load_motif<P: AsRef<Path>>(&mut self, file_path: P) -> Result<&Motif> {
+    let file_path = file_path.as_ref();
+    log::trace!("Loading motif from {file_path:?}");
+    let data = std::fs::read(file_path)?;
+    let motif: Motif = serde_json::from_slice(&data).context("The motif file is invalid")?;
+    motif.validate().context("The motif file is invalid")?;
+    Ok(self.insert(motif))
+  }
+
+  /// Loads all the motif files from the directory.
+  /// The invalid motif files are skipped, so a single broken file doesn't hide the others.
+  pub fn load_dir<P: AsRef<Path>>(&mut self, dir_path: P) -> Result<()> {
+    let dir_path = dir_path.as_ref();
+    if !dir_path.exists() {
+      return Ok(());
+    }
+    log::debug!("Loading motifs from {dir_path:?}");
+    for entry in std::fs::read_dir(dir_path)? {
+      let file_path = entry?.path();
+      let is_motif = file_path.extension().is_some_and(|extension| extension == "json");
+      if !is_motif {
+        continue;
+      }
+      if let Err(error) = self.load_motif(&file_path) {
+        log::warn!("Failed to load the motif {file_path:?}: {error:#}");
+      }
+    }
+    Ok(())
+  }
+
+  /// Writes the motif to the directory and adds it to the library.
+  /// The motif with the same name is replaced.
+  /// Fails if the motif would be stored in the file of another motif, e.g. `a/b` and `a_b`.
+  pub fn save_motif<P: AsRef<Path>>(&mut self, dir_path: P, motif: Motif) -> Result<PathBuf> {
+    anyhow::ensure!(!motif.name.trim().is_empty(), "The motif name is empty");
+    motif.validate()?;
+    let file_name = motif_file_name(&motif.name);
+    // The file names are compared case-insensitively, since some file systems don't distinguish the cases.
+    if let Some(other) = self.motifs.iter().find(|other| {
+      !other.name.eq_ignore_ascii_case(&motif.name) && motif_file_name(&other.name).eq_ignore_ascii_case(&file_name)
+    }) {
+      anyhow::bail!(
+        "The motif name {} clashes with the motif {}, rename one of them",
+        motif.name,
+        other.name
+      );
+    }
+    let dir_path = dir_path.as_ref();
+    let file_path = dir_path.join(file_name);
+    std::fs::create_dir_all(dir_path)?;
+    std::fs::write(&file_path, serde_json::to_vec_pretty(&motif)?)?;
+    // The names are case-insensitive, so the replaced motif may be stored in a file with a different name.
+    if let Some(replaced) = self.get(&motif.name) {
+      let replaced_path = dir_path.join(motif_file_name(&replaced.name));
+      if replaced_path != file_path && replaced_path.exists() {
+        std::fs::remove_file(replaced_path)?;
+      }
+    }
+    self.insert(motif);
+    Ok(file_path)
+  }
+
+  /// Removes the motif from the library and deletes its file.
+  pub fn remove_motif<P: AsRef<Path>>(&mut self, dir_path: P, name: &str) -> Result<Motif> {
+    let index = self
+      .motifs
+      .iter()
+      .position(|motif| motif.name.eq_ignore_ascii_case(name))
+      .with_context(|| format!("The motif {name} is not in the library"))?;
+    let file_path = dir_path.as_ref().join(motif_file_name(&self.motifs[index].name));
+    if file_path.exists() {
+      std::fs::remove_file(&file_path)?;
+    }
+    Ok(self.motifs.remove(index))
+  }
+
+  fn insert(&mut self, motif: Motif) -> &Motif {
+    let index = match self
+      .motifs
+      .iter()
+      .position(|other| other.name.eq_ignore_ascii_case(&motif.name))
+    {
+      Some(index) => {
+        self.motifs[index] = motif;
+        index
+      }
+      None => {
+        self.motifs.push(motif);
+        self.motifs.len() - 1
+      }
+    };
+    &self.motifs[index]
+  }
+
+  pub fn motifs(&self) -> &[Motif] {
+    &self.motifs
+  }
+
+  /// Returns the motif with the given name.
+  pub fn get(&self, name: &str) -> Option<&Motif> {
+    self.motifs.iter().find(|motif| motif.name.eq_ignore_ascii_case(name))
+  }
+
+  /// Returns the motifs whose name or tags contain the query and that have all the given tags.
+  /// The motifs are sorted by their names.
+  pub fn search(&self, query: &str, tags: &[String]) -> Vec<&Motif> {
+    let mut motifs: Vec<&Motif> = self
+      .motifs
+      .iter()
+      .filter(|motif| motif.matches(query) && tags.iter().all(|tag| motif.has_tag(tag)))
+      .collect();
+    motifs.sort_by_key(|motif| motif.name.to_lowercase());
+    motifs
+  }
+
+  /// Returns all the tags used by the motifs, sorted and without duplicates.
+  pub fn tags(&self) -> Vec<String> {
+    let mut tags: Vec<String> = self
+      .motifs
+      .iter()
+      .flat_map(|motif| motif.tags.iter().cloned())
+      .collect();
+    tags.sort_by_key(|tag| tag.to_lowercase());
+    tags.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+    tags
+  }
+}
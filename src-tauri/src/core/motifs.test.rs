@@ -0,0 +1,182 @@
+use ordered_float::NotNan;
+
+use super::*;
+use crate::core::pattern::{FullStitch, FullStitchKind, LineKind, PatternProperties};
+
+fn palitem(brand: &str, number: &str) -> PaletteItem {
+  PaletteItem {
+    brand: String::from(brand),
+    number: String::from(number),
+    name: String::new(),
+    color: String::from("FFFFFF"),
+    blends: None,
+    bead: None,
+    strands: None,
+  }
+}
+
+fn full(x: f32, y: f32, palindex: u8) -> Stitch {
+  Stitch::Full(FullStitch {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(y).unwrap(),
+    palindex,
+    kind: FullStitchKind::Full,
+  })
+}
+
+fn motif(name: &str, tags: &[&str]) -> Motif {
+  Motif {
+    name: String::from(name),
+    tags: tags.iter().map(|tag| String::from(*tag)).collect(),
+    width: 1,
+    height: 1,
+    palette: vec![palitem("DMC", "310")],
+    stitches: vec![full(0.0, 0.0, 0)],
+  }
+}
+
+#[test]
+fn creates_motif_from_region() {
+  let mut pattern = Pattern {
+    properties: PatternProperties { width: 10, height: 10 },
+    palette: vec![palitem("DMC", "310"), palitem("DMC", "321"), palitem("DMC", "444")],
+    ..Pattern::default()
+  };
+  pattern.add_stitches(vec![
+    full(2.0, 3.0, 2),
+    full(3.0, 3.0, 0),
+    full(8.0, 8.0, 1),
+    Stitch::Line(Line {
+      x: (NotNan::new(2.0).unwrap(), NotNan::new(4.0).unwrap()),
+      y: (NotNan::new(3.0).unwrap(), NotNan::new(3.0).unwrap()),
+      palindex: 2,
+      kind: LineKind::Back,
+    }),
+  ]);
+
+  let region = Region { x: 2, y: 3, width: 2, height: 1 };
+  let motif = Motif::from_region(&pattern, &region, String::from("Rose"), vec![String::from("flowers")]).unwrap();
+  assert_eq!((motif.width, motif.height), (2, 1));
+  // The unused palette items are not copied, the others are reindexed.
+  assert_eq!(motif.palette, vec![palitem("DMC", "444"), palitem("DMC", "310")]);
+  assert_eq!(
+    motif.stitches,
+    vec![
+      full(0.0, 0.0, 0),
+      full(1.0, 0.0, 1),
+      Stitch::Line(Line {
+        x: (NotNan::new(0.0).unwrap(), NotNan::new(2.0).unwrap()),
+        y: (NotNan::new(0.0).unwrap(), NotNan::new(0.0).unwrap()),
+        palindex: 0,
+        kind: LineKind::Back,
+      })
+    ]
+  );
+
+  // The stitch with the missing palette item can't get into the motif.
+  pattern.add_stitch(full(2.0, 3.0, 7));
+  assert!(Motif::from_region(&pattern, &region, String::from("Rose"), Vec::new()).is_err());
+}
+
+#[test]
+fn maps_palette_and_places_stitches() {
+  let motif = Motif {
+    palette: vec![palitem("DMC", "444"), palitem(" dmc ", "310"), palitem("Anchor", "403")],
+    stitches: vec![full(0.0, 0.0, 0), full(1.0, 0.0, 1), full(1.0, 1.0, 2)],
+    width: 2,
+    height: 2,
+    ..motif("Rose", &[])
+  };
+  let pattern = Pattern {
+    properties: PatternProperties { width: 5, height: 5 },
+    palette: vec![palitem("DMC", "310"), palitem("DMC", "321")],
+    ..Pattern::default()
+  };
+
+  let (mapping, appended) = motif.map_palette(&pattern.palette);
+  assert_eq!(mapping, vec![2, 0, 3]);
+  assert_eq!(appended, vec![palitem("DMC", "444"), palitem("Anchor", "403")]);
+
+  // The stitches outside the pattern are clipped.
+  assert_eq!(
+    motif.stitches_at(3, 4, &mapping, &pattern),
+    vec![full(3.0, 4.0, 2), full(4.0, 4.0, 0)]
+  );
+}
+
+#[test]
+fn searches_motifs() {
+  let mut library = MotifLibrary::new();
+  library.insert(motif("Rose", &["Flowers", "red"]));
+  library.insert(motif("Tulip", &["flowers"]));
+  library.insert(motif("Zigzag border", &["borders"]));
+
+  let names = |motifs: Vec<&Motif>| motifs.into_iter().map(|motif| motif.name.clone()).collect::<Vec<_>>();
+  assert_eq!(names(library.search("", &[])), vec!["Rose", "Tulip", "Zigzag border"]);
+  assert_eq!(names(library.search("BORDER", &[])), vec!["Zigzag border"]);
+  assert_eq!(names(library.search("red", &[])), vec!["Rose"]);
+  assert_eq!(
+    names(library.search("", &[String::from("flowers")])),
+    vec!["Rose", "Tulip"]
+  );
+  assert_eq!(
+    names(library.search("t", &[String::from("flowers"), String::from("red")])),
+    Vec::<String>::new()
+  );
+  assert_eq!(library.tags(), vec!["borders", "Flowers", "red"]);
+}
+
+#[test]
+fn saves_and_loads_motifs() {
+  let dir = std::env::temp_dir().join("embroidery-studio-motifs-test");
+  let _ = std::fs::remove_dir_all(&dir);
+
+  let mut library = MotifLibrary::new();
+  let file_path = library.save_motif(&dir, motif("Rose/Red", &["flowers"])).unwrap();
+  assert_eq!(file_path, dir.join("Rose_Red.json"));
+  library.save_motif(&dir, motif("Tulip", &[])).unwrap();
+  // The motif with the same name is replaced.
+  library.save_motif(&dir, motif("tulip", &["spring"])).unwrap();
+  assert!(library.save_motif(&dir, motif(" ", &[])).is_err());
+  // The names that would share the file are rejected.
+  assert!(library.save_motif(&dir, motif("Rose_Red", &[])).is_err());
+  assert!(library.save_motif(&dir, motif("rose:red", &[])).is_err());
+  std::fs::write(dir.join("broken.json"), b"not a motif").unwrap();
+
+  let mut loaded = MotifLibrary::new();
+  loaded.load_dir(&dir).unwrap();
+  assert_eq!(loaded.motifs().len(), 2);
+  assert_eq!(loaded.get("rose/red"), library.get("Rose/Red"));
+  assert_eq!(loaded.get("Tulip").unwrap().tags, vec!["spring"]);
+
+  loaded.remove_motif(&dir, "Rose/Red").unwrap();
+  assert!(!file_path.exists());
+  assert!(loaded.remove_motif(&dir, "Rose/Red").is_err());
+
+  std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn rejects_motifs_with_missing_palette_items() {
+  let dir = std::env::temp_dir().join("embroidery-studio-invalid-motifs-test");
+  let _ = std::fs::remove_dir_all(&dir);
+  std::fs::create_dir_all(&dir).unwrap();
+
+  let mut invalid = motif("Invalid", &[]);
+  invalid.stitches.push(full(1.0, 1.0, invalid.palette.len() as u8));
+  let file_path = dir.join("Invalid.json");
+  std::fs::write(&file_path, serde_json::to_vec(&invalid).unwrap()).unwrap();
+
+  let mut library = MotifLibrary::new();
+  assert!(library.load_motif(&file_path).is_err());
+  library.load_dir(&dir).unwrap();
+  assert!(library.motifs().is_empty());
+
+  // The stitches without the mapped palette items are skipped instead of panicking.
+  let pattern = Pattern::default();
+  let (mapping, _) = invalid.map_palette(&pattern.palette);
+  let stitches = invalid.stitches_at(0, 0, &mapping, &pattern);
+  assert_eq!(stitches.len(), invalid.stitches.len() - 1);
+
+  std::fs::remove_dir_all(&dir).unwrap();
+}
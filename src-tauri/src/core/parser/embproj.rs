@@ -2,18 +2,27 @@ use std::io::Write;
 
 use anyhow::Result;
 
-use crate::core::pattern::PatternProject;
+use crate::core::pattern::progress::{timestamp, Progress};
+use crate::core::pattern::sessions::SessionLog;
+use crate::core::pattern::{Pattern, PatternProject};
 
 pub fn parse_pattern(file_path: std::path::PathBuf) -> Result<PatternProject> {
   log::info!("Parsing the EMBPROJ pattern file");
   let file = std::fs::File::open(&file_path)?;
   let mut zip = zip::ZipArchive::new(file)?;
 
-  Ok(PatternProject {
+  let mut patproj = PatternProject {
     file_path,
     pattern: {
-      let mut file = zip.by_name("pattern")?;
-      borsh::from_reader(&mut file)?
+      let mut pattern: Pattern = {
+        let mut file = zip.by_name("pattern")?;
+        borsh::from_reader(&mut file)?
+      };
+      // The files saved before the layers were introduced have the only layer.
+      if let Ok(mut file) = zip.by_name("layers") {
+        pattern.layers = borsh::from_reader(&mut file)?;
+      }
+      pattern
     },
     display_settings: {
       let mut file = zip.by_name("display_settings")?;
@@ -23,7 +32,27 @@ pub fn parse_pattern(file_path: std::path::PathBuf) -> Result<PatternProject> {
       let mut file = zip.by_name("print_settings")?;
       borsh::from_reader(&mut file)?
     },
-  })
+    progress: {
+      // The files saved before the progress tracking was introduced have no progress.
+      let mut progress = Progress::default();
+      if let Ok(mut file) = zip.by_name("progress") {
+        progress = borsh::from_reader(&mut file)?;
+      }
+      progress
+    },
+    sessions: {
+      let mut sessions = SessionLog::default();
+      if let Ok(mut file) = zip.by_name("sessions") {
+        sessions = borsh::from_reader(&mut file)?;
+      }
+      sessions
+    },
+  };
+  // The marks of the stitches removed in the previous editing session are not needed anymore.
+  patproj.progress.prune(&patproj.pattern);
+  // The files saved before the running session was capped on saving may have it running.
+  patproj.sessions.stop_abandoned(&patproj.progress, &patproj.pattern);
+  Ok(patproj)
 }
 
 pub fn save_pattern(patproj: &PatternProject) -> Result<()> {
@@ -39,12 +68,21 @@ pub fn save_pattern(patproj: &PatternProject) -> Result<()> {
   zip.start_file("pattern", options)?;
   zip.write_all(&borsh::to_vec(&patproj.pattern).unwrap())?;
 
+  zip.start_file("layers", options)?;
+  zip.write_all(&borsh::to_vec(&patproj.pattern.layers).unwrap())?;
+
   zip.start_file("display_settings", options)?;
   zip.write_all(&borsh::to_vec(&patproj.display_settings).unwrap())?;
 
   zip.start_file("print_settings", options)?;
   zip.write_all(&borsh::to_vec(&patproj.print_settings).unwrap())?;
 
+  zip.start_file("progress", options)?;
+  zip.write_all(&borsh::to_vec(&patproj.progress).unwrap())?;
+
+  zip.start_file("sessions", options)?;
+  zip.write_all(&borsh::to_vec(&patproj.sessions.capped(timestamp())).unwrap())?;
+
   zip.finish()?;
   Ok(())
 }
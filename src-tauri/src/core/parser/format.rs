@@ -1,6 +1,23 @@
 use std::ffi::OsStr;
+use std::io::{self, BufRead, Read, Seek};
+use std::path::Path;
 
-#[derive(Default)]
+use quick_xml::events::Event;
+
+use super::xsd::VALID_SIGNATURE as XSD_SIGNATURE;
+
+#[cfg(test)]
+#[path = "format.test.rs"]
+mod tests;
+
+/// The local file header signature, which starts every non-empty ZIP archive.
+const ZIP_SIGNATURE: &[u8] = b"PK\x03\x04";
+/// The end of central directory signature, which starts an empty ZIP archive.
+const EMPTY_ZIP_SIGNATURE: &[u8] = b"PK\x05\x06";
+/// The name of the root element of OXS documents.
+const OXS_ROOT_ELEMENT: &[u8] = b"chart";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub enum PatternFormat {
   /// Probably, stands for `Cross-Stitch Design`.
   /// Only **read-only** mode is currently available.
@@ -18,20 +35,113 @@ pub enum PatternFormat {
   EmbProj,
 }
 
+/// An error that occurs when the pattern file format is not supported for the requested operation.
+#[derive(Debug)]
+pub enum UnsupportedFormatError {
+  /// The file has an unknown extension.
+  UnknownExtension(String),
+  /// The file has no extension, so its format cannot be determined.
+  NoExtension,
+  /// The file content doesn't match any of the supported formats.
+  UnrecognizedContent,
+  /// The format can be read, but not written.
+  ReadOnly(PatternFormat),
+}
+
+impl UnsupportedFormatError {
+  pub fn read_only(format: PatternFormat) -> Self {
+    Self::ReadOnly(format)
+  }
+}
+
+impl std::fmt::Display for UnsupportedFormatError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::UnknownExtension(extension) => write!(f, "Unsupported pattern type: {extension}."),
+      Self::NoExtension => write!(f, "Unsupported pattern type"),
+      Self::UnrecognizedContent => write!(f, "The file is not a pattern of any supported type."),
+      Self::ReadOnly(format) => write!(
+        f,
+        "The {} format is not supported for saving.",
+        format.to_string().to_uppercase()
+      ),
+    }
+  }
+}
+
+impl std::error::Error for UnsupportedFormatError {}
+
+impl PatternFormat {
+  /// Detects the format of the pattern file by its content.
+  /// The file extension is used only if the content doesn't match any of the supported formats,
+  /// so the parser can report what exactly is wrong with the file.
+  pub fn detect(file_path: &Path) -> anyhow::Result<Self> {
+    let mut reader = io::BufReader::new(std::fs::File::open(file_path)?);
+    let detected = Self::detect_by_content(&mut reader)?;
+    let by_extension = Self::try_from(file_path.extension());
+
+    match (detected, by_extension) {
+      (Some(detected), Ok(by_extension)) => {
+        if detected != by_extension {
+          log::warn!("The file has the {by_extension} extension, but its content is {detected}");
+        }
+        Ok(detected)
+      }
+      (Some(detected), Err(_)) => Ok(detected),
+      (None, Ok(by_extension)) => {
+        log::warn!("Failed to detect the file format by its content, relying on the {by_extension} extension");
+        Ok(by_extension)
+      }
+      (None, Err(_)) => Err(UnsupportedFormatError::UnrecognizedContent.into()),
+    }
+  }
+
+  /// Detects the format by the magic bytes at the beginning of the file.
+  /// Returns `None` if the content doesn't match any of the supported formats.
+  pub fn detect_by_content<R: BufRead + Seek>(reader: &mut R) -> io::Result<Option<Self>> {
+    let mut signature = Vec::with_capacity(4);
+    reader.by_ref().take(4).read_to_end(&mut signature)?;
+    reader.rewind()?;
+
+    if signature.starts_with(&XSD_SIGNATURE.to_le_bytes()) {
+      return Ok(Some(Self::Xsd));
+    }
+    if signature.starts_with(ZIP_SIGNATURE) || signature.starts_with(EMPTY_ZIP_SIGNATURE) {
+      return Ok(Some(Self::EmbProj));
+    }
+
+    // Skip the XML declaration, comments and other prolog parts until the root element.
+    let mut reader = quick_xml::Reader::from_reader(reader);
+    let mut buf = Vec::new();
+    loop {
+      match reader.read_event_into(&mut buf) {
+        Ok(Event::Start(e) | Event::Empty(e)) => {
+          let is_oxs = e.name().as_ref() == OXS_ROOT_ELEMENT;
+          return Ok(if is_oxs { Some(Self::Oxs) } else { None });
+        }
+        Ok(Event::Text(e)) if !e.iter().all(u8::is_ascii_whitespace) => return Ok(None),
+        Ok(Event::Eof) | Err(_) => return Ok(None),
+        _ => {}
+      }
+      buf.clear();
+    }
+  }
+}
+
 impl TryFrom<Option<&OsStr>> for PatternFormat {
   type Error = anyhow::Error;
 
   fn try_from(value: Option<&OsStr>) -> anyhow::Result<Self, Self::Error> {
     if let Some(extension) = value {
-      let extension = extension.to_str().unwrap();
+      let extension = extension.to_string_lossy();
       match extension.to_lowercase().as_str() {
         "xsd" => Ok(Self::Xsd),
         "oxs" | "xml" => Ok(Self::Oxs),
         "embproj" => Ok(Self::EmbProj),
-        _ => anyhow::bail!("Unsupported pattern type: {extension}."),
+        _ => Err(UnsupportedFormatError::UnknownExtension(extension.into_owned()).into()),
       }
     } else {
-      anyhow::bail!("Unsupported pattern type")
+      Err(UnsupportedFormatError::NoExtension.into())
     }
   }
 }
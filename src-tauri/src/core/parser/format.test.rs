@@ -0,0 +1,47 @@
+use std::io::Cursor;
+
+use super::*;
+
+fn detect(content: &[u8]) -> Option<PatternFormat> {
+  PatternFormat::detect_by_content(&mut Cursor::new(content)).unwrap()
+}
+
+#[test]
+fn detects_formats_by_content() {
+  let patterns = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/patterns");
+  assert_eq!(
+    detect(&std::fs::read(patterns.join("piggies.xsd")).unwrap()),
+    Some(PatternFormat::Xsd)
+  );
+  assert_eq!(
+    detect(&std::fs::read(patterns.join("piggies.oxs")).unwrap()),
+    Some(PatternFormat::Oxs)
+  );
+  assert_eq!(detect(b"PK\x03\x04\x14\x00"), Some(PatternFormat::EmbProj));
+  assert_eq!(detect(b"PK\x05\x06"), Some(PatternFormat::EmbProj));
+}
+
+#[test]
+fn skips_xml_prolog() {
+  let content = b"\xEF\xBB\xBF<?xml version=\"1.0\"?>\n<!-- A comment -->\n<!DOCTYPE chart>\n<chart></chart>";
+  assert_eq!(detect(content), Some(PatternFormat::Oxs));
+  assert_eq!(detect(b"<chart/>"), Some(PatternFormat::Oxs));
+}
+
+#[test]
+fn rejects_unknown_content() {
+  assert_eq!(detect(b""), None);
+  assert_eq!(detect(b"Hello, world!"), None);
+  assert_eq!(detect(b"<?xml version=\"1.0\"?><html></html>"), None);
+  assert_eq!(detect(b"\x00\x01\x02\x03"), None);
+}
+
+#[test]
+fn does_not_panic_on_non_utf8_extensions() {
+  #[cfg(unix)]
+  {
+    use std::os::unix::ffi::OsStrExt;
+    assert!(PatternFormat::try_from(Some(OsStr::from_bytes(b"\xFFoxs"))).is_err());
+  }
+  assert!(PatternFormat::try_from(None).is_err());
+}
@@ -3,4 +3,4 @@ pub mod oxs;
 pub mod xsd;
 
 mod format;
-pub use format::PatternFormat;
+pub use format::{PatternFormat, UnsupportedFormatError};
@@ -0,0 +1,146 @@
+//! The OXS format is written by many applications, and each of them interprets the format a bit differently.
+//! This module describes such variations (dialects) and normalizes them to the reference format of Ursa Software.
+
+use quick_xml::events::BytesStart;
+
+use super::error::OxsError;
+use super::utils::{read_attributes, ElementAttributes, MapAttributes, Software};
+
+#[cfg(test)]
+#[path = "dialect.test.rs"]
+mod tests;
+
+/// Alternative attribute names that are used by other applications, mapped to the reference ones.
+/// Each entry is `(element, alternative name, reference name)`.
+const ATTRIBUTE_ALIASES: &[(&str, &str, &str)] = &[
+  ("properties", "width", "chartwidth"),
+  ("properties", "height", "chartheight"),
+  ("properties", "title", "charttitle"),
+  ("properties", "name", "charttitle"),
+  ("properties", "designer", "author"),
+  ("properties", "notes", "instructions"),
+  ("properties", "description", "instructions"),
+  ("properties", "stitchesperinch_x", "stitchesperinch"),
+  ("properties", "colorcount", "palettecount"),
+  ("palette_item", "colour", "color"),
+  ("palette_item", "manufacturer", "brand"),
+  ("palette_item", "code", "number"),
+  ("palette_item", "description", "name"),
+  ("stitch", "palette_index", "palindex"),
+  ("partstitch", "palette_index1", "palindex1"),
+  ("partstitch", "palette_index2", "palindex2"),
+  ("backstitch", "palette_index", "palindex"),
+  ("backstitch", "type", "objecttype"),
+  ("object", "x", "x1"),
+  ("object", "y", "y1"),
+  ("object", "palette_index", "palindex"),
+  ("object", "type", "objecttype"),
+];
+
+/// A kind of the ornament object that is stored in the `ornaments_inc_knots_and_beads` element.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrnamentKind {
+  Petite,
+  FrenchKnot,
+  Bead,
+  Special,
+}
+
+/// Describes how a particular application writes the OXS format.
+#[derive(Debug, Clone, Copy)]
+pub struct Dialect {
+  software: Software,
+}
+
+impl Dialect {
+  pub fn new(software: Software) -> Self {
+    Self { software }
+  }
+
+  pub fn software(&self) -> Software {
+    self.software
+  }
+
+  /// Returns `true` if the file follows the reference format and doesn't need any normalization.
+  fn is_reference(&self) -> bool {
+    matches!(self.software, Software::Ursa | Software::EmbroideryStudio)
+  }
+
+  /// Reads the attributes of the element and normalizes them.
+  pub fn read_attributes(&self, element: &BytesStart, position: u64) -> Result<ElementAttributes, OxsError> {
+    let mut attributes = read_attributes(element, position)?;
+    self.normalize_attributes(element.name().as_ref(), &mut attributes);
+    Ok(attributes)
+  }
+
+  /// Renames the attributes of the element that are known under alternative names to the reference ones.
+  /// The reference attributes take precedence if both are present.
+  pub fn normalize_attributes(&self, element: &[u8], attributes: &mut MapAttributes) {
+    if self.is_reference() {
+      return;
+    }
+
+    for (_, alias, reference) in ATTRIBUTE_ALIASES
+      .iter()
+      .filter(|(name, _, _)| name.as_bytes() == element)
+    {
+      if attributes.contains_key(*reference) {
+        continue;
+      }
+      if let Some(value) = attributes.remove(*alias) {
+        attributes.insert(reference.to_string(), value);
+      }
+    }
+
+    // Some applications omit the vertical count if the fabric has the same density in both directions.
+    if element == b"properties" && !attributes.contains_key("stitchesperinch_y") {
+      if let Some(spi) = attributes.get("stitchesperinch").cloned() {
+        attributes.insert(String::from("stitchesperinch_y"), spi);
+      }
+    }
+  }
+
+  /// Extracts the thread brand and number from the palette item (or blend) attributes.
+  ///
+  /// The reference format stores both of them in the `number` attribute (e.g., `DMC 310`),
+  /// while other applications may store the brand in a separate attribute.
+  pub fn read_brand_and_number(&self, attributes: &MapAttributes) -> (String, String) {
+    let number = attributes.get("number").map(|number| number.trim()).unwrap_or_default();
+
+    if !self.is_reference() {
+      if let Some(brand) = attributes.get("brand").map(|brand| brand.trim()) {
+        let number = number.strip_prefix(brand).unwrap_or(number).trim_start();
+        return (brand.to_owned(), number.to_owned());
+      }
+    }
+
+    match number.rsplit_once(' ') {
+      Some((brand, number)) => (brand.trim_end().to_owned(), number.to_owned()),
+      None => (String::new(), number.to_owned()),
+    }
+  }
+
+  /// Maps the `objecttype` of the ornament object to its kind.
+  /// Returns `None` for the unknown objects, so they can be skipped.
+  pub fn read_ornament_kind(&self, objecttype: &str) -> Option<OrnamentKind> {
+    // Yes, the Ursa Software's OXS format uses the "quarter" stitch for petites.
+    match objecttype {
+      "quarter" => return Some(OrnamentKind::Petite),
+      "knot" => return Some(OrnamentKind::FrenchKnot),
+      "special" => return Some(OrnamentKind::Special),
+      _ if objecttype.starts_with("bead") => return Some(OrnamentKind::Bead),
+      _ => {}
+    }
+
+    if self.is_reference() {
+      return None;
+    }
+
+    match objecttype.to_lowercase().replace([' ', '_', '-'], "").as_str() {
+      "petite" | "petitestitch" => Some(OrnamentKind::Petite),
+      "frenchknot" => Some(OrnamentKind::FrenchKnot),
+      kind if kind.starts_with("bead") => Some(OrnamentKind::Bead),
+      _ => None,
+    }
+  }
+}
@@ -0,0 +1,93 @@
+use super::*;
+
+fn attributes(pairs: &[(&str, &str)]) -> MapAttributes {
+  pairs
+    .iter()
+    .map(|(key, value)| (key.to_string(), value.to_string()))
+    .collect()
+}
+
+#[test]
+fn keeps_reference_attributes_untouched() {
+  let dialect = Dialect::new(Software::Ursa);
+  let mut attrs = attributes(&[("width", "10"), ("stitchesperinch", "14")]);
+  dialect.normalize_attributes(b"properties", &mut attrs);
+  assert_eq!(attrs, attributes(&[("width", "10"), ("stitchesperinch", "14")]));
+}
+
+#[test]
+fn normalizes_attribute_aliases() {
+  let dialect = Dialect::new(Software::Other);
+
+  let mut attrs = attributes(&[
+    ("width", "10"),
+    ("chartheight", "20"),
+    ("height", "30"),
+    ("stitchesperinch_x", "16"),
+  ]);
+  dialect.normalize_attributes(b"properties", &mut attrs);
+  assert_eq!(
+    attrs,
+    attributes(&[
+      ("chartwidth", "10"),
+      ("chartheight", "20"),
+      ("height", "30"),
+      ("stitchesperinch", "16"),
+      ("stitchesperinch_y", "16"),
+    ])
+  );
+
+  // Aliases are applied only to the elements they belong to.
+  let mut attrs = attributes(&[("width", "10")]);
+  dialect.normalize_attributes(b"stitch", &mut attrs);
+  assert_eq!(attrs, attributes(&[("width", "10")]));
+}
+
+#[test]
+fn reads_brand_and_number() {
+  let reference = Dialect::new(Software::Ursa);
+  let other = Dialect::new(Software::Other);
+
+  for dialect in [reference, other] {
+    assert_eq!(
+      dialect.read_brand_and_number(&attributes(&[("number", "DMC    310")])),
+      (String::from("DMC"), String::from("310"))
+    );
+    assert_eq!(
+      dialect.read_brand_and_number(&attributes(&[("number", "Mill Hill Glass Seed Bead 00479")])),
+      (String::from("Mill Hill Glass Seed Bead"), String::from("00479"))
+    );
+    assert_eq!(
+      dialect.read_brand_and_number(&attributes(&[("number", "B5200")])),
+      (String::new(), String::from("B5200"))
+    );
+  }
+
+  assert_eq!(
+    other.read_brand_and_number(&attributes(&[("brand", "DMC"), ("number", "310")])),
+    (String::from("DMC"), String::from("310"))
+  );
+  assert_eq!(
+    other.read_brand_and_number(&attributes(&[("brand", "Anchor"), ("number", "Anchor 403")])),
+    (String::from("Anchor"), String::from("403"))
+  );
+}
+
+#[test]
+fn reads_ornament_kinds() {
+  let reference = Dialect::new(Software::Ursa);
+  let other = Dialect::new(Software::Other);
+
+  for dialect in [reference, other] {
+    assert_eq!(dialect.read_ornament_kind("quarter"), Some(OrnamentKind::Petite));
+    assert_eq!(dialect.read_ornament_kind("knot"), Some(OrnamentKind::FrenchKnot));
+    assert_eq!(dialect.read_ornament_kind("bead3mm"), Some(OrnamentKind::Bead));
+    assert_eq!(dialect.read_ornament_kind("special"), Some(OrnamentKind::Special));
+    assert_eq!(dialect.read_ornament_kind("unknown"), None);
+  }
+
+  assert_eq!(reference.read_ornament_kind("petite"), None);
+  assert_eq!(other.read_ornament_kind("petite"), Some(OrnamentKind::Petite));
+  assert_eq!(other.read_ornament_kind("French Knot"), Some(OrnamentKind::FrenchKnot));
+  assert_eq!(other.read_ornament_kind("Bead_2mm"), Some(OrnamentKind::Bead));
+}
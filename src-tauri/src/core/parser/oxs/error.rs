@@ -0,0 +1,80 @@
+use std::fmt;
+
+/// An error that occurs while reading an OXS file.
+/// Its messages are meant to be shown to the user as is.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OxsError {
+  /// The file is not a well-formed XML document.
+  Xml { position: u64, message: String },
+  /// The file doesn't contain the `properties` element, so it is not an OXS pattern.
+  MissingProperties,
+  /// The palette doesn't contain the fabric item, which must be the first one.
+  MissingFabric,
+  /// The file ended before the end of the element it was reading.
+  UnexpectedEof { element: String },
+  /// The element doesn't have an attribute that is required to read it.
+  MissingAttribute {
+    element: String,
+    attribute: String,
+    position: u64,
+  },
+  /// The attribute value cannot be interpreted.
+  InvalidAttribute {
+    element: String,
+    attribute: String,
+    value: String,
+    reason: String,
+    position: u64,
+  },
+  /// The pattern uses the OXS version that is not supported.
+  UnsupportedVersion(String),
+}
+
+impl OxsError {
+  pub fn unexpected_eof(element: &str) -> Self {
+    OxsError::UnexpectedEof { element: element.to_owned() }
+  }
+
+  /// Returns the position (the byte offset in the document) the error is related to, if any.
+  pub fn position(&self) -> Option<u64> {
+    match self {
+      OxsError::Xml { position, .. }
+      | OxsError::MissingAttribute { position, .. }
+      | OxsError::InvalidAttribute { position, .. } => Some(*position),
+      _ => None,
+    }
+  }
+}
+
+impl fmt::Display for OxsError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      OxsError::Xml { position, message } => {
+        write!(
+          f,
+          "The file is not a valid XML document (at position {position}): {message}"
+        )
+      }
+      OxsError::MissingProperties => write!(f, "The file is not an OXS pattern: it has no pattern properties"),
+      OxsError::MissingFabric => write!(f, "The pattern palette has no fabric item"),
+      OxsError::UnexpectedEof { element } => write!(f, "The file ended before the end of the `{element}` element"),
+      OxsError::MissingAttribute { element, attribute, position } => write!(
+        f,
+        "The `{element}` element (at position {position}) has no required `{attribute}` attribute"
+      ),
+      OxsError::InvalidAttribute {
+        element,
+        attribute,
+        value,
+        reason,
+        position,
+      } => write!(
+        f,
+        "The `{element}` element (at position {position}) has an invalid `{attribute}` attribute \"{value}\": {reason}"
+      ),
+      OxsError::UnsupportedVersion(version) => write!(f, "The OXS version {version} is not supported"),
+    }
+  }
+}
+
+impl std::error::Error for OxsError {}
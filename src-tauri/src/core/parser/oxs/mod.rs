@@ -1,6 +1,9 @@
+mod dialect;
+mod error;
 mod utils;
 mod v1_0;
 
 #[allow(clippy::module_inception)]
 mod oxs;
+pub use error::OxsError;
 pub use oxs::*;
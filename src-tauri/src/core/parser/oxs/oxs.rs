@@ -1,43 +1,67 @@
-use anyhow::{bail, Result};
+use anyhow::Result;
 use quick_xml::events::Event;
 
-use super::utils::{process_attributes, OxsVersion, Software};
+use super::error::OxsError;
+use super::utils::{map_xml_error, read_attributes, OxsVersion, Software};
 use super::v1_0;
 use crate::core::pattern::PatternProject;
 
+#[cfg(test)]
+#[path = "oxs.test.rs"]
+mod tests;
+
 pub fn parse_pattern(file_path: std::path::PathBuf) -> Result<PatternProject> {
   log::info!("Parsing the OXS pattern");
+  let content = std::fs::read(&file_path)?;
+  let pattern_project = parse_pattern_from_slice(&content)?;
+  Ok(PatternProject { file_path, ..pattern_project })
+}
+
+/// Parses the OXS pattern from its content.
+/// The file path of the returned pattern project is left empty.
+pub fn parse_pattern_from_slice(content: &[u8]) -> Result<PatternProject> {
+  let (oxs_version, software) = read_header(content)?;
+  match oxs_version {
+    OxsVersion::V1_0 => v1_0::parse_pattern(content, software),
+  }
+}
 
-  let mut reader = quick_xml::Reader::from_file(&file_path)?;
+/// Reads the OXS version and the software that produced the file from the `properties` element.
+fn read_header(content: &[u8]) -> Result<(OxsVersion, Software)> {
+  let mut reader = quick_xml::Reader::from_reader(content);
   let mut buf = Vec::new();
-  let (oxs_version, software) = loop {
+  loop {
     match reader.read_event_into(&mut buf) {
-      Ok(Event::Empty(ref e)) => {
-        if e.name().as_ref() == b"properties" {
-          let attributes = process_attributes(e.attributes())?;
-          let oxs_version: OxsVersion = attributes.get("oxsversion").unwrap().parse()?;
-          let software: Software = attributes.get("software").unwrap().parse()?;
-          break (oxs_version, software);
-        }
+      // The properties element is usually empty, but some applications write it with the closing tag.
+      Ok(Event::Start(ref e) | Event::Empty(ref e)) if e.name().as_ref() == b"properties" => {
+        let attributes = read_attributes(e, reader.buffer_position())?;
+
+        let oxs_version = match attributes.get("oxsversion") {
+          Some(version) => version.parse()?,
+          None => {
+            log::warn!("The OXS version is not specified, reading the file as the version 1.0");
+            OxsVersion::V1_0
+          }
+        };
+        let software = match attributes.get("software") {
+          Some(software) => software.parse().unwrap_or(Software::Other),
+          None => Software::Other,
+        };
+
+        return Ok((oxs_version, software));
       }
-      // We don't expect to receive EOF here,
-      // because we should have found the properties tag,
-      // which is at the beginning of the file.
-      Ok(Event::Eof) => bail!("Unexpected EOF"),
-      Err(e) => bail!("Error at position {}: {e:?}", reader.error_position()),
+      // The properties element is at the beginning of the file,
+      // so if we reach the end, the file is not an OXS pattern at all.
+      Ok(Event::Eof) => return Err(OxsError::MissingProperties.into()),
+      Err(e) => return Err(map_xml_error(&reader, e.into())),
       _ => {}
     }
     buf.clear();
-  };
-
-  let pattern_project = match oxs_version {
-    OxsVersion::V1_0 => v1_0::parse_pattern(file_path, software)?,
-  };
-
-  Ok(pattern_project)
+  }
 }
 
 pub fn save_pattern(patproj: &PatternProject) -> Result<()> {
   log::info!("Saving the OXS pattern");
-  v1_0::save_pattern(patproj)
+  // The format doesn't support layers, so all of them, including the hidden ones, are merged into one.
+  v1_0::save_pattern(&patproj.flattened_all())
 }
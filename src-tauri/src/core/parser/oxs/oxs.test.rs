@@ -0,0 +1,211 @@
+use ordered_float::NotNan;
+
+use super::*;
+use crate::core::pattern::*;
+
+fn parse_fixture(name: &str) -> PatternProject {
+  let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+    .join("testdata/oxs")
+    .join(name);
+  parse_pattern(path).unwrap()
+}
+
+#[test]
+fn parses_all_dialect_fixtures() {
+  let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/oxs");
+  for entry in std::fs::read_dir(fixtures).unwrap() {
+    let path = entry.unwrap().path();
+    assert!(parse_pattern(path.clone()).is_ok(), "Failed to parse {path:?}");
+  }
+}
+
+#[test]
+fn parses_reference_dialect() {
+  let patproj = parse_fixture("ursa_software.oxs");
+  let pattern = &patproj.pattern;
+
+  assert_eq!(pattern.properties, PatternProperties { width: 12, height: 10 });
+  assert_eq!(pattern.palette.len(), 2);
+  assert_eq!(pattern.palette[0].brand, "DMC");
+  assert_eq!(pattern.palette[0].number, "310");
+  assert_eq!(pattern.palette[1].brand, "Mill Hill Glass Seed Bead");
+  assert_eq!(pattern.palette[1].number, "00479");
+
+  assert_eq!(pattern.fullstitches.len(), 2); // One full and one petite stitch.
+  assert_eq!(pattern.partstitches.len(), 1);
+  assert_eq!(pattern.lines.len(), 1);
+  assert_eq!(pattern.nodes.len(), 2);
+}
+
+#[test]
+fn parses_attribute_aliases() {
+  let patproj = parse_fixture("attribute_aliases.oxs");
+  let pattern = &patproj.pattern;
+
+  assert_eq!(pattern.properties, PatternProperties { width: 12, height: 10 });
+  assert_eq!(pattern.info.title, "Aliases");
+  assert_eq!(pattern.info.author, "Designer");
+  assert_eq!(pattern.info.description, "Some notes");
+  assert_eq!(pattern.fabric.spi, (16, 16));
+
+  assert_eq!(pattern.palette.len(), 1);
+  assert_eq!(pattern.palette[0].name, "Black");
+  assert_eq!(pattern.palette[0].color, "000000");
+
+  assert!(pattern.fullstitches.contains(&FullStitch {
+    x: NotNan::new(1.0).unwrap(),
+    y: NotNan::new(1.0).unwrap(),
+    palindex: 0,
+    kind: FullStitchKind::Full,
+  }));
+  assert_eq!(pattern.partstitches.len(), 1);
+  assert_eq!(pattern.lines.len(), 1);
+  assert!(pattern.nodes.contains(&Node {
+    x: NotNan::new(4.0).unwrap(),
+    y: NotNan::new(4.0).unwrap(),
+    rotated: false,
+    palindex: 0,
+    kind: NodeKind::FrenchKnot,
+  }));
+}
+
+#[test]
+fn parses_separate_brand_attribute() {
+  let patproj = parse_fixture("separate_brand.oxs");
+  let palette = &patproj.pattern.palette;
+
+  assert_eq!(palette.len(), 3);
+  assert_eq!((palette[0].brand.as_str(), palette[0].number.as_str()), ("DMC", "310"));
+  assert_eq!(
+    (palette[1].brand.as_str(), palette[1].number.as_str()),
+    ("Anchor", "403")
+  );
+  assert_eq!((palette[2].brand.as_str(), palette[2].number.as_str()), ("", "B5200"));
+}
+
+#[test]
+fn parses_ornament_aliases() {
+  let patproj = parse_fixture("ornament_aliases.oxs");
+  let pattern = &patproj.pattern;
+
+  assert!(pattern.fullstitches.contains(&FullStitch {
+    x: NotNan::new(1.5).unwrap(),
+    y: NotNan::new(1.0).unwrap(),
+    palindex: 0,
+    kind: FullStitchKind::Petite,
+  }));
+  assert_eq!(
+    pattern.nodes.iter().map(|node| node.kind).collect::<Vec<_>>(),
+    vec![NodeKind::FrenchKnot, NodeKind::Bead]
+  );
+}
+
+#[test]
+fn parses_minor_version_with_missing_attributes() {
+  let patproj = parse_fixture("minor_version.oxs");
+  let pattern = &patproj.pattern;
+
+  assert_eq!(pattern.info.title, "");
+  assert_eq!(pattern.fabric.spi, (11, 11));
+  assert_eq!(pattern.fabric.color, "F0E6D2");
+  assert_eq!(pattern.palette.len(), 2);
+  assert_eq!(patproj.display_settings.symbols.len(), 2);
+  assert_eq!(pattern.fullstitches.iter().next().unwrap().palindex, 1);
+}
+
+#[test]
+fn parses_properties_with_end_tag() {
+  let patproj = parse_fixture("properties_with_end_tag.oxs");
+  assert_eq!(patproj.pattern.properties, PatternProperties { width: 12, height: 10 });
+  assert_eq!(patproj.pattern.fullstitches.len(), 1);
+}
+
+fn parse_error(content: &str) -> OxsError {
+  let error = parse_pattern_from_slice(content.as_bytes()).unwrap_err();
+  error.downcast::<OxsError>().unwrap()
+}
+
+#[test]
+fn reports_missing_properties() {
+  assert_eq!(parse_error(""), OxsError::MissingProperties);
+  assert_eq!(
+    parse_error("<chart><palette></palette></chart>"),
+    OxsError::MissingProperties
+  );
+}
+
+#[test]
+fn reports_unsupported_version() {
+  assert_eq!(
+    parse_error(r#"<chart><properties oxsversion="2.0"/></chart>"#),
+    OxsError::UnsupportedVersion(String::from("2.0"))
+  );
+}
+
+#[test]
+fn reports_missing_attributes() {
+  let content = r#"<chart><properties oxsversion="1.0" chartwidth="10"/></chart>"#;
+  let start = content.find("<properties").unwrap() as u64;
+  let end = start + content[start as usize..].find("/>").unwrap() as u64 + 2;
+  match parse_error(content) {
+    OxsError::MissingAttribute { element, attribute, position } => {
+      assert_eq!((element.as_str(), attribute.as_str()), ("properties", "chartheight"));
+      // The position points to the offending element.
+      assert!(
+        (start..=end).contains(&position),
+        "{position} is outside of {start}..={end}"
+      );
+    }
+    error => panic!("Unexpected error: {error:?}"),
+  }
+}
+
+#[test]
+fn reports_invalid_palette_indices() {
+  let content = r#"<chart>
+    <properties oxsversion="1.0" chartwidth="1" chartheight="1" stitchesperinch="14"/>
+    <palette><palette_item color="FFFFFF"/></palette>
+    <fullstitches><stitch x="0" y="0" palindex="0"/></fullstitches>
+  </chart>"#;
+  assert!(matches!(
+    parse_error(content),
+    OxsError::InvalidAttribute { element, attribute, .. } if element == "stitch" && attribute == "palindex"
+  ));
+}
+
+#[test]
+fn reports_malformed_xml() {
+  let content =
+    r#"<chart><properties oxsversion="1.0" chartwidth="1" chartheight="1" stitchesperinch="14"/></palette>"#;
+  assert!(matches!(parse_error(content), OxsError::Xml { .. }));
+}
+
+#[test]
+fn reports_truncated_files() {
+  let content =
+    r#"<chart><properties oxsversion="1.0" chartwidth="1" chartheight="1" stitchesperinch="14"/><fullstitches>"#;
+  assert_eq!(parse_error(content), OxsError::unexpected_eof("fullstitches"));
+}
+
+/// A quick deterministic counterpart of the `oxs_reader` fuzz target:
+/// none of the truncated or corrupted fixtures should make the reader panic or hang.
+#[test]
+fn does_not_panic_on_corrupted_files() {
+  let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/oxs");
+  for entry in std::fs::read_dir(fixtures).unwrap() {
+    let content = std::fs::read(entry.unwrap().path()).unwrap();
+    for len in 0..content.len() {
+      let _ = parse_pattern_from_slice(&content[..len]);
+    }
+    for (i, byte) in [b'<', b'>', b'"', b'/', b'0', b'-', 0xFF]
+      .into_iter()
+      .cycle()
+      .take(content.len())
+      .enumerate()
+    {
+      let mut corrupted = content.clone();
+      corrupted[i] = byte;
+      let _ = parse_pattern_from_slice(&corrupted);
+    }
+  }
+}
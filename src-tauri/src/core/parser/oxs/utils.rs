@@ -1,8 +1,10 @@
 use std::collections::HashMap;
 use std::str::FromStr;
 
-use anyhow::Result;
-use quick_xml::events::attributes::Attributes;
+use quick_xml::events::BytesStart;
+use quick_xml::Reader;
+
+use super::error::OxsError;
 
 #[derive(Debug)]
 pub enum OxsVersion {
@@ -10,43 +12,156 @@ pub enum OxsVersion {
 }
 
 impl FromStr for OxsVersion {
-  type Err = anyhow::Error;
+  type Err = OxsError;
 
   fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let s = s.trim();
     match s {
       "1.0" => Ok(OxsVersion::V1_0),
-      _ => anyhow::bail!("Unsupported OXS version: {s}"),
+      // Minor revisions of the format are backward compatible, so we can read them as the version 1.0.
+      "1" => Ok(OxsVersion::V1_0),
+      _ if s.starts_with("1.") => {
+        log::warn!("OXS version {s} is not fully supported, reading it as the version 1.0");
+        Ok(OxsVersion::V1_0)
+      }
+      _ => Err(OxsError::UnsupportedVersion(s.to_owned())),
     }
   }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Software {
-  UrsaSoftware,
+  Ursa,
   EmbroideryStudio,
+  /// Any other application that produces OXS files.
+  /// Its files are read using the lenient dialect.
+  Other,
 }
 
 impl FromStr for Software {
-  type Err = anyhow::Error;
+  type Err = std::convert::Infallible;
 
   fn from_str(s: &str) -> Result<Self, Self::Err> {
-    match s {
-      "Ursa Software" => Ok(Software::UrsaSoftware),
+    match s.trim() {
+      "Ursa Software" => Ok(Software::Ursa),
       "Embroidery Studio" => Ok(Software::EmbroideryStudio),
-      _ => anyhow::bail!("Unsupported software: {s}"),
+      _ => {
+        log::debug!("Unknown OXS producer: {s}");
+        Ok(Software::Other)
+      }
     }
   }
 }
 
 pub type MapAttributes = HashMap<String, String>;
 
-pub fn process_attributes(attributes: Attributes) -> Result<MapAttributes> {
-  let mut map = HashMap::new();
-  for attr in attributes {
-    let attr = attr?;
-    let key = String::from_utf8(attr.key.as_ref().to_vec())?;
-    let value = String::from_utf8(attr.value.to_vec())?;
-    map.insert(key, value);
+/// The attributes of an XML element along with the information needed to report errors about them.
+#[derive(Debug, Clone)]
+pub struct ElementAttributes {
+  element: String,
+  position: u64,
+  attributes: MapAttributes,
+}
+
+impl ElementAttributes {
+  /// Returns the value of the attribute that is required to read the element.
+  pub fn required(&self, key: &str) -> Result<&str, OxsError> {
+    match self.attributes.get(key) {
+      Some(value) => Ok(value),
+      None => Err(OxsError::MissingAttribute {
+        element: self.element.clone(),
+        attribute: key.to_owned(),
+        position: self.position,
+      }),
+    }
+  }
+
+  /// Parses the value of the attribute that is required to read the element.
+  pub fn parse<T>(&self, key: &str) -> Result<T, OxsError>
+  where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+  {
+    let value = self.required(key)?;
+    value.parse().map_err(|e| self.invalid(key, value, e))
+  }
+
+  /// Parses the attribute value or returns the default one if the attribute is missing.
+  pub fn parse_or<T>(&self, key: &str, default: T) -> Result<T, OxsError>
+  where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+  {
+    match self.attributes.get(key) {
+      Some(value) => value.parse().map_err(|e| self.invalid(key, value, e)),
+      None => Ok(default),
+    }
+  }
+
+  /// Parses the one-based palette index and converts it to the zero-based one.
+  pub fn parse_palindex(&self, key: &str) -> Result<u8, OxsError> {
+    let palindex: u8 = self.parse(key)?;
+    palindex
+      .checked_sub(1)
+      .ok_or_else(|| self.invalid(key, "0", "palette indices start from 1"))
+  }
+
+  /// Creates an error about the invalid value of the attribute.
+  pub fn invalid(&self, key: &str, value: &str, reason: impl std::fmt::Display) -> OxsError {
+    OxsError::InvalidAttribute {
+      element: self.element.clone(),
+      attribute: key.to_owned(),
+      value: value.to_owned(),
+      reason: reason.to_string(),
+      position: self.position,
+    }
+  }
+}
+
+impl std::ops::Deref for ElementAttributes {
+  type Target = MapAttributes;
+
+  fn deref(&self) -> &Self::Target {
+    &self.attributes
+  }
+}
+
+impl std::ops::DerefMut for ElementAttributes {
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    &mut self.attributes
+  }
+}
+
+/// Reads the attributes of the element.
+/// The `position` is the current position of the reader and is used only for error reporting.
+pub fn read_attributes(element: &BytesStart, position: u64) -> Result<ElementAttributes, OxsError> {
+  let name = String::from_utf8_lossy(element.name().as_ref()).into_owned();
+  let xml_error = |message: String| OxsError::Xml { position, message };
+
+  let mut attributes = HashMap::new();
+  for attr in element.attributes() {
+    let attr = attr.map_err(|e| xml_error(e.to_string()))?;
+    let key = String::from_utf8(attr.key.as_ref().to_vec()).map_err(|e| xml_error(e.to_string()))?;
+    let value = attr.unescape_value().map_err(|e| xml_error(e.to_string()))?.to_string();
+    attributes.insert(key, value);
+  }
+
+  Ok(ElementAttributes {
+    element: name,
+    position,
+    attributes,
+  })
+}
+
+/// Converts the XML errors that occurred while reading the file to the user-presentable ones.
+/// The rest of the errors are returned as is.
+pub fn map_xml_error<R>(reader: &Reader<R>, error: anyhow::Error) -> anyhow::Error {
+  match error.downcast::<quick_xml::Error>() {
+    Ok(error) => OxsError::Xml {
+      position: reader.error_position(),
+      message: error.to_string(),
+    }
+    .into(),
+    Err(error) => error,
   }
-  Ok(map)
 }
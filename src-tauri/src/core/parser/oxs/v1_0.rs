@@ -1,28 +1,41 @@
 use std::io;
-use std::str::FromStr;
 
 use anyhow::Result;
 use ordered_float::NotNan;
 use quick_xml::events::{BytesDecl, Event};
 use quick_xml::{Reader, Writer};
 
+use super::dialect::{Dialect, OrnamentKind};
+use super::error::OxsError;
 use super::utils::*;
-use crate::core::pattern::display::DisplaySettings;
-use crate::core::pattern::print::PrintSettings;
+use crate::core::pattern::display::*;
+use crate::core::pattern::print::*;
 use crate::core::pattern::*;
 
-pub fn parse_pattern(file_path: std::path::PathBuf, software: Software) -> Result<PatternProject> {
+#[cfg(test)]
+#[path = "v1_0.test.rs"]
+mod tests;
+
+pub fn parse_pattern(content: &[u8], software: Software) -> Result<PatternProject> {
   log::trace!("OXS version is 1.0 in the {software:?} edition");
+  let dialect = Dialect::new(software);
 
-  let mut buf = Vec::new();
-  let mut reader = Reader::from_file(&file_path)?;
+  let mut reader = Reader::from_reader(content);
 
   reader.config_mut().expand_empty_elements = true;
   reader.config_mut().check_end_names = true;
   reader.config_mut().trim_text(true);
 
+  read_chart(&mut reader, &dialect).map_err(|error| map_xml_error(&reader, error))
+}
+
+fn read_chart<R: io::BufRead>(reader: &mut Reader<R>, dialect: &Dialect) -> Result<PatternProject> {
+  let software = dialect.software();
+  let mut buf = Vec::new();
   let mut pattern = Pattern::default();
   let mut palette_size = None;
+  let mut display_settings = None;
+  let mut print_settings = None;
 
   loop {
     match reader.read_event_into(&mut buf) {
@@ -30,7 +43,7 @@ pub fn parse_pattern(file_path: std::path::PathBuf, software: Software) -> Resul
         log::trace!("Parsing {:?}", String::from_utf8(e.name().as_ref().to_vec())?);
         match e.name().as_ref() {
           b"properties" => {
-            let attributes = process_attributes(e.attributes())?;
+            let attributes = dialect.read_attributes(e, reader.buffer_position())?;
             let (properties, info, spi, palsize) = read_pattern_properties(&attributes)?;
             pattern.properties = properties;
             pattern.info = info;
@@ -39,45 +52,68 @@ pub fn parse_pattern(file_path: std::path::PathBuf, software: Software) -> Resul
           }
           b"palette" => {
             if let Some(palette_size) = palette_size {
-              let (fabric, palette) = read_palette(&mut reader, software, palette_size)?;
+              let (fabric, palette) = read_palette(reader, dialect, palette_size)?;
               pattern.fabric = Fabric {
                 spi: pattern.fabric.spi,
                 ..fabric
               };
               pattern.palette = palette;
             } else {
-              anyhow::bail!("Palette size is not set or the pattern properties are not read yet");
+              // The palette cannot be read without the fabric density, which is set in the pattern properties.
+              return Err(OxsError::MissingProperties.into());
             }
           }
-          b"fullstitches" => pattern.fullstitches.extend(read_fullstitches(&mut reader)?),
-          b"partstitches" => pattern.partstitches.extend(read_partstitches(&mut reader)?),
-          b"backstitches" => pattern.lines.extend(read_lines(&mut reader)?),
+          b"fullstitches" => pattern.fullstitches.extend(read_fullstitches(reader, dialect)?),
+          b"partstitches" => pattern.partstitches.extend(read_partstitches(reader, dialect)?),
+          b"backstitches" => pattern.lines.extend(read_lines(reader, dialect)?),
           b"ornaments_inc_knots_and_beads" => {
-            let (fullstitches, nodes, specialstitches) = read_ornaments(&mut reader)?;
+            let (fullstitches, nodes, specialstitches) = read_ornaments(reader, dialect)?;
             pattern.fullstitches.extend(fullstitches);
             pattern.nodes.extend(nodes);
             pattern.specialstitches.extend(specialstitches);
           }
           b"special_stitch_models" if software == Software::EmbroideryStudio => pattern
             .special_stitch_models
-            .extend(read_special_stitch_models(&mut reader)?),
+            .extend(read_special_stitch_models(reader)?),
+          b"display_settings" if software == Software::EmbroideryStudio => {
+            let attributes = read_attributes(e, reader.buffer_position())?;
+            display_settings = Some(read_display_settings(reader, &attributes)?);
+          }
+          b"print_settings" if software == Software::EmbroideryStudio => {
+            let attributes = read_attributes(e, reader.buffer_position())?;
+            print_settings = Some(read_print_settings(reader, &attributes)?);
+          }
           _ => {}
         }
       }
       Ok(Event::End(ref e)) if e.name().as_ref() == b"chart" => break,
       // We don't expect to receive EOF here, because we should have found the end of the `chart` tag.
-      Ok(Event::Eof) => anyhow::bail!("Unexpected EOF"),
-      Err(e) => anyhow::bail!("Error at position {}: {e:?}", reader.error_position()),
+      Ok(Event::Eof) => return Err(OxsError::unexpected_eof("chart").into()),
+      Err(e) => return Err(e.into()),
       _ => {}
     }
     buf.clear();
   }
 
+  let display_settings = match display_settings {
+    Some(mut display_settings) => {
+      // Keep the palette-related settings in sync with the palette in case they were edited by another application.
+      display_settings
+        .symbols
+        .resize(pattern.palette.len(), Symbols::default());
+      display_settings
+        .formats
+        .resize(pattern.palette.len(), Formats::default());
+      display_settings
+    }
+    None => DisplaySettings::new(pattern.palette.len()),
+  };
+
   Ok(PatternProject {
-    file_path,
-    display_settings: DisplaySettings::new(pattern.palette.len()),
-    print_settings: PrintSettings::default(),
+    display_settings,
+    print_settings: print_settings.unwrap_or_default(),
     pattern,
+    ..PatternProject::default()
   })
 }
 
@@ -110,6 +146,8 @@ pub fn save_pattern(patproj: &PatternProject) -> Result<()> {
     write_lines(writer, &pattern.lines)?;
     write_ornaments(writer, &pattern.fullstitches, &pattern.nodes, &pattern.specialstitches)?;
     write_special_stitch_models(writer, &pattern.special_stitch_models)?;
+    write_display_settings(writer, &patproj.display_settings)?;
+    write_print_settings(writer, &patproj.print_settings)?;
     Ok(())
   })?;
 
@@ -117,27 +155,28 @@ pub fn save_pattern(patproj: &PatternProject) -> Result<()> {
 }
 
 fn read_pattern_properties(
-  attributes: &MapAttributes,
+  attributes: &ElementAttributes,
 ) -> Result<(PatternProperties, PatternInfo, StitchesPerInch, usize)> {
   let properties = PatternProperties {
-    width: attributes.get("chartwidth").unwrap().parse()?,
-    height: attributes.get("chartheight").unwrap().parse()?,
+    width: attributes.parse("chartwidth")?,
+    height: attributes.parse("chartheight")?,
   };
 
   let info = PatternInfo {
-    title: attributes.get("charttitle").unwrap().to_owned(),
-    author: attributes.get("author").unwrap().to_owned(),
-    company: attributes.get("company").unwrap_or(&String::new()).to_owned(),
-    copyright: attributes.get("copyright").unwrap().to_owned(),
-    description: attributes.get("instructions").unwrap().to_owned(),
+    title: attributes.get("charttitle").cloned().unwrap_or_default(),
+    author: attributes.get("author").cloned().unwrap_or_default(),
+    company: attributes.get("company").cloned().unwrap_or_default(),
+    copyright: attributes.get("copyright").cloned().unwrap_or_default(),
+    description: attributes.get("instructions").cloned().unwrap_or_default(),
   };
 
   let spi = (
-    attributes.get("stitchesperinch").unwrap().parse()?,
-    attributes.get("stitchesperinch_y").unwrap().parse()?,
+    attributes.parse("stitchesperinch")?,
+    attributes.parse("stitchesperinch_y")?,
   );
 
-  let palette_size: usize = attributes.get("palettecount").unwrap().parse()?;
+  // The palette size is only used as a hint, since the palette is read until its end tag.
+  let palette_size: usize = attributes.parse_or("palettecount", 0)?;
 
   Ok((properties, info, spi, palette_size))
 }
@@ -172,80 +211,81 @@ fn write_pattern_properties<W: io::Write>(
 
 fn read_palette<R: io::BufRead>(
   reader: &mut Reader<R>,
-  software: Software,
+  dialect: &Dialect,
   palette_size: usize,
 ) -> Result<(Fabric, Vec<PaletteItem>)> {
-  fn parse_brand_and_number(value: &str) -> (String, String) {
-    let data = value.split(' ').collect::<Vec<_>>();
-    (
-      data[0..(data.len() - 1)].join(" ").trim_end().to_string(),
-      data.last().unwrap().to_string(),
-    )
-  }
-
   let mut buf = Vec::new();
-  let fabric = if let Event::Start(ref e) = reader.read_event_into(&mut buf)? {
-    let attributes = process_attributes(e.attributes())?;
-    Fabric {
-      name: attributes.get("name").unwrap().to_owned(),
-      color: attributes.get("color").unwrap().to_owned(),
-      kind: attributes.get("kind").unwrap_or(&String::from("Aida")).to_owned(),
-      ..Fabric::default()
-    }
-  } else {
-    anyhow::bail!("Expected a start tag for the fabric palette item")
-  };
-  reader.read_event_into(&mut buf)?; // end of the fabric palette item tag
+  let mut fabric = None;
+  // The hint comes from the file, so it is limited by the maximum number of colors a pattern can have.
+  let mut palette = Vec::with_capacity(palette_size.min(u8::MAX as usize));
+  loop {
+    match reader.read_event_into(&mut buf)? {
+      Event::Start(ref e) if e.name().as_ref() == b"palette_item" => {
+        let attributes = dialect.read_attributes(e, reader.buffer_position())?;
 
-  let mut palette = Vec::with_capacity(palette_size);
-  for _ in 0..palette_size {
-    buf.clear();
-    if let Event::Start(ref e) = reader.read_event_into(&mut buf)? {
-      let attributes = process_attributes(e.attributes())?;
-      let mut palette_item = {
-        let (brand, number) = parse_brand_and_number(attributes.get("number").unwrap());
-        PaletteItem {
+        // The first palette item is always the fabric.
+        if fabric.is_none() {
+          fabric = Some(Fabric {
+            name: attributes
+              .get("name")
+              .cloned()
+              .unwrap_or_else(|| Fabric::default().name),
+            color: attributes.required("color")?.to_owned(),
+            kind: attributes
+              .get("kind")
+              .cloned()
+              .unwrap_or_else(|| Fabric::default().kind),
+            ..Fabric::default()
+          });
+          reader.read_to_end_into(e.to_end().name(), &mut Vec::new())?;
+          buf.clear();
+          continue;
+        }
+
+        let (brand, number) = dialect.read_brand_and_number(&attributes);
+        let mut palette_item = PaletteItem {
           brand,
           number,
-          name: attributes.get("name").unwrap().to_owned(),
-          color: attributes.get("color").unwrap().to_owned(),
+          name: attributes.get("name").cloned().unwrap_or_default(),
+          color: attributes.required("color")?.to_owned(),
           blends: None,
           bead: None,
           strands: None,
-        }
-      };
+        };
 
-      if software == Software::EmbroideryStudio {
+        // Read the blends (they are written only by Embroidery Studio) and skip the rest of the palette item tag.
         let mut buf = Vec::new();
-        let blendscount: usize = attributes.get("blendscount").unwrap().parse()?;
-        for _ in 0..blendscount {
-          if let Event::Start(ref e) = reader.read_event_into(&mut buf)? {
-            let attributes = process_attributes(e.attributes())?;
-            let (brand, number) = parse_brand_and_number(attributes.get("number").unwrap());
-            palette_item
-              .blends
-              .get_or_insert(Vec::with_capacity(blendscount))
-              .push(Blend {
+        loop {
+          match reader.read_event_into(&mut buf)? {
+            Event::Start(ref e) if e.name().as_ref() == b"blend" => {
+              let attributes = dialect.read_attributes(e, reader.buffer_position())?;
+              let (brand, number) = dialect.read_brand_and_number(&attributes);
+              palette_item.blends.get_or_insert_with(Vec::new).push(Blend {
                 brand,
                 number,
                 strands: BlendStrands::new(1),
               });
-            reader.read_event_into(&mut buf)?; // end of the blend tag
+            }
+            Event::End(ref e) if e.name().as_ref() == b"palette_item" => break,
+            Event::Eof => return Err(OxsError::unexpected_eof("palette_item").into()),
+            _ => {}
           }
           buf.clear();
         }
-      }
 
-      palette.push(palette_item);
-
-      // Skip the rest of the palette item tag.
-      reader.read_to_end_into(e.to_end().name(), &mut Vec::new())?;
-    } else {
-      anyhow::bail!("Expected a start tag for the fabric palette item")
+        palette.push(palette_item);
+      }
+      Event::End(ref e) if e.name().as_ref() == b"palette" => break,
+      Event::Eof => return Err(OxsError::unexpected_eof("palette").into()),
+      _ => {}
     }
+    buf.clear();
   }
 
-  Ok((fabric, palette))
+  match fabric {
+    Some(fabric) => Ok((fabric, palette)),
+    None => Err(OxsError::MissingFabric.into()),
+  }
 }
 
 fn write_palette<W: io::Write>(writer: &mut Writer<W>, palette: &[PaletteItem], fabric: &Fabric) -> io::Result<()> {
@@ -295,21 +335,22 @@ fn write_palette<W: io::Write>(writer: &mut Writer<W>, palette: &[PaletteItem],
   Ok(())
 }
 
-fn read_fullstitches<R: io::BufRead>(reader: &mut Reader<R>) -> Result<Stitches<FullStitch>> {
+fn read_fullstitches<R: io::BufRead>(reader: &mut Reader<R>, dialect: &Dialect) -> Result<Stitches<FullStitch>> {
   let mut buf = Vec::new();
   let mut fullstitches = Stitches::new();
   loop {
     match reader.read_event_into(&mut buf)? {
       Event::Start(ref e) if e.name().as_ref() == b"stitch" => {
-        let attributes = process_attributes(e.attributes())?;
+        let attributes = dialect.read_attributes(e, reader.buffer_position())?;
         fullstitches.insert(FullStitch {
-          x: attributes.get("x").unwrap().parse()?,
-          y: attributes.get("y").unwrap().parse()?,
-          palindex: attributes.get("palindex").unwrap().parse::<u8>()? - 1,
+          x: attributes.parse("x")?,
+          y: attributes.parse("y")?,
+          palindex: attributes.parse_palindex("palindex")?,
           kind: FullStitchKind::Full,
         });
       }
       Event::End(ref e) if e.name().as_ref() == b"fullstitches" => break,
+      Event::Eof => return Err(OxsError::unexpected_eof("fullstitches").into()),
       _ => {}
     }
     buf.clear();
@@ -334,31 +375,37 @@ fn write_fullstitches<W: io::Write>(writer: &mut Writer<W>, fullstitches: &Stitc
   Ok(())
 }
 
-fn read_partstitches<R: io::BufRead>(reader: &mut Reader<R>) -> Result<Stitches<PartStitch>> {
+fn read_partstitches<R: io::BufRead>(reader: &mut Reader<R>, dialect: &Dialect) -> Result<Stitches<PartStitch>> {
   let mut buf = Vec::new();
   let mut partstitches = Stitches::new();
   loop {
     match reader.read_event_into(&mut buf)? {
       Event::Start(ref e) if e.name().as_ref() == b"partstitch" => {
-        let attributes = process_attributes(e.attributes())?;
+        let attributes = dialect.read_attributes(e, reader.buffer_position())?;
 
-        let x: Coord = attributes.get("x").unwrap().parse()?;
-        let y: Coord = attributes.get("y").unwrap().parse()?;
+        let x: Coord = attributes.parse("x")?;
+        let y: Coord = attributes.parse("y")?;
 
-        let direction_value: u8 = attributes.get("direction").unwrap().parse()?;
+        let direction_value: u8 = attributes.parse("direction")?;
         let direction = match direction_value {
           1 | 3 => PartStitchDirection::Forward,
           2 | 4 => PartStitchDirection::Backward,
-          _ => anyhow::bail!("Unknown part stitch direction"),
+          _ => {
+            return Err(
+              attributes
+                .invalid("direction", &direction_value.to_string(), "expected 1 to 4")
+                .into(),
+            )
+          }
         };
-        let kind = match direction_value {
-          1 | 2 => PartStitchKind::Quarter,
-          3 | 4 => PartStitchKind::Half,
-          _ => anyhow::bail!("Unknown part stitch kind"),
+        let kind = if direction_value <= 2 {
+          PartStitchKind::Quarter
+        } else {
+          PartStitchKind::Half
         };
 
-        let palindex1: u8 = attributes.get("palindex1").unwrap().parse()?;
-        let palindex2: u8 = attributes.get("palindex2").unwrap().parse()?;
+        let palindex1: u8 = attributes.parse("palindex1")?;
+        let palindex2: u8 = attributes.parse("palindex2")?;
 
         if palindex1 != 0 {
           let (x, y) = if direction_value == 1 { (x, y + 0.5) } else { (x, y) };
@@ -389,6 +436,7 @@ fn read_partstitches<R: io::BufRead>(reader: &mut Reader<R>) -> Result<Stitches<
         }
       }
       Event::End(ref e) if e.name().as_ref() == b"partstitches" => break,
+      Event::Eof => return Err(OxsError::unexpected_eof("partstitches").into()),
       _ => {}
     }
     buf.clear();
@@ -494,31 +542,22 @@ fn write_partstitches<W: io::Write>(writer: &mut Writer<W>, partstitches: &Stitc
   Ok(())
 }
 
-fn read_lines<R: io::BufRead>(reader: &mut Reader<R>) -> Result<Stitches<Line>> {
+fn read_lines<R: io::BufRead>(reader: &mut Reader<R>, dialect: &Dialect) -> Result<Stitches<Line>> {
   let mut buf = Vec::new();
   let mut lines = Stitches::new();
   loop {
     match reader.read_event_into(&mut buf)? {
       Event::Start(ref e) if e.name().as_ref() == b"backstitch" => {
-        let attributes = process_attributes(e.attributes())?;
+        let attributes = dialect.read_attributes(e, reader.buffer_position())?;
         lines.insert(Line {
-          x: (
-            attributes.get("x1").unwrap().parse()?,
-            attributes.get("x2").unwrap().parse()?,
-          ),
-          y: (
-            attributes.get("y1").unwrap().parse()?,
-            attributes.get("y2").unwrap().parse()?,
-          ),
-          palindex: attributes.get("palindex").unwrap().parse::<u8>()? - 1,
-          kind: attributes
-            .get("objecttype")
-            .unwrap()
-            .parse::<LineKind>()
-            .map_err(|e| anyhow::anyhow!(e))?,
+          x: (attributes.parse("x1")?, attributes.parse("x2")?),
+          y: (attributes.parse("y1")?, attributes.parse("y2")?),
+          palindex: attributes.parse_palindex("palindex")?,
+          kind: attributes.parse::<LineKind>("objecttype")?,
         });
       }
       Event::End(ref e) if e.name().as_ref() == b"backstitches" => break,
+      Event::Eof => return Err(OxsError::unexpected_eof("backstitches").into()),
       _ => {}
     }
     buf.clear();
@@ -548,6 +587,7 @@ fn write_lines<W: io::Write>(writer: &mut Writer<W>, lines: &Stitches<Line>) ->
 
 fn read_ornaments<R: io::BufRead>(
   reader: &mut Reader<R>,
+  dialect: &Dialect,
 ) -> Result<(Stitches<FullStitch>, Stitches<Node>, Stitches<SpecialStitch>)> {
   let mut buf = Vec::new();
   let mut fullstitches = Stitches::new();
@@ -556,44 +596,42 @@ fn read_ornaments<R: io::BufRead>(
   loop {
     match reader.read_event_into(&mut buf)? {
       Event::Start(ref e) if e.name().as_ref() == b"object" => {
-        let attributes = process_attributes(e.attributes())?;
+        let attributes = dialect.read_attributes(e, reader.buffer_position())?;
 
-        let x: Coord = attributes.get("x1").unwrap().parse()?;
-        let y: Coord = attributes.get("y1").unwrap().parse()?;
-        let rotated = if let Some(rotated) = attributes.get("rotated") {
-          rotated.parse()?
-        } else {
-          false
-        };
-        let palindex: u8 = attributes.get("palindex").unwrap().parse::<u8>()? - 1;
-        let kind = attributes.get("objecttype").unwrap();
-
-        // Yes, the Ursa Software's OXS format uses the "quarter" stitch for petites.
-        if kind == "quarter" {
-          let kind = FullStitchKind::Petite;
-          fullstitches.insert(FullStitch { x, y, palindex, kind });
-        }
+        let x: Coord = attributes.parse("x1")?;
+        let y: Coord = attributes.parse("y1")?;
+        let rotated = attributes.parse_or("rotated", false)?;
+        let palindex: u8 = attributes.parse_palindex("palindex")?;
+        let objecttype = attributes.required("objecttype")?;
 
-        if kind.starts_with("bead") || kind == "knot" {
-          let kind = NodeKind::from_str(kind).unwrap();
-          nodes.insert(Node { x, y, rotated, palindex, kind });
-        }
-
-        if kind == "special" {
-          specialstitches.insert(SpecialStitch {
-            x,
-            y,
-            rotation: attributes.get("rotation").unwrap().parse()?,
-            flip: (
-              attributes.get("flip_x").unwrap().parse()?,
-              attributes.get("flip_y").unwrap().parse()?,
-            ),
-            palindex,
-            modindex: attributes.get("modindex").unwrap().parse()?,
-          });
+        match dialect.read_ornament_kind(objecttype) {
+          Some(OrnamentKind::Petite) => {
+            let kind = FullStitchKind::Petite;
+            fullstitches.insert(FullStitch { x, y, palindex, kind });
+          }
+          Some(OrnamentKind::FrenchKnot) => {
+            let kind = NodeKind::FrenchKnot;
+            nodes.insert(Node { x, y, rotated, palindex, kind });
+          }
+          Some(OrnamentKind::Bead) => {
+            let kind = NodeKind::Bead;
+            nodes.insert(Node { x, y, rotated, palindex, kind });
+          }
+          Some(OrnamentKind::Special) => {
+            specialstitches.insert(SpecialStitch {
+              x,
+              y,
+              rotation: attributes.parse("rotation")?,
+              flip: (attributes.parse("flip_x")?, attributes.parse("flip_y")?),
+              palindex,
+              modindex: attributes.parse("modindex")?,
+            });
+          }
+          None => log::debug!("Skipping unknown ornament object: {objecttype}"),
         }
       }
       Event::End(ref e) if e.name().as_ref() == b"ornaments_inc_knots_and_beads" => break,
+      Event::Eof => return Err(OxsError::unexpected_eof("ornaments_inc_knots_and_beads").into()),
       _ => {}
     }
     buf.clear();
@@ -663,44 +701,30 @@ fn read_special_stitch_models<R: io::BufRead>(reader: &mut Reader<R>) -> Result<
   loop {
     match reader.read_event_into(&mut buf)? {
       Event::Start(ref e) if e.name().as_ref() == b"model" => {
-        let attributes = process_attributes(e.attributes())?;
-        let unique_name = attributes.get("unique_name").unwrap().to_owned();
-        let name = attributes.get("name").unwrap().to_owned();
+        let attributes = read_attributes(e, reader.buffer_position())?;
+        let unique_name = attributes.required("unique_name")?.to_owned();
+        let name = attributes.required("name")?.to_owned();
         let mut lines = Vec::new();
         let mut nodes = Vec::new();
         let mut curves = Vec::new();
         loop {
           match reader.read_event_into(&mut buf)? {
             Event::Start(ref e) if e.name().as_ref() == b"line" => {
-              let attributes = process_attributes(e.attributes())?;
+              let attributes = read_attributes(e, reader.buffer_position())?;
               lines.push(Line {
-                x: (
-                  attributes.get("x1").unwrap().parse()?,
-                  attributes.get("x2").unwrap().parse()?,
-                ),
-                y: (
-                  attributes.get("y1").unwrap().parse()?,
-                  attributes.get("y2").unwrap().parse()?,
-                ),
-                kind: attributes
-                  .get("kind")
-                  .unwrap()
-                  .parse::<LineKind>()
-                  .map_err(|e| anyhow::anyhow!(e))?,
+                x: (attributes.parse("x1")?, attributes.parse("x2")?),
+                y: (attributes.parse("y1")?, attributes.parse("y2")?),
+                kind: attributes.parse::<LineKind>("kind")?,
                 palindex: 0,
               });
             }
             Event::Start(ref e) if e.name().as_ref() == b"node" => {
-              let attributes = process_attributes(e.attributes())?;
+              let attributes = read_attributes(e, reader.buffer_position())?;
               nodes.push(Node {
-                x: attributes.get("x").unwrap().parse()?,
-                y: attributes.get("y").unwrap().parse()?,
-                rotated: attributes.get("rotated").unwrap().parse()?,
-                kind: attributes
-                  .get("kind")
-                  .unwrap()
-                  .parse::<NodeKind>()
-                  .map_err(|e| anyhow::anyhow!(e))?,
+                x: attributes.parse("x")?,
+                y: attributes.parse("y")?,
+                rotated: attributes.parse("rotated")?,
+                kind: attributes.parse::<NodeKind>("kind")?,
                 palindex: 0,
               });
             }
@@ -709,16 +733,14 @@ fn read_special_stitch_models<R: io::BufRead>(reader: &mut Reader<R>) -> Result<
               loop {
                 match reader.read_event_into(&mut buf)? {
                   Event::Start(ref e) if e.name().as_ref() == b"point" => {
-                    let attributes = process_attributes(e.attributes())?;
-                    points.push((
-                      attributes.get("x").unwrap().parse()?,
-                      attributes.get("y").unwrap().parse()?,
-                    ));
+                    let attributes = read_attributes(e, reader.buffer_position())?;
+                    points.push((attributes.parse("x")?, attributes.parse("y")?));
                   }
                   Event::End(ref e) if e.name().as_ref() == b"curve" => {
                     curves.push(Curve { points });
                     break;
                   }
+                  Event::Eof => return Err(OxsError::unexpected_eof("curve").into()),
                   _ => {}
                 }
                 buf.clear();
@@ -734,11 +756,13 @@ fn read_special_stitch_models<R: io::BufRead>(reader: &mut Reader<R>) -> Result<
               });
               break;
             }
+            Event::Eof => return Err(OxsError::unexpected_eof("model").into()),
             _ => {}
           }
         }
       }
       Event::End(ref e) if e.name().as_ref() == b"special_stitch_models" => break,
+      Event::Eof => return Err(OxsError::unexpected_eof("special_stitch_models").into()),
       _ => {}
     }
   }
@@ -805,3 +829,645 @@ fn write_special_stitch_models<W: io::Write>(
     })?;
   Ok(())
 }
+
+// The display and print settings are not a part of the OXS specification.
+// They are written only by Embroidery Studio as extension elements, so other applications can safely ignore them.
+
+fn read_display_settings<R: io::BufRead>(
+  reader: &mut Reader<R>,
+  attributes: &ElementAttributes,
+) -> Result<DisplaySettings> {
+  let default = DisplaySettings::default();
+  let mut display_settings = DisplaySettings {
+    default_stitch_font: attributes
+      .get("default_stitch_font")
+      .cloned()
+      .unwrap_or(default.default_stitch_font),
+    view: attributes.parse_or("view", default.view)?,
+    zoom: attributes.parse_or("zoom", default.zoom)?,
+    show_grid: attributes.parse_or("show_grid", default.show_grid)?,
+    show_rulers: attributes.parse_or("show_rulers", default.show_rulers)?,
+    show_centering_marks: attributes.parse_or("show_centering_marks", default.show_centering_marks)?,
+    show_fabric_colors_with_symbols: attributes.parse_or(
+      "show_fabric_colors_with_symbols",
+      default.show_fabric_colors_with_symbols,
+    )?,
+    gaps_between_stitches: attributes.parse_or("gaps_between_stitches", default.gaps_between_stitches)?,
+    outlined_stitches: attributes.parse_or("outlined_stitches", default.outlined_stitches)?,
+    ..DisplaySettings::default()
+  };
+
+  let mut buf = Vec::new();
+  loop {
+    match reader.read_event_into(&mut buf)? {
+      Event::Start(ref e) => {
+        let attributes = read_attributes(e, reader.buffer_position())?;
+        match e.name().as_ref() {
+          b"symbol" => display_settings.symbols.push(read_symbols(&attributes)?),
+          b"symbol_settings" => display_settings.symbol_settings = read_symbol_settings(&attributes)?,
+          b"format" => display_settings.formats.push(read_formats(reader)?),
+          b"grid" => display_settings.grid = read_grid(reader, &attributes)?,
+          b"stitch_outline" => display_settings.stitch_outline = read_stitch_outline(&attributes)?,
+          b"stitch_settings" => display_settings.stitch_settings = read_stitch_settings(reader)?,
+          _ => {}
+        }
+      }
+      Event::End(ref e) if e.name().as_ref() == b"display_settings" => break,
+      Event::Eof => return Err(OxsError::unexpected_eof("display_settings").into()),
+      _ => {}
+    }
+    buf.clear();
+  }
+
+  Ok(display_settings)
+}
+
+fn write_display_settings<W: io::Write>(writer: &mut Writer<W>, display_settings: &DisplaySettings) -> io::Result<()> {
+  writer
+    .create_element("display_settings")
+    .with_attributes([
+      ("default_stitch_font", display_settings.default_stitch_font.as_str()),
+      ("view", display_settings.view.to_string().as_str()),
+      ("zoom", display_settings.zoom.to_string().as_str()),
+      ("show_grid", display_settings.show_grid.to_string().as_str()),
+      ("show_rulers", display_settings.show_rulers.to_string().as_str()),
+      (
+        "show_centering_marks",
+        display_settings.show_centering_marks.to_string().as_str(),
+      ),
+      (
+        "show_fabric_colors_with_symbols",
+        display_settings.show_fabric_colors_with_symbols.to_string().as_str(),
+      ),
+      (
+        "gaps_between_stitches",
+        display_settings.gaps_between_stitches.to_string().as_str(),
+      ),
+      (
+        "outlined_stitches",
+        display_settings.outlined_stitches.to_string().as_str(),
+      ),
+    ])
+    .write_inner_content(|writer| {
+      writer.create_element("symbols").write_inner_content(|writer| {
+        for (index, symbols) in display_settings.symbols.iter().enumerate() {
+          write_symbols(writer, index, symbols)?;
+        }
+        Ok(())
+      })?;
+      write_symbol_settings(writer, &display_settings.symbol_settings)?;
+      writer.create_element("formats").write_inner_content(|writer| {
+        for (index, formats) in display_settings.formats.iter().enumerate() {
+          write_formats(writer, index, formats)?;
+        }
+        Ok(())
+      })?;
+      write_grid(writer, &display_settings.grid)?;
+      write_stitch_outline(writer, &display_settings.stitch_outline)?;
+      write_stitch_settings(writer, &display_settings.stitch_settings)?;
+      Ok(())
+    })?;
+  Ok(())
+}
+
+fn read_symbols(attributes: &ElementAttributes) -> Result<Symbols> {
+  let parse_symbol = |key: &str| -> Result<Option<u16>> {
+    attributes
+      .get(key)
+      .map(|value| value.parse())
+      .transpose()
+      .map_err(Into::into)
+  };
+  Ok(Symbols {
+    full: parse_symbol("full")?,
+    petite: parse_symbol("petite")?,
+    half: parse_symbol("half")?,
+    quarter: parse_symbol("quarter")?,
+    french_knot: parse_symbol("french_knot")?,
+    bead: parse_symbol("bead")?,
+  })
+}
+
+fn write_symbols<W: io::Write>(writer: &mut Writer<W>, index: usize, symbols: &Symbols) -> io::Result<()> {
+  let mut attributes = vec![("index", (index + 1).to_string())];
+  for (key, symbol) in [
+    ("full", symbols.full),
+    ("petite", symbols.petite),
+    ("half", symbols.half),
+    ("quarter", symbols.quarter),
+    ("french_knot", symbols.french_knot),
+    ("bead", symbols.bead),
+  ] {
+    if let Some(symbol) = symbol {
+      attributes.push((key, symbol.to_string()));
+    }
+  }
+  writer
+    .create_element("symbol")
+    .with_attributes(attributes.iter().map(|(key, value)| (*key, value.as_str())))
+    .write_empty()?;
+  Ok(())
+}
+
+fn read_symbol_settings(attributes: &ElementAttributes) -> Result<SymbolSettings> {
+  let default = SymbolSettings::default();
+  Ok(SymbolSettings {
+    screen_spacing: (
+      attributes.parse_or("screen_spacing_x", default.screen_spacing.0)?,
+      attributes.parse_or("screen_spacing_y", default.screen_spacing.1)?,
+    ),
+    printer_spacing: (
+      attributes.parse_or("printer_spacing_x", default.printer_spacing.0)?,
+      attributes.parse_or("printer_spacing_y", default.printer_spacing.1)?,
+    ),
+    scale_using_maximum_font_width: attributes
+      .parse_or("scale_using_maximum_font_width", default.scale_using_maximum_font_width)?,
+    scale_using_font_height: attributes.parse_or("scale_using_font_height", default.scale_using_font_height)?,
+    stitch_size: Percentage::new(attributes.parse_or("stitch_size", default.stitch_size.into_inner())?),
+    small_stitch_size: Percentage::new(
+      attributes.parse_or("small_stitch_size", default.small_stitch_size.into_inner())?,
+    ),
+    draw_symbols_over_backstitches: attributes
+      .parse_or("draw_symbols_over_backstitches", default.draw_symbols_over_backstitches)?,
+    show_stitch_color: attributes.parse_or("show_stitch_color", default.show_stitch_color)?,
+    use_large_half_stitch_symbol: attributes
+      .parse_or("use_large_half_stitch_symbol", default.use_large_half_stitch_symbol)?,
+    use_triangles_behind_quarter_stitches: attributes.parse_or(
+      "use_triangles_behind_quarter_stitches",
+      default.use_triangles_behind_quarter_stitches,
+    )?,
+  })
+}
+
+fn write_symbol_settings<W: io::Write>(writer: &mut Writer<W>, symbol_settings: &SymbolSettings) -> io::Result<()> {
+  writer
+    .create_element("symbol_settings")
+    .with_attributes([
+      (
+        "screen_spacing_x",
+        symbol_settings.screen_spacing.0.to_string().as_str(),
+      ),
+      (
+        "screen_spacing_y",
+        symbol_settings.screen_spacing.1.to_string().as_str(),
+      ),
+      (
+        "printer_spacing_x",
+        symbol_settings.printer_spacing.0.to_string().as_str(),
+      ),
+      (
+        "printer_spacing_y",
+        symbol_settings.printer_spacing.1.to_string().as_str(),
+      ),
+      (
+        "scale_using_maximum_font_width",
+        symbol_settings.scale_using_maximum_font_width.to_string().as_str(),
+      ),
+      (
+        "scale_using_font_height",
+        symbol_settings.scale_using_font_height.to_string().as_str(),
+      ),
+      (
+        "stitch_size",
+        symbol_settings.stitch_size.into_inner().to_string().as_str(),
+      ),
+      (
+        "small_stitch_size",
+        symbol_settings.small_stitch_size.into_inner().to_string().as_str(),
+      ),
+      (
+        "draw_symbols_over_backstitches",
+        symbol_settings.draw_symbols_over_backstitches.to_string().as_str(),
+      ),
+      (
+        "show_stitch_color",
+        symbol_settings.show_stitch_color.to_string().as_str(),
+      ),
+      (
+        "use_large_half_stitch_symbol",
+        symbol_settings.use_large_half_stitch_symbol.to_string().as_str(),
+      ),
+      (
+        "use_triangles_behind_quarter_stitches",
+        symbol_settings
+          .use_triangles_behind_quarter_stitches
+          .to_string()
+          .as_str(),
+      ),
+    ])
+    .write_empty()?;
+  Ok(())
+}
+
+fn read_formats<R: io::BufRead>(reader: &mut Reader<R>) -> Result<Formats> {
+  fn read_line_format(attributes: &ElementAttributes) -> Result<LineFormat> {
+    let default = LineFormat::default();
+    Ok(LineFormat {
+      use_alt_color: attributes.parse_or("use_alt_color", default.use_alt_color)?,
+      color: attributes.get("color").cloned().unwrap_or(default.color),
+      style: attributes.parse_or("style", default.style)?,
+      thickness: StitchThickness::new(attributes.parse_or("thickness", default.thickness.into_inner())?),
+    })
+  }
+
+  fn read_node_format(attributes: &ElementAttributes) -> Result<NodeFormat> {
+    let default = NodeFormat::default();
+    Ok(NodeFormat {
+      use_dot_style: attributes.parse_or("use_dot_style", default.use_dot_style)?,
+      use_alt_color: attributes.parse_or("use_alt_color", default.use_alt_color)?,
+      color: attributes.get("color").cloned().unwrap_or(default.color),
+      thickness: StitchThickness::new(attributes.parse_or("thickness", default.thickness.into_inner())?),
+    })
+  }
+
+  let mut formats = Formats::default();
+  let mut buf = Vec::new();
+  loop {
+    match reader.read_event_into(&mut buf)? {
+      Event::Start(ref e) => {
+        let attributes = read_attributes(e, reader.buffer_position())?;
+        match e.name().as_ref() {
+          b"symbol" => {
+            let default = SymbolFormat::default();
+            formats.symbol = SymbolFormat {
+              use_alt_bg_color: attributes.parse_or("use_alt_bg_color", default.use_alt_bg_color)?,
+              bg_color: attributes.get("bg_color").cloned().unwrap_or(default.bg_color),
+              fg_color: attributes.get("fg_color").cloned().unwrap_or(default.fg_color),
+            };
+          }
+          b"back" => formats.back = read_line_format(&attributes)?,
+          b"straight" => formats.straight = read_line_format(&attributes)?,
+          b"special" => formats.special = read_line_format(&attributes)?,
+          b"french" => formats.french = read_node_format(&attributes)?,
+          b"bead" => formats.bead = read_node_format(&attributes)?,
+          b"font" => {
+            let default = FontFormat::default();
+            formats.font = FontFormat {
+              font_name: attributes.get("font_name").cloned(),
+              bold: attributes.parse_or("bold", default.bold)?,
+              italic: attributes.parse_or("italic", default.italic)?,
+              stitch_size: Percentage::new(attributes.parse_or("stitch_size", default.stitch_size.into_inner())?),
+              small_stitch_size: Percentage::new(
+                attributes.parse_or("small_stitch_size", default.small_stitch_size.into_inner())?,
+              ),
+            };
+          }
+          _ => {}
+        }
+      }
+      Event::End(ref e) if e.name().as_ref() == b"format" => break,
+      Event::Eof => return Err(OxsError::unexpected_eof("format").into()),
+      _ => {}
+    }
+    buf.clear();
+  }
+
+  Ok(formats)
+}
+
+fn write_formats<W: io::Write>(writer: &mut Writer<W>, index: usize, formats: &Formats) -> io::Result<()> {
+  fn write_line_format<W: io::Write>(writer: &mut Writer<W>, name: &str, format: &LineFormat) -> io::Result<()> {
+    writer
+      .create_element(name)
+      .with_attributes([
+        ("use_alt_color", format.use_alt_color.to_string().as_str()),
+        ("color", format.color.as_str()),
+        ("style", format.style.to_string().as_str()),
+        ("thickness", format.thickness.into_inner().to_string().as_str()),
+      ])
+      .write_empty()?;
+    Ok(())
+  }
+
+  fn write_node_format<W: io::Write>(writer: &mut Writer<W>, name: &str, format: &NodeFormat) -> io::Result<()> {
+    writer
+      .create_element(name)
+      .with_attributes([
+        ("use_dot_style", format.use_dot_style.to_string().as_str()),
+        ("use_alt_color", format.use_alt_color.to_string().as_str()),
+        ("color", format.color.as_str()),
+        ("thickness", format.thickness.into_inner().to_string().as_str()),
+      ])
+      .write_empty()?;
+    Ok(())
+  }
+
+  writer
+    .create_element("format")
+    .with_attribute(("index", (index + 1).to_string().as_str()))
+    .write_inner_content(|writer| {
+      writer
+        .create_element("symbol")
+        .with_attributes([
+          ("use_alt_bg_color", formats.symbol.use_alt_bg_color.to_string().as_str()),
+          ("bg_color", formats.symbol.bg_color.as_str()),
+          ("fg_color", formats.symbol.fg_color.as_str()),
+        ])
+        .write_empty()?;
+      write_line_format(writer, "back", &formats.back)?;
+      write_line_format(writer, "straight", &formats.straight)?;
+      write_line_format(writer, "special", &formats.special)?;
+      write_node_format(writer, "french", &formats.french)?;
+      write_node_format(writer, "bead", &formats.bead)?;
+
+      let font = &formats.font;
+      let mut attributes = vec![
+        ("bold", font.bold.to_string()),
+        ("italic", font.italic.to_string()),
+        ("stitch_size", font.stitch_size.into_inner().to_string()),
+        ("small_stitch_size", font.small_stitch_size.into_inner().to_string()),
+      ];
+      if let Some(font_name) = &font.font_name {
+        attributes.push(("font_name", font_name.to_owned()));
+      }
+      writer
+        .create_element("font")
+        .with_attributes(attributes.iter().map(|(key, value)| (*key, value.as_str())))
+        .write_empty()?;
+
+      Ok(())
+    })?;
+  Ok(())
+}
+
+fn read_grid<R: io::BufRead>(reader: &mut Reader<R>, attributes: &ElementAttributes) -> Result<Grid> {
+  fn read_grid_line_style(attributes: &ElementAttributes, default: GridLineStyle) -> Result<GridLineStyle> {
+    Ok(GridLineStyle {
+      color: attributes.get("color").cloned().unwrap_or(default.color),
+      thickness: attributes.parse_or("thickness", default.thickness)?,
+    })
+  }
+
+  let default = Grid::default();
+  let mut grid = Grid {
+    major_line_every_stitches: attributes.parse_or("major_line_every_stitches", default.major_line_every_stitches)?,
+    ..default
+  };
+
+  let mut buf = Vec::new();
+  loop {
+    match reader.read_event_into(&mut buf)? {
+      Event::Start(ref e) => {
+        let attributes = read_attributes(e, reader.buffer_position())?;
+        match e.name().as_ref() {
+          b"minor_screen_lines" => {
+            grid.minor_screen_lines = read_grid_line_style(&attributes, grid.minor_screen_lines)?;
+          }
+          b"major_screen_lines" => {
+            grid.major_screen_lines = read_grid_line_style(&attributes, grid.major_screen_lines)?;
+          }
+          b"minor_printer_lines" => {
+            grid.minor_printer_lines = read_grid_line_style(&attributes, grid.minor_printer_lines)?;
+          }
+          b"major_printer_lines" => {
+            grid.major_printer_lines = read_grid_line_style(&attributes, grid.major_printer_lines)?;
+          }
+          _ => {}
+        }
+      }
+      Event::End(ref e) if e.name().as_ref() == b"grid" => break,
+      Event::Eof => return Err(OxsError::unexpected_eof("grid").into()),
+      _ => {}
+    }
+    buf.clear();
+  }
+
+  Ok(grid)
+}
+
+fn write_grid<W: io::Write>(writer: &mut Writer<W>, grid: &Grid) -> io::Result<()> {
+  writer
+    .create_element("grid")
+    .with_attribute((
+      "major_line_every_stitches",
+      grid.major_line_every_stitches.to_string().as_str(),
+    ))
+    .write_inner_content(|writer| {
+      for (name, style) in [
+        ("minor_screen_lines", &grid.minor_screen_lines),
+        ("major_screen_lines", &grid.major_screen_lines),
+        ("minor_printer_lines", &grid.minor_printer_lines),
+        ("major_printer_lines", &grid.major_printer_lines),
+      ] {
+        writer
+          .create_element(name)
+          .with_attributes([
+            ("color", style.color.as_str()),
+            ("thickness", style.thickness.to_string().as_str()),
+          ])
+          .write_empty()?;
+      }
+      Ok(())
+    })?;
+  Ok(())
+}
+
+fn read_stitch_outline(attributes: &ElementAttributes) -> Result<StitchOutline> {
+  let default = StitchOutline::default();
+  Ok(StitchOutline {
+    color: attributes.get("color").cloned(),
+    color_percentage: Percentage::new(attributes.parse_or("color_percentage", default.color_percentage.into_inner())?),
+    thickness: StitchOutlineThickness::new(attributes.parse_or("thickness", default.thickness.into_inner())?),
+  })
+}
+
+fn write_stitch_outline<W: io::Write>(writer: &mut Writer<W>, stitch_outline: &StitchOutline) -> io::Result<()> {
+  let mut attributes = vec![
+    (
+      "color_percentage",
+      stitch_outline.color_percentage.into_inner().to_string(),
+    ),
+    ("thickness", stitch_outline.thickness.into_inner().to_string()),
+  ];
+  if let Some(color) = &stitch_outline.color {
+    attributes.push(("color", color.to_owned()));
+  }
+  writer
+    .create_element("stitch_outline")
+    .with_attributes(attributes.iter().map(|(key, value)| (*key, value.as_str())))
+    .write_empty()?;
+  Ok(())
+}
+
+fn read_stitch_settings<R: io::BufRead>(reader: &mut Reader<R>) -> Result<StitchSettings> {
+  let mut stitch_settings = StitchSettings::default();
+  let mut thickness_index = 0;
+
+  let mut buf = Vec::new();
+  loop {
+    match reader.read_event_into(&mut buf)? {
+      Event::Start(ref e) => {
+        let attributes = read_attributes(e, reader.buffer_position())?;
+        match e.name().as_ref() {
+          b"default_strands" => {
+            let default = DefaultStitchStrands::default();
+            let parse_strands = |key: &str, default: StitchStrands| -> Result<StitchStrands> {
+              Ok(StitchStrands::new(attributes.parse_or(key, default.into_inner())?))
+            };
+            stitch_settings.default_strands = DefaultStitchStrands {
+              full: parse_strands("full", default.full)?,
+              petite: parse_strands("petite", default.petite)?,
+              half: parse_strands("half", default.half)?,
+              quarter: parse_strands("quarter", default.quarter)?,
+              back: parse_strands("back", default.back)?,
+              straight: parse_strands("straight", default.straight)?,
+              french_knot: parse_strands("french_knot", default.french_knot)?,
+              special: parse_strands("special", default.special)?,
+            };
+          }
+          b"thickness" => {
+            if let Some(thickness) = stitch_settings.display_thickness.get_mut(thickness_index) {
+              *thickness = StitchThickness::new(attributes.parse_or("value", thickness.into_inner())?);
+            }
+            thickness_index += 1;
+          }
+          _ => {}
+        }
+      }
+      Event::End(ref e) if e.name().as_ref() == b"stitch_settings" => break,
+      Event::Eof => return Err(OxsError::unexpected_eof("stitch_settings").into()),
+      _ => {}
+    }
+    buf.clear();
+  }
+
+  Ok(stitch_settings)
+}
+
+fn write_stitch_settings<W: io::Write>(writer: &mut Writer<W>, stitch_settings: &StitchSettings) -> io::Result<()> {
+  writer.create_element("stitch_settings").write_inner_content(|writer| {
+    let strands = &stitch_settings.default_strands;
+    writer
+      .create_element("default_strands")
+      .with_attributes([
+        ("full", strands.full.into_inner().to_string().as_str()),
+        ("petite", strands.petite.into_inner().to_string().as_str()),
+        ("half", strands.half.into_inner().to_string().as_str()),
+        ("quarter", strands.quarter.into_inner().to_string().as_str()),
+        ("back", strands.back.into_inner().to_string().as_str()),
+        ("straight", strands.straight.into_inner().to_string().as_str()),
+        ("french_knot", strands.french_knot.into_inner().to_string().as_str()),
+        ("special", strands.special.into_inner().to_string().as_str()),
+      ])
+      .write_empty()?;
+
+    // 1..=12 - strands, 13 - french knot.
+    writer
+      .create_element("display_thickness")
+      .write_inner_content(|writer| {
+        for thickness in stitch_settings.display_thickness.iter() {
+          writer
+            .create_element("thickness")
+            .with_attribute(("value", thickness.into_inner().to_string().as_str()))
+            .write_empty()?;
+        }
+        Ok(())
+      })?;
+
+    Ok(())
+  })?;
+  Ok(())
+}
+
+fn read_print_settings<R: io::BufRead>(
+  reader: &mut Reader<R>,
+  attributes: &ElementAttributes,
+) -> Result<PrintSettings> {
+  let default = PrintSettings::default();
+  let mut print_settings = PrintSettings {
+    header: attributes.get("header").cloned().unwrap_or(default.header),
+    footer: attributes.get("footer").cloned().unwrap_or(default.footer),
+    show_page_numbers: attributes.parse_or("show_page_numbers", default.show_page_numbers)?,
+    show_adjacent_page_numbers: attributes
+      .parse_or("show_adjacent_page_numbers", default.show_adjacent_page_numbers)?,
+    center_chart_on_pages: attributes.parse_or("center_chart_on_pages", default.center_chart_on_pages)?,
+    ..PrintSettings::default()
+  };
+
+  let mut buf = Vec::new();
+  loop {
+    match reader.read_event_into(&mut buf)? {
+      Event::Start(ref e) => {
+        let attributes = read_attributes(e, reader.buffer_position())?;
+        match e.name().as_ref() {
+          b"font" => {
+            let default = Font::default();
+            print_settings.font = Font {
+              name: attributes.get("name").cloned().unwrap_or(default.name),
+              size: attributes.parse_or("size", default.size)?,
+              weight: FontWeight::new(attributes.parse_or("weight", default.weight.into_inner())?),
+              italic: attributes.parse_or("italic", default.italic)?,
+            };
+          }
+          b"margins" => {
+            let default = PageMargins::default();
+            print_settings.margins = PageMargins {
+              left: attributes.parse_or("left", default.left)?,
+              right: attributes.parse_or("right", default.right)?,
+              top: attributes.parse_or("top", default.top)?,
+              bottom: attributes.parse_or("bottom", default.bottom)?,
+              header: attributes.parse_or("header", default.header)?,
+              footer: attributes.parse_or("footer", default.footer)?,
+            };
+          }
+          _ => {}
+        }
+      }
+      Event::End(ref e) if e.name().as_ref() == b"print_settings" => break,
+      Event::Eof => return Err(OxsError::unexpected_eof("print_settings").into()),
+      _ => {}
+    }
+    buf.clear();
+  }
+
+  Ok(print_settings)
+}
+
+fn write_print_settings<W: io::Write>(writer: &mut Writer<W>, print_settings: &PrintSettings) -> io::Result<()> {
+  writer
+    .create_element("print_settings")
+    .with_attributes([
+      ("header", print_settings.header.as_str()),
+      ("footer", print_settings.footer.as_str()),
+      (
+        "show_page_numbers",
+        print_settings.show_page_numbers.to_string().as_str(),
+      ),
+      (
+        "show_adjacent_page_numbers",
+        print_settings.show_adjacent_page_numbers.to_string().as_str(),
+      ),
+      (
+        "center_chart_on_pages",
+        print_settings.center_chart_on_pages.to_string().as_str(),
+      ),
+    ])
+    .write_inner_content(|writer| {
+      let font = &print_settings.font;
+      writer
+        .create_element("font")
+        .with_attributes([
+          ("name", font.name.as_str()),
+          ("size", font.size.to_string().as_str()),
+          ("weight", font.weight.clone().into_inner().to_string().as_str()),
+          ("italic", font.italic.to_string().as_str()),
+        ])
+        .write_empty()?;
+
+      let margins = &print_settings.margins;
+      writer
+        .create_element("margins")
+        .with_attributes([
+          ("left", margins.left.to_string().as_str()),
+          ("right", margins.right.to_string().as_str()),
+          ("top", margins.top.to_string().as_str()),
+          ("bottom", margins.bottom.to_string().as_str()),
+          ("header", margins.header.to_string().as_str()),
+          ("footer", margins.footer.to_string().as_str()),
+        ])
+        .write_empty()?;
+
+      Ok(())
+    })?;
+  Ok(())
+}
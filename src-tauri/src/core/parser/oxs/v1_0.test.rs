@@ -0,0 +1,66 @@
+use super::*;
+
+fn load_pattern_project() -> PatternProject {
+  let file_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/patterns/piggies.oxs");
+  let patproj = parse_pattern(&std::fs::read(&file_path).unwrap(), Software::Ursa).unwrap();
+  PatternProject { file_path, ..patproj }
+}
+
+#[test]
+fn uses_default_settings_for_foreign_files() {
+  let patproj = load_pattern_project();
+  assert_eq!(
+    patproj.display_settings,
+    DisplaySettings::new(patproj.pattern.palette.len())
+  );
+  assert_eq!(patproj.print_settings, PrintSettings::default());
+}
+
+#[test]
+fn round_trips_display_and_print_settings() {
+  let mut patproj = load_pattern_project();
+  patproj.file_path = std::env::temp_dir().join("round_trips_display_and_print_settings.oxs");
+
+  let display_settings = &mut patproj.display_settings;
+  display_settings.default_stitch_font = String::from("CrossStitch2");
+  display_settings.view = View::Symbols;
+  display_settings.zoom = 250;
+  display_settings.show_rulers = false;
+  display_settings.gaps_between_stitches = true;
+  display_settings.symbols[0] = Symbols {
+    full: Some(164),
+    petite: None,
+    half: Some(165),
+    quarter: None,
+    french_knot: Some(33),
+    bead: None,
+  };
+  display_settings.symbol_settings.screen_spacing = (2, 3);
+  display_settings.symbol_settings.small_stitch_size = Percentage::new(45);
+  display_settings.formats[1].back = LineFormat {
+    use_alt_color: true,
+    color: String::from("FF0000"),
+    style: LineStyle::ZigZag,
+    thickness: StitchThickness::new(2.5),
+  };
+  display_settings.formats[1].font.font_name = Some(String::from("Ursa"));
+  display_settings.formats[2].french.use_dot_style = false;
+  display_settings.grid.major_line_every_stitches = 5;
+  display_settings.grid.major_screen_lines.color = String::from("112233");
+  display_settings.stitch_outline.color = Some(String::from("ABCDEF"));
+  display_settings.stitch_settings.default_strands.back = StitchStrands::new(3);
+  display_settings.stitch_settings.display_thickness[12] = StitchThickness::new(5.5);
+
+  let print_settings = &mut patproj.print_settings;
+  print_settings.header = String::from("&l&t");
+  print_settings.show_adjacent_page_numbers = false;
+  print_settings.font.name = String::from("Courier New");
+  print_settings.font.weight = FontWeight::new(700);
+  print_settings.margins.left = 0.75;
+
+  save_pattern(&patproj).unwrap();
+  let loaded = parse_pattern(&std::fs::read(&patproj.file_path).unwrap(), Software::EmbroideryStudio).unwrap();
+
+  assert_eq!(loaded.display_settings, patproj.display_settings);
+  assert_eq!(loaded.print_settings, patproj.print_settings);
+}
@@ -2,4 +2,4 @@ mod read;
 
 #[allow(clippy::module_inception)]
 mod xsd;
-pub use xsd::parse_pattern;
+pub use xsd::{parse_pattern, XsdError, VALID_SIGNATURE};
@@ -24,7 +24,7 @@ static PM_FLOSS_BRANDS: LazyLock<std::collections::HashMap<u8, String>> = LazyLo
   serde_json::from_str(pm_floss_brands).expect("Failed to parse the PM floss brands")
 });
 
-const VALID_SIGNATURE: u16 = 0x0510;
+pub const VALID_SIGNATURE: u16 = 0x0510;
 
 const COLOR_NUMBER_LENGTH: usize = 10;
 const COLOR_NAME_LENGTH: usize = 40;
@@ -52,12 +52,31 @@ const PAGE_HEADER_AND_FOOTER_LENGTH: usize = 119;
 
 const SPECIAL_STITCH_NAME_LENGTH: usize = 255;
 
+/// An error that occurs while reading an XSD file.
+/// It is attached as a context to the underlying error to point out where the file is corrupted.
+#[derive(Debug)]
+pub struct XsdError {
+  /// The byte offset in the file the error occurred at.
+  pub offset: u64,
+}
+
+impl std::fmt::Display for XsdError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "The XSD file is corrupted (at byte offset {})", self.offset)
+  }
+}
+
 pub fn parse_pattern(file_path: std::path::PathBuf) -> Result<PatternProject> {
   log::info!("Parsing the XSD pattern file");
   let buf = std::fs::read(&file_path)?;
   let mut cursor = std::io::Cursor::new(buf);
 
-  let signature = read_signature(&mut cursor)?;
+  let patproj = read_pattern(&mut cursor).map_err(|error| error.context(XsdError { offset: cursor.position() }))?;
+  Ok(PatternProject { file_path, ..patproj })
+}
+
+fn read_pattern(cursor: &mut io::Cursor<Vec<u8>>) -> Result<PatternProject> {
+  let signature = read_signature(cursor)?;
   if signature != VALID_SIGNATURE {
     log::error!("The file has an invalid signature. Expected {VALID_SIGNATURE:#06X}, but got {signature:#06X}");
     anyhow::bail!("The signature of Pattern Maker v4 is incorrect");
@@ -78,36 +97,34 @@ pub fn parse_pattern(file_path: std::path::PathBuf) -> Result<PatternProject> {
   let spi = (cursor.read_u16::<LittleEndian>()?, cursor.read_u16::<LittleEndian>()?);
   cursor.seek_relative(6)?;
 
-  let palette = read_palette(&mut cursor)?;
-  let formats = read_formats(&mut cursor, palette.len())?;
-  let symbols = read_symbols(&mut cursor, palette.len())?;
+  let palette = read_palette(cursor)?;
+  let formats = read_formats(cursor, palette.len())?;
+  let symbols = read_symbols(cursor, palette.len())?;
 
-  let pattern_settings = read_pattern_settings(&mut cursor)?;
-  let grid = read_grid_settings(&mut cursor)?;
+  let pattern_settings = read_pattern_settings(cursor)?;
+  let grid = read_grid_settings(cursor)?;
 
   let fabric_color_name = cursor.read_cstring(FABRIC_COLOR_NAME_LENGTH)?;
   let fabric_color = cursor.read_hex_color()?;
   cursor.seek_relative(65)?;
-  let pattern_info = read_pattern_info(&mut cursor)?;
+  let pattern_info = read_pattern_info(cursor)?;
   cursor.seek_relative(6)?;
   let fabric_kind_name = cursor.read_cstring(FABRIC_KIND_NAME_LENGTH)?;
   cursor.seek_relative(206)?;
 
-  let (stitch_settings, outlined_stitches, stitch_outline) = read_stitch_settings(&mut cursor)?;
-  let symbol_settings = read_symbol_settings(&mut cursor)?;
+  let (stitch_settings, outlined_stitches, stitch_outline) = read_stitch_settings(cursor)?;
+  let symbol_settings = read_symbol_settings(cursor)?;
 
   cursor.seek_relative(16412)?; // Skip library info.
   cursor.seek_relative(512)?; // Skip machine export info.
 
-  let (fullstitches, partstitches) =
-    read_stitches(&mut cursor, coord_factor, total_stitches_count, small_stitches_count)?;
+  let (fullstitches, partstitches) = read_stitches(cursor, coord_factor, total_stitches_count, small_stitches_count)?;
 
-  let special_stitch_models = read_special_stitch_models(&mut cursor)?;
+  let special_stitch_models = read_special_stitch_models(cursor)?;
 
-  let (nodes, lines, _curves, specialstitches) = read_joints(&mut cursor, joints_count)?;
+  let (nodes, lines, _curves, specialstitches) = read_joints(cursor, joints_count)?;
 
   Ok(PatternProject {
-    file_path,
     pattern: Pattern {
       properties: pattern_properties,
       info: pattern_info,
@@ -124,6 +141,7 @@ pub fn parse_pattern(file_path: std::path::PathBuf) -> Result<PatternProject> {
       lines: Stitches::from_iter(lines),
       specialstitches: Stitches::from_iter(specialstitches),
       special_stitch_models,
+      layers: Layers::default(),
     },
     display_settings: DisplaySettings {
       default_stitch_font: pattern_settings.stitch_font_name,
@@ -151,6 +169,7 @@ pub fn parse_pattern(file_path: std::path::PathBuf) -> Result<PatternProject> {
       show_adjacent_page_numbers: pattern_settings.show_adjacent_page_numbers,
       center_chart_on_pages: pattern_settings.center_chart_on_pages,
     },
+    ..PatternProject::default()
   })
 }
 
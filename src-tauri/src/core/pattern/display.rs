@@ -1,5 +1,5 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use super::DefaultStitchStrands;
 
@@ -54,8 +54,7 @@ impl DisplaySettings {
   }
 }
 
-#[derive(Debug, Default, Clone, PartialEq, Serialize, BorshSerialize, BorshDeserialize)]
-#[cfg_attr(test, derive(serde::Deserialize))]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct Symbols {
   pub full: Option<u16>,
   pub petite: Option<u16>,
@@ -65,7 +64,7 @@ pub struct Symbols {
   pub bead: Option<u16>,
 }
 
-#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct SymbolSettings {
   pub screen_spacing: (u16, u16),
   pub printer_spacing: (u16, u16),
@@ -96,8 +95,7 @@ impl Default for SymbolSettings {
   }
 }
 
-#[derive(Debug, Default, Clone, PartialEq, Serialize, BorshSerialize, BorshDeserialize)]
-#[cfg_attr(test, derive(serde::Deserialize))]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct Formats {
   pub symbol: SymbolFormat,
   pub back: LineFormat,
@@ -108,8 +106,7 @@ pub struct Formats {
   pub font: FontFormat,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, BorshSerialize, BorshDeserialize)]
-#[cfg_attr(test, derive(serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct SymbolFormat {
   pub use_alt_bg_color: bool,
   pub bg_color: String,
@@ -126,8 +123,7 @@ impl Default for SymbolFormat {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, BorshSerialize, BorshDeserialize)]
-#[cfg_attr(test, derive(serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct LineFormat {
   pub use_alt_color: bool,
   pub color: String,
@@ -146,8 +142,7 @@ impl Default for LineFormat {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, BorshSerialize, BorshDeserialize)]
-#[cfg_attr(test, derive(serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 #[borsh(use_discriminant = true)]
 pub enum LineStyle {
   Solid = 0,
@@ -179,8 +174,42 @@ impl From<u16> for LineStyle {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, BorshSerialize, BorshDeserialize)]
-#[cfg_attr(test, derive(serde::Deserialize))]
+impl std::fmt::Display for LineStyle {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    match self {
+      LineStyle::Solid => write!(f, "solid"),
+      LineStyle::Barred => write!(f, "barred"),
+      LineStyle::Dotted => write!(f, "dotted"),
+      LineStyle::ChainDotted => write!(f, "chain_dotted"),
+      LineStyle::Dashed => write!(f, "dashed"),
+      LineStyle::Outlined => write!(f, "outlined"),
+      LineStyle::Zebra => write!(f, "zebra"),
+      LineStyle::ZigZag => write!(f, "zigzag"),
+      LineStyle::Morse => write!(f, "morse"),
+    }
+  }
+}
+
+impl std::str::FromStr for LineStyle {
+  type Err = &'static str;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "solid" => Ok(LineStyle::Solid),
+      "barred" => Ok(LineStyle::Barred),
+      "dotted" => Ok(LineStyle::Dotted),
+      "chain_dotted" => Ok(LineStyle::ChainDotted),
+      "dashed" => Ok(LineStyle::Dashed),
+      "outlined" => Ok(LineStyle::Outlined),
+      "zebra" => Ok(LineStyle::Zebra),
+      "zigzag" => Ok(LineStyle::ZigZag),
+      "morse" => Ok(LineStyle::Morse),
+      _ => Err("Unknown line style"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct NodeFormat {
   pub use_dot_style: bool,
   pub use_alt_color: bool,
@@ -199,8 +228,7 @@ impl Default for NodeFormat {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, BorshSerialize, BorshDeserialize)]
-#[cfg_attr(test, derive(serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct FontFormat {
   pub font_name: Option<String>,
   pub bold: bool,
@@ -221,7 +249,7 @@ impl Default for FontFormat {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct Grid {
   pub major_line_every_stitches: u16,
   pub minor_screen_lines: GridLineStyle,
@@ -254,7 +282,7 @@ impl Default for Grid {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct GridLineStyle {
   pub color: String,
 
@@ -262,7 +290,7 @@ pub struct GridLineStyle {
   pub thickness: f32,
 }
 
-#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 #[borsh(use_discriminant = true)]
 pub enum View {
   Stitches = 0,
@@ -285,7 +313,34 @@ impl From<u16> for View {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+impl std::fmt::Display for View {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    match self {
+      View::Stitches => write!(f, "stitches"),
+      View::Symbols => write!(f, "symbols"),
+      View::Solid => write!(f, "solid"),
+      View::Information => write!(f, "information"),
+      View::MachineEmbInfo => write!(f, "machine_emb_info"),
+    }
+  }
+}
+
+impl std::str::FromStr for View {
+  type Err = &'static str;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "stitches" => Ok(View::Stitches),
+      "symbols" => Ok(View::Symbols),
+      "solid" => Ok(View::Solid),
+      "information" => Ok(View::Information),
+      "machine_emb_info" => Ok(View::MachineEmbInfo),
+      _ => Err("Unknown view"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct StitchOutline {
   pub color: Option<String>,
   pub color_percentage: Percentage,
@@ -304,11 +359,11 @@ impl Default for StitchOutline {
 
 #[nutype::nutype(
   sanitize(with = |raw| raw.clamp(0.1, 1.0)),
-  derive(Debug, Clone, Copy, PartialEq, BorshSerialize, BorshDeserialize)
+  derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)
 )]
 pub struct StitchOutlineThickness(f32);
 
-#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct StitchSettings {
   pub default_strands: DefaultStitchStrands,
   /// 1..=12 - strands, 13 - french knot.
@@ -0,0 +1,409 @@
+//! The rasterization of the shapes into the stitches.
+//!
+//! The lines, rectangles and ellipses are rasterized into the cells, which are then filled with the full, petite or half stitches.
+//! The polylines are converted into the backstitches that go through the grid points.
+//! The rasterized cells can also be outlined or stroked with the backstitches.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use ordered_float::NotNan;
+use serde::Deserialize;
+
+use super::fill::FillStitchKind;
+use super::{Line, LineKind, Node, NodeKind, Pattern, Stitch};
+
+#[cfg(test)]
+#[path = "draw.test.rs"]
+mod tests;
+
+/// A cell position. It may lie outside the pattern, so the shapes can be clipped.
+pub type Cell = (i32, i32);
+
+/// A grid point position. The backstitches can go through the centers of the cells, so the points can be fractional.
+pub type Point = (f32, f32);
+
+/// A shape to draw.
+///
+/// It is serialized as `{ "shape": "rectangle", "x": 0, "y": 0, "width": 10, "height": 5, "filled": false }`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+pub enum Shape {
+  Line {
+    start: Cell,
+    end: Cell,
+  },
+  Rectangle {
+    x: i32,
+    y: i32,
+    width: u16,
+    height: u16,
+    filled: bool,
+  },
+  /// The ellipse inscribed in the rectangle.
+  Ellipse {
+    x: i32,
+    y: i32,
+    width: u16,
+    height: u16,
+    filled: bool,
+  },
+  Polyline {
+    points: Vec<Point>,
+    closed: bool,
+  },
+}
+
+impl Pattern {
+  /// Returns the stitches that draw the shape.
+  /// The stitch kind is used for all the shapes except the polylines, which are always drawn with the backstitches.
+  /// The parts of the shape that lie outside the pattern are skipped.
+  pub fn draw_shape(&self, shape: &Shape, palindex: u8, kind: FillStitchKind) -> Vec<Stitch> {
+    let size = (self.properties.width, self.properties.height);
+    let cells = match *shape {
+      Shape::Line { start, end } => rasterize_line(start, end, size),
+      Shape::Rectangle { x, y, width, height, filled } => rasterize_rectangle(x, y, width, height, filled, size),
+      Shape::Ellipse { x, y, width, height, filled } => rasterize_ellipse(x, y, width, height, filled, size),
+      Shape::Polyline { ref points, closed } => {
+        return self
+          .polyline_backstitches(points, closed, palindex)
+          .into_iter()
+          .map(Stitch::Line)
+          .collect();
+      }
+    };
+
+    self.fill_cells(cells, palindex, kind)
+  }
+
+  /// Returns the stitches that fill the cells.
+  /// The duplicate cells and the ones that lie outside the pattern are skipped.
+  pub fn fill_cells<I: IntoIterator<Item = Cell>>(&self, cells: I, palindex: u8, kind: FillStitchKind) -> Vec<Stitch> {
+    let (width, height) = (i32::from(self.properties.width), i32::from(self.properties.height));
+    cells
+      .into_iter()
+      .filter(|&(x, y)| (0..width).contains(&x) && (0..height).contains(&y))
+      .collect::<BTreeSet<_>>()
+      .into_iter()
+      .flat_map(|(x, y)| kind.stitches(x as u16, y as u16, palindex))
+      .collect()
+  }
+
+  /// Returns the backstitches that go along the outline of the cells.
+  /// The outline runs along the cell sides, but the steps of the figure are cut diagonally through the side middles,
+  /// so the sloped and curved edges don't turn into staircases.
+  /// The cells outside the pattern are skipped.
+  pub fn outline_cells<I: IntoIterator<Item = Cell>>(&self, cells: I, palindex: u8) -> Vec<Stitch> {
+    let cells = self.cells_inside(cells);
+    let Some(((left, top), (right, bottom))) = cells_extent(&cells) else {
+      return Vec::new();
+    };
+
+    // The marching squares over the cell centers: every square has the centers of four adjacent cells in its corners,
+    // and the outline crosses the square sides between the filled and empty corners.
+    let mut segments = Vec::new();
+    for cy in top - 1..=bottom {
+      for cx in left - 1..=right {
+        let filled = |dx: i64, dy: i64| cells.contains(&((cx + dx) as i32, (cy + dy) as i32));
+        let (a, b, c, d) = (filled(0, 0), filled(1, 0), filled(1, 1), filled(0, 1));
+        // The middles of the square sides in the half-cell units.
+        let top_side = (2 * cx + 2, 2 * cy + 1);
+        let right_side = (2 * cx + 3, 2 * cy + 2);
+        let bottom_side = (2 * cx + 2, 2 * cy + 3);
+        let left_side = (2 * cx + 1, 2 * cy + 2);
+        let square: &[(HalfPoint, HalfPoint)] = match (a, b, c, d) {
+          (true, false, false, false) | (false, true, true, true) => &[(left_side, top_side)],
+          (false, true, false, false) | (true, false, true, true) => &[(top_side, right_side)],
+          (false, false, true, false) | (true, true, false, true) => &[(right_side, bottom_side)],
+          (false, false, false, true) | (true, true, true, false) => &[(bottom_side, left_side)],
+          (true, true, false, false) | (false, false, true, true) => &[(left_side, right_side)],
+          (false, true, true, false) | (true, false, false, true) => &[(top_side, bottom_side)],
+          // The diagonally adjacent cells are treated as connected.
+          (true, false, true, false) => &[(top_side, right_side), (bottom_side, left_side)],
+          (false, true, false, true) => &[(left_side, top_side), (right_side, bottom_side)],
+          _ => &[],
+        };
+        segments.extend_from_slice(square);
+      }
+    }
+
+    join_segments(segments)
+      .into_iter()
+      .map(|(start, end)| half_backstitch(start, end, palindex))
+      .collect()
+  }
+
+  /// Returns the backstitches that go through the centers of the adjacent cells, i.e. the single-stroke lettering.
+  /// The diagonal neighbours are joined only when they are not connected through a side neighbour.
+  /// The single cells become the french knots.
+  /// The cells outside the pattern are skipped.
+  pub fn stroke_cells<I: IntoIterator<Item = Cell>>(&self, cells: I, palindex: u8) -> Vec<Stitch> {
+    let cells = self.cells_inside(cells);
+    let has = |x: i32, y: i32| cells.contains(&(x, y));
+    let center = |x: i32, y: i32| (2 * i64::from(x) + 1, 2 * i64::from(y) + 1);
+
+    let mut segments = Vec::new();
+    let mut knots = Vec::new();
+    for &(x, y) in cells.iter() {
+      let neighbours = [
+        has(x + 1, y).then_some((x + 1, y)),
+        has(x, y + 1).then_some((x, y + 1)),
+        (has(x + 1, y + 1) && !has(x + 1, y) && !has(x, y + 1)).then_some((x + 1, y + 1)),
+        (has(x - 1, y + 1) && !has(x - 1, y) && !has(x, y + 1)).then_some((x - 1, y + 1)),
+      ];
+      for (nx, ny) in neighbours.into_iter().flatten() {
+        segments.push((center(x, y), center(nx, ny)));
+      }
+
+      let is_single = [-1, 0, 1]
+        .into_iter()
+        .flat_map(|dy| [-1, 0, 1].map(|dx| (dx, dy)))
+        .all(|(dx, dy)| (dx, dy) == (0, 0) || !has(x + dx, y + dy));
+      if is_single {
+        knots.push(Stitch::Node(Node {
+          x: NotNan::new(x as f32 + 0.5).unwrap(),
+          y: NotNan::new(y as f32 + 0.5).unwrap(),
+          rotated: false,
+          palindex,
+          kind: NodeKind::FrenchKnot,
+        }));
+      }
+    }
+
+    join_segments(segments)
+      .into_iter()
+      .map(|(start, end)| half_backstitch(start, end, palindex))
+      .chain(knots)
+      .collect()
+  }
+
+  /// Returns the unique cells that lie inside the pattern.
+  fn cells_inside<I: IntoIterator<Item = Cell>>(&self, cells: I) -> BTreeSet<Cell> {
+    let (width, height) = (i32::from(self.properties.width), i32::from(self.properties.height));
+    cells
+      .into_iter()
+      .filter(|&(x, y)| (0..width).contains(&x) && (0..height).contains(&y))
+      .collect()
+  }
+
+  /// Converts the polyline into the backstitches, one per segment.
+  /// The points are snapped to the half-cell grid, i.e. to the cell corners, side middles and centers.
+  fn polyline_backstitches(&self, points: &[Point], closed: bool, palindex: u8) -> Vec<Line> {
+    let (width, height) = (f32::from(self.properties.width), f32::from(self.properties.height));
+    let contains = |(x, y): Point| (0.0..=width).contains(&x) && (0.0..=height).contains(&y);
+
+    let points: Vec<Point> = points.iter().map(|&(x, y)| (snap(x), snap(y))).collect();
+    let mut segments: Vec<(Point, Point)> = points.windows(2).map(|pair| (pair[0], pair[1])).collect();
+    if closed && points.len() > 2 {
+      segments.push((points[points.len() - 1], points[0]));
+    }
+
+    let mut lines = BTreeSet::new();
+    for (start, end) in segments {
+      if start == end || !contains(start) || !contains(end) {
+        continue;
+      }
+      let (Ok(x1), Ok(y1), Ok(x2), Ok(y2)) = (
+        NotNan::new(start.0),
+        NotNan::new(start.1),
+        NotNan::new(end.0),
+        NotNan::new(end.1),
+      ) else {
+        continue;
+      };
+      // The points are ordered, so the same segment drawn in the opposite direction is not duplicated.
+      let ((x1, y1), (x2, y2)) = if (y1, x1) <= (y2, x2) {
+        ((x1, y1), (x2, y2))
+      } else {
+        ((x2, y2), (x1, y1))
+      };
+      lines.insert(Line {
+        x: (x1, x2),
+        y: (y1, y2),
+        palindex,
+        kind: LineKind::Back,
+      });
+    }
+    lines.into_iter().collect()
+  }
+}
+
+/// The size of the area the shapes are clipped to, i.e. the pattern width and height.
+pub type Size = (u16, u16);
+
+/// Rasterizes the line between the cells.
+/// Every column (or row for the steep lines) of the line gets the cell closest to the ideal line,
+/// so the same cells are produced regardless of the line direction.
+///
+/// Only the cells inside the area of the given size are returned, from the start to the end.
+pub fn rasterize_line(start: Cell, end: Cell, size: Size) -> Vec<Cell> {
+  let (x0, y0) = (i64::from(start.0), i64::from(start.1));
+  let (dx, dy) = (i64::from(end.0) - x0, i64::from(end.1) - y0);
+  let (width, height) = (i64::from(size.0), i64::from(size.1));
+
+  // The line is walked along its major axis, so the cells are computed for the clipped part only.
+  let steep = dy.abs() > dx.abs();
+  let (major_start, major_delta, minor_start, minor_delta, major_size, minor_size) = if steep {
+    (y0, dy, x0, dx, height, width)
+  } else {
+    (x0, dx, y0, dy, width, height)
+  };
+  let major_end = major_start + major_delta;
+  let (first, last) = (
+    major_start.min(major_end).max(0),
+    major_start.max(major_end).min(major_size - 1),
+  );
+  if first > last {
+    return Vec::new();
+  }
+
+  let minor_at = |major: i64| {
+    if major_delta == 0 {
+      return minor_start;
+    }
+    // Rounds `minor_start + (major - major_start) * minor_delta / major_delta` half up without the overflows.
+    let (mut numerator, mut denominator) = (
+      2 * i128::from(major - major_start) * i128::from(minor_delta) + i128::from(major_delta),
+      2 * i128::from(major_delta),
+    );
+    if denominator < 0 {
+      (numerator, denominator) = (-numerator, -denominator);
+    }
+    minor_start + numerator.div_euclid(denominator) as i64
+  };
+  let mut cells: Vec<Cell> = (first..=last)
+    .filter_map(|major| {
+      let minor = minor_at(major);
+      if !(0..minor_size).contains(&minor) {
+        return None;
+      }
+      let (x, y) = if steep { (minor, major) } else { (major, minor) };
+      Some((x as i32, y as i32))
+    })
+    .collect();
+  if major_delta < 0 {
+    cells.reverse();
+  }
+  cells
+}
+
+/// Rasterizes the rectangle with the top-left cell at the given position.
+/// Only the cells inside the area of the given size are returned.
+pub fn rasterize_rectangle(x: i32, y: i32, width: u16, height: u16, filled: bool, size: Size) -> Vec<Cell> {
+  let (left, top) = (i64::from(x), i64::from(y));
+  let (right, bottom) = (left + i64::from(width) - 1, top + i64::from(height) - 1);
+  let mut cells = Vec::new();
+  for cy in top.max(0)..=bottom.min(i64::from(size.1) - 1) {
+    for cx in left.max(0)..=right.min(i64::from(size.0) - 1) {
+      if filled || cx == left || cx == right || cy == top || cy == bottom {
+        cells.push((cx as i32, cy as i32));
+      }
+    }
+  }
+  cells
+}
+
+/// Rasterizes the ellipse inscribed in the rectangle with the top-left cell at the given position.
+/// Only the cells inside the area of the given size are returned.
+///
+/// The cell belongs to the ellipse if its center lies inside it.
+/// The outline consists of the ellipse cells that have a side-adjacent cell outside the ellipse.
+pub fn rasterize_ellipse(x: i32, y: i32, width: u16, height: u16, filled: bool, size: Size) -> Vec<Cell> {
+  if width == 0 || height == 0 {
+    return Vec::new();
+  }
+  let (left, top) = (i64::from(x), i64::from(y));
+  let (rx, ry) = (f64::from(width) / 2.0, f64::from(height) / 2.0);
+  let inside = |cx: i64, cy: i64| {
+    let dx = (cx - left) as f64 + 0.5 - rx;
+    let dy = (cy - top) as f64 + 0.5 - ry;
+    (dx / rx).powi(2) + (dy / ry).powi(2) <= 1.0
+  };
+
+  let mut cells = Vec::new();
+  for cy in top.max(0)..(top + i64::from(height)).min(i64::from(size.1)) {
+    for cx in left.max(0)..(left + i64::from(width)).min(i64::from(size.0)) {
+      if !inside(cx, cy) {
+        continue;
+      }
+      let is_edge = !inside(cx - 1, cy) || !inside(cx + 1, cy) || !inside(cx, cy - 1) || !inside(cx, cy + 1);
+      if filled || is_edge {
+        cells.push((cx as i32, cy as i32));
+      }
+    }
+  }
+  cells
+}
+
+/// A grid point in the half-cell units, so the cell centers and side middles have the integer coordinates.
+type HalfPoint = (i64, i64);
+
+/// Returns the top-left and bottom-right cells of the cells.
+fn cells_extent(cells: &BTreeSet<Cell>) -> Option<((i64, i64), (i64, i64))> {
+  let left = cells.iter().map(|&(x, _)| i64::from(x)).min()?;
+  let right = cells.iter().map(|&(x, _)| i64::from(x)).max()?;
+  let top = cells.iter().map(|&(_, y)| i64::from(y)).min()?;
+  let bottom = cells.iter().map(|&(_, y)| i64::from(y)).max()?;
+  Some(((left, top), (right, bottom)))
+}
+
+/// The segment with its start and end positions along the line it lies on.
+type Run = (i64, i64, HalfPoint, HalfPoint);
+
+/// Joins the collinear segments that continue each other into the longer ones.
+/// The segments must be horizontal, vertical or diagonal.
+fn join_segments(segments: Vec<(HalfPoint, HalfPoint)>) -> Vec<(HalfPoint, HalfPoint)> {
+  // The segments are grouped by the line they lie on and ordered along it.
+  let mut lines: BTreeMap<(HalfPoint, i64), Vec<Run>> = BTreeMap::new();
+  for (start, end) in segments {
+    let (start, end) = if (start.1, start.0) <= (end.1, end.0) {
+      (start, end)
+    } else {
+      (end, start)
+    };
+    let direction = ((end.0 - start.0).signum(), (end.1 - start.1).signum());
+    let offset = direction.1 * start.0 - direction.0 * start.1;
+    let position = |point: HalfPoint| direction.0 * point.0 + direction.1 * point.1;
+    lines
+      .entry((direction, offset))
+      .or_default()
+      .push((position(start), position(end), start, end));
+  }
+
+  let mut joined = Vec::new();
+  for (_, mut segments) in lines {
+    segments.sort_unstable();
+    let mut current: Option<Run> = None;
+    for segment in segments {
+      current = match current {
+        Some(run) if run.1 >= segment.0 => Some(if segment.1 > run.1 {
+          (run.0, segment.1, run.2, segment.3)
+        } else {
+          run
+        }),
+        Some(run) => {
+          joined.push((run.2, run.3));
+          Some(segment)
+        }
+        None => Some(segment),
+      };
+    }
+    joined.extend(current.map(|run| (run.2, run.3)));
+  }
+  joined
+}
+
+/// Rounds the coordinate to the nearest half of the cell.
+fn snap(value: f32) -> f32 {
+  (value * 2.0).round() / 2.0
+}
+
+/// Creates the backstitch between the points given in the half-cell units.
+fn half_backstitch(start: HalfPoint, end: HalfPoint, palindex: u8) -> Stitch {
+  let coord = |value: i64| NotNan::new(value as f32 / 2.0).unwrap();
+  Stitch::Line(Line {
+    x: (coord(start.0), coord(end.0)),
+    y: (coord(start.1), coord(end.1)),
+    palindex,
+    kind: LineKind::Back,
+  })
+}
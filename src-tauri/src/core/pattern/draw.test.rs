@@ -0,0 +1,214 @@
+use ordered_float::NotNan;
+
+use super::*;
+use crate::core::pattern::{FullStitchKind, PatternProperties};
+
+const SIZE: Size = (10, 10);
+
+fn create_pattern() -> Pattern {
+  Pattern {
+    properties: PatternProperties { width: 10, height: 10 },
+    ..Pattern::default()
+  }
+}
+
+#[test]
+fn rasterizes_lines() {
+  assert_eq!(
+    rasterize_line((0, 0), (3, 0), SIZE),
+    vec![(0, 0), (1, 0), (2, 0), (3, 0)]
+  );
+  assert_eq!(rasterize_line((2, 2), (0, 0), SIZE), vec![(2, 2), (1, 1), (0, 0)]);
+  assert_eq!(
+    rasterize_line((0, 0), (4, 2), SIZE),
+    vec![(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)]
+  );
+  assert_eq!(rasterize_line((5, 5), (5, 5), SIZE), vec![(5, 5)]);
+
+  // The steep lines have a single cell per row.
+  let cells = rasterize_line((0, 0), (2, 7), SIZE);
+  assert_eq!(cells.len(), 8);
+  assert!(cells.windows(2).all(|pair| pair[1].1 == pair[0].1 + 1));
+
+  // The line has the same cells in both directions.
+  let mut reversed = rasterize_line((4, 2), (0, 0), SIZE);
+  reversed.reverse();
+  assert_eq!(reversed, rasterize_line((0, 0), (4, 2), SIZE));
+}
+
+#[test]
+fn clips_huge_shapes() {
+  let cells = rasterize_line((i32::MIN, i32::MIN), (i32::MAX, i32::MAX), SIZE);
+  assert_eq!(cells.len(), 10);
+  assert!(cells.iter().all(|&(x, y)| x == y));
+  assert!(rasterize_line((i32::MAX, 0), (i32::MAX - 5, 3), SIZE).is_empty());
+
+  assert_eq!(
+    rasterize_rectangle(i32::MAX, 0, u16::MAX, u16::MAX, true, SIZE).len(),
+    0
+  );
+  assert_eq!(rasterize_rectangle(-5, -5, u16::MAX, u16::MAX, true, SIZE).len(), 100);
+  let outline = rasterize_rectangle(-5, -5, u16::MAX, u16::MAX, false, SIZE);
+  assert_eq!(outline.len(), 0);
+
+  let filled = rasterize_ellipse(i32::MIN, i32::MIN, u16::MAX, u16::MAX, true, SIZE);
+  assert!(filled.is_empty());
+  assert!(rasterize_ellipse(i32::MAX, 0, u16::MAX, 10, true, SIZE).is_empty());
+}
+
+#[test]
+fn rasterizes_rectangles() {
+  assert_eq!(rasterize_rectangle(1, 1, 3, 2, true, SIZE).len(), 6);
+  let outline = rasterize_rectangle(0, 0, 4, 4, false, SIZE);
+  assert_eq!(outline.len(), 12);
+  assert!(!outline.contains(&(1, 1)));
+  assert!(rasterize_rectangle(0, 0, 0, 4, true, SIZE).is_empty());
+}
+
+#[test]
+fn rasterizes_ellipses() {
+  let filled = rasterize_ellipse(0, 0, 7, 5, true, SIZE);
+  let outline = rasterize_ellipse(0, 0, 7, 5, false, SIZE);
+  assert!(filled.contains(&(3, 2)));
+  assert!(filled.contains(&(0, 2)) && filled.contains(&(6, 2)));
+  // The corners of the bounding box are outside the ellipse.
+  assert!(!filled.contains(&(0, 0)));
+  assert!(!outline.contains(&(3, 2)));
+  assert!(outline.iter().all(|cell| filled.contains(cell)));
+  assert!(outline.len() < filled.len());
+
+  // The ellipse is symmetric.
+  assert!(filled.iter().all(|&(x, y)| filled.contains(&(6 - x, 4 - y))));
+}
+
+#[test]
+fn clips_shapes_to_pattern() {
+  let pattern = create_pattern();
+  let stitches = pattern.draw_shape(
+    &Shape::Rectangle {
+      x: -2,
+      y: 8,
+      width: 5,
+      height: 5,
+      filled: true,
+    },
+    1,
+    FillStitchKind::Full,
+  );
+  assert_eq!(stitches.len(), 6);
+  assert!(stitches
+    .iter()
+    .all(|stitch| matches!(stitch, Stitch::Full(fullstitch) if fullstitch.kind == FullStitchKind::Full && fullstitch.palindex == 1)));
+
+  let stitches = pattern.draw_shape(&Shape::Line { start: (0, 0), end: (1, 1) }, 0, FillStitchKind::Petite);
+  assert_eq!(stitches.len(), 8);
+}
+
+#[test]
+fn converts_polylines_to_backstitches() {
+  let pattern = create_pattern();
+  let shape = Shape::Polyline {
+    points: vec![(1.0, 1.0), (3.0, 1.0), (3.0, 2.5), (1.0, 1.0), (11.0, 1.0)],
+    closed: false,
+  };
+  let stitches = pattern.draw_shape(&shape, 2, FillStitchKind::Full);
+  // The segment outside the pattern is skipped.
+  assert_eq!(stitches.len(), 3);
+  assert!(stitches
+    .iter()
+    .all(|stitch| matches!(stitch, Stitch::Line(line) if line.kind == LineKind::Back && line.palindex == 2)));
+
+  // The closing segment duplicates the first one drawn in the opposite direction.
+  let shape = Shape::Polyline {
+    points: vec![(1.0, 1.0), (3.0, 1.0), (1.0, 1.0)],
+    closed: true,
+  };
+  assert_eq!(pattern.draw_shape(&shape, 2, FillStitchKind::Full).len(), 1);
+
+  // The points are snapped to the half-cell grid.
+  let shape = Shape::Polyline {
+    points: vec![(1.1, 0.9), (2.6, 1.2)],
+    closed: false,
+  };
+  let stitches = pattern.draw_shape(&shape, 2, FillStitchKind::Full);
+  let coord = |value: f32| NotNan::new(value).unwrap();
+  assert!(matches!(
+    stitches.as_slice(),
+    [Stitch::Line(line)] if line.x == (coord(1.0), coord(2.5)) && line.y == (coord(1.0), coord(1.0))
+  ));
+}
+
+#[test]
+fn deserializes_shapes() {
+  assert_eq!(
+    serde_json::from_str::<Shape>(r#"{"shape":"line","start":[0,0],"end":[3,4]}"#).unwrap(),
+    Shape::Line { start: (0, 0), end: (3, 4) }
+  );
+  assert_eq!(
+    serde_json::from_str::<Shape>(r#"{"shape":"polyline","points":[[0,0],[0.5,1]],"closed":false}"#).unwrap(),
+    Shape::Polyline {
+      points: vec![(0.0, 0.0), (0.5, 1.0)],
+      closed: false
+    }
+  );
+}
+
+fn backstitches(stitches: &[Stitch]) -> Vec<Line> {
+  stitches
+    .iter()
+    .map(|stitch| match stitch {
+      Stitch::Line(line) if line.kind == LineKind::Back => *line,
+      _ => panic!("The stitches must be the backstitches"),
+    })
+    .collect()
+}
+
+fn is_diagonal(line: &Line) -> bool {
+  line.x.0 != line.x.1 && line.y.0 != line.y.1
+}
+
+#[test]
+fn outlines_cells() {
+  let pattern = create_pattern();
+  // An L-shaped figure of three cells.
+  let lines = backstitches(&pattern.outline_cells([(1, 1), (1, 2), (2, 2)], 0));
+  let coord = |value: f32| NotNan::new(value).unwrap();
+
+  // The straight sides lie on the cell sides, while the corners are cut diagonally.
+  assert!(lines.contains(&Line {
+    x: (coord(1.0), coord(1.0)),
+    y: (coord(1.5), coord(2.5)),
+    palindex: 0,
+    kind: LineKind::Back,
+  }));
+  assert!(lines.iter().any(is_diagonal));
+
+  // The outline is closed, i.e. every point joins two backstitches.
+  let mut points = std::collections::HashMap::new();
+  for line in lines.iter() {
+    *points.entry((line.x.0, line.y.0)).or_insert(0) += 1;
+    *points.entry((line.x.1, line.y.1)).or_insert(0) += 1;
+  }
+  assert!(points.values().all(|&count| count == 2));
+
+  // The cells outside the pattern are skipped, so the single cell becomes a diamond.
+  let lines = backstitches(&pattern.outline_cells([(-1, 0), (0, 0)], 0));
+  assert_eq!(lines.len(), 4);
+  assert!(lines.iter().all(is_diagonal));
+}
+
+#[test]
+fn strokes_cells() {
+  let pattern = create_pattern();
+  // A diagonal of three cells and a horizontal bar joined to it.
+  let stitches = pattern.stroke_cells([(0, 0), (1, 1), (2, 2), (3, 2), (4, 2)], 0);
+  let lines = backstitches(&stitches);
+  assert_eq!(lines.len(), 2);
+  assert!(lines
+    .iter()
+    .all(|line| line.x.0.fract() == 0.5 && line.y.0.fract() == 0.5));
+
+  // The single cells become the french knots.
+  let stitches = pattern.stroke_cells([(5, 5)], 0);
+  assert!(matches!(stitches.as_slice(), [Stitch::Node(node)] if node.kind == NodeKind::FrenchKnot));
+}
@@ -0,0 +1,307 @@
+//! The flood fill of the pattern cells.
+//!
+//! The fill starts from a cell and spreads to the adjacent cells of the same colour, i.e. the empty cells or the cells whose stitches all have the same palette index.
+//! The cells that contain the stitches of several colours stop the fill.
+//!
+//! The empty and full cells are filled with the stitches of the requested kind,
+//! while the cells with petites, half or quarter stitches keep their stitches and only change their palette index.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use ordered_float::NotNan;
+use serde::{Deserialize, Serialize};
+
+use super::{
+  Bounds, Coord, FullStitch, FullStitchKind, Line, LineKind, PartStitch, PartStitchDirection, PartStitchKind, Pattern,
+  Stitch,
+};
+
+#[cfg(test)]
+#[path = "fill.test.rs"]
+mod tests;
+
+/// The kind of stitches used to fill the cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FillStitchKind {
+  Full,
+  /// Four petite stitches per cell.
+  Petite,
+  Half {
+    direction: PartStitchDirection,
+  },
+}
+
+impl FillStitchKind {
+  /// Returns the stitches that fill the cell.
+  pub fn stitches(&self, x: u16, y: u16, palindex: u8) -> Vec<Stitch> {
+    let (x, y) = (NotNan::new(f32::from(x)).unwrap(), NotNan::new(f32::from(y)).unwrap());
+    match *self {
+      FillStitchKind::Full => vec![Stitch::Full(FullStitch {
+        x,
+        y,
+        palindex,
+        kind: FullStitchKind::Full,
+      })],
+      FillStitchKind::Petite => [(x, y), (x + 0.5, y), (x, y + 0.5), (x + 0.5, y + 0.5)]
+        .into_iter()
+        .map(|(x, y)| {
+          Stitch::Full(FullStitch {
+            x,
+            y,
+            palindex,
+            kind: FullStitchKind::Petite,
+          })
+        })
+        .collect(),
+      FillStitchKind::Half { direction } => vec![Stitch::Part(PartStitch {
+        x,
+        y,
+        palindex,
+        direction,
+        kind: PartStitchKind::Half,
+      })],
+    }
+  }
+}
+
+/// What the cell contains in terms of the fill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CellContent {
+  Empty,
+  Full(u8),
+  /// The cell contains only the petites, half or quarter stitches of the same palette index.
+  Partial(u8),
+  /// The cell contains the stitches of different palette indices, so it can't be filled.
+  Mixed,
+}
+
+impl CellContent {
+  /// Returns the palette index of the stitches of the cell or `None` if the cell is empty.
+  fn colour(self) -> Option<u8> {
+    match self {
+      CellContent::Empty | CellContent::Mixed => None,
+      CellContent::Full(palindex) | CellContent::Partial(palindex) => Some(palindex),
+    }
+  }
+
+  /// Merges the content of another stitch placed in the same cell.
+  fn merge(self, other: CellContent) -> CellContent {
+    match (self.colour(), other.colour()) {
+      (Some(a), Some(b)) if a == b => CellContent::Partial(a),
+      _ => CellContent::Mixed,
+    }
+  }
+}
+
+/// The side of the cell that is blocked by the backstitch.
+/// The coordinates are of the top-left grid point of the side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Edge {
+  Vertical(u16, u16),
+  Horizontal(u16, u16),
+}
+
+/// The backstitches that stop the fill.
+#[derive(Debug, Default)]
+struct Barriers {
+  /// The sides of the cells covered by the backstitches.
+  edges: HashSet<Edge>,
+  /// The cells that the backstitches cross, e.g. diagonally.
+  /// They are filled, but the fill doesn't spread from them, so it can't leak across the backstitch.
+  cells: HashSet<(u16, u16)>,
+}
+
+impl Pattern {
+  /// Returns the cells connected to the start one that have the same colour.
+  /// If `respect_lines` is set, the backstitches stop the fill.
+  pub fn flood_fill_cells(&self, x: u16, y: u16, respect_lines: bool) -> Vec<(u16, u16)> {
+    self.connected_cells(&self.cell_contents(), x, y, respect_lines)
+  }
+
+  /// Returns the stitches that fill the area connected to the start cell.
+  /// The empty and full cells receive the stitches of the given kind, while the stitches of the partial cells are recoloured.
+  /// The stitches that are already in the pattern are skipped, so nothing is returned when the area is already filled.
+  pub fn flood_fill(&self, x: u16, y: u16, palindex: u8, kind: FillStitchKind, respect_lines: bool) -> Vec<Stitch> {
+    let contents = self.cell_contents();
+    self
+      .connected_cells(&contents, x, y, respect_lines)
+      .into_iter()
+      .flat_map(|(x, y)| match contents.get(&(x, y)) {
+        Some(CellContent::Partial(_)) => self.recoloured_cell_stitches(x, y, palindex),
+        _ => kind.stitches(x, y, palindex),
+      })
+      .filter(|stitch| !self.has_same_stitch(stitch))
+      .collect()
+  }
+
+  fn connected_cells(
+    &self,
+    contents: &HashMap<(u16, u16), CellContent>,
+    x: u16,
+    y: u16,
+    respect_lines: bool,
+  ) -> Vec<(u16, u16)> {
+    let (width, height) = (self.properties.width, self.properties.height);
+    if x >= width || y >= height {
+      return Vec::new();
+    }
+
+    let content_of = |cell: &(u16, u16)| contents.get(cell).copied().unwrap_or(CellContent::Empty);
+    let start_content = content_of(&(x, y));
+    if start_content == CellContent::Mixed {
+      return Vec::new();
+    }
+    let barriers = if respect_lines {
+      self.barriers()
+    } else {
+      Barriers::default()
+    };
+
+    let mut visited = HashSet::from([(x, y)]);
+    let mut queue = VecDeque::from([(x, y)]);
+    let mut cells = Vec::new();
+    while let Some((x, y)) = queue.pop_front() {
+      cells.push((x, y));
+      if barriers.cells.contains(&(x, y)) {
+        continue;
+      }
+      let neighbours = [
+        (x > 0).then(|| ((x - 1, y), Edge::Vertical(x, y))),
+        (x + 1 < width).then(|| ((x + 1, y), Edge::Vertical(x + 1, y))),
+        (y > 0).then(|| ((x, y - 1), Edge::Horizontal(x, y))),
+        (y + 1 < height).then(|| ((x, y + 1), Edge::Horizontal(x, y + 1))),
+      ];
+      for (neighbour, edge) in neighbours.into_iter().flatten() {
+        let content = content_of(&neighbour);
+        if !barriers.edges.contains(&edge)
+          && content != CellContent::Mixed
+          && content.colour() == start_content.colour()
+          && visited.insert(neighbour)
+        {
+          queue.push_back(neighbour);
+        }
+      }
+    }
+    cells
+  }
+
+  /// Collects the contents of the non-empty cells.
+  fn cell_contents(&self) -> HashMap<(u16, u16), CellContent> {
+    let mut contents = HashMap::new();
+    let mut put = |x: Coord, y: Coord, content: CellContent| {
+      let cell = (x.floor() as u16, y.floor() as u16);
+      contents
+        .entry(cell)
+        .and_modify(|existing: &mut CellContent| *existing = existing.merge(content))
+        .or_insert(content);
+    };
+    for fullstitch in self.fullstitches.iter() {
+      let content = match fullstitch.kind {
+        FullStitchKind::Full => CellContent::Full(fullstitch.palindex),
+        FullStitchKind::Petite => CellContent::Partial(fullstitch.palindex),
+      };
+      put(fullstitch.x, fullstitch.y, content);
+    }
+    for partstitch in self.partstitches.iter() {
+      put(partstitch.x, partstitch.y, CellContent::Partial(partstitch.palindex));
+    }
+    contents
+  }
+
+  /// Returns the stitches of the partial cell with the new palette index.
+  fn recoloured_cell_stitches(&self, x: u16, y: u16, palindex: u8) -> Vec<Stitch> {
+    let (x, y) = (f32::from(x), f32::from(y));
+    let in_cell = |sx: Coord, sy: Coord| sx.floor() == x && sy.floor() == y;
+    let area = Bounds::new(x, y, x + 1.0, y + 1.0);
+    let fullstitches = self
+      .fullstitches
+      .intersecting(&area)
+      .filter(|fullstitch| in_cell(fullstitch.x, fullstitch.y))
+      .map(|&fullstitch| Stitch::Full(FullStitch { palindex, ..fullstitch }));
+    let partstitches = self
+      .partstitches
+      .intersecting(&area)
+      .filter(|partstitch| in_cell(partstitch.x, partstitch.y))
+      .map(|&partstitch| Stitch::Part(PartStitch { palindex, ..partstitch }));
+    fullstitches.chain(partstitches).collect()
+  }
+
+  /// Checks whether the pattern has the stitch with the same palette index.
+  fn has_same_stitch(&self, stitch: &Stitch) -> bool {
+    match stitch {
+      Stitch::Full(fullstitch) => self.fullstitches.get(fullstitch) == Some(fullstitch),
+      Stitch::Part(partstitch) => self.partstitches.get(partstitch) == Some(partstitch),
+      _ => false,
+    }
+  }
+
+  /// Collects the sides of the cells that are fully covered by the backstitches and the cells the backstitches cross.
+  fn barriers(&self) -> Barriers {
+    let (width, height) = (self.properties.width, self.properties.height);
+    let mut barriers = Barriers::default();
+    for line in self.lines.iter().filter(|line| line.kind == LineKind::Back) {
+      let ((x1, x2), (y1, y2)) = (line.x, line.y);
+      if x1 == x2 && x1.fract() == 0.0 {
+        for y in unit_steps(y1, y2) {
+          barriers.edges.insert(Edge::Vertical(x1.into_inner() as u16, y));
+        }
+      } else if y1 == y2 && y1.fract() == 0.0 {
+        for x in unit_steps(x1, x2) {
+          barriers.edges.insert(Edge::Horizontal(x, y1.into_inner() as u16));
+        }
+      } else {
+        let (left, right) = (x1.min(x2).floor() as u16, (x1.max(x2).ceil() as u16).min(width));
+        let (top, bottom) = (y1.min(y2).floor() as u16, (y1.max(y2).ceil() as u16).min(height));
+        for y in top..bottom {
+          for x in left..right {
+            if crosses_cell(line, x, y) {
+              barriers.cells.insert((x, y));
+            }
+          }
+        }
+      }
+    }
+    barriers
+  }
+}
+
+/// Returns the starts of the unit segments that lie between the coordinates.
+fn unit_steps(a: Coord, b: Coord) -> std::ops::Range<u16> {
+  let (min, max) = if a < b { (a, b) } else { (b, a) };
+  (min.ceil() as u16)..(max.floor() as u16)
+}
+
+/// Checks whether the line passes through the inside of the cell, not only touches its sides or corners.
+fn crosses_cell(line: &Line, x: u16, y: u16) -> bool {
+  let start = (line.x.0.into_inner(), line.y.0.into_inner());
+  let (dx, dy) = (line.x.1.into_inner() - start.0, line.y.1.into_inner() - start.1);
+  let (left, top) = (f32::from(x), f32::from(y));
+
+  // Clips the line to the cell (the Liang-Barsky algorithm).
+  let (mut t0, mut t1) = (0.0f32, 1.0f32);
+  for (p, q) in [
+    (-dx, start.0 - left),
+    (dx, left + 1.0 - start.0),
+    (-dy, start.1 - top),
+    (dy, top + 1.0 - start.1),
+  ] {
+    if p == 0.0 {
+      if q < 0.0 {
+        return false;
+      }
+    } else if p < 0.0 {
+      t0 = t0.max(q / p);
+    } else {
+      t1 = t1.min(q / p);
+    }
+  }
+  if t0 >= t1 {
+    return false;
+  }
+
+  // The clipped part lies on a side of the cell when its middle does.
+  let t = (t0 + t1) / 2.0;
+  let (mx, my) = (start.0 + t * dx, start.1 + t * dy);
+  mx > left && mx < left + 1.0 && my > top && my < top + 1.0
+}
@@ -0,0 +1,169 @@
+use ordered_float::NotNan;
+
+use super::*;
+use crate::core::pattern::{Line, PartStitch};
+
+fn full_stitch(x: f32, y: f32, palindex: u8) -> FullStitch {
+  FullStitch {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(y).unwrap(),
+    palindex,
+    kind: FullStitchKind::Full,
+  }
+}
+
+fn back_stitch(x: (f32, f32), y: (f32, f32)) -> Line {
+  Line {
+    x: (NotNan::new(x.0).unwrap(), NotNan::new(x.1).unwrap()),
+    y: (NotNan::new(y.0).unwrap(), NotNan::new(y.1).unwrap()),
+    palindex: 0,
+    kind: LineKind::Back,
+  }
+}
+
+fn create_pattern() -> Pattern {
+  let mut pattern = Pattern::default();
+  pattern.properties.width = 5;
+  pattern.properties.height = 5;
+  // A vertical wall of full stitches at x = 2 with a gap at y = 4.
+  for y in 0..4 {
+    pattern.fullstitches.insert(full_stitch(2.0, y as f32, 1));
+  }
+  pattern
+}
+
+#[test]
+fn fills_empty_area() {
+  let pattern = create_pattern();
+  let mut cells = pattern.flood_fill_cells(0, 0, false);
+  cells.sort_unstable();
+  // All the empty cells are connected through the gap.
+  assert_eq!(cells.len(), 25 - 4);
+  assert!(!cells.contains(&(2, 0)));
+}
+
+#[test]
+fn fills_same_palette_index() {
+  let mut pattern = create_pattern();
+  pattern.fullstitches.insert(full_stitch(3.0, 0.0, 1));
+  pattern.fullstitches.insert(full_stitch(3.0, 1.0, 2));
+  let mut cells = pattern.flood_fill_cells(2, 1, false);
+  cells.sort_unstable();
+  assert_eq!(cells, vec![(2, 0), (2, 1), (2, 2), (2, 3), (3, 0)]);
+}
+
+#[test]
+fn stops_at_other_colours_and_bounds() {
+  let mut pattern = create_pattern();
+  pattern.fullstitches.insert(FullStitch {
+    kind: FullStitchKind::Petite,
+    ..full_stitch(2.5, 4.5, 0)
+  });
+  // The gap is closed by the cell with the petite stitch.
+  assert_eq!(pattern.flood_fill_cells(0, 0, false).len(), 10);
+  assert_eq!(pattern.flood_fill_cells(2, 4, false), vec![(2, 4)]);
+  assert!(pattern.flood_fill_cells(5, 0, false).is_empty());
+
+  // The cell with the stitches of different colours can't be filled.
+  pattern.fullstitches.insert(FullStitch {
+    kind: FullStitchKind::Petite,
+    ..full_stitch(2.0, 4.0, 1)
+  });
+  assert!(pattern.flood_fill_cells(2, 4, false).is_empty());
+}
+
+#[test]
+fn fills_partial_cells_of_same_colour() {
+  let mut pattern = create_pattern();
+  let half = PartStitch {
+    x: NotNan::new(2.0).unwrap(),
+    y: NotNan::new(4.0).unwrap(),
+    palindex: 1,
+    direction: PartStitchDirection::Forward,
+    kind: PartStitchKind::Half,
+  };
+  pattern.partstitches.insert(half);
+
+  // The half stitch continues the wall, so the cells of the wall and the half stitch are connected.
+  let mut cells = pattern.flood_fill_cells(2, 0, false);
+  cells.sort_unstable();
+  assert_eq!(cells, vec![(2, 0), (2, 1), (2, 2), (2, 3), (2, 4)]);
+
+  // The full cells receive the new stitches, while the half stitch is recoloured.
+  let stitches = pattern.flood_fill(2, 0, 3, FillStitchKind::Full, false);
+  assert_eq!(stitches.len(), 5);
+  assert!(stitches.contains(&Stitch::Part(PartStitch { palindex: 3, ..half })));
+  assert!(!stitches.contains(&Stitch::Full(full_stitch(2.0, 4.0, 3))));
+}
+
+#[test]
+fn respects_backstitches() {
+  let mut pattern = create_pattern();
+  // The backstitch closes the gap between the cells (1, 4) and (2, 4).
+  pattern.lines.insert(back_stitch((2.0, 2.0), (3.0, 5.0)));
+  assert_eq!(pattern.flood_fill_cells(0, 0, true).len(), 10);
+  assert_eq!(pattern.flood_fill_cells(0, 0, false).len(), 21);
+
+  // The backstitch that covers only a half of the side doesn't stop the fill.
+  let mut pattern = create_pattern();
+  pattern.lines.insert(back_stitch((2.0, 2.0), (4.0, 4.5)));
+  assert_eq!(pattern.flood_fill_cells(0, 0, true).len(), 21);
+}
+
+#[test]
+fn respects_diagonal_backstitches() {
+  let mut pattern = Pattern::default();
+  pattern.properties.width = 5;
+  pattern.properties.height = 5;
+  // The diagonal backstitches split the pattern into two triangles.
+  pattern.lines.insert(back_stitch((0.0, 3.0), (0.0, 3.0)));
+  pattern.lines.insert(back_stitch((3.0, 5.0), (3.0, 5.0)));
+
+  let mut cells = pattern.flood_fill_cells(4, 0, true);
+  cells.sort_unstable();
+  // The cells above the diagonal and the crossed ones.
+  assert_eq!(cells.len(), 15);
+  assert!(cells.iter().all(|&(x, y)| x >= y));
+
+  let cells = pattern.flood_fill_cells(0, 4, true);
+  assert_eq!(cells.len(), 15);
+  assert!(cells.iter().all(|&(x, y)| x <= y));
+
+  assert_eq!(pattern.flood_fill_cells(4, 0, false).len(), 25);
+}
+
+#[test]
+fn creates_fill_stitches() {
+  let pattern = create_pattern();
+  let stitches = pattern.flood_fill(2, 0, 3, FillStitchKind::Full, false);
+  assert_eq!(stitches.len(), 4);
+  assert!(stitches.contains(&Stitch::Full(full_stitch(2.0, 3.0, 3))));
+
+  // The area is already filled with the same stitches.
+  assert!(pattern.flood_fill(2, 0, 1, FillStitchKind::Full, false).is_empty());
+  assert!(pattern.flood_fill(2, 0, 1, FillStitchKind::Full, true).is_empty());
+
+  assert_eq!(pattern.flood_fill(2, 0, 1, FillStitchKind::Petite, false).len(), 16);
+  let stitches = pattern.flood_fill(
+    2,
+    0,
+    1,
+    FillStitchKind::Half {
+      direction: PartStitchDirection::Forward,
+    },
+    false,
+  );
+  assert!(stitches
+    .iter()
+    .all(|stitch| matches!(stitch, Stitch::Part(partstitch) if partstitch.kind == PartStitchKind::Half)));
+}
+
+#[test]
+fn deserializes_fill_stitch_kind() {
+  assert_eq!(
+    serde_json::from_str::<FillStitchKind>(r#"{"kind":"half","direction":"Backward"}"#).unwrap(),
+    FillStitchKind::Half {
+      direction: PartStitchDirection::Backward
+    }
+  );
+}
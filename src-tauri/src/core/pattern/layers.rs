@@ -0,0 +1,698 @@
+//! The layers of the pattern.
+//!
+//! Each layer holds its own stitches, so the conflicts between the stitches are resolved only within a layer.
+//! The stitches of the active layer are kept right in the pattern, so all the editing tools work on the active layer,
+//! while the stitches of the other layers are kept aside in their slots.
+//! The layers are merged from the bottom to the top one when the pattern is rendered or saved to the formats without layers.
+
+use std::borrow::Cow;
+
+use anyhow::Result;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use super::progress::ProgressStitch;
+use super::{Bounds, FullStitch, Line, Node, PartStitch, Pattern, PatternProject, SpecialStitch, Stitch, Stitches};
+
+#[cfg(test)]
+#[path = "layers.test.rs"]
+mod tests;
+
+/// The properties of a layer that can be changed by the user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+pub struct LayerProperties {
+  pub name: String,
+  /// Whether the layer stitches are shown and exported.
+  pub visible: bool,
+  /// Whether the layer stitches can be edited.
+  pub locked: bool,
+}
+
+impl LayerProperties {
+  pub fn new(name: String) -> Self {
+    Self {
+      name,
+      visible: true,
+      locked: false,
+    }
+  }
+}
+
+#[derive(Debug, Default, Clone, BorshSerialize, BorshDeserialize)]
+struct LayerStitches {
+  fullstitches: Stitches<FullStitch>,
+  partstitches: Stitches<PartStitch>,
+  nodes: Stitches<Node>,
+  lines: Stitches<Line>,
+  specialstitches: Stitches<SpecialStitch>,
+}
+
+impl LayerStitches {
+  fn borrow(&self) -> LayerStitchesRef<'_> {
+    LayerStitchesRef {
+      fullstitches: &self.fullstitches,
+      partstitches: &self.partstitches,
+      nodes: &self.nodes,
+      lines: &self.lines,
+      specialstitches: &self.specialstitches,
+    }
+  }
+
+  fn has_stitch(&self, stitch: &ProgressStitch) -> bool {
+    match stitch {
+      ProgressStitch::Stitch(Stitch::Full(fullstitch)) => self.fullstitches.get(fullstitch) == Some(fullstitch),
+      ProgressStitch::Stitch(Stitch::Part(partstitch)) => self.partstitches.get(partstitch) == Some(partstitch),
+      ProgressStitch::Stitch(Stitch::Node(node)) => self.nodes.get(node) == Some(node),
+      ProgressStitch::Stitch(Stitch::Line(line)) => self.lines.get(line) == Some(line),
+      ProgressStitch::Special(specialstitch) => self.specialstitches.get(specialstitch) == Some(specialstitch),
+    }
+  }
+
+  fn uses_palindex(&self, palindex: u8) -> bool {
+    self.fullstitches.iter().any(|stitch| stitch.palindex == palindex)
+      || self.partstitches.iter().any(|stitch| stitch.palindex == palindex)
+      || self.lines.iter().any(|stitch| stitch.palindex == palindex)
+      || self.nodes.iter().any(|stitch| stitch.palindex == palindex)
+      || self.specialstitches.iter().any(|stitch| stitch.palindex == palindex)
+  }
+}
+
+/// The borrowed stitches of a layer, to query them without copying.
+#[derive(Clone, Copy)]
+struct LayerStitchesRef<'a> {
+  fullstitches: &'a Stitches<FullStitch>,
+  partstitches: &'a Stitches<PartStitch>,
+  nodes: &'a Stitches<Node>,
+  lines: &'a Stitches<Line>,
+  specialstitches: &'a Stitches<SpecialStitch>,
+}
+
+impl LayerStitchesRef<'_> {
+  /// Returns the stitches intersecting the area or all of them if the area is not set.
+  fn stitches(&self, area: Option<&Bounds>) -> Vec<Stitch> {
+    match area {
+      Some(area) => {
+        let fullstitches = self.fullstitches.intersecting(area).map(|&stitch| Stitch::Full(stitch));
+        let partstitches = self.partstitches.intersecting(area).map(|&stitch| Stitch::Part(stitch));
+        let lines = self.lines.intersecting(area).map(|&stitch| Stitch::Line(stitch));
+        let nodes = self.nodes.intersecting(area).map(|&stitch| Stitch::Node(stitch));
+        fullstitches.chain(partstitches).chain(lines).chain(nodes).collect()
+      }
+      None => {
+        let fullstitches = self.fullstitches.iter().map(|&stitch| Stitch::Full(stitch));
+        let partstitches = self.partstitches.iter().map(|&stitch| Stitch::Part(stitch));
+        let lines = self.lines.iter().map(|&stitch| Stitch::Line(stitch));
+        let nodes = self.nodes.iter().map(|&stitch| Stitch::Node(stitch));
+        fullstitches.chain(partstitches).chain(lines).chain(nodes).collect()
+      }
+    }
+  }
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct Layer {
+  pub properties: LayerProperties,
+  /// The stitches of the layer. They are empty for the active layer, since its stitches are kept in the pattern.
+  stitches: LayerStitches,
+}
+
+impl Layer {
+  pub fn new(properties: LayerProperties) -> Self {
+    Self {
+      properties,
+      stitches: LayerStitches::default(),
+    }
+  }
+}
+
+/// The stitches of the inactive layers changed by an operation, as they were before it.
+/// The layers are referred by their positions, which stay the same until the operation is undone.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LayersDiff(Vec<LayerDiff>);
+
+#[derive(Debug, Clone, PartialEq)]
+struct LayerDiff {
+  index: usize,
+  stitches: Vec<Stitch>,
+  specialstitches: Vec<SpecialStitch>,
+}
+
+impl LayersDiff {
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  /// Adds the changes of the operation made right after the one this diff belongs to.
+  pub fn append(&mut self, other: LayersDiff) {
+    self.0.extend(other.0);
+  }
+
+  /// Returns the changed stitches of all the layers as they were before the change.
+  pub fn stitches(&self) -> impl Iterator<Item = &Stitch> {
+    self.0.iter().flat_map(|diff| diff.stitches.iter())
+  }
+
+  /// Returns the changed special stitches of all the layers as they were before the change.
+  pub fn specialstitches(&self) -> impl Iterator<Item = &SpecialStitch> {
+    self.0.iter().flat_map(|diff| diff.specialstitches.iter())
+  }
+}
+
+/// The layers merged by `merge_layer_down`, kept to split them back.
+#[derive(Debug, Clone)]
+pub struct MergedLayers {
+  upper: Layer,
+  lower: LayerStitches,
+  active: usize,
+}
+
+/// The brief information about a layer for the frontend.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(test, derive(Deserialize))]
+pub struct LayerInfo {
+  #[serde(flatten)]
+  pub properties: LayerProperties,
+  pub active: bool,
+}
+
+/// The layers of the pattern, from the bottom to the top one.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct Layers {
+  layers: Vec<Layer>,
+  active: usize,
+}
+
+impl Default for Layers {
+  fn default() -> Self {
+    Self {
+      layers: vec![Layer::new(LayerProperties::new(String::from("Layer 1")))],
+      active: 0,
+    }
+  }
+}
+
+impl Layers {
+  pub fn len(&self) -> usize {
+    self.layers.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.layers.is_empty()
+  }
+
+  pub fn active(&self) -> usize {
+    self.active
+  }
+
+  pub fn active_properties(&self) -> &LayerProperties {
+    &self.layers[self.active].properties
+  }
+
+  pub fn info(&self) -> Vec<LayerInfo> {
+    self
+      .layers
+      .iter()
+      .enumerate()
+      .map(|(index, layer)| LayerInfo {
+        properties: layer.properties.clone(),
+        active: index == self.active,
+      })
+      .collect()
+  }
+
+  /// Returns an error if the stitches of the active layer can't be edited.
+  pub fn ensure_active_editable(&self) -> Result<()> {
+    let properties = self.active_properties();
+    anyhow::ensure!(!properties.locked, "The layer {} is locked", properties.name);
+    Ok(())
+  }
+
+  fn ensure_exists(&self, index: usize) -> Result<()> {
+    anyhow::ensure!(index < self.layers.len(), "There is no layer with index {index}");
+    Ok(())
+  }
+
+  /// Returns a name for a new layer that is not used by the existing ones.
+  pub fn next_name(&self) -> String {
+    (self.layers.len() + 1..)
+      .map(|number| format!("Layer {number}"))
+      .find(|name| self.layers.iter().all(|layer| &layer.properties.name != name))
+      .unwrap()
+  }
+
+  fn inactive_mut(&mut self) -> impl Iterator<Item = &mut LayerStitches> {
+    let active = self.active;
+    self
+      .layers
+      .iter_mut()
+      .enumerate()
+      .filter(move |(index, _)| *index != active)
+      .map(|(_, layer)| &mut layer.stitches)
+  }
+
+  fn inactive(&self) -> impl Iterator<Item = &LayerStitches> {
+    self
+      .layers
+      .iter()
+      .enumerate()
+      .filter(|(index, _)| *index != self.active)
+      .map(|(_, layer)| &layer.stitches)
+  }
+}
+
+impl Pattern {
+  fn take_stitches(&mut self) -> LayerStitches {
+    LayerStitches {
+      fullstitches: std::mem::take(&mut self.fullstitches),
+      partstitches: std::mem::take(&mut self.partstitches),
+      nodes: std::mem::take(&mut self.nodes),
+      lines: std::mem::take(&mut self.lines),
+      specialstitches: std::mem::take(&mut self.specialstitches),
+    }
+  }
+
+  fn clone_stitches(&self) -> LayerStitches {
+    LayerStitches {
+      fullstitches: self.fullstitches.clone(),
+      partstitches: self.partstitches.clone(),
+      nodes: self.nodes.clone(),
+      lines: self.lines.clone(),
+      specialstitches: self.specialstitches.clone(),
+    }
+  }
+
+  fn put_stitches(&mut self, stitches: LayerStitches) {
+    self.fullstitches = stitches.fullstitches;
+    self.partstitches = stitches.partstitches;
+    self.nodes = stitches.nodes;
+    self.lines = stitches.lines;
+    self.specialstitches = stitches.specialstitches;
+  }
+
+  /// Returns all the stitches of the active layer.
+  pub fn all_stitches(&self) -> Vec<Stitch> {
+    let fullstitches = self.fullstitches.iter().map(|&fullstitch| Stitch::Full(fullstitch));
+    let partstitches = self.partstitches.iter().map(|&partstitch| Stitch::Part(partstitch));
+    let lines = self.lines.iter().map(|&line| Stitch::Line(line));
+    let nodes = self.nodes.iter().map(|&node| Stitch::Node(node));
+    fullstitches.chain(partstitches).chain(lines).chain(nodes).collect()
+  }
+
+  /// Runs the function on each inactive layer as if it were a separate pattern.
+  /// The locked layers are included, so it is used only to follow the changes of the palette order.
+  pub(super) fn with_inactive_layers<F: FnMut(&mut Pattern)>(&mut self, mut f: F) {
+    let mut scratch = Pattern::default();
+    for stitches in self.layers.inactive_mut() {
+      scratch.put_stitches(std::mem::take(stitches));
+      f(&mut scratch);
+      *stitches = scratch.take_stitches();
+    }
+  }
+
+  /// Runs the function on each inactive layer as if it were a separate pattern and keeps the stitches it returns.
+  /// The function gets the layer properties, so it can leave the locked layers intact.
+  pub(super) fn change_inactive_layers<F>(&mut self, mut f: F) -> LayersDiff
+  where
+    F: FnMut(&mut Pattern, &LayerProperties) -> (Vec<Stitch>, Vec<SpecialStitch>),
+  {
+    let mut diff = Vec::new();
+    let mut scratch = Pattern::default();
+    for (index, layer) in self.layers.layers.iter_mut().enumerate() {
+      if index == self.layers.active {
+        continue;
+      }
+      scratch.put_stitches(std::mem::take(&mut layer.stitches));
+      let (stitches, specialstitches) = f(&mut scratch, &layer.properties);
+      layer.stitches = scratch.take_stitches();
+      if !stitches.is_empty() || !specialstitches.is_empty() {
+        diff.push(LayerDiff { index, stitches, specialstitches });
+      }
+    }
+    LayersDiff(diff)
+  }
+
+  /// Puts the stitches kept by `change_inactive_layers` back to their layers, replacing the ones at the same positions.
+  pub fn put_back_layers_stitches(&mut self, diff: &LayersDiff) {
+    let mut scratch = Pattern::default();
+    for change in diff.0.iter().rev() {
+      let stitches = &mut self.layers.layers[change.index].stitches;
+      scratch.put_stitches(std::mem::take(stitches));
+      scratch.put_back_stitches(&change.stitches, &change.specialstitches);
+      *stitches = scratch.take_stitches();
+    }
+  }
+
+  /// Checks if any layer, including the hidden ones, has exactly this stitch.
+  pub fn has_stitch_in_any_layer(&self, stitch: &ProgressStitch) -> bool {
+    let in_active_layer = match stitch {
+      ProgressStitch::Stitch(stitch) => self.stitches_at(&[*stitch]).first() == Some(stitch),
+      ProgressStitch::Special(specialstitch) => self.specialstitches.get(specialstitch) == Some(specialstitch),
+    };
+    in_active_layer || self.layers.inactive().any(|stitches| stitches.has_stitch(stitch))
+  }
+
+  /// Checks if any stitch of the inactive layers uses the palette item.
+  pub(super) fn is_palindex_used_by_inactive_layers(&self, palindex: u8) -> bool {
+    self.layers.inactive().any(|stitches| stitches.uses_palindex(palindex))
+  }
+
+  /// Returns an error if the stitches of a locked layer use the palette item, so it can't be removed.
+  pub fn ensure_palindex_unlocked(&self, palindex: u8) -> Result<()> {
+    for (index, layer) in self.layers.layers.iter().enumerate() {
+      let used = if index == self.layers.active {
+        self.is_palindex_used_by_active_layer(palindex)
+      } else {
+        layer.stitches.uses_palindex(palindex)
+      };
+      anyhow::ensure!(
+        !layer.properties.locked || !used,
+        "The palette item is used by the locked layer {}",
+        layer.properties.name
+      );
+    }
+    Ok(())
+  }
+
+  /// Moves the stitches of the active layer into its slot, so all the layers can be rearranged.
+  fn stash_active_layer(&mut self) {
+    let stitches = self.take_stitches();
+    self.layers.layers[self.layers.active].stitches = stitches;
+  }
+
+  /// Takes the stitches of the active layer out of its slot into the pattern.
+  fn unstash_active_layer(&mut self) {
+    let stitches = std::mem::take(&mut self.layers.layers[self.layers.active].stitches);
+    self.put_stitches(stitches);
+  }
+
+  /// Makes the layer active.
+  pub fn set_active_layer(&mut self, index: usize) -> Result<()> {
+    self.layers.ensure_exists(index)?;
+    self.stash_active_layer();
+    self.layers.active = index;
+    self.unstash_active_layer();
+    Ok(())
+  }
+
+  /// Inserts the layer at the given position.
+  /// The active layer stays the same.
+  pub fn insert_layer(&mut self, index: usize, layer: Layer) -> Result<()> {
+    anyhow::ensure!(index <= self.layers.len(), "There is no layer with index {index}");
+    self.layers.layers.insert(index, layer);
+    if index <= self.layers.active {
+      self.layers.active += 1;
+    }
+    Ok(())
+  }
+
+  /// Removes and returns the layer with its stitches.
+  /// When the active layer is removed, the one below it becomes active.
+  pub fn remove_layer(&mut self, index: usize) -> Result<Layer> {
+    self.layers.ensure_exists(index)?;
+    anyhow::ensure!(self.layers.len() > 1, "The last layer can't be removed");
+    self.stash_active_layer();
+    let layer = self.layers.layers.remove(index);
+    let active = self.layers.active;
+    if index < active || (index == active && active > 0) {
+      self.layers.active -= 1;
+    }
+    self.unstash_active_layer();
+    Ok(layer)
+  }
+
+  /// Puts the layer removed by `remove_layer` back and makes the layer that was active before the removal active again.
+  pub fn restore_layer(&mut self, index: usize, layer: Layer, active: usize) -> Result<()> {
+    anyhow::ensure!(index <= self.layers.len(), "There is no layer with index {index}");
+    anyhow::ensure!(active <= self.layers.len(), "There is no layer with index {active}");
+    self.stash_active_layer();
+    self.layers.layers.insert(index, layer);
+    self.layers.active = active;
+    self.unstash_active_layer();
+    Ok(())
+  }
+
+  /// Moves the layer to the new position, shifting the layers in between.
+  /// The active layer stays the same.
+  pub fn move_layer(&mut self, from: usize, to: usize) -> Result<()> {
+    self.layers.ensure_exists(from.max(to))?;
+    let active = self.layers.active;
+    let layer = self.layers.layers.remove(from);
+    self.layers.layers.insert(to, layer);
+    self.layers.active = match active {
+      _ if active == from => to,
+      _ if from < to && (from..=to).contains(&active) => active - 1,
+      _ if to < from && (to..=from).contains(&active) => active + 1,
+      _ => active,
+    };
+    Ok(())
+  }
+
+  /// Replaces the layer properties and returns the previous ones.
+  pub fn update_layer(&mut self, index: usize, properties: LayerProperties) -> Result<LayerProperties> {
+    self.layers.ensure_exists(index)?;
+    Ok(std::mem::replace(&mut self.layers.layers[index].properties, properties))
+  }
+
+  /// Merges the layer into the one below it.
+  /// The stitches of the merged layer win the conflicts, and the lower layer becomes active if the merged one was.
+  /// Returns both layers as they were before the merge, so they can be split back.
+  pub fn merge_layer_down(&mut self, index: usize) -> Result<MergedLayers> {
+    self.layers.ensure_exists(index)?;
+    anyhow::ensure!(index > 0, "The bottom layer can't be merged down");
+    for layer in &self.layers.layers[index - 1..=index] {
+      anyhow::ensure!(
+        !layer.properties.locked,
+        "The layer {} is locked",
+        layer.properties.name
+      );
+    }
+    let active = self.layers.active;
+    self.stash_active_layer();
+    let upper = self.layers.layers.remove(index);
+    if index <= self.layers.active {
+      self.layers.active -= 1;
+    }
+
+    let lower = std::mem::take(&mut self.layers.layers[index - 1].stitches);
+    let mut merged = Pattern::default();
+    merged.put_stitches(lower.clone());
+    merged.merge_stitches(upper.stitches.clone());
+    self.layers.layers[index - 1].stitches = merged.take_stitches();
+
+    self.unstash_active_layer();
+    Ok(MergedLayers { upper, lower, active })
+  }
+
+  /// Splits the layers merged by `merge_layer_down` back.
+  pub fn split_layer(&mut self, index: usize, merged: MergedLayers) -> Result<()> {
+    anyhow::ensure!(
+      index > 0 && index <= self.layers.len(),
+      "There is no layer with index {index}"
+    );
+    self.stash_active_layer();
+    self.layers.layers[index - 1].stitches = merged.lower;
+    self.layers.layers.insert(index, merged.upper);
+    self.layers.active = merged.active;
+    self.unstash_active_layer();
+    Ok(())
+  }
+
+  /// Adds the stitches on top of the current ones, resolving the conflicts in favor of the added stitches.
+  fn merge_stitches(&mut self, stitches: LayerStitches) {
+    let mut layer = Pattern::default();
+    layer.put_stitches(stitches);
+    self.add_stitches(layer.all_stitches());
+    for &specialstitch in layer.specialstitches.iter() {
+      self.specialstitches.insert(specialstitch);
+    }
+  }
+
+  /// Returns the stitches of the layers merged from the bottom to the top one.
+  /// The hidden layers are skipped unless they are asked for.
+  fn merge_layers(&self, include_hidden: bool) -> Pattern {
+    let mut merged = Pattern::default();
+    for (index, layer) in self.layers.layers.iter().enumerate() {
+      if !include_hidden && !layer.properties.visible {
+        continue;
+      }
+      let stitches = if index == self.layers.active {
+        self.clone_stitches()
+      } else {
+        layer.stitches.clone()
+      };
+      merged.merge_stitches(stitches);
+    }
+    merged
+  }
+
+  /// Returns the copy of the pattern without the stitches and with the only empty layer.
+  fn without_stitches(&self) -> Pattern {
+    Pattern {
+      properties: self.properties.clone(),
+      info: self.info.clone(),
+      palette: self.palette.clone(),
+      fabric: self.fabric.clone(),
+      special_stitch_models: self.special_stitch_models.clone(),
+      ..Pattern::default()
+    }
+  }
+
+  fn flatten_layers(&self, include_hidden: bool) -> Pattern {
+    let mut merged = self.merge_layers(include_hidden);
+    let mut flattened = self.without_stitches();
+    flattened.put_stitches(merged.take_stitches());
+    flattened
+  }
+
+  fn active_stitches(&self) -> LayerStitchesRef<'_> {
+    LayerStitchesRef {
+      fullstitches: &self.fullstitches,
+      partstitches: &self.partstitches,
+      nodes: &self.nodes,
+      lines: &self.lines,
+      specialstitches: &self.specialstitches,
+    }
+  }
+
+  /// Returns the stitches of the layers from the bottom to the top one.
+  /// The hidden layers are skipped unless they are asked for.
+  fn layers_stitches(&self, include_hidden: bool) -> impl Iterator<Item = LayerStitchesRef<'_>> {
+    self
+      .layers
+      .layers
+      .iter()
+      .enumerate()
+      .filter(move |(_, layer)| include_hidden || layer.properties.visible)
+      .map(|(index, layer)| {
+        if index == self.layers.active {
+          self.active_stitches()
+        } else {
+          layer.stitches.borrow()
+        }
+      })
+  }
+
+  /// Checks whether the active layer is the only visible one, so the pattern is shown as it is.
+  fn shows_active_layer_only(&self) -> bool {
+    self.layers.layers.iter().enumerate().all(|(index, layer)| {
+      let active = index == self.layers.active;
+      layer.properties.visible == active
+    })
+  }
+
+  /// Returns the layers merged like in `flatten` or `flatten_all`.
+  /// If the area is set, only the stitches around it are merged, so the result is valid inside the area only.
+  /// The special stitches are merged all, since there are few of them.
+  fn merge_layers_around(&self, area: Option<&Bounds>, include_hidden: bool) -> Pattern {
+    // The conflicting stitches lie in the same cell, so the cells around the area are merged to resolve them at its border.
+    let area = area.map(|area| area.grown(1.0));
+    let mut merged = self.without_stitches();
+    for layer in self.layers_stitches(include_hidden) {
+      merged.add_stitches(layer.stitches(area.as_ref()));
+      for &specialstitch in layer.specialstitches.iter() {
+        merged.specialstitches.insert(specialstitch);
+      }
+    }
+    merged
+  }
+
+  /// Returns the pattern with all the layers merged into one, including the hidden ones.
+  /// The pattern is borrowed as it is if it has the only layer.
+  pub fn merged(&self) -> Cow<'_, Pattern> {
+    if self.layers.len() == 1 {
+      return Cow::Borrowed(self);
+    }
+    Cow::Owned(self.merge_layers_around(None, true))
+  }
+
+  /// Returns the pattern with all the layers merged into one inside the area.
+  /// Only the stitches around the area are copied from the layers, so it is cheap to call for a small area.
+  pub fn merged_around(&self, area: &Bounds) -> Cow<'_, Pattern> {
+    if self.layers.len() == 1 {
+      return Cow::Borrowed(self);
+    }
+    Cow::Owned(self.merge_layers_around(Some(area), true))
+  }
+
+  /// Returns the pattern as it is shown, with the visible layers merged into the active one.
+  /// The pattern is borrowed as it is if the active layer is the only visible one, so it is not copied in most cases.
+  pub fn visible(&self) -> Cow<'_, Pattern> {
+    if self.shows_active_layer_only() {
+      return Cow::Borrowed(self);
+    }
+    Cow::Owned(self.merge_layers_around(None, false))
+  }
+
+  /// Returns the pattern as it is shown inside the area.
+  /// Only the stitches around the area are copied from the layers, so it is cheap to call for a small area.
+  pub fn visible_around(&self, area: &Bounds) -> Cow<'_, Pattern> {
+    if self.shows_active_layer_only() {
+      return Cow::Borrowed(self);
+    }
+    Cow::Owned(self.merge_layers_around(Some(area), false))
+  }
+
+  /// Returns the stitches of the visible layers whose bounding boxes intersect or touch the area.
+  pub fn visible_stitches_intersecting(&self, area: &Bounds) -> Vec<Stitch> {
+    self.visible_around(area).stitches_intersecting(area)
+  }
+
+  /// Returns the copy of the pattern with all the visible layers merged into one.
+  /// It is used to render the pattern as it is shown.
+  pub fn flatten(&self) -> Pattern {
+    self.flatten_layers(false)
+  }
+
+  /// Returns the copy of the pattern with all the layers merged into one, including the hidden ones.
+  /// It is used to save the pattern to the formats without layers, so no stitches are lost.
+  pub fn flatten_all(&self) -> Pattern {
+    self.flatten_layers(true)
+  }
+
+  /// Returns the stitches of the visible layers as they are shown, with the special stitches separately.
+  pub fn visible_stitches(&self) -> (Vec<Stitch>, Vec<SpecialStitch>) {
+    let merged = self.merge_layers(false);
+    let specialstitches = merged.specialstitches.iter().copied().collect();
+    (merged.all_stitches(), specialstitches)
+  }
+}
+
+impl PatternProject {
+  fn with_pattern(&self, pattern: Pattern) -> PatternProject {
+    PatternProject {
+      file_path: self.file_path.clone(),
+      pattern,
+      display_settings: self.display_settings.clone(),
+      print_settings: self.print_settings.clone(),
+      progress: self.progress.clone(),
+      sessions: self.sessions.clone(),
+    }
+  }
+
+  /// Returns the project with the visible pattern layers flattened for rendering and exporting to images.
+  /// The project is borrowed as it is if the active layer is the only visible one.
+  pub fn flattened(&self) -> Cow<'_, PatternProject> {
+    match self.pattern.visible() {
+      Cow::Borrowed(_) => Cow::Borrowed(self),
+      Cow::Owned(pattern) => Cow::Owned(self.with_pattern(pattern)),
+    }
+  }
+
+  /// Returns the project with the visible pattern layers flattened for rendering the area.
+  /// Only the stitches around the area are merged, so the project must not be used outside of it.
+  pub fn flattened_around(&self, area: &Bounds) -> Cow<'_, PatternProject> {
+    match self.pattern.visible_around(area) {
+      Cow::Borrowed(_) => Cow::Borrowed(self),
+      Cow::Owned(pattern) => Cow::Owned(self.with_pattern(pattern)),
+    }
+  }
+
+  /// Returns the project with all the pattern layers flattened for saving to the formats without layers.
+  /// The project is borrowed as it is if it has the only layer.
+  pub fn flattened_all(&self) -> Cow<'_, PatternProject> {
+    if self.pattern.layers.len() == 1 {
+      return Cow::Borrowed(self);
+    }
+    Cow::Owned(self.with_pattern(self.pattern.flatten_all()))
+  }
+}
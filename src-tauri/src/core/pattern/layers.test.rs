@@ -0,0 +1,216 @@
+use ordered_float::NotNan;
+
+use super::*;
+use crate::core::pattern::{FullStitchKind, PartStitchDirection, PartStitchKind};
+
+fn full(x: f32, palindex: u8) -> Stitch {
+  Stitch::Full(FullStitch {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(0.0).unwrap(),
+    palindex,
+    kind: FullStitchKind::Full,
+  })
+}
+
+fn half(x: f32, palindex: u8) -> Stitch {
+  Stitch::Part(PartStitch {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(0.0).unwrap(),
+    palindex,
+    direction: PartStitchDirection::Forward,
+    kind: PartStitchKind::Half,
+  })
+}
+
+/// Creates a pattern with two layers: the bottom one with the full stitch and the top active one with the half stitch.
+fn create_pattern() -> Pattern {
+  let mut pattern = Pattern::default();
+  pattern.add_stitch(full(0.0, 0));
+  pattern
+    .insert_layer(1, Layer::new(LayerProperties::new(String::from("Layer 2"))))
+    .unwrap();
+  pattern.set_active_layer(1).unwrap();
+  pattern.add_stitch(half(0.0, 1));
+  pattern
+}
+
+#[test]
+fn resolves_conflicts_per_layer() {
+  let mut pattern = create_pattern();
+  assert_eq!(pattern.all_stitches(), vec![half(0.0, 1)]);
+
+  pattern.set_active_layer(0).unwrap();
+  assert_eq!(pattern.all_stitches(), vec![full(0.0, 0)]);
+  assert_eq!(
+    pattern
+      .layers
+      .info()
+      .iter()
+      .map(|layer| layer.active)
+      .collect::<Vec<_>>(),
+    vec![true, false]
+  );
+}
+
+#[test]
+fn queries_visible_layers_around_area() {
+  let mut pattern = create_pattern();
+  pattern.set_active_layer(0).unwrap();
+  pattern.add_stitch(full(5.0, 0));
+  pattern.set_active_layer(1).unwrap();
+
+  // The half stitch on the upper layer wins the conflict at the area border.
+  let area = Bounds::new(0.0, 0.0, 0.0, 1.0);
+  assert_eq!(pattern.visible_stitches_intersecting(&area), vec![half(0.0, 1)]);
+  assert!(matches!(pattern.visible_around(&area), Cow::Owned(_)));
+
+  let area = Bounds::new(4.5, 0.0, 6.0, 1.0);
+  assert_eq!(pattern.visible_stitches_intersecting(&area), vec![full(5.0, 0)]);
+  assert_eq!(pattern.visible().all_stitches(), pattern.flatten().all_stitches());
+
+  // The pattern is not copied when only the active layer is shown.
+  let mut properties = pattern.layers.info()[0].properties.clone();
+  properties.visible = false;
+  pattern.update_layer(0, properties).unwrap();
+  assert!(matches!(pattern.visible(), Cow::Borrowed(_)));
+  assert_eq!(pattern.visible_stitches_intersecting(&area), Vec::new());
+}
+
+#[test]
+fn flattens_visible_layers() {
+  let mut pattern = create_pattern();
+  // The upper layer wins the conflicts.
+  assert_eq!(pattern.flatten().all_stitches(), vec![half(0.0, 1)]);
+  assert_eq!(pattern.flatten().layers.len(), 1);
+
+  let mut properties = pattern.layers.info()[1].properties.clone();
+  properties.visible = false;
+  pattern.update_layer(1, properties).unwrap();
+  assert_eq!(pattern.flatten().all_stitches(), vec![full(0.0, 0)]);
+  assert_eq!(pattern.visible_stitches().0, vec![full(0.0, 0)]);
+  // The hidden layers are kept when the pattern is saved.
+  assert_eq!(pattern.flatten_all().all_stitches(), vec![half(0.0, 1)]);
+  // The active layer is kept as it is.
+  assert_eq!(pattern.all_stitches(), vec![half(0.0, 1)]);
+}
+
+#[test]
+fn merges_layer_down() {
+  let mut pattern = create_pattern();
+  pattern.add_stitch(full(1.0, 1));
+  let merged = pattern.merge_layer_down(1).unwrap();
+
+  assert_eq!(pattern.layers.len(), 1);
+  assert_eq!(pattern.layers.active(), 0);
+  assert_eq!(pattern.all_stitches(), vec![full(1.0, 1), half(0.0, 1)]);
+  assert!(pattern.merge_layer_down(0).is_err());
+
+  pattern.split_layer(1, merged).unwrap();
+  assert_eq!(pattern.layers.len(), 2);
+  assert_eq!(pattern.layers.active(), 1);
+  assert_eq!(pattern.all_stitches(), vec![full(1.0, 1), half(0.0, 1)]);
+  pattern.set_active_layer(0).unwrap();
+  assert_eq!(pattern.all_stitches(), vec![full(0.0, 0)]);
+}
+
+#[test]
+fn does_not_merge_locked_layers() {
+  let mut pattern = create_pattern();
+  let mut properties = pattern.layers.info()[0].properties.clone();
+  properties.locked = true;
+  pattern.update_layer(0, properties).unwrap();
+  assert!(pattern.merge_layer_down(1).is_err());
+  assert_eq!(pattern.layers.len(), 2);
+}
+
+#[test]
+fn removes_and_moves_layers() {
+  let mut pattern = create_pattern();
+  pattern.move_layer(1, 0).unwrap();
+  assert_eq!(pattern.layers.active(), 0);
+  assert_eq!(pattern.layers.info()[0].properties.name, "Layer 2");
+
+  // The layer below the removed active one becomes active.
+  let layer = pattern.remove_layer(0).unwrap();
+  assert_eq!(pattern.layers.active(), 0);
+  assert_eq!(pattern.all_stitches(), vec![full(0.0, 0)]);
+  assert!(pattern.remove_layer(0).is_err());
+
+  pattern.restore_layer(0, layer, 0).unwrap();
+  assert_eq!(pattern.layers.len(), 2);
+  assert_eq!(pattern.all_stitches(), vec![half(0.0, 1)]);
+}
+
+#[test]
+fn activates_layer_below_removed_middle_one() {
+  let mut pattern = create_pattern();
+  pattern
+    .insert_layer(2, Layer::new(LayerProperties::new(String::from("Layer 3"))))
+    .unwrap();
+  pattern.set_active_layer(2).unwrap();
+  pattern.add_stitch(full(1.0, 2));
+  pattern.set_active_layer(1).unwrap();
+
+  pattern.remove_layer(1).unwrap();
+  assert_eq!(pattern.layers.active(), 0);
+  assert_eq!(pattern.all_stitches(), vec![full(0.0, 0)]);
+  assert_eq!(pattern.layers.info()[1].properties.name, "Layer 3");
+}
+
+#[test]
+fn applies_palette_changes_to_all_layers() {
+  let mut pattern = create_pattern();
+  assert!(pattern.is_palindex_used(0));
+
+  pattern.remap_palindexes(|palindex| palindex + 1);
+  assert_eq!(pattern.all_stitches(), vec![half(0.0, 2)]);
+  assert!(!pattern.is_palindex_used(0));
+
+  // The stitches of the inactive layers are removed too and returned separately.
+  let (stitches, _, layers) = pattern.remove_stitches_by_palindex(1);
+  assert!(stitches.is_empty());
+  assert!(!layers.is_empty());
+  assert_eq!(pattern.all_stitches(), vec![half(0.0, 1)]);
+
+  pattern.restore_stitches(&stitches, &[], &layers, 1);
+  assert_eq!(pattern.all_stitches(), vec![half(0.0, 2)]);
+  pattern.set_active_layer(0).unwrap();
+  assert_eq!(pattern.all_stitches(), vec![full(0.0, 1)]);
+
+  pattern.remove_stitches_by_palindex(1);
+  assert!(pattern.all_stitches().is_empty());
+}
+
+#[test]
+fn keeps_locked_layers_on_palette_changes() {
+  let mut pattern = create_pattern();
+  let mut properties = pattern.layers.info()[0].properties.clone();
+  properties.locked = true;
+  pattern.update_layer(0, properties).unwrap();
+
+  // The stitches of the locked layers are not replaced.
+  let (stitches, _, layers) = pattern.replace_palindex(0, 1, |_| true, |_| true);
+  assert!(stitches.is_empty());
+  assert!(layers.is_empty());
+  assert!(pattern.is_palindex_used(0));
+
+  // The palette item used by the locked layers can't be removed.
+  assert!(pattern.ensure_palindex_unlocked(0).is_err());
+  assert!(pattern.ensure_palindex_unlocked(1).is_ok());
+
+  // But the palette indices still follow the palette order.
+  pattern.remap_palindexes(|palindex| palindex + 1);
+  pattern.set_active_layer(0).unwrap();
+  assert_eq!(pattern.all_stitches(), vec![full(0.0, 1)]);
+}
+
+#[test]
+fn checks_active_layer_is_editable() {
+  let mut pattern = create_pattern();
+  assert!(pattern.layers.ensure_active_editable().is_ok());
+  let mut properties = pattern.layers.info()[1].properties.clone();
+  properties.locked = true;
+  pattern.update_layer(1, properties).unwrap();
+  assert!(pattern.layers.ensure_active_editable().is_err());
+  assert_eq!(pattern.layers.next_name(), "Layer 3");
+}
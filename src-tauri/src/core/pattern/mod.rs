@@ -5,8 +5,24 @@ pub use pattern::*;
 mod stitches;
 pub use stitches::*;
 
+mod layers;
+pub use layers::*;
+
+mod region;
+pub use region::*;
+
 pub mod display;
+pub mod draw;
+pub mod fill;
 pub mod print;
+pub mod progress;
+mod query;
+pub mod repeat;
+pub mod sessions;
+pub mod symbols;
+pub mod symmetry;
+pub mod text;
+mod transform;
 
 mod project;
 pub use project::*;
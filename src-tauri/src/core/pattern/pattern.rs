@@ -2,6 +2,7 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 
 use super::stitches::*;
+use super::{Layers, LayersDiff};
 
 #[derive(Debug, Default, Clone, BorshSerialize, BorshDeserialize)]
 pub struct Pattern {
@@ -15,6 +16,10 @@ pub struct Pattern {
   pub lines: Stitches<Line>,
   pub specialstitches: Stitches<SpecialStitch>,
   pub special_stitch_models: Vec<SpecialStitchModel>,
+  /// The layers of the pattern. The stitch collections above belong to the active layer.
+  /// The layers are stored separately, so the pattern layout stays the same for the frontend.
+  #[borsh(skip)]
+  pub layers: Layers,
 }
 
 impl Pattern {
@@ -150,9 +155,18 @@ impl Pattern {
     }
   }
 
-  /// Removes and returns all stitches with a given palette index from the pattern.
-  pub fn remove_stitches_by_palindex(&mut self, palindex: u8) -> Vec<Stitch> {
+  /// Removes all stitches with a given palette index from all the layers, including the special ones.
+  /// The stitches with the greater palette indices are shifted down, so the palette item can be removed.
+  /// Returns the removed stitches of the active layer and the ones of the inactive layers separately.
+  /// The locked layers must not use the palette index, see `ensure_palindex_unlocked`.
+  pub fn remove_stitches_by_palindex(&mut self, palindex: u8) -> (Vec<Stitch>, Vec<SpecialStitch>, LayersDiff) {
     log::trace!("Removing stitches by palette index");
+    let layers = self.change_inactive_layers(|layer, _| layer.remove_layer_stitches_by_palindex(palindex));
+    let (stitches, specialstitches) = self.remove_layer_stitches_by_palindex(palindex);
+    (stitches, specialstitches, layers)
+  }
+
+  fn remove_layer_stitches_by_palindex(&mut self, palindex: u8) -> (Vec<Stitch>, Vec<SpecialStitch>) {
     let mut conflicts = Vec::new();
     conflicts.extend(
       self
@@ -182,31 +196,258 @@ impl Pattern {
         .into_iter()
         .map(Stitch::Node),
     );
-    conflicts
+    let specialstitches = self.specialstitches.remove_stitches_by_palindex(palindex);
+    (conflicts, specialstitches)
+  }
+
+  /// Finds the palette items that no stitch uses and the ones that list the same thread.
+  pub fn analyze_palette(&self) -> PaletteAnalysis {
+    let unused = (0..self.palette.len())
+      .filter(|&palindex| !self.is_palindex_used(palindex as u8))
+      .collect();
+
+    let mut duplicates: Vec<Vec<usize>> = Vec::new();
+    for (palindex, palitem) in self.palette.iter().enumerate() {
+      match duplicates
+        .iter_mut()
+        .find(|group| self.palette[group[0]].is_same_thread(palitem))
+      {
+        Some(group) => group.push(palindex),
+        None => duplicates.push(vec![palindex]),
+      }
+    }
+    duplicates.retain(|group| group.len() > 1);
+
+    PaletteAnalysis { unused, duplicates }
   }
 
-  pub fn restore_stitches(&mut self, stitches: Vec<Stitch>, palindex: u8) {
-    let mut fullstitches = Vec::new();
-    let mut partstitches = Vec::new();
-    let mut lines = Vec::new();
-    let mut nodes = Vec::new();
-    for stitch in stitches.into_iter() {
+  /// Checks if any stitch of any layer, including the special ones, uses the palette item.
+  pub fn is_palindex_used(&self, palindex: u8) -> bool {
+    self.is_palindex_used_by_inactive_layers(palindex) || self.is_palindex_used_by_active_layer(palindex)
+  }
+
+  pub(super) fn is_palindex_used_by_active_layer(&self, palindex: u8) -> bool {
+    self.fullstitches.iter().any(|stitch| stitch.palindex == palindex)
+      || self.partstitches.iter().any(|stitch| stitch.palindex == palindex)
+      || self.lines.iter().any(|stitch| stitch.palindex == palindex)
+      || self.nodes.iter().any(|stitch| stitch.palindex == palindex)
+      || self.specialstitches.iter().any(|stitch| stitch.palindex == palindex)
+  }
+
+  /// Sets the new palette index to the stitches of all the unlocked layers that have the given one and match the predicates.
+  /// Returns the changed stitches of the active layer and the ones of the inactive layers as they were before the change.
+  pub fn replace_palindex<F, G>(
+    &mut self,
+    from: u8,
+    to: u8,
+    predicate: F,
+    special_predicate: G,
+  ) -> (Vec<Stitch>, Vec<SpecialStitch>, LayersDiff)
+  where
+    F: Fn(&Stitch) -> bool,
+    G: Fn(&SpecialStitch) -> bool,
+  {
+    let layers = self.change_inactive_layers(|layer, properties| {
+      if properties.locked {
+        return Default::default();
+      }
+      layer.replace_layer_palindex(from, to, &predicate, &special_predicate)
+    });
+    if self.layers.active_properties().locked {
+      return (Vec::new(), Vec::new(), layers);
+    }
+    let (stitches, specialstitches) = self.replace_layer_palindex(from, to, predicate, special_predicate);
+    (stitches, specialstitches, layers)
+  }
+
+  /// Sets the new palette index to the stitches of the active layer only.
+  fn replace_layer_palindex<F, G>(
+    &mut self,
+    from: u8,
+    to: u8,
+    predicate: F,
+    special_predicate: G,
+  ) -> (Vec<Stitch>, Vec<SpecialStitch>)
+  where
+    F: Fn(&Stitch) -> bool,
+    G: Fn(&SpecialStitch) -> bool,
+  {
+    let mut replaced = Vec::new();
+    replaced.extend(
+      self
+        .fullstitches
+        .replace_palindex(from, to, |&stitch| predicate(&stitch.into()))
+        .into_iter()
+        .map(Stitch::Full),
+    );
+    replaced.extend(
+      self
+        .partstitches
+        .replace_palindex(from, to, |&stitch| predicate(&stitch.into()))
+        .into_iter()
+        .map(Stitch::Part),
+    );
+    replaced.extend(
+      self
+        .lines
+        .replace_palindex(from, to, |&stitch| predicate(&stitch.into()))
+        .into_iter()
+        .map(Stitch::Line),
+    );
+    replaced.extend(
+      self
+        .nodes
+        .replace_palindex(from, to, |&stitch| predicate(&stitch.into()))
+        .into_iter()
+        .map(Stitch::Node),
+    );
+    let specialstitches = self.specialstitches.replace_palindex(from, to, special_predicate);
+    (replaced, specialstitches)
+  }
+
+  /// Returns the stitches that are currently placed at the positions of the given ones.
+  pub fn stitches_at(&self, stitches: &[Stitch]) -> Vec<Stitch> {
+    stitches
+      .iter()
+      .filter_map(|stitch| match stitch {
+        Stitch::Full(fullstitch) => self.fullstitches.get(fullstitch).copied().map(Stitch::Full),
+        Stitch::Part(partstitch) => self.partstitches.get(partstitch).copied().map(Stitch::Part),
+        Stitch::Line(line) => self.lines.get(line).copied().map(Stitch::Line),
+        Stitch::Node(node) => self.nodes.get(node).copied().map(Stitch::Node),
+      })
+      .collect()
+  }
+
+  /// Puts the stitches back, replacing the ones placed at the same positions.
+  /// Unlike `add_stitches`, it doesn't look for conflicts, so it is used to undo the changes of the existing stitches.
+  pub fn put_back_stitches(&mut self, stitches: &[Stitch], specialstitches: &[SpecialStitch]) {
+    for &stitch in stitches {
       match stitch {
-        Stitch::Full(fullstitch) => fullstitches.push(fullstitch),
-        Stitch::Part(partstitch) => partstitches.push(partstitch),
-        Stitch::Line(line) => lines.push(line),
-        Stitch::Node(node) => nodes.push(node),
+        Stitch::Full(fullstitch) => {
+          self.fullstitches.insert(fullstitch);
+        }
+        Stitch::Part(partstitch) => {
+          self.partstitches.insert(partstitch);
+        }
+        Stitch::Line(line) => {
+          self.lines.insert(line);
+        }
+        Stitch::Node(node) => {
+          self.nodes.insert(node);
+        }
       }
     }
+    for &specialstitch in specialstitches {
+      self.specialstitches.insert(specialstitch);
+    }
+  }
+
+  /// Changes the palette indices of all the stitches of all the layers, including the special ones, using the mapping function.
+  /// The locked layers are changed too, since their stitches must keep their colours when the palette is reordered.
+  pub fn remap_palindexes<F: Fn(u8) -> u8 + Copy>(&mut self, remap: F) {
+    self.with_inactive_layers(|layer| layer.remap_palindexes(remap));
+    self.fullstitches.remap_palindexes(remap);
+    self.partstitches.remap_palindexes(remap);
+    self.lines.remap_palindexes(remap);
+    self.nodes.remap_palindexes(remap);
+    self.specialstitches.remap_palindexes(remap);
+  }
+
+  /// Moves the palette item to the new position, shifting the items in between.
+  /// The palette indices of the stitches are updated accordingly.
+  pub fn move_palette_item(&mut self, from: usize, to: usize) {
+    let palitem = self.palette.remove(from);
+    self.palette.insert(to, palitem);
+    self.remap_palindexes(|palindex| moved_palindex(palindex, from as u8, to as u8));
+  }
+
+  /// Removes all the stitches of all the layers that don't fit the pattern of the given size.
+  /// The locked layers are cropped too, since the stitches can't lie outside of the pattern.
+  /// Returns the removed stitches of the active layer and the ones of the inactive layers separately.
+  /// The special stitches are returned separately since they are not the part of the `Stitch` enum.
+  pub fn remove_stitches_outside(&mut self, width: u16, height: u16) -> (Vec<Stitch>, Vec<SpecialStitch>, LayersDiff) {
+    log::trace!("Removing stitches outside of {width}x{height}");
+    let layers = self.change_inactive_layers(|layer, _| layer.remove_layer_stitches_outside(width, height));
+    let (stitches, specialstitches) = self.remove_layer_stitches_outside(width, height);
+    (stitches, specialstitches, layers)
+  }
+
+  fn remove_layer_stitches_outside(&mut self, width: u16, height: u16) -> (Vec<Stitch>, Vec<SpecialStitch>) {
+    let (width, height) = (f32::from(width), f32::from(height));
+    // The full and part stitches occupy the cells, while the lines and nodes are placed on the grid points.
+    let outside_cells = |x: Coord, y: Coord| x.into_inner() >= width || y.into_inner() >= height;
+    let outside_points = |x: Coord, y: Coord| x.into_inner() > width || y.into_inner() > height;
+
+    let mut removed = Vec::new();
+    removed.extend(
+      self
+        .fullstitches
+        .remove_where(|fullstitch| outside_cells(fullstitch.x, fullstitch.y))
+        .into_iter()
+        .map(Stitch::Full),
+    );
+    removed.extend(
+      self
+        .partstitches
+        .remove_where(|partstitch| outside_cells(partstitch.x, partstitch.y))
+        .into_iter()
+        .map(Stitch::Part),
+    );
+    removed.extend(
+      self
+        .lines
+        .remove_where(|line| outside_points(line.x.0, line.y.0) || outside_points(line.x.1, line.y.1))
+        .into_iter()
+        .map(Stitch::Line),
+    );
+    removed.extend(
+      self
+        .nodes
+        .remove_where(|node| outside_points(node.x, node.y))
+        .into_iter()
+        .map(Stitch::Node),
+    );
+    let specialstitches = self
+      .specialstitches
+      .remove_where(|specialstitch| outside_cells(specialstitch.x, specialstitch.y));
+    (removed, specialstitches)
+  }
+
+  /// Restores the stitches removed by `remove_stitches_by_palindex`, shifting the greater palette indices back up.
+  pub fn restore_stitches(
+    &mut self,
+    stitches: &[Stitch],
+    specialstitches: &[SpecialStitch],
+    layers: &LayersDiff,
+    palindex: u8,
+  ) {
+    self.remap_palindexes(|index| if index >= palindex { index + 1 } else { index });
+    self.put_back_stitches(stitches, specialstitches);
+    self.put_back_layers_stitches(layers);
+  }
+}
 
-    self.fullstitches.restore_stitches(fullstitches, palindex);
-    self.partstitches.restore_stitches(partstitches, palindex);
-    self.lines.restore_stitches(lines, palindex);
-    self.nodes.restore_stitches(nodes, palindex);
+/// Returns the new palette index of the palette item after another one is moved from one position to another.
+pub fn moved_palindex(palindex: u8, from: u8, to: u8) -> u8 {
+  match palindex {
+    _ if palindex == from => to,
+    _ if from < to && (from..=to).contains(&palindex) => palindex - 1,
+    _ if to < from && (to..=from).contains(&palindex) => palindex + 1,
+    _ => palindex,
   }
 }
 
-#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+/// The problems of the palette that can be cleaned up.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PaletteAnalysis {
+  /// The indices of the palette items that no stitch uses.
+  pub unused: Vec<usize>,
+  /// The groups of indices of the palette items with the same brand and number.
+  /// The first index of each group is the one that is kept when merging.
+  pub duplicates: Vec<Vec<usize>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct PatternProperties {
   pub width: u16,
   pub height: u16,
@@ -218,7 +459,7 @@ impl Default for PatternProperties {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct PatternInfo {
   pub title: String,
   pub author: String,
@@ -250,6 +491,23 @@ pub struct PaletteItem {
   pub strands: Option<PaletteItemStitchStrands>,
 }
 
+impl PaletteItem {
+  /// Checks whether the palette items list the same thread, i.e. they have the same brand and number.
+  /// The items without a number (e.g. the blends and custom colours) have no identity,
+  /// so they are the same only when their colours and blends are equal too.
+  pub fn is_same_thread(&self, other: &PaletteItem) -> bool {
+    let same_identity = self.brand.trim().eq_ignore_ascii_case(other.brand.trim())
+      && self.number.trim().eq_ignore_ascii_case(other.number.trim());
+    if !same_identity {
+      return false;
+    }
+    if self.number.trim().is_empty() {
+      return self.color.eq_ignore_ascii_case(&other.color) && self.blends == other.blends;
+    }
+    true
+  }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct Blend {
   pub brand: String,
@@ -305,7 +563,7 @@ impl Default for DefaultStitchStrands {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct Fabric {
   pub spi: StitchesPerInch,
   pub kind: String,
@@ -1,6 +1,7 @@
 use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct PrintSettings {
   pub font: Font,
   pub header: String,
@@ -25,7 +26,7 @@ impl Default for PrintSettings {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct Font {
   pub name: String,
   pub size: u16,
@@ -46,11 +47,11 @@ impl Default for Font {
 
 #[nutype::nutype(
   sanitize(with = |raw| raw.clamp(100, 900)),
-  derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)
+  derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)
 )]
 pub struct FontWeight(u16);
 
-#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub struct PageMargins {
   pub left: f32,
   pub right: f32,
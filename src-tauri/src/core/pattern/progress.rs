@@ -0,0 +1,491 @@
+//! The stitching progress, i.e. which stitches of the pattern have already been stitched.
+//!
+//! The stitches of all the layers are tracked, merged from the bottom to the top one, so the progress doesn't depend on
+//! the active layer or the visibility of the layers.
+//! The completed stitches are identified by all their properties, including the palette indices,
+//! so the marks of the removed or replaced stitches don't apply to the stitches placed at their positions later.
+//! The marks of the removed stitches are ignored while the stitches are missing, so they come back with them on undo,
+//! and dropped when the pattern is loaded next time.
+//! The marks follow the palette changes, so the progress survives reordering and merging the palette items.
+
+use std::collections::BTreeMap;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use super::{FullStitch, Line, Node, PaletteIndex, PartStitch, Pattern, Region, SpecialStitch, Stitch};
+
+#[cfg(test)]
+#[path = "progress.test.rs"]
+mod tests;
+
+/// Returns the current time in seconds since the Unix epoch.
+pub fn timestamp() -> u64 {
+  std::time::SystemTime::now()
+    .duration_since(std::time::UNIX_EPOCH)
+    .map(|duration| duration.as_secs())
+    .unwrap_or_default()
+}
+
+/// A stitch whose progress is tracked: a regular or a special one.
+///
+/// It is serialized like the regular stitch, e.g. `{ "full": { ... } }`, or as `{ "special": { ... } }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProgressStitch {
+  #[serde(rename = "special")]
+  Special(SpecialStitch),
+  #[serde(untagged)]
+  Stitch(Stitch),
+}
+
+impl ProgressStitch {
+  pub fn palindex(&self) -> u8 {
+    match self {
+      Self::Stitch(stitch) => stitch.palindex(),
+      Self::Special(specialstitch) => specialstitch.palindex,
+    }
+  }
+
+  fn with_palindex(mut self, palindex: u8) -> Self {
+    match &mut self {
+      Self::Stitch(stitch) => stitch.set_palindex(palindex),
+      Self::Special(specialstitch) => specialstitch.palindex = palindex,
+    }
+    self
+  }
+}
+
+impl From<Stitch> for ProgressStitch {
+  fn from(stitch: Stitch) -> Self {
+    Self::Stitch(stitch)
+  }
+}
+
+impl From<SpecialStitch> for ProgressStitch {
+  fn from(specialstitch: SpecialStitch) -> Self {
+    Self::Special(specialstitch)
+  }
+}
+
+/// The completion times of the stitches of one type.
+/// The stitches are ordered by their positions only, so the marks of the stitches at the same position are kept
+/// together and told apart by comparing all the stitch properties.
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+struct Marks<T: Ord>(BTreeMap<T, Vec<(T, u64)>>);
+
+impl<T: Ord> Default for Marks<T> {
+  fn default() -> Self {
+    Self(BTreeMap::new())
+  }
+}
+
+impl<T: Ord + Copy + PaletteIndex> Marks<T> {
+  fn get(&self, stitch: &T) -> Option<u64> {
+    self
+      .0
+      .get(stitch)?
+      .iter()
+      .find(|(marked, _)| marked == stitch)
+      .map(|&(_, completed_at)| completed_at)
+  }
+
+  fn set(&mut self, stitch: T, completed_at: Option<u64>) -> Option<u64> {
+    let marks = self.0.entry(stitch).or_default();
+    let previous = marks
+      .iter()
+      .position(|(marked, _)| *marked == stitch)
+      .map(|index| marks.swap_remove(index).1);
+    if let Some(completed_at) = completed_at {
+      marks.push((stitch, completed_at));
+    }
+    if marks.is_empty() {
+      self.0.remove(&stitch);
+    }
+    previous
+  }
+
+  fn iter(&self) -> impl Iterator<Item = (T, u64)> + '_ {
+    self.0.values().flatten().copied()
+  }
+
+  /// Removes the marks that don't match the predicate and returns them.
+  fn remove_where<F: Fn(&T) -> bool>(&mut self, predicate: F) -> Vec<(T, u64)> {
+    let (removed, kept): (Vec<_>, Vec<_>) = self.iter().partition(|(stitch, _)| predicate(stitch));
+    *self = Self::default();
+    for (stitch, completed_at) in kept {
+      self.set(stitch, Some(completed_at));
+    }
+    removed
+  }
+
+  fn remap_palindexes<F: Fn(u8) -> u8>(&mut self, remap: F) {
+    let marks: Vec<_> = self.iter().collect();
+    *self = Self::default();
+    for (mut stitch, completed_at) in marks {
+      stitch.set_palindex(remap(stitch.palindex()));
+      self.set(stitch, Some(completed_at));
+    }
+  }
+}
+
+/// The completed stitches with the times they were completed at, in seconds since the Unix epoch.
+#[derive(Debug, Default, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Progress {
+  fullstitches: Marks<FullStitch>,
+  partstitches: Marks<PartStitch>,
+  nodes: Marks<Node>,
+  lines: Marks<Line>,
+  specialstitches: Marks<SpecialStitch>,
+}
+
+impl Progress {
+  /// Returns the time the stitch was completed at or `None` if it is not completed.
+  pub fn completed_at(&self, stitch: &ProgressStitch) -> Option<u64> {
+    match stitch {
+      ProgressStitch::Stitch(Stitch::Full(fullstitch)) => self.fullstitches.get(fullstitch),
+      ProgressStitch::Stitch(Stitch::Part(partstitch)) => self.partstitches.get(partstitch),
+      ProgressStitch::Stitch(Stitch::Node(node)) => self.nodes.get(node),
+      ProgressStitch::Stitch(Stitch::Line(line)) => self.lines.get(line),
+      ProgressStitch::Special(specialstitch) => self.specialstitches.get(specialstitch),
+    }
+  }
+
+  /// Marks the stitch as completed at the given time or as not completed.
+  /// Returns the previous completion time.
+  pub fn set_completed_at(&mut self, stitch: ProgressStitch, completed_at: Option<u64>) -> Option<u64> {
+    match stitch {
+      ProgressStitch::Stitch(Stitch::Full(fullstitch)) => self.fullstitches.set(fullstitch, completed_at),
+      ProgressStitch::Stitch(Stitch::Part(partstitch)) => self.partstitches.set(partstitch, completed_at),
+      ProgressStitch::Stitch(Stitch::Node(node)) => self.nodes.set(node, completed_at),
+      ProgressStitch::Stitch(Stitch::Line(line)) => self.lines.set(line, completed_at),
+      ProgressStitch::Special(specialstitch) => self.specialstitches.set(specialstitch, completed_at),
+    }
+  }
+
+  /// Returns all the marks, including the ones of the stitches missing in the pattern.
+  fn marks(&self) -> Vec<ProgressEntry> {
+    let stitches = self
+      .fullstitches
+      .iter()
+      .map(|(stitch, time)| (Stitch::Full(stitch), time))
+      .chain(
+        self
+          .partstitches
+          .iter()
+          .map(|(stitch, time)| (Stitch::Part(stitch), time)),
+      )
+      .chain(self.lines.iter().map(|(stitch, time)| (Stitch::Line(stitch), time)))
+      .chain(self.nodes.iter().map(|(stitch, time)| (Stitch::Node(stitch), time)))
+      .map(|(stitch, time)| ProgressEntry::new(stitch.into(), Some(time)));
+    let specialstitches = self
+      .specialstitches
+      .iter()
+      .map(|(specialstitch, time)| ProgressEntry::new(specialstitch.into(), Some(time)));
+    stitches.chain(specialstitches).collect()
+  }
+
+  /// Marks the stitches as they are in the entries.
+  pub fn restore(&mut self, entries: &[ProgressEntry]) {
+    for entry in entries {
+      self.set_completed_at(entry.stitch, entry.completed_at);
+    }
+  }
+
+  /// Removes the marks of the stitches with the palette indices matching the predicate, e.g. the removed ones.
+  /// Returns the removed marks, so they can be restored by `restore`.
+  pub fn remove_palindexes<F: Fn(u8) -> bool + Copy>(&mut self, predicate: F) -> Vec<ProgressEntry> {
+    let mut removed = Vec::new();
+    let entry = |stitch: Stitch, time| ProgressEntry::new(stitch.into(), Some(time));
+    removed.extend(
+      self
+        .fullstitches
+        .remove_where(|stitch| predicate(stitch.palindex))
+        .into_iter()
+        .map(|(stitch, time)| entry(Stitch::Full(stitch), time)),
+    );
+    removed.extend(
+      self
+        .partstitches
+        .remove_where(|stitch| predicate(stitch.palindex))
+        .into_iter()
+        .map(|(stitch, time)| entry(Stitch::Part(stitch), time)),
+    );
+    removed.extend(
+      self
+        .lines
+        .remove_where(|stitch| predicate(stitch.palindex))
+        .into_iter()
+        .map(|(stitch, time)| entry(Stitch::Line(stitch), time)),
+    );
+    removed.extend(
+      self
+        .nodes
+        .remove_where(|stitch| predicate(stitch.palindex))
+        .into_iter()
+        .map(|(stitch, time)| entry(Stitch::Node(stitch), time)),
+    );
+    removed.extend(
+      self
+        .specialstitches
+        .remove_where(|stitch| predicate(stitch.palindex))
+        .into_iter()
+        .map(|(stitch, time)| ProgressEntry::new(stitch.into(), Some(time))),
+    );
+    removed
+  }
+
+  /// Changes the palette indices of the marked stitches using the mapping function, like the stitches of the pattern.
+  pub fn remap_palindexes<F: Fn(u8) -> u8 + Copy>(&mut self, remap: F) {
+    self.fullstitches.remap_palindexes(remap);
+    self.partstitches.remap_palindexes(remap);
+    self.lines.remap_palindexes(remap);
+    self.nodes.remap_palindexes(remap);
+    self.specialstitches.remap_palindexes(remap);
+  }
+
+  /// Moves the marks of the stitches to their copies with the new palette index, when their colour is replaced.
+  /// Returns the stitches whose marks were moved, as they were before, so the marks can be moved back by `unrecolor`.
+  pub fn recolor<I: IntoIterator<Item = ProgressStitch>>(&mut self, stitches: I, palindex: u8) -> Vec<ProgressEntry> {
+    let mut moved = Vec::new();
+    for stitch in stitches {
+      if let Some(completed_at) = self.set_completed_at(stitch, None) {
+        self.set_completed_at(stitch.with_palindex(palindex), Some(completed_at));
+        moved.push(ProgressEntry::new(stitch, Some(completed_at)));
+      }
+    }
+    moved
+  }
+
+  /// Moves the marks moved by `recolor` back.
+  pub fn unrecolor(&mut self, moved: &[ProgressEntry], palindex: u8) {
+    for entry in moved {
+      self.set_completed_at(entry.stitch.with_palindex(palindex), None);
+      self.set_completed_at(entry.stitch, entry.completed_at);
+    }
+  }
+
+  /// Removes the marks of the stitches that no layer of the pattern has.
+  pub fn prune(&mut self, pattern: &Pattern) {
+    for entry in self.marks() {
+      if !pattern.has_stitch_in_any_layer(&entry.stitch) {
+        self.set_completed_at(entry.stitch, None);
+      }
+    }
+  }
+
+  /// Returns the stitches of all the layers of the pattern, including the special ones, to track.
+  /// The layers are merged, so the stitches covered by the conflicting ones of the upper layers are not tracked.
+  fn tracked_stitches(pattern: &Pattern) -> Vec<ProgressStitch> {
+    let pattern = pattern.merged();
+    let specialstitches = pattern
+      .specialstitches
+      .iter()
+      .map(|&specialstitch| specialstitch.into());
+    pattern
+      .all_stitches()
+      .into_iter()
+      .map(ProgressStitch::from)
+      .chain(specialstitches)
+      .collect()
+  }
+
+  /// Returns the completed stitches of the pattern.
+  /// The completed stitches that were removed from the pattern are skipped.
+  pub fn entries(&self, pattern: &Pattern) -> Vec<ProgressEntry> {
+    Self::tracked_stitches(pattern)
+      .into_iter()
+      .filter_map(|stitch| {
+        self
+          .completed_at(&stitch)
+          .map(|completed_at| ProgressEntry::new(stitch, Some(completed_at)))
+      })
+      .collect()
+  }
+
+  /// Calculates the completion of the whole pattern.
+  pub fn completion(&self, pattern: &Pattern) -> Completion {
+    let mut completion = Completion::default();
+    for stitch in Self::tracked_stitches(pattern) {
+      completion.count(self.completed_at(&stitch).is_some());
+    }
+    completion
+  }
+
+  /// Calculates the completion of the whole pattern, each palette item and each page.
+  /// The pages are the parts of the pattern of the given size in cells, counted row by row from the top-left corner.
+  pub fn stats(&self, pattern: &Pattern, page_width: u16, page_height: u16) -> ProgressStats {
+    let (width, height) = (pattern.properties.width, pattern.properties.height);
+    let (page_width, page_height) = (page_width.max(1), page_height.max(1));
+    let columns = width.div_ceil(page_width).max(1);
+    let rows = height.div_ceil(page_height).max(1);
+
+    let mut stats = ProgressStats {
+      overall: Completion::default(),
+      colors: (0..pattern.palette.len())
+        .map(|palindex| ColorProgress {
+          palindex,
+          completion: Completion::default(),
+        })
+        .collect(),
+      pages: (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| (row, column)))
+        .map(|(row, column)| {
+          let (x, y) = (column * page_width, row * page_height);
+          PageProgress {
+            region: Region {
+              x,
+              y,
+              width: page_width.min(width.saturating_sub(x)),
+              height: page_height.min(height.saturating_sub(y)),
+            },
+            completion: Completion::default(),
+          }
+        })
+        .collect(),
+    };
+
+    for stitch in Self::tracked_stitches(pattern) {
+      let completed = self.completed_at(&stitch).is_some();
+      stats.overall.count(completed);
+      if let Some(color) = stats.colors.get_mut(stitch.palindex() as usize) {
+        color.completion.count(completed);
+      }
+      let (x, y) = stitch_cell(&stitch, width, height);
+      let page = usize::from(y / page_height) * usize::from(columns) + usize::from(x / page_width);
+      if let Some(page) = stats.pages.get_mut(page) {
+        page.completion.count(completed);
+      }
+    }
+    stats
+  }
+}
+
+/// Returns the cell the stitch belongs to.
+/// The lines and nodes, which are placed on the grid points, belong to the cell to the bottom-right of their top-left point.
+fn stitch_cell(stitch: &ProgressStitch, width: u16, height: u16) -> (u16, u16) {
+  let (x, y) = match stitch {
+    ProgressStitch::Stitch(Stitch::Full(fullstitch)) => (fullstitch.x, fullstitch.y),
+    ProgressStitch::Stitch(Stitch::Part(partstitch)) => (partstitch.x, partstitch.y),
+    ProgressStitch::Stitch(Stitch::Node(node)) => (node.x, node.y),
+    ProgressStitch::Stitch(Stitch::Line(line)) => (line.x.0.min(line.x.1), line.y.0.min(line.y.1)),
+    ProgressStitch::Special(specialstitch) => (specialstitch.x, specialstitch.y),
+  };
+  let clamp = |coord: f32, size: u16| (coord.floor().max(0.0) as u16).min(size.saturating_sub(1));
+  (clamp(x.into_inner(), width), clamp(y.into_inner(), height))
+}
+
+/// The completion state of a stitch.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(test, derive(Deserialize))]
+pub struct ProgressEntry {
+  pub stitch: ProgressStitch,
+  /// The time the stitch was completed at, in seconds since the Unix epoch, or `None` if it is not completed.
+  #[serde(rename = "completedAt")]
+  pub completed_at: Option<u64>,
+}
+
+impl ProgressEntry {
+  pub fn new(stitch: ProgressStitch, completed_at: Option<u64>) -> Self {
+    Self { stitch, completed_at }
+  }
+}
+
+/// The stitches to mark.
+///
+/// It is serialized as `{ "target": "color", "palindex": 2 }`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "target", rename_all = "snake_case")]
+pub enum ProgressTarget {
+  Stitches {
+    stitches: Vec<ProgressStitch>,
+  },
+  /// All the stitches inside the region, including the special ones. A single cell is the region of one cell.
+  Region {
+    region: Region,
+  },
+  /// All the stitches of the palette item, including the special ones.
+  Color {
+    palindex: u8,
+  },
+}
+
+impl ProgressTarget {
+  /// Returns the stitches of all the layers of the pattern the target refers to.
+  pub fn stitches(&self, pattern: &Pattern) -> Vec<ProgressStitch> {
+    match self {
+      Self::Stitches { stitches } => {
+        let pattern = pattern.merged();
+        stitches
+          .iter()
+          .filter_map(|stitch| match stitch {
+            ProgressStitch::Stitch(stitch) => pattern.stitches_at(&[*stitch]).pop().map(ProgressStitch::from),
+            ProgressStitch::Special(specialstitch) => pattern
+              .specialstitches
+              .get(specialstitch)
+              .map(|&specialstitch| specialstitch.into()),
+          })
+          .collect()
+      }
+      Self::Region { region } => {
+        // Only the part of the pattern around the region is merged to resolve it.
+        let pattern = pattern.merged_around(&region.bounds());
+        let specialstitches = pattern
+          .specialstitches
+          .intersecting(&region.bounds())
+          .filter(|specialstitch| region.contains_cell(specialstitch.x, specialstitch.y))
+          .map(|&specialstitch| specialstitch.into());
+        pattern
+          .stitches_in(region)
+          .into_iter()
+          .map(ProgressStitch::from)
+          .chain(specialstitches)
+          .collect()
+      }
+      Self::Color { palindex } => Progress::tracked_stitches(pattern)
+        .into_iter()
+        .filter(|stitch| stitch.palindex() == *palindex)
+        .collect(),
+    }
+  }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(test, derive(Deserialize))]
+pub struct Completion {
+  pub total: usize,
+  pub completed: usize,
+}
+
+impl Completion {
+  fn count(&mut self, completed: bool) {
+    self.total += 1;
+    if completed {
+      self.completed += 1;
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(test, derive(Deserialize))]
+pub struct ColorProgress {
+  pub palindex: usize,
+  #[serde(flatten)]
+  pub completion: Completion,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(test, derive(Deserialize))]
+pub struct PageProgress {
+  pub region: Region,
+  #[serde(flatten)]
+  pub completion: Completion,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(test, derive(Deserialize))]
+pub struct ProgressStats {
+  #[serde(flatten)]
+  pub overall: Completion,
+  pub colors: Vec<ColorProgress>,
+  pub pages: Vec<PageProgress>,
+}
@@ -0,0 +1,255 @@
+use ordered_float::NotNan;
+
+use super::*;
+use crate::core::pattern::layers::{Layer, LayerProperties};
+use crate::core::pattern::{Degree, FullStitchKind, LineKind, PaletteItem, PatternProperties};
+
+fn coord(value: f32) -> NotNan<f32> {
+  NotNan::new(value).unwrap()
+}
+
+fn full(x: f32, y: f32, palindex: u8) -> Stitch {
+  Stitch::Full(FullStitch {
+    x: coord(x),
+    y: coord(y),
+    palindex,
+    kind: FullStitchKind::Full,
+  })
+}
+
+fn line(x: (f32, f32), y: (f32, f32), palindex: u8) -> Stitch {
+  Stitch::Line(Line {
+    x: (coord(x.0), coord(x.1)),
+    y: (coord(y.0), coord(y.1)),
+    palindex,
+    kind: LineKind::Back,
+  })
+}
+
+fn palitem(number: &str) -> PaletteItem {
+  PaletteItem {
+    brand: String::from("DMC"),
+    number: String::from(number),
+    name: String::new(),
+    color: String::from("FFFFFF"),
+    blends: None,
+    bead: None,
+    strands: None,
+  }
+}
+
+fn create_pattern() -> Pattern {
+  let mut pattern = Pattern {
+    properties: PatternProperties { width: 4, height: 2 },
+    palette: vec![palitem("310"), palitem("444")],
+    ..Pattern::default()
+  };
+  pattern.add_stitches(vec![
+    full(0.0, 0.0, 0),
+    full(1.0, 0.0, 0),
+    full(3.0, 1.0, 1),
+    line((3.0, 4.0), (2.0, 1.0), 1),
+  ]);
+  pattern
+}
+
+fn special(x: f32, y: f32, palindex: u8) -> SpecialStitch {
+  SpecialStitch {
+    x: coord(x),
+    y: coord(y),
+    rotation: Degree::new(0),
+    flip: (false, false),
+    palindex,
+    modindex: 0,
+  }
+}
+
+#[test]
+fn tracks_exact_stitches() {
+  let mut progress = Progress::default();
+  assert_eq!(progress.set_completed_at(full(0.0, 0.0, 0).into(), Some(10)), None);
+  // The stitch of another colour at the same position is another stitch.
+  assert_eq!(progress.completed_at(&full(0.0, 0.0, 1).into()), None);
+  assert_eq!(progress.set_completed_at(full(0.0, 0.0, 1).into(), Some(20)), None);
+  assert_eq!(progress.completed_at(&full(0.0, 0.0, 0).into()), Some(10));
+
+  assert_eq!(progress.set_completed_at(full(0.0, 0.0, 0).into(), None), Some(10));
+  assert_eq!(progress.completed_at(&full(0.0, 0.0, 0).into()), None);
+  assert_eq!(progress.completed_at(&full(0.0, 0.0, 1).into()), Some(20));
+}
+
+#[test]
+fn ignores_marks_of_replaced_stitches() {
+  let mut pattern = create_pattern();
+  let mut progress = Progress::default();
+  progress.set_completed_at(full(0.0, 0.0, 0).into(), Some(10));
+
+  // The stitch is replaced with the one of another colour, e.g. by the flood fill.
+  pattern.add_stitch(full(0.0, 0.0, 1));
+  assert!(progress.entries(&pattern).is_empty());
+  assert_eq!(progress.completion(&pattern).completed, 0);
+
+  // The mark comes back with the stitch on undo.
+  pattern.add_stitch(full(0.0, 0.0, 0));
+  assert_eq!(progress.entries(&pattern).len(), 1);
+
+  // The marks of the missing stitches are dropped when the progress is pruned.
+  pattern.remove_stitch(full(0.0, 0.0, 0));
+  progress.prune(&pattern);
+  pattern.add_stitch(full(0.0, 0.0, 0));
+  assert!(progress.entries(&pattern).is_empty());
+}
+
+#[test]
+fn follows_palette_changes() {
+  let mut progress = Progress::default();
+  progress.set_completed_at(full(0.0, 0.0, 0).into(), Some(10));
+  progress.set_completed_at(full(1.0, 0.0, 1).into(), Some(20));
+
+  progress.remap_palindexes(|palindex| 1 - palindex);
+  assert_eq!(progress.completed_at(&full(0.0, 0.0, 1).into()), Some(10));
+  assert_eq!(progress.completed_at(&full(1.0, 0.0, 0).into()), Some(20));
+
+  let moved = progress.recolor([full(0.0, 0.0, 1).into()], 0);
+  assert_eq!(progress.completed_at(&full(0.0, 0.0, 0).into()), Some(10));
+  progress.unrecolor(&moved, 0);
+  assert_eq!(progress.completed_at(&full(0.0, 0.0, 0).into()), None);
+  assert_eq!(progress.completed_at(&full(0.0, 0.0, 1).into()), Some(10));
+
+  let removed = progress.remove_palindexes(|palindex| palindex == 1);
+  assert_eq!(removed, vec![ProgressEntry::new(full(0.0, 0.0, 1).into(), Some(10))]);
+  assert_eq!(progress.completed_at(&full(0.0, 0.0, 1).into()), None);
+  progress.restore(&removed);
+  assert_eq!(progress.completed_at(&full(0.0, 0.0, 1).into()), Some(10));
+}
+
+#[test]
+fn tracks_special_stitches() {
+  let mut pattern = create_pattern();
+  pattern.specialstitches.insert(special(2.0, 1.0, 1));
+  let mut progress = Progress::default();
+  progress.set_completed_at(special(2.0, 1.0, 1).into(), Some(30));
+
+  assert_eq!(
+    progress.entries(&pattern),
+    vec![ProgressEntry::new(special(2.0, 1.0, 1).into(), Some(30))]
+  );
+  let stats = progress.stats(&pattern, 4, 2);
+  assert_eq!(stats.overall, Completion { total: 5, completed: 1 });
+  assert_eq!(stats.colors[1].completion, Completion { total: 3, completed: 1 });
+
+  let region = Region { x: 2, y: 1, width: 1, height: 1 };
+  assert_eq!(
+    ProgressTarget::Region { region }.stitches(&pattern),
+    vec![ProgressStitch::from(special(2.0, 1.0, 1))]
+  );
+  assert_eq!(ProgressTarget::Color { palindex: 1 }.stitches(&pattern).len(), 3);
+}
+
+#[test]
+fn serializes_progress_stitches() {
+  let stitch = ProgressStitch::from(full(0.0, 0.0, 0));
+  let json = serde_json::to_string(&stitch).unwrap();
+  assert!(json.starts_with(r#"{"full":"#));
+  assert_eq!(serde_json::from_str::<ProgressStitch>(&json).unwrap(), stitch);
+
+  let specialstitch = ProgressStitch::from(special(1.0, 1.0, 0));
+  let json = serde_json::to_string(&specialstitch).unwrap();
+  assert!(json.starts_with(r#"{"special":"#));
+  assert_eq!(serde_json::from_str::<ProgressStitch>(&json).unwrap(), specialstitch);
+}
+
+#[test]
+fn returns_entries_of_existing_stitches() {
+  let pattern = create_pattern();
+  let mut progress = Progress::default();
+  progress.set_completed_at(full(1.0, 0.0, 0).into(), Some(10));
+  progress.set_completed_at(full(2.0, 0.0, 0).into(), Some(20));
+  assert_eq!(
+    progress.entries(&pattern),
+    vec![ProgressEntry::new(full(1.0, 0.0, 0).into(), Some(10))]
+  );
+}
+
+#[test]
+fn resolves_targets() {
+  let pattern = create_pattern();
+  let region = Region { x: 0, y: 0, width: 1, height: 1 };
+  let stitches = |stitches: Vec<Stitch>| stitches.into_iter().map(ProgressStitch::from).collect::<Vec<_>>();
+  assert_eq!(
+    ProgressTarget::Region { region }.stitches(&pattern),
+    stitches(vec![full(0.0, 0.0, 0)])
+  );
+  assert_eq!(
+    ProgressTarget::Color { palindex: 1 }.stitches(&pattern),
+    stitches(vec![full(3.0, 1.0, 1), line((3.0, 4.0), (2.0, 1.0), 1)])
+  );
+  assert_eq!(
+    ProgressTarget::Stitches {
+      stitches: stitches(vec![full(1.0, 0.0, 1), full(2.0, 0.0, 0)])
+    }
+    .stitches(&pattern),
+    stitches(vec![full(1.0, 0.0, 0)])
+  );
+}
+
+#[test]
+fn calculates_stats() {
+  let pattern = create_pattern();
+  let mut progress = Progress::default();
+  progress.set_completed_at(full(0.0, 0.0, 0).into(), Some(10));
+  progress.set_completed_at(line((3.0, 4.0), (2.0, 1.0), 1).into(), Some(20));
+
+  let stats = progress.stats(&pattern, 3, 2);
+  assert_eq!(stats.overall, Completion { total: 4, completed: 2 });
+  assert_eq!(stats.colors[0].completion, Completion { total: 2, completed: 1 });
+  assert_eq!(stats.colors[1].completion, Completion { total: 2, completed: 1 });
+
+  assert_eq!(stats.pages.len(), 2);
+  assert_eq!(stats.pages[1].region, Region { x: 3, y: 0, width: 1, height: 2 });
+  assert_eq!(stats.pages[0].completion, Completion { total: 2, completed: 1 });
+  // The line on the right edge of the pattern belongs to the last column.
+  assert_eq!(stats.pages[1].completion, Completion { total: 2, completed: 1 });
+}
+
+#[test]
+fn tracks_stitches_of_all_layers() {
+  let mut pattern = create_pattern();
+  pattern
+    .insert_layer(1, Layer::new(LayerProperties::new(String::from("Layer 2"))))
+    .unwrap();
+  pattern.set_active_layer(1).unwrap();
+  pattern.add_stitches(vec![full(2.0, 0.0, 1), full(0.0, 0.0, 1)]);
+
+  let mut progress = Progress::default();
+  progress.set_completed_at(full(1.0, 0.0, 0).into(), Some(10));
+  progress.set_completed_at(full(2.0, 0.0, 1).into(), Some(20));
+
+  // The full stitch on the bottom layer is covered by the one on the top layer.
+  let expected = Completion { total: 5, completed: 2 };
+  assert_eq!(progress.completion(&pattern), expected);
+  let stats = progress.stats(&pattern, 2, 2);
+  assert_eq!(stats.overall, expected);
+  assert_eq!(stats.colors[0].completion, Completion { total: 1, completed: 1 });
+  assert_eq!(stats.colors[1].completion, Completion { total: 4, completed: 1 });
+  assert_eq!(stats.pages[0].completion, Completion { total: 2, completed: 1 });
+  assert_eq!(stats.pages[1].completion, Completion { total: 3, completed: 1 });
+  assert_eq!(progress.entries(&pattern).len(), 2);
+  assert_eq!(
+    ProgressTarget::Color { palindex: 0 }.stitches(&pattern),
+    vec![full(1.0, 0.0, 0).into()]
+  );
+
+  // The numbers don't depend on the active layer and the visibility of the layers.
+  pattern.set_active_layer(0).unwrap();
+  let mut properties = pattern.layers.info()[1].properties.clone();
+  properties.visible = false;
+  pattern.update_layer(1, properties).unwrap();
+  assert_eq!(progress.completion(&pattern), expected);
+  assert_eq!(progress.stats(&pattern, 2, 2), stats);
+  let region = Region { x: 2, y: 0, width: 1, height: 1 };
+  assert_eq!(
+    ProgressTarget::Region { region }.stitches(&pattern),
+    vec![full(2.0, 0.0, 1).into()]
+  );
+}
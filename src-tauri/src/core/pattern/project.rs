@@ -2,6 +2,8 @@ use borsh::{BorshDeserialize, BorshSerialize};
 
 use super::display::DisplaySettings;
 use super::print::PrintSettings;
+use super::progress::Progress;
+use super::sessions::SessionLog;
 use super::Pattern;
 
 #[derive(Debug, Default, Clone, BorshSerialize, BorshDeserialize)]
@@ -11,4 +13,10 @@ pub struct PatternProject {
   pub pattern: Pattern,
   pub display_settings: DisplaySettings,
   pub print_settings: PrintSettings,
+  /// The stitching progress. It is stored separately, so the project layout stays the same for the frontend.
+  #[borsh(skip)]
+  pub progress: Progress,
+  /// The log of the stitching sessions. It is stored separately like the progress.
+  #[borsh(skip)]
+  pub sessions: SessionLog,
 }
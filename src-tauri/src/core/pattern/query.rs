@@ -0,0 +1,93 @@
+//! The area queries of the stitches, e.g. to fetch the visible part of the pattern, select or hit-test the stitches.
+//!
+//! They use the spatial indexes of the stitch sets, so they don't scan the whole pattern.
+
+use super::{Bounds, Line, Pattern, Region, Stitch};
+
+#[cfg(test)]
+#[path = "query.test.rs"]
+mod tests;
+
+/// The largest distance in cells within which the lines and nodes are hit.
+/// It keeps the hit area small, however large the requested tolerance is.
+pub const MAX_HIT_TOLERANCE: f32 = 2.0;
+
+impl Pattern {
+  /// Returns the stitches whose bounding boxes intersect or touch the area.
+  /// The stitches are ordered like in `all_stitches`.
+  pub fn stitches_intersecting(&self, area: &Bounds) -> Vec<Stitch> {
+    let fullstitches = self
+      .fullstitches
+      .intersecting(area)
+      .map(|&fullstitch| Stitch::Full(fullstitch));
+    let partstitches = self
+      .partstitches
+      .intersecting(area)
+      .map(|&partstitch| Stitch::Part(partstitch));
+    let lines = self.lines.intersecting(area).map(|&line| Stitch::Line(line));
+    let nodes = self.nodes.intersecting(area).map(|&node| Stitch::Node(node));
+    fullstitches.chain(partstitches).chain(lines).chain(nodes).collect()
+  }
+
+  /// Returns the stitches lying inside the region.
+  pub fn stitches_in(&self, region: &Region) -> Vec<Stitch> {
+    self
+      .stitches_intersecting(&region.bounds())
+      .into_iter()
+      .filter(|stitch| region.contains_stitch(stitch))
+      .collect()
+  }
+
+  /// Returns the stitches under the point, from the topmost to the bottommost one.
+  /// The nodes and lines are hit within the tolerance, while the full and part stitches are hit inside their areas.
+  /// The tolerance is clamped to `MAX_HIT_TOLERANCE`.
+  pub fn stitches_at_point(&self, x: f32, y: f32, tolerance: f32) -> Vec<Stitch> {
+    // `NaN` is treated as no tolerance.
+    let tolerance = if tolerance.is_nan() {
+      0.0
+    } else {
+      tolerance.clamp(0.0, MAX_HIT_TOLERANCE)
+    };
+    let area = Bounds::around(x, y, tolerance);
+    let point = Bounds::around(x, y, 0.0);
+
+    let nodes = self
+      .nodes
+      .intersecting(&area)
+      .filter(|node| distance((x, y), (node.x.into_inner(), node.y.into_inner())) <= tolerance)
+      .map(|&node| Stitch::Node(node));
+    let lines = self
+      .lines
+      .intersecting(&area)
+      .filter(|line| distance_to_line((x, y), line) <= tolerance)
+      .map(|&line| Stitch::Line(line));
+    let partstitches = self
+      .partstitches
+      .intersecting(&point)
+      .map(|&partstitch| Stitch::Part(partstitch));
+    let fullstitches = self
+      .fullstitches
+      .intersecting(&point)
+      .map(|&fullstitch| Stitch::Full(fullstitch));
+
+    // The stitches are painted in the reverse order, so the nodes are on the top.
+    nodes.chain(lines).chain(partstitches).chain(fullstitches).collect()
+  }
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+  (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+/// Returns the distance from the point to the line segment.
+fn distance_to_line(point: (f32, f32), line: &Line) -> f32 {
+  let start = (line.x.0.into_inner(), line.y.0.into_inner());
+  let end = (line.x.1.into_inner(), line.y.1.into_inner());
+  let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+  let length = dx * dx + dy * dy;
+  if length == 0.0 {
+    return distance(point, start);
+  }
+  let t = (((point.0 - start.0) * dx + (point.1 - start.1) * dy) / length).clamp(0.0, 1.0);
+  distance(point, (start.0 + t * dx, start.1 + t * dy))
+}
@@ -0,0 +1,125 @@
+use ordered_float::NotNan;
+
+use super::*;
+use crate::core::pattern::{
+  FullStitch, FullStitchKind, LineKind, Node, NodeKind, PartStitch, PartStitchDirection, PartStitchKind,
+};
+
+fn full(x: f32, y: f32) -> Stitch {
+  Stitch::Full(FullStitch {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(y).unwrap(),
+    palindex: 0,
+    kind: FullStitchKind::Full,
+  })
+}
+
+fn quarter(x: f32, y: f32) -> Stitch {
+  Stitch::Part(PartStitch {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(y).unwrap(),
+    palindex: 0,
+    direction: PartStitchDirection::Backward,
+    kind: PartStitchKind::Quarter,
+  })
+}
+
+fn line(x: (f32, f32), y: (f32, f32)) -> Stitch {
+  Stitch::Line(Line {
+    x: (NotNan::new(x.0).unwrap(), NotNan::new(x.1).unwrap()),
+    y: (NotNan::new(y.0).unwrap(), NotNan::new(y.1).unwrap()),
+    palindex: 0,
+    kind: LineKind::Back,
+  })
+}
+
+fn node(x: f32, y: f32) -> Stitch {
+  Stitch::Node(Node {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(y).unwrap(),
+    rotated: false,
+    palindex: 0,
+    kind: NodeKind::FrenchKnot,
+  })
+}
+
+fn create_pattern() -> Pattern {
+  let mut pattern = Pattern::default();
+  for stitch in [
+    full(1.0, 1.0),
+    full(50.0, 50.0),
+    quarter(2.5, 1.5),
+    line((0.0, 40.0), (2.0, 2.0)),
+    node(2.0, 2.0),
+  ] {
+    pattern.add_stitch(stitch);
+  }
+  pattern
+}
+
+#[test]
+fn fetches_stitches_in_viewport() {
+  let pattern = create_pattern();
+  let viewport = Region {
+    x: 20,
+    y: 0,
+    width: 10,
+    height: 10,
+  };
+  // The line crosses the viewport, while both its ends lie outside it.
+  assert_eq!(
+    pattern.stitches_intersecting(&viewport.bounds()),
+    vec![line((0.0, 40.0), (2.0, 2.0))]
+  );
+
+  let viewport = Region {
+    x: 0,
+    y: 0,
+    width: 10,
+    height: 10,
+  };
+  assert_eq!(pattern.stitches_intersecting(&viewport.bounds()).len(), 4);
+}
+
+#[test]
+fn selects_stitches_inside_region() {
+  let pattern = create_pattern();
+  let region = Region { x: 0, y: 0, width: 3, height: 3 };
+  assert_eq!(
+    pattern.stitches_in(&region),
+    vec![full(1.0, 1.0), quarter(2.5, 1.5), node(2.0, 2.0)]
+  );
+}
+
+#[test]
+fn hit_tests_stitches() {
+  let pattern = create_pattern();
+  assert_eq!(
+    pattern.stitches_at_point(2.05, 2.0, 0.1),
+    vec![node(2.0, 2.0), line((0.0, 40.0), (2.0, 2.0))]
+  );
+  assert_eq!(
+    pattern.stitches_at_point(10.0, 2.05, 0.1),
+    vec![line((0.0, 40.0), (2.0, 2.0))]
+  );
+  assert_eq!(pattern.stitches_at_point(2.7, 1.7, 0.1), vec![quarter(2.5, 1.5)]);
+  assert_eq!(pattern.stitches_at_point(1.5, 1.5, 0.1), vec![full(1.0, 1.0)]);
+  assert!(pattern.stitches_at_point(10.0, 10.0, 0.1).is_empty());
+}
+
+#[test]
+fn clamps_hit_tolerance() {
+  let pattern = create_pattern();
+  // The node is too far away to be hit even with the huge tolerance.
+  assert!(pattern
+    .stitches_at_point(2.0, 2.0 + MAX_HIT_TOLERANCE + 1.0, f32::MAX)
+    .is_empty());
+  assert!(pattern
+    .stitches_at_point(2.0, 2.0 + MAX_HIT_TOLERANCE + 1.0, f32::INFINITY)
+    .is_empty());
+  assert!(pattern.stitches_at_point(2.0, 2.05, f32::NAN).is_empty());
+  assert_eq!(
+    pattern.stitches_at_point(2.0, 2.0, f32::NAN),
+    pattern.stitches_at_point(2.0, 2.0, 0.0)
+  );
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Bounds, Coord, Stitch};
+
+/// A rectangular part of the pattern in cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Region {
+  pub x: u16,
+  pub y: u16,
+  pub width: u16,
+  pub height: u16,
+}
+
+impl Region {
+  /// Returns the rectangle the region covers in the pattern coordinates.
+  pub fn bounds(&self) -> Bounds {
+    Bounds::new(
+      f32::from(self.x),
+      f32::from(self.y),
+      f32::from(self.x) + f32::from(self.width),
+      f32::from(self.y) + f32::from(self.height),
+    )
+  }
+
+  /// Checks whether the cell with the given top-left corner lies inside the region.
+  pub fn contains_cell(&self, x: Coord, y: Coord) -> bool {
+    let (x, y) = (x.into_inner(), y.into_inner());
+    x >= f32::from(self.x)
+      && x < f32::from(self.x) + f32::from(self.width)
+      && y >= f32::from(self.y)
+      && y < f32::from(self.y) + f32::from(self.height)
+  }
+
+  /// Checks whether the grid point lies inside the region or on its border.
+  pub fn contains_point(&self, x: Coord, y: Coord) -> bool {
+    let (x, y) = (x.into_inner(), y.into_inner());
+    x >= f32::from(self.x)
+      && x <= f32::from(self.x) + f32::from(self.width)
+      && y >= f32::from(self.y)
+      && y <= f32::from(self.y) + f32::from(self.height)
+  }
+
+  /// Checks whether the stitch lies inside the region.
+  /// The full and part stitches occupy the cells, while the lines and nodes are placed on the grid points.
+  pub fn contains_stitch(&self, stitch: &Stitch) -> bool {
+    match stitch {
+      Stitch::Full(fullstitch) => self.contains_cell(fullstitch.x, fullstitch.y),
+      Stitch::Part(partstitch) => self.contains_cell(partstitch.x, partstitch.y),
+      Stitch::Line(line) => self.contains_point(line.x.0, line.y.0) && self.contains_point(line.x.1, line.y.1),
+      Stitch::Node(node) => self.contains_point(node.x, node.y),
+    }
+  }
+}
@@ -0,0 +1,218 @@
+//! The repetition of a motif across the pattern, e.g. to make the all-over patterns and the borders.
+//!
+//! The motif is a region of the pattern. Its stitches are copied, clipping the copies that don't fit their areas.
+//! The special stitches are not repeated.
+
+use std::collections::BTreeSet;
+
+use serde::Deserialize;
+
+use super::transform::*;
+use super::{Pattern, Region, Stitch};
+
+#[cfg(test)]
+#[path = "repeat.test.rs"]
+mod tests;
+
+/// How the motif is repeated.
+///
+/// It is serialized as `{ "layout": "tile", "spacing_x": 2, "spacing_y": 2, "offset": "brick" }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(tag = "layout", rename_all = "snake_case")]
+pub enum RepeatLayout {
+  /// Tiles the motif across the whole pattern, aligning the tiles to the motif itself.
+  Tile {
+    #[serde(default)]
+    spacing_x: u16,
+    #[serde(default)]
+    spacing_y: u16,
+    #[serde(default)]
+    offset: TileOffset,
+  },
+  /// Repeats the motif along the edges of the pattern, turning it so its top faces the edge.
+  /// The copies are centered on each edge.
+  Border {
+    #[serde(default)]
+    spacing: u16,
+    /// The distance between the border and the edges of the pattern.
+    #[serde(default)]
+    margin: u16,
+    #[serde(default)]
+    corners: BorderCorners,
+  },
+}
+
+/// The shift of the alternate rows or columns of the tiles.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TileOffset {
+  #[default]
+  None,
+  /// Shifts every other row by half of the tile width.
+  Brick,
+  /// Shifts every other column by half of the tile height.
+  HalfDrop,
+}
+
+/// What is placed into the corners of the border.
+/// The corners are the squares with the side equal to the motif height.
+///
+/// It is serialized as `{ "kind": "motif", "region": { "x": 0, "y": 0, "width": 5, "height": 5 } }`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BorderCorners {
+  #[default]
+  Empty,
+  /// The motif for the top-left corner. It is turned clockwise for the other corners.
+  Motif { region: Region },
+}
+
+type Bounds = (f32, f32, f32, f32);
+
+/// Returns the positions of the copies of the given length centered along the edge.
+fn centered_positions(start: i32, length: i32, motif: i32, spacing: i32) -> impl Iterator<Item = i32> {
+  let count = if length >= motif {
+    (length + spacing) / (motif + spacing)
+  } else {
+    0
+  };
+  let used = count * motif + (count - 1).max(0) * spacing;
+  let first = start + (length - used) / 2;
+  (0..count).map(move |i| first + i * (motif + spacing))
+}
+
+impl Pattern {
+  /// Returns the copies of the motif stitches laid out across the pattern.
+  /// The motif itself is not copied.
+  pub fn repeat_motif(&self, motif: &Region, layout: &RepeatLayout) -> Vec<Stitch> {
+    if motif.width == 0 || motif.height == 0 {
+      return Vec::new();
+    }
+    let stitches = self.stitches_in(motif);
+    let copies = match *layout {
+      RepeatLayout::Tile { spacing_x, spacing_y, offset } => self.tile(motif, &stitches, spacing_x, spacing_y, offset),
+      RepeatLayout::Border { spacing, margin, corners } => self.border(motif, &stitches, spacing, margin, corners),
+    };
+    dedup(copies)
+  }
+
+  fn tile(
+    &self,
+    motif: &Region,
+    stitches: &[Stitch],
+    spacing_x: u16,
+    spacing_y: u16,
+    offset: TileOffset,
+  ) -> Vec<Stitch> {
+    let (width, height) = (i32::from(self.properties.width), i32::from(self.properties.height));
+    let (mx, my) = (i32::from(motif.x), i32::from(motif.y));
+    let step_x = i32::from(motif.width) + i32::from(spacing_x);
+    let step_y = i32::from(motif.height) + i32::from(spacing_y);
+    let bounds = (0.0, 0.0, width as f32, height as f32);
+
+    let mut copies = Vec::new();
+    // The range is wider than needed to cover the shifted tiles, the copies outside the pattern are clipped anyway.
+    for row in (-my / step_y - 2)..=((height - my) / step_y + 2) {
+      for column in (-mx / step_x - 2)..=((width - mx) / step_x + 2) {
+        if row == 0 && column == 0 {
+          continue;
+        }
+        let (mut x, mut y) = (mx + column * step_x, my + row * step_y);
+        match offset {
+          TileOffset::None => {}
+          TileOffset::Brick => x += row.rem_euclid(2) * (step_x / 2),
+          TileOffset::HalfDrop => y += column.rem_euclid(2) * (step_y / 2),
+        }
+        copies.extend(place(stitches, motif, IDENTITY, (x, y), bounds));
+      }
+    }
+    copies
+  }
+
+  fn border(
+    &self,
+    motif: &Region,
+    stitches: &[Stitch],
+    spacing: u16,
+    margin: u16,
+    corners: BorderCorners,
+  ) -> Vec<Stitch> {
+    let (width, height) = (i32::from(self.properties.width), i32::from(self.properties.height));
+    let (motif_width, band) = (i32::from(motif.width), i32::from(motif.height));
+    let (spacing, margin) = (i32::from(spacing), i32::from(margin));
+    let bounds = |left: i32, top: i32, right: i32, bottom: i32| (left as f32, top as f32, right as f32, bottom as f32);
+
+    // The outer and inner edges of the border.
+    let (left, top, right, bottom) = (margin, margin, width - margin, height - margin);
+    let (inner_left, inner_top, inner_right, inner_bottom) = (left + band, top + band, right - band, bottom - band);
+
+    let mut copies = Vec::new();
+    for x in centered_positions(inner_left, inner_right - inner_left, motif_width, spacing) {
+      let top_bounds = bounds(inner_left, top, inner_right, inner_top);
+      copies.extend(place(stitches, motif, IDENTITY, (x, top), top_bounds));
+      let bottom_bounds = bounds(inner_left, inner_bottom, inner_right, bottom);
+      copies.extend(place(stitches, motif, ROTATE_180, (x, inner_bottom), bottom_bounds));
+    }
+    for y in centered_positions(inner_top, inner_bottom - inner_top, motif_width, spacing) {
+      let right_bounds = bounds(inner_right, inner_top, right, inner_bottom);
+      copies.extend(place(stitches, motif, ROTATE_90, (inner_right, y), right_bounds));
+      let left_bounds = bounds(left, inner_top, inner_left, inner_bottom);
+      copies.extend(place(stitches, motif, ROTATE_270, (left, y), left_bounds));
+    }
+
+    if let BorderCorners::Motif { region } = corners {
+      let stitches = self.stitches_in(&region);
+      let (corner_width, corner_height) = (i32::from(region.width), i32::from(region.height));
+      // The corner motifs are anchored to the outer corners of the border.
+      let placements = [
+        (IDENTITY, (left, top), bounds(left, top, inner_left, inner_top)),
+        (
+          ROTATE_90,
+          (right - corner_height, top),
+          bounds(inner_right, top, right, inner_top),
+        ),
+        (
+          ROTATE_180,
+          (right - corner_width, bottom - corner_height),
+          bounds(inner_right, inner_bottom, right, bottom),
+        ),
+        (
+          ROTATE_270,
+          (left, bottom - corner_width),
+          bounds(left, inner_bottom, inner_left, bottom),
+        ),
+      ];
+      for (matrix, position, corner_bounds) in placements {
+        copies.extend(place(&stitches, &region, matrix, position, corner_bounds));
+      }
+    }
+    copies
+  }
+}
+
+/// Transforms the motif stitches and moves them so the top-left corner of the transformed motif lies at the given position.
+fn place(stitches: &[Stitch], motif: &Region, matrix: Matrix, (x, y): (i32, i32), bounds: Bounds) -> Vec<Stitch> {
+  let transform = Transform::placing(
+    matrix,
+    (f32::from(motif.x), f32::from(motif.y)),
+    (f32::from(motif.width), f32::from(motif.height)),
+    (x as f32, y as f32),
+    bounds,
+  );
+  stitches.iter().filter_map(|&stitch| transform.stitch(stitch)).collect()
+}
+
+/// Removes the duplicates, e.g. the lines on the edges shared by the adjacent copies.
+fn dedup(stitches: Vec<Stitch>) -> Vec<Stitch> {
+  let (mut fullstitches, mut partstitches, mut lines, mut nodes) =
+    (BTreeSet::new(), BTreeSet::new(), BTreeSet::new(), BTreeSet::new());
+  stitches
+    .into_iter()
+    .filter(|stitch| match *stitch {
+      Stitch::Full(fullstitch) => fullstitches.insert(fullstitch),
+      Stitch::Part(partstitch) => partstitches.insert(partstitch),
+      Stitch::Line(line) => lines.insert(line),
+      Stitch::Node(node) => nodes.insert(node),
+    })
+    .collect()
+}
@@ -0,0 +1,171 @@
+use ordered_float::NotNan;
+
+use super::*;
+use crate::core::pattern::{
+  FullStitch, FullStitchKind, Line, LineKind, PartStitch, PartStitchDirection, PartStitchKind, PatternProperties,
+};
+
+fn create_pattern(width: u16, height: u16) -> Pattern {
+  Pattern {
+    properties: PatternProperties { width, height },
+    ..Pattern::default()
+  }
+}
+
+fn full(x: f32, y: f32) -> Stitch {
+  Stitch::Full(FullStitch {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(y).unwrap(),
+    palindex: 0,
+    kind: FullStitchKind::Full,
+  })
+}
+
+fn half(x: f32, y: f32, direction: PartStitchDirection) -> Stitch {
+  Stitch::Part(PartStitch {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(y).unwrap(),
+    palindex: 0,
+    direction,
+    kind: PartStitchKind::Half,
+  })
+}
+
+fn line(x: (f32, f32), y: (f32, f32)) -> Stitch {
+  Stitch::Line(Line {
+    x: (NotNan::new(x.0).unwrap(), NotNan::new(x.1).unwrap()),
+    y: (NotNan::new(y.0).unwrap(), NotNan::new(y.1).unwrap()),
+    palindex: 0,
+    kind: LineKind::Back,
+  })
+}
+
+const MOTIF: Region = Region { x: 0, y: 0, width: 2, height: 2 };
+
+#[test]
+fn collects_stitches_in_region() {
+  let mut pattern = create_pattern(10, 10);
+  pattern.add_stitches(vec![full(1.0, 1.0), full(2.0, 0.0), line((0.0, 2.0), (2.0, 2.0))]);
+  assert_eq!(
+    pattern.stitches_in(&MOTIF),
+    vec![full(1.0, 1.0), line((0.0, 2.0), (2.0, 2.0))]
+  );
+}
+
+#[test]
+fn tiles_motif() {
+  let mut pattern = create_pattern(20, 10);
+  pattern.add_stitch(full(0.0, 0.0));
+
+  let tile = |offset| RepeatLayout::Tile {
+    spacing_x: 1,
+    spacing_y: 1,
+    offset,
+  };
+
+  // 7 columns and 4 rows of the tiles, except for the motif itself.
+  let copies = pattern.repeat_motif(&MOTIF, &tile(TileOffset::None));
+  assert_eq!(copies.len(), 7 * 4 - 1);
+  assert!(copies.contains(&full(18.0, 9.0)));
+  assert!(!copies.contains(&full(0.0, 0.0)));
+
+  let copies = pattern.repeat_motif(&MOTIF, &tile(TileOffset::Brick));
+  assert!(copies.contains(&full(1.0, 3.0)));
+  assert!(copies.contains(&full(3.0, 6.0)));
+  assert!(!copies.contains(&full(3.0, 3.0)));
+
+  let copies = pattern.repeat_motif(&MOTIF, &tile(TileOffset::HalfDrop));
+  assert!(copies.contains(&full(3.0, 1.0)));
+  assert!(copies.contains(&full(6.0, 3.0)));
+}
+
+#[test]
+fn skips_duplicate_lines_on_shared_edges() {
+  let mut pattern = create_pattern(10, 2);
+  pattern.add_stitches(vec![line((0.0, 0.0), (0.0, 2.0)), line((2.0, 2.0), (0.0, 2.0))]);
+
+  let layout = RepeatLayout::Tile {
+    spacing_x: 0,
+    spacing_y: 0,
+    offset: TileOffset::None,
+  };
+  let copies = pattern.repeat_motif(&MOTIF, &layout);
+  // The lines go along every edge of the tiles once, including the edges shared with the motif.
+  assert_eq!(copies.len(), 6);
+  for x in [0.0, 2.0, 4.0, 6.0, 8.0, 10.0] {
+    assert!(copies.contains(&line((x, x), (0.0, 2.0))));
+  }
+}
+
+#[test]
+fn makes_border() {
+  let mut pattern = create_pattern(12, 12);
+  let motif = Region { x: 5, y: 5, width: 2, height: 2 };
+  pattern.add_stitches(vec![full(5.0, 5.0), half(6.0, 5.0, PartStitchDirection::Forward)]);
+
+  let layout = RepeatLayout::Border {
+    spacing: 0,
+    margin: 0,
+    corners: BorderCorners::Motif { region: motif },
+  };
+  let copies = pattern.repeat_motif(&motif, &layout);
+  // 4 copies along each edge and 4 corners.
+  assert_eq!(copies.len(), (4 * 4 + 4) * 2);
+
+  for position in [2.0, 4.0, 6.0, 8.0] {
+    // The motif faces the edges.
+    assert!(copies.contains(&full(position, 0.0)));
+    assert!(copies.contains(&half(position + 1.0, 0.0, PartStitchDirection::Forward)));
+    assert!(copies.contains(&full(11.0, position)));
+    assert!(copies.contains(&half(11.0, position + 1.0, PartStitchDirection::Backward)));
+    assert!(copies.contains(&full(position + 1.0, 11.0)));
+    assert!(copies.contains(&half(position, 11.0, PartStitchDirection::Forward)));
+    assert!(copies.contains(&full(0.0, position + 1.0)));
+    assert!(copies.contains(&half(0.0, position, PartStitchDirection::Backward)));
+  }
+
+  for (x, y) in [(0.0, 0.0), (11.0, 0.0), (11.0, 11.0), (0.0, 11.0)] {
+    assert!(copies.contains(&full(x, y)));
+  }
+}
+
+#[test]
+fn clips_border_to_its_area() {
+  let mut pattern = create_pattern(9, 9);
+  pattern.add_stitch(full(0.0, 0.0));
+
+  let layout = RepeatLayout::Border {
+    spacing: 1,
+    margin: 1,
+    corners: BorderCorners::Empty,
+  };
+  // The edges are 3 cells long, so only one copy fits on each of them.
+  let copies = pattern.repeat_motif(&MOTIF, &layout);
+  assert_eq!(
+    copies,
+    vec![full(3.0, 1.0), full(4.0, 7.0), full(7.0, 3.0), full(1.0, 4.0)]
+  );
+}
+
+#[test]
+fn deserializes_layout() {
+  let layout: RepeatLayout = serde_json::from_str(r#"{"layout":"tile","spacing_x":2,"offset":"half_drop"}"#).unwrap();
+  assert_eq!(
+    layout,
+    RepeatLayout::Tile {
+      spacing_x: 2,
+      spacing_y: 0,
+      offset: TileOffset::HalfDrop
+    }
+  );
+
+  let layout: RepeatLayout = serde_json::from_str(r#"{"layout":"border","margin":1}"#).unwrap();
+  assert_eq!(
+    layout,
+    RepeatLayout::Border {
+      spacing: 0,
+      margin: 1,
+      corners: BorderCorners::Empty
+    }
+  );
+}
@@ -0,0 +1,212 @@
+//! The log of the stitching sessions.
+//!
+//! The stitches completed during a session are the ones marked as completed between its start and end,
+//! so the statistics always agree with the stitching progress.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use super::progress::Progress;
+use super::Pattern;
+
+#[cfg(test)]
+#[path = "sessions.test.rs"]
+mod tests;
+
+const SECONDS_PER_HOUR: f64 = 3600.0;
+const SECONDS_PER_DAY: u64 = 86400;
+
+/// A stitching session. The times are in seconds since the Unix epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+pub struct StitchingSession {
+  #[serde(rename = "startedAt")]
+  pub started_at: u64,
+  /// The time the session was stopped at or `None` if it is still running.
+  #[serde(rename = "endedAt")]
+  pub ended_at: Option<u64>,
+}
+
+impl StitchingSession {
+  /// Returns the duration of the session in seconds. The running session lasts until now.
+  fn duration(&self, now: u64) -> u64 {
+    self.ended_at.unwrap_or(now).saturating_sub(self.started_at)
+  }
+
+  fn contains(&self, time: u64, now: u64) -> bool {
+    (self.started_at..=self.ended_at.unwrap_or(now)).contains(&time)
+  }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct SessionLog {
+  sessions: Vec<StitchingSession>,
+}
+
+impl SessionLog {
+  pub fn sessions(&self) -> &[StitchingSession] {
+    &self.sessions
+  }
+
+  /// Returns the running session.
+  pub fn active(&self) -> Option<&StitchingSession> {
+    self.sessions.last().filter(|session| session.ended_at.is_none())
+  }
+
+  /// Starts a new session.
+  pub fn start(&mut self, now: u64) -> Result<StitchingSession, SessionError> {
+    if self.active().is_some() {
+      return Err(SessionError::AlreadyStarted);
+    }
+    let session = StitchingSession { started_at: now, ended_at: None };
+    self.sessions.push(session);
+    Ok(session)
+  }
+
+  /// Stops the running session.
+  pub fn stop(&mut self, now: u64) -> Result<StitchingSession, SessionError> {
+    if self.active().is_none() {
+      return Err(SessionError::NotStarted);
+    }
+    let session = self.sessions.last_mut().unwrap();
+    session.ended_at = Some(now.max(session.started_at));
+    Ok(*session)
+  }
+
+  /// Returns the log to save to the file, with the running session ended at the given time.
+  /// The session keeps running in the opened pattern, so the next save moves its end further.
+  pub fn capped(&self, now: u64) -> SessionLog {
+    let mut log = self.clone();
+    let _ = log.stop(now);
+    log
+  }
+
+  /// Stops the session that was left running in the file.
+  /// The session is ended at the last stitch completed during it, or at its start if there are none.
+  pub fn stop_abandoned(&mut self, progress: &Progress, pattern: &Pattern) {
+    let Some(session) = self.active().copied() else {
+      return;
+    };
+    let last_completion = progress
+      .entries(pattern)
+      .into_iter()
+      .filter_map(|entry| entry.completed_at)
+      .filter(|&time| time >= session.started_at)
+      .max();
+    let _ = self.stop(last_completion.unwrap_or(session.started_at));
+  }
+
+  /// Calculates the statistics of the sessions and projects the completion date of the pattern.
+  /// The projection is based on the average number of stitches completed per day since the first session.
+  pub fn stats(&self, progress: &Progress, pattern: &Pattern, now: u64) -> SessionStats {
+    let completion_times: Vec<u64> = progress
+      .entries(pattern)
+      .into_iter()
+      .filter_map(|entry| entry.completed_at)
+      .collect();
+
+    let sessions: Vec<SessionSummary> = self
+      .sessions
+      .iter()
+      .map(|session| {
+        let stitches = completion_times
+          .iter()
+          .filter(|&&time| session.contains(time, now))
+          .count();
+        SessionSummary::new(*session, session.duration(now), stitches)
+      })
+      .collect();
+
+    let duration = sessions.iter().map(|summary| summary.duration).sum();
+    let stitches = sessions.iter().map(|summary| summary.stitches).sum();
+    let completion = progress.completion(pattern);
+    let remaining = completion.total - completion.completed;
+
+    let projected_completion = if remaining == 0 {
+      Some(now)
+    } else {
+      self.sessions.first().filter(|_| stitches > 0).map(|first| {
+        // The pace is averaged over at least one day, so a single short session doesn't give a too optimistic date.
+        let days = now.saturating_sub(first.started_at).max(SECONDS_PER_DAY) as f64 / SECONDS_PER_DAY as f64;
+        let stitches_per_day = stitches as f64 / days;
+        now + (remaining as f64 / stitches_per_day * SECONDS_PER_DAY as f64).ceil() as u64
+      })
+    };
+
+    SessionStats {
+      sessions,
+      duration,
+      stitches,
+      stitches_per_hour: stitches_per_hour(stitches, duration),
+      remaining,
+      projected_completion,
+    }
+  }
+}
+
+/// An error that occurs when the session is started or stopped at the wrong time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionError {
+  /// The session is started while another one is running.
+  AlreadyStarted,
+  /// The session is stopped while none is running.
+  NotStarted,
+}
+
+impl std::fmt::Display for SessionError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      SessionError::AlreadyStarted => write!(f, "The stitching session is already started"),
+      SessionError::NotStarted => write!(f, "There is no started stitching session"),
+    }
+  }
+}
+
+impl std::error::Error for SessionError {}
+
+fn stitches_per_hour(stitches: usize, duration: u64) -> f64 {
+  if duration == 0 {
+    return 0.0;
+  }
+  stitches as f64 / (duration as f64 / SECONDS_PER_HOUR)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(test, derive(Deserialize))]
+pub struct SessionSummary {
+  #[serde(flatten)]
+  pub session: StitchingSession,
+  /// The duration of the session in seconds.
+  pub duration: u64,
+  /// The number of stitches completed during the session.
+  pub stitches: usize,
+  #[serde(rename = "stitchesPerHour")]
+  pub stitches_per_hour: f64,
+}
+
+impl SessionSummary {
+  fn new(session: StitchingSession, duration: u64, stitches: usize) -> Self {
+    Self {
+      session,
+      duration,
+      stitches,
+      stitches_per_hour: stitches_per_hour(stitches, duration),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(test, derive(Deserialize))]
+pub struct SessionStats {
+  pub sessions: Vec<SessionSummary>,
+  /// The total duration of all the sessions in seconds.
+  pub duration: u64,
+  /// The number of stitches completed during all the sessions.
+  pub stitches: usize,
+  #[serde(rename = "stitchesPerHour")]
+  pub stitches_per_hour: f64,
+  /// The number of stitches that are not completed yet.
+  pub remaining: usize,
+  /// The projected time the pattern will be completed at or `None` if there is not enough data yet.
+  #[serde(rename = "projectedCompletion")]
+  pub projected_completion: Option<u64>,
+}
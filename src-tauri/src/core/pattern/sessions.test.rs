@@ -0,0 +1,116 @@
+use ordered_float::NotNan;
+
+use super::*;
+use crate::core::pattern::{FullStitch, FullStitchKind, Stitch};
+
+fn full(x: f32) -> Stitch {
+  Stitch::Full(FullStitch {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(0.0).unwrap(),
+    palindex: 0,
+    kind: FullStitchKind::Full,
+  })
+}
+
+fn create_pattern() -> Pattern {
+  let mut pattern = Pattern::default();
+  pattern.add_stitches((0..10).map(|x| full(x as f32)).collect());
+  pattern
+}
+
+#[test]
+fn starts_and_stops_sessions() {
+  let mut log = SessionLog::default();
+  assert_eq!(log.stop(10), Err(SessionError::NotStarted));
+
+  log.start(10).unwrap();
+  assert_eq!(log.start(20), Err(SessionError::AlreadyStarted));
+  assert_eq!(log.active().unwrap().started_at, 10);
+
+  let session = log.stop(30).unwrap();
+  assert_eq!(
+    session,
+    StitchingSession {
+      started_at: 10,
+      ended_at: Some(30)
+    }
+  );
+  assert!(log.active().is_none());
+  assert_eq!(log.sessions().len(), 1);
+}
+
+#[test]
+fn caps_running_session_on_saving() {
+  let mut log = SessionLog::default();
+  log.start(10).unwrap();
+
+  let saved = log.capped(30);
+  assert_eq!(saved.sessions()[0].ended_at, Some(30));
+  assert!(saved.active().is_none());
+  // The session keeps running in the opened pattern.
+  assert!(log.active().is_some());
+}
+
+#[test]
+fn stops_abandoned_session() {
+  let pattern = create_pattern();
+  let mut progress = Progress::default();
+  progress.set_completed_at(full(0.0).into(), Some(5));
+  progress.set_completed_at(full(1.0).into(), Some(40));
+
+  let mut log = SessionLog::default();
+  log.start(10).unwrap();
+  log.stop_abandoned(&progress, &pattern);
+  assert_eq!(log.sessions()[0].ended_at, Some(40));
+
+  // The session without completed stitches is ended at its start.
+  log.start(50).unwrap();
+  log.stop_abandoned(&progress, &pattern);
+  assert_eq!(log.sessions()[1].ended_at, Some(50));
+}
+
+#[test]
+fn calculates_session_stats() {
+  let pattern = create_pattern();
+  let mut progress = Progress::default();
+  let mut log = SessionLog::default();
+
+  // The first session lasts an hour and completes 2 stitches.
+  log.start(0).unwrap();
+  progress.set_completed_at(full(0.0).into(), Some(600));
+  progress.set_completed_at(full(1.0).into(), Some(1200));
+  log.stop(3600).unwrap();
+
+  // The stitch completed between the sessions doesn't belong to any of them.
+  progress.set_completed_at(full(2.0).into(), Some(4000));
+
+  // The running session lasts half an hour so far and completes 1 stitch.
+  log.start(5000).unwrap();
+  progress.set_completed_at(full(3.0).into(), Some(5100));
+
+  let stats = log.stats(&progress, &pattern, 6800);
+  assert_eq!(stats.sessions.len(), 2);
+  assert_eq!(stats.sessions[0].stitches, 2);
+  assert_eq!(stats.sessions[0].stitches_per_hour, 2.0);
+  assert_eq!(stats.sessions[1].duration, 1800);
+  assert_eq!(stats.sessions[1].stitches, 1);
+  assert_eq!(stats.duration, 5400);
+  assert_eq!(stats.stitches, 3);
+  assert_eq!(stats.stitches_per_hour, 2.0);
+  assert_eq!(stats.remaining, 6);
+  // 3 stitches per day, since the pace is averaged over at least one day.
+  assert_eq!(stats.projected_completion, Some(6800 + 2 * SECONDS_PER_DAY));
+}
+
+#[test]
+fn does_not_project_without_data() {
+  let pattern = create_pattern();
+  let mut progress = Progress::default();
+  let log = SessionLog::default();
+  assert_eq!(log.stats(&progress, &pattern, 100).projected_completion, None);
+
+  for x in 0..10 {
+    progress.set_completed_at(full(x as f32).into(), Some(50));
+  }
+  assert_eq!(log.stats(&progress, &pattern, 100).projected_completion, Some(100));
+}
@@ -0,0 +1,191 @@
+//! The spatial index of the stitches.
+//!
+//! The pattern is split into square buckets, and every stitch is put into each bucket its bounding box touches.
+//! So an area query only looks through the buckets that overlap the area instead of scanning all the stitches.
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap};
+
+use super::{FullStitch, FullStitchKind, Line, Node, PartStitch, PartStitchKind, SpecialStitch};
+
+#[cfg(test)]
+#[path = "index.test.rs"]
+mod tests;
+
+/// The side of a bucket in cells.
+const BUCKET_SIZE: f32 = 16.0;
+
+/// An axis-aligned rectangle in the pattern coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+  pub left: f32,
+  pub top: f32,
+  pub right: f32,
+  pub bottom: f32,
+}
+
+impl Bounds {
+  pub fn new(left: f32, top: f32, right: f32, bottom: f32) -> Self {
+    Self { left, top, right, bottom }
+  }
+
+  /// Returns the square around the point.
+  pub fn around(x: f32, y: f32, radius: f32) -> Self {
+    Self::new(x - radius, y - radius, x + radius, y + radius)
+  }
+
+  /// Returns the rectangle grown by the margin on each side.
+  pub fn grown(&self, margin: f32) -> Self {
+    Self::new(
+      self.left - margin,
+      self.top - margin,
+      self.right + margin,
+      self.bottom + margin,
+    )
+  }
+
+  /// Checks whether the rectangles overlap or touch each other.
+  pub fn intersects(&self, other: &Bounds) -> bool {
+    self.left <= other.right && other.left <= self.right && self.top <= other.bottom && other.top <= self.bottom
+  }
+
+  fn bucket_range(&self) -> (i32, i32, i32, i32) {
+    (
+      bucket(self.left),
+      bucket(self.top),
+      bucket(self.right),
+      bucket(self.bottom),
+    )
+  }
+
+  fn buckets(&self) -> impl Iterator<Item = (i32, i32)> {
+    let (left, top, right, bottom) = self.bucket_range();
+    (top..=bottom).flat_map(move |y| (left..=right).map(move |x| (x, y)))
+  }
+}
+
+fn bucket(coord: f32) -> i32 {
+  (coord / BUCKET_SIZE).floor() as i32
+}
+
+/// A stitch that occupies some area of the pattern.
+pub trait Bounded {
+  /// Returns the bounding box of the stitch.
+  /// It must depend only on the fields the stitches are ordered by, so the index can find the stitch by its key.
+  fn bounds(&self) -> Bounds;
+}
+
+impl Bounded for FullStitch {
+  fn bounds(&self) -> Bounds {
+    let size = match self.kind {
+      FullStitchKind::Full => 1.0,
+      FullStitchKind::Petite => 0.5,
+    };
+    let (x, y) = (self.x.into_inner(), self.y.into_inner());
+    Bounds::new(x, y, x + size, y + size)
+  }
+}
+
+impl Bounded for PartStitch {
+  fn bounds(&self) -> Bounds {
+    let size = match self.kind {
+      PartStitchKind::Half => 1.0,
+      PartStitchKind::Quarter => 0.5,
+    };
+    let (x, y) = (self.x.into_inner(), self.y.into_inner());
+    Bounds::new(x, y, x + size, y + size)
+  }
+}
+
+impl Bounded for Node {
+  fn bounds(&self) -> Bounds {
+    Bounds::around(self.x.into_inner(), self.y.into_inner(), 0.0)
+  }
+}
+
+impl Bounded for Line {
+  fn bounds(&self) -> Bounds {
+    let (x1, x2) = (self.x.0.into_inner(), self.x.1.into_inner());
+    let (y1, y2) = (self.y.0.into_inner(), self.y.1.into_inner());
+    Bounds::new(x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2))
+  }
+}
+
+impl Bounded for SpecialStitch {
+  /// The special stitches are indexed by the cell they are placed at, since their shapes are defined by the modules.
+  fn bounds(&self) -> Bounds {
+    let (x, y) = (self.x.into_inner(), self.y.into_inner());
+    Bounds::new(x, y, x + 1.0, y + 1.0)
+  }
+}
+
+/// The grid of buckets with the keys of the stitches.
+/// The stored stitches are used only as keys, so their other fields (mainly, palindex) may be outdated.
+#[derive(Debug, Clone)]
+pub struct SpatialIndex<T> {
+  buckets: HashMap<(i32, i32), BTreeSet<T>>,
+}
+
+impl<T> Default for SpatialIndex<T> {
+  fn default() -> Self {
+    Self { buckets: HashMap::new() }
+  }
+}
+
+impl<T: Ord + Copy + Bounded> SpatialIndex<T> {
+  pub fn insert(&mut self, stitch: T) {
+    for key in stitch.bounds().buckets() {
+      self.buckets.entry(key).or_default().replace(stitch);
+    }
+  }
+
+  pub fn remove(&mut self, stitch: &T) {
+    for key in stitch.bounds().buckets() {
+      if let Entry::Occupied(mut bucket) = self.buckets.entry(key) {
+        bucket.get_mut().remove(stitch);
+        if bucket.get().is_empty() {
+          bucket.remove();
+        }
+      }
+    }
+  }
+
+  /// Returns the keys of the stitches whose bounding boxes intersect or touch the area.
+  pub fn query(&self, area: &Bounds) -> BTreeSet<T> {
+    let (left, top, right, bottom) = area.bucket_range();
+    // The area may span the whole coordinate range, so the number of its buckets may not fit even into `i64`.
+    let area_buckets = (i64::from(right) - i64::from(left) + 1).saturating_mul(i64::from(bottom) - i64::from(top) + 1);
+
+    let mut found = BTreeSet::new();
+    let mut collect = |bucket: &BTreeSet<T>| {
+      found.extend(bucket.iter().filter(|stitch| stitch.bounds().intersects(area)));
+    };
+
+    // The area may be much larger than the pattern, so it is cheaper to look through the occupied buckets then.
+    if area_buckets > self.buckets.len() as i64 {
+      for (&(x, y), bucket) in &self.buckets {
+        if (left..=right).contains(&x) && (top..=bottom).contains(&y) {
+          collect(bucket);
+        }
+      }
+    } else {
+      for key in area.buckets() {
+        if let Some(bucket) = self.buckets.get(&key) {
+          collect(bucket);
+        }
+      }
+    }
+
+    found
+  }
+}
+
+impl<T: Ord + Copy + Bounded> FromIterator<T> for SpatialIndex<T> {
+  fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+    let mut index = Self::default();
+    for stitch in iter {
+      index.insert(stitch);
+    }
+    index
+  }
+}
@@ -0,0 +1,102 @@
+use ordered_float::NotNan;
+
+use super::*;
+use crate::core::pattern::{LineKind, Stitches};
+
+fn full(x: f32, y: f32, palindex: u8) -> FullStitch {
+  FullStitch {
+    x: NotNan::new(x).unwrap(),
+    y: NotNan::new(y).unwrap(),
+    palindex,
+    kind: FullStitchKind::Full,
+  }
+}
+
+fn line(x: (f32, f32), y: (f32, f32)) -> Line {
+  Line {
+    x: (NotNan::new(x.0).unwrap(), NotNan::new(x.1).unwrap()),
+    y: (NotNan::new(y.0).unwrap(), NotNan::new(y.1).unwrap()),
+    palindex: 0,
+    kind: LineKind::Back,
+  }
+}
+
+#[test]
+fn queries_stitches_in_area() {
+  let index: SpatialIndex<FullStitch> = [full(0.0, 0.0, 0), full(20.0, 20.0, 0), full(100.0, 5.0, 0)]
+    .into_iter()
+    .collect();
+
+  let found = index.query(&Bounds::new(0.0, 0.0, 25.0, 25.0));
+  assert_eq!(
+    found.into_iter().collect::<Vec<_>>(),
+    vec![full(0.0, 0.0, 0), full(20.0, 20.0, 0)]
+  );
+
+  // The area larger than the pattern is handled by looking through the occupied buckets.
+  let found = index.query(&Bounds::new(-10000.0, -10000.0, 10000.0, 10000.0));
+  assert_eq!(found.len(), 3);
+
+  assert!(index.query(&Bounds::new(50.0, 50.0, 60.0, 60.0)).is_empty());
+
+  // The number of buckets in the area spanning the whole coordinate range doesn't overflow.
+  let found = index.query(&Bounds::new(f32::MIN, f32::MIN, f32::MAX, f32::MAX));
+  assert_eq!(found.len(), 3);
+}
+
+#[test]
+fn indexes_lines_across_buckets() {
+  let mut index = SpatialIndex::default();
+  let long = line((0.0, 40.0), (0.0, 40.0));
+  index.insert(long);
+
+  // The line crosses the area, while its ends lie far from it.
+  assert_eq!(index.query(&Bounds::new(20.0, 20.0, 21.0, 21.0)).len(), 1);
+
+  index.remove(&long);
+  assert!(index.query(&Bounds::new(-100.0, -100.0, 100.0, 100.0)).is_empty());
+  assert!(index.buckets.is_empty());
+}
+
+#[test]
+fn keeps_index_in_sync_with_stitches() {
+  let mut stitches = Stitches::from_iter([full(0.0, 0.0, 0), full(1.0, 0.0, 1), full(30.0, 30.0, 2)]);
+  let area = Bounds::new(0.0, 0.0, 2.0, 1.0);
+
+  stitches.remove(&full(0.0, 0.0, 0));
+  stitches.insert(full(0.5, 0.5, 3));
+  assert_eq!(
+    stitches.intersecting(&area).copied().collect::<Vec<_>>(),
+    vec![full(1.0, 0.0, 1), full(0.5, 0.5, 3)]
+  );
+
+  // The changed palette indices are returned from the set, not from the index.
+  stitches.remap_palindexes(|palindex| palindex + 10);
+  assert_eq!(
+    stitches
+      .intersecting(&area)
+      .map(|stitch| stitch.palindex)
+      .collect::<Vec<_>>(),
+    vec![11, 13]
+  );
+
+  stitches.remove_where(|stitch| stitch.x.into_inner() < 2.0);
+  assert_eq!(stitches.intersecting(&area).count(), 0);
+
+  stitches.remove_stitches_by_palindex(12);
+  assert_eq!(stitches.intersecting(&Bounds::new(0.0, 0.0, 50.0, 50.0)).count(), 0);
+}
+
+#[test]
+fn rebuilds_index_on_deserialization() {
+  let stitches = Stitches::from_iter([full(5.0, 5.0, 0), full(40.0, 40.0, 1)]);
+  let bytes = borsh::to_vec(&stitches).unwrap();
+  let restored: Stitches<FullStitch> = borsh::from_slice(&bytes).unwrap();
+  assert_eq!(
+    restored
+      .intersecting(&Bounds::new(39.0, 39.0, 41.0, 41.0))
+      .copied()
+      .collect::<Vec<_>>(),
+    vec![full(40.0, 40.0, 1)]
+  );
+}
@@ -13,6 +13,9 @@ pub use line::*;
 mod special;
 pub use special::*;
 
+mod index;
+pub use index::*;
+
 #[allow(clippy::module_inception)]
 mod stitches;
 pub use stitches::*;
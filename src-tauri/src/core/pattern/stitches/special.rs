@@ -1,7 +1,7 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 
-use super::{Line, Node};
+use super::{Line, Node, PaletteIndex};
 use crate::core::pattern::Coord;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
@@ -26,6 +26,16 @@ impl Ord for SpecialStitch {
   }
 }
 
+impl PaletteIndex for SpecialStitch {
+  fn palindex(&self) -> u8 {
+    self.palindex
+  }
+
+  fn set_palindex(&mut self, palindex: u8) {
+    self.palindex = palindex;
+  }
+}
+
 #[nutype::nutype(
   sanitize(with = |raw| raw.clamp(0, 360)),
   derive(Debug, Clone, Copy, PartialEq, Eq, FromStr, Display, Serialize, Deserialize, BorshSerialize, BorshDeserialize)
@@ -22,6 +22,62 @@ pub enum Stitch {
   Node(Node),
 }
 
+impl Stitch {
+  pub fn kind(&self) -> StitchKind {
+    match self {
+      Stitch::Full(fullstitch) => match fullstitch.kind {
+        FullStitchKind::Full => StitchKind::Full,
+        FullStitchKind::Petite => StitchKind::Petite,
+      },
+      Stitch::Part(partstitch) => match partstitch.kind {
+        PartStitchKind::Half => StitchKind::Half,
+        PartStitchKind::Quarter => StitchKind::Quarter,
+      },
+      Stitch::Line(line) => match line.kind {
+        LineKind::Back => StitchKind::Back,
+        LineKind::Straight => StitchKind::Straight,
+      },
+      Stitch::Node(node) => match node.kind {
+        NodeKind::FrenchKnot => StitchKind::FrenchKnot,
+        NodeKind::Bead => StitchKind::Bead,
+      },
+    }
+  }
+
+  pub fn palindex(&self) -> u8 {
+    match self {
+      Stitch::Full(fullstitch) => fullstitch.palindex,
+      Stitch::Part(partstitch) => partstitch.palindex,
+      Stitch::Line(line) => line.palindex,
+      Stitch::Node(node) => node.palindex,
+    }
+  }
+
+  pub fn set_palindex(&mut self, palindex: u8) {
+    match self {
+      Stitch::Full(fullstitch) => fullstitch.palindex = palindex,
+      Stitch::Part(partstitch) => partstitch.palindex = palindex,
+      Stitch::Line(line) => line.palindex = palindex,
+      Stitch::Node(node) => node.palindex = palindex,
+    }
+  }
+}
+
+/// The kinds of all the stitches, including the special ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StitchKind {
+  Full,
+  Petite,
+  Half,
+  Quarter,
+  Back,
+  Straight,
+  FrenchKnot,
+  Bead,
+  Special,
+}
+
 impl From<FullStitch> for Stitch {
   fn from(fullstitch: FullStitch) -> Self {
     Self::Full(fullstitch)
@@ -47,15 +103,22 @@ impl From<Line> for Stitch {
 }
 
 /// A set of stitches.
-#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+///
+/// The set is accompanied by the spatial index, so the stitches in an area can be found without a full scan.
+#[derive(Debug, Clone, BorshSerialize)]
 pub struct Stitches<T: Ord> {
   inner: BTreeSet<T>,
+  #[borsh(skip)]
+  index: SpatialIndex<T>,
 }
 
-impl<T: Ord> Stitches<T> {
+impl<T: Ord + Copy + Bounded> Stitches<T> {
   #[allow(clippy::new_without_default)]
   pub fn new() -> Self {
-    Self { inner: BTreeSet::new() }
+    Self {
+      inner: BTreeSet::new(),
+      index: SpatialIndex::default(),
+    }
   }
 
   pub fn iter(&self) -> impl Iterator<Item = &T> {
@@ -85,6 +148,7 @@ impl<T: Ord> Stitches<T> {
   pub fn insert(&mut self, stitch: T) -> Option<T> {
     // We need to use the `replace` method to get the replaced value from the set.
     // We need to return the previous value to pass it back to the caller, so it can be used to update the pattern on the frontend.
+    self.index.insert(stitch);
     self.inner.replace(stitch)
   }
 
@@ -93,30 +157,74 @@ impl<T: Ord> Stitches<T> {
     // We need to use the `take` method to get the actual value from the set.
     // The passed `stitch` contains only the fields that are used for ordering (coordinates, kind, etc.).
     // Hovewer, we need to return the actual stitch that contains all the other values (mainly, palindex), so it can be used to update the pattern on the frontend.
-    self.inner.take(stitch)
+    let removed = self.inner.take(stitch);
+    if removed.is_some() {
+      self.index.remove(stitch);
+    }
+    removed
   }
 
   pub fn get(&self, stitch: &T) -> Option<&T> {
     self.inner.get(stitch)
   }
 
+  /// Returns the stitches whose bounding boxes intersect or touch the area, in the set order.
+  pub fn intersecting(&self, area: &Bounds) -> impl Iterator<Item = &T> {
+    // The index stores only the keys, so the actual stitches are taken from the set.
+    self
+      .index
+      .query(area)
+      .into_iter()
+      .filter_map(|stitch| self.inner.get(&stitch))
+  }
+
   pub fn extend(&mut self, stitches: Stitches<T>) {
-    self.inner.extend(stitches.inner);
+    self.extend_from(stitches.inner);
+  }
+
+  /// Adds the stitches, keeping the existing ones placed at the same positions.
+  fn extend_from<I: IntoIterator<Item = T>>(&mut self, stitches: I) {
+    for stitch in stitches {
+      if self.inner.insert(stitch) {
+        self.index.insert(stitch);
+      }
+    }
+  }
+
+  /// Removes and returns all the stitches that match the predicate.
+  pub fn remove_where<F: FnMut(&T) -> bool>(&mut self, mut predicate: F) -> Vec<T> {
+    let (removed, kept): (BTreeSet<T>, BTreeSet<T>) = std::mem::take(&mut self.inner)
+      .into_iter()
+      .partition(|stitch| predicate(stitch));
+    self.inner = kept;
+    for stitch in removed.iter() {
+      self.index.remove(stitch);
+    }
+    removed.into_iter().collect()
   }
 }
 
-impl<T: Ord> FromIterator<T> for Stitches<T> {
+impl<T: Ord + Copy + Bounded> FromIterator<T> for Stitches<T> {
   fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-    Self { inner: BTreeSet::from_iter(iter) }
+    let inner = BTreeSet::from_iter(iter);
+    let index = inner.iter().copied().collect();
+    Self { inner, index }
   }
 }
 
-impl<T: Ord> Default for Stitches<T> {
+impl<T: Ord + Copy + Bounded> Default for Stitches<T> {
   fn default() -> Self {
     Self::new()
   }
 }
 
+// The index is not saved, so it is rebuilt on loading.
+impl<T: Ord + Copy + Bounded + BorshDeserialize> BorshDeserialize for Stitches<T> {
+  fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+    Ok(Self::from_iter(BTreeSet::<T>::deserialize_reader(reader)?))
+  }
+}
+
 impl Stitches<FullStitch> {
   /// Removes and returns all the conflicts with a given full stitch.
   /// It looks for any petite stitches that overlap with the full stitch.
@@ -376,7 +484,7 @@ pub trait PaletteIndex {
   fn set_palindex(&mut self, palindex: u8);
 }
 
-impl<T: Ord + PaletteIndex> Stitches<T> {
+impl<T: Ord + Copy + Bounded + PaletteIndex> Stitches<T> {
   pub fn remove_stitches_by_palindex(&mut self, palindex: u8) -> Vec<T> {
     let mut conflicts = Vec::new();
     for mut stitch in std::mem::take(&mut self.inner).into_iter() {
@@ -385,6 +493,7 @@ impl<T: Ord + PaletteIndex> Stitches<T> {
           self.inner.insert(stitch);
         }
         Ordering::Equal => {
+          self.index.remove(&stitch);
           conflicts.push(stitch);
         }
         Ordering::Greater => {
@@ -403,6 +512,31 @@ impl<T: Ord + PaletteIndex> Stitches<T> {
       }
       self.inner.insert(stitch);
     }
-    self.inner.extend(stitches);
+    self.extend_from(stitches);
+  }
+
+  /// Sets the new palette index to the stitches that have the given one and match the predicate.
+  /// Returns the changed stitches as they were before the change.
+  pub fn replace_palindex<F: FnMut(&T) -> bool>(&mut self, from: u8, to: u8, mut predicate: F) -> Vec<T> {
+    let mut replaced = Vec::new();
+    for mut stitch in std::mem::take(&mut self.inner).into_iter() {
+      if stitch.palindex() == from && predicate(&stitch) {
+        replaced.push(stitch);
+        stitch.set_palindex(to);
+      }
+      self.inner.insert(stitch);
+    }
+    replaced
+  }
+
+  /// Changes the palette indices of all the stitches using the mapping function.
+  pub fn remap_palindexes<F: Fn(u8) -> u8>(&mut self, remap: F) {
+    self.inner = std::mem::take(&mut self.inner)
+      .into_iter()
+      .map(|mut stitch| {
+        stitch.set_palindex(remap(stitch.palindex()));
+        stitch
+      })
+      .collect();
   }
 }
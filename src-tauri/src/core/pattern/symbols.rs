@@ -0,0 +1,73 @@
+//! The automatic assignment of the chart symbols to the palette items.
+//!
+//! Every palette item receives a single base glyph that is shared by all its stitch kinds.
+//! The charts tell the kinds apart by the size and the position of the glyph, e.g. the petite stitch symbol is drawn in a quarter of the cell.
+//! The candidate glyphs come from the stitch font, ordered so that the first ones are the most distinguishable from each other.
+
+use std::collections::HashSet;
+
+use super::display::Symbols;
+
+#[cfg(test)]
+#[path = "symbols.test.rs"]
+mod tests;
+
+/// Picks the symbols that are not used by the other palette items yet.
+pub struct SymbolAllocator {
+  used: HashSet<u16>,
+  /// The code points of the font glyphs in the order they should be assigned.
+  candidates: Vec<u16>,
+}
+
+impl SymbolAllocator {
+  /// Creates an allocator that picks the candidates in order, avoiding the symbols already assigned to the palette items.
+  pub fn new<'a>(candidates: Vec<u16>, symbols: impl IntoIterator<Item = &'a Symbols>) -> Self {
+    let mut used = HashSet::new();
+    for symbols in symbols {
+      used.extend(
+        [
+          symbols.full,
+          symbols.petite,
+          symbols.half,
+          symbols.quarter,
+          symbols.french_knot,
+          symbols.bead,
+        ]
+        .into_iter()
+        .flatten(),
+      );
+    }
+    Self { used, candidates }
+  }
+
+  /// Returns the next free symbol or `None` if all of them are used.
+  pub fn allocate(&mut self) -> Option<u16> {
+    let symbol = self
+      .candidates
+      .iter()
+      .copied()
+      .find(|symbol| !self.used.contains(symbol))?;
+    self.used.insert(symbol);
+    Some(symbol)
+  }
+
+  /// Returns the symbols of a new palette item: its base glyph is used for all the stitch kinds.
+  pub fn allocate_symbols(&mut self) -> Symbols {
+    let symbol = self.allocate();
+    Symbols {
+      full: symbol,
+      petite: symbol,
+      half: symbol,
+      quarter: symbol,
+      french_knot: symbol,
+      bead: symbol,
+    }
+  }
+}
+
+/// Assigns the new symbols to all the palette items.
+/// The first palette items receive the most distinguishable symbols.
+pub fn reassign_symbols(palette_size: usize, candidates: Vec<u16>) -> Vec<Symbols> {
+  let mut allocator = SymbolAllocator::new(candidates, &[]);
+  (0..palette_size).map(|_| allocator.allocate_symbols()).collect()
+}
@@ -0,0 +1,53 @@
+use super::*;
+
+fn candidates() -> Vec<u16> {
+  "+XO#*".chars().map(|symbol| symbol as u16).collect()
+}
+
+#[test]
+fn shares_symbol_across_stitch_kinds() {
+  let symbols = SymbolAllocator::new(candidates(), &[]).allocate_symbols();
+  let symbol = Some(u16::from(b'+'));
+  assert_eq!(
+    symbols,
+    Symbols {
+      full: symbol,
+      petite: symbol,
+      half: symbol,
+      quarter: symbol,
+      french_knot: symbol,
+      bead: symbol,
+    }
+  );
+}
+
+#[test]
+fn avoids_existing_symbols() {
+  let existing = vec![
+    Symbols {
+      full: Some(u16::from(b'+')),
+      bead: Some(u16::from(b'#')),
+      ..Symbols::default()
+    },
+    Symbols {
+      full: Some(u16::from(b'X')),
+      ..Symbols::default()
+    },
+  ];
+  let mut allocator = SymbolAllocator::new(candidates(), &existing);
+  assert_eq!(allocator.allocate(), Some(u16::from(b'O')));
+  assert_eq!(allocator.allocate(), Some(u16::from(b'*')));
+  assert_eq!(allocator.allocate(), None);
+}
+
+#[test]
+fn reassigns_symbols_in_candidate_order() {
+  let symbols = reassign_symbols(3, candidates());
+  let full: Vec<_> = symbols.iter().map(|symbols| symbols.full).collect();
+  let expected: Vec<_> = candidates().into_iter().take(3).map(Some).collect();
+  assert_eq!(full, expected);
+
+  // The palette items that don't get a symbol are drawn without it.
+  let symbols = reassign_symbols(6, candidates());
+  assert_eq!(symbols[5], Symbols::default());
+}
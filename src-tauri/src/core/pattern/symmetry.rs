@@ -0,0 +1,85 @@
+//! The symmetric drawing of the stitches.
+//!
+//! The stitches are mirrored or rotated around the center of the pattern.
+//! The mirrored stitches that don't fit the grid (e.g. the full stitches rotated in the pattern with an odd sum of the sides) or the pattern itself are skipped.
+
+use serde::Deserialize;
+
+use super::transform::*;
+use super::{Pattern, Stitch};
+
+#[cfg(test)]
+#[path = "symmetry.test.rs"]
+mod tests;
+
+/// A symmetry mode.
+///
+/// It is serialized as `{ "mode": "radial", "order": 4 }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Symmetry {
+  /// Mirrors the stitches across the vertical axis.
+  Vertical,
+  /// Mirrors the stitches across the horizontal axis.
+  Horizontal,
+  /// Mirrors the stitches across both axes.
+  Both,
+  /// Rotates the stitches around the center.
+  /// Only the orders 2, 4 and 8 fit the grid, so the other ones are rounded down to them.
+  /// The order 8 also mirrors the rotated stitches across the diagonals.
+  Radial { order: u8 },
+}
+
+impl Symmetry {
+  fn matrices(&self) -> &'static [Matrix] {
+    match *self {
+      Symmetry::Vertical => &[MIRROR_X],
+      Symmetry::Horizontal => &[MIRROR_Y],
+      Symmetry::Both => &[MIRROR_X, MIRROR_Y, ROTATE_180],
+      Symmetry::Radial { order } => match order {
+        0..=1 => &[],
+        2..=3 => &[ROTATE_180],
+        4..=7 => &[ROTATE_90, ROTATE_180, ROTATE_270],
+        _ => &[
+          ROTATE_90,
+          ROTATE_180,
+          ROTATE_270,
+          MIRROR_X,
+          MIRROR_Y,
+          MIRROR_DIAGONAL,
+          MIRROR_ANTIDIAGONAL,
+        ],
+      },
+    }
+  }
+}
+
+/// Checks whether the stitches take the same place, so only one of them can be added.
+fn same_place(a: &Stitch, b: &Stitch) -> bool {
+  match (a, b) {
+    (Stitch::Part(a), Stitch::Part(b)) => a.x == b.x && a.y == b.y && a.kind == b.kind,
+    (Stitch::Node(a), Stitch::Node(b)) => a.x == b.x && a.y == b.y,
+    (Stitch::Line(a), Stitch::Line(b)) => {
+      (a.x == b.x && a.y == b.y) || (a.x == (b.x.1, b.x.0) && a.y == (b.y.1, b.y.0))
+    }
+    _ => a == b,
+  }
+}
+
+impl Pattern {
+  /// Returns the stitch followed by its symmetric copies.
+  /// The copies that take the same place as the previous stitches are skipped.
+  pub fn symmetric_stitches(&self, stitch: Stitch, symmetry: Symmetry) -> Vec<Stitch> {
+    let (width, height) = (f32::from(self.properties.width), f32::from(self.properties.height));
+    let mut stitches = vec![stitch];
+    for &matrix in symmetry.matrices() {
+      let transform = Transform::around(matrix, (width / 2.0, height / 2.0), (0.0, 0.0, width, height));
+      if let Some(copy) = transform.stitch(stitch) {
+        if !stitches.iter().any(|other| same_place(other, &copy)) {
+          stitches.push(copy);
+        }
+      }
+    }
+    stitches
+  }
+}
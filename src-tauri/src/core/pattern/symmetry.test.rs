@@ -0,0 +1,176 @@
+use ordered_float::NotNan;
+
+use super::*;
+use crate::core::pattern::{
+  Coord, FullStitch, FullStitchKind, Line, LineKind, Node, NodeKind, PartStitch, PartStitchDirection, PartStitchKind,
+  PatternProperties,
+};
+
+fn create_pattern(width: u16, height: u16) -> Pattern {
+  Pattern {
+    properties: PatternProperties { width, height },
+    ..Pattern::default()
+  }
+}
+
+fn coord(value: f32) -> Coord {
+  NotNan::new(value).unwrap()
+}
+
+fn full(x: f32, y: f32, kind: FullStitchKind) -> Stitch {
+  Stitch::Full(FullStitch {
+    x: coord(x),
+    y: coord(y),
+    palindex: 0,
+    kind,
+  })
+}
+
+fn part(x: f32, y: f32, direction: PartStitchDirection, kind: PartStitchKind) -> Stitch {
+  Stitch::Part(PartStitch {
+    x: coord(x),
+    y: coord(y),
+    palindex: 0,
+    direction,
+    kind,
+  })
+}
+
+#[test]
+fn mirrors_full_stitches() {
+  let pattern = create_pattern(10, 8);
+
+  let stitches = pattern.symmetric_stitches(full(1.0, 2.0, FullStitchKind::Full), Symmetry::Vertical);
+  assert_eq!(
+    stitches,
+    vec![
+      full(1.0, 2.0, FullStitchKind::Full),
+      full(8.0, 2.0, FullStitchKind::Full)
+    ]
+  );
+
+  let stitches = pattern.symmetric_stitches(full(1.5, 2.0, FullStitchKind::Petite), Symmetry::Both);
+  assert_eq!(
+    stitches,
+    vec![
+      full(1.5, 2.0, FullStitchKind::Petite),
+      full(8.0, 2.0, FullStitchKind::Petite),
+      full(1.5, 5.5, FullStitchKind::Petite),
+      full(8.0, 5.5, FullStitchKind::Petite),
+    ]
+  );
+
+  // The stitch on the axis is not duplicated.
+  let pattern = create_pattern(9, 8);
+  let stitches = pattern.symmetric_stitches(full(4.0, 2.0, FullStitchKind::Full), Symmetry::Vertical);
+  assert_eq!(stitches, vec![full(4.0, 2.0, FullStitchKind::Full)]);
+}
+
+#[test]
+fn mirrors_part_stitch_directions() {
+  let pattern = create_pattern(10, 10);
+
+  let stitch = part(1.0, 2.0, PartStitchDirection::Forward, PartStitchKind::Half);
+  assert_eq!(
+    pattern.symmetric_stitches(stitch, Symmetry::Horizontal),
+    vec![
+      stitch,
+      part(1.0, 7.0, PartStitchDirection::Backward, PartStitchKind::Half)
+    ]
+  );
+  assert_eq!(
+    pattern.symmetric_stitches(stitch, Symmetry::Radial { order: 4 }),
+    vec![
+      stitch,
+      part(7.0, 1.0, PartStitchDirection::Backward, PartStitchKind::Half),
+      part(8.0, 7.0, PartStitchDirection::Forward, PartStitchKind::Half),
+      part(2.0, 8.0, PartStitchDirection::Backward, PartStitchKind::Half),
+    ]
+  );
+
+  // The top-right quarter becomes the top-left one in the mirrored cell.
+  let stitch = part(1.5, 2.0, PartStitchDirection::Forward, PartStitchKind::Quarter);
+  assert_eq!(
+    pattern.symmetric_stitches(stitch, Symmetry::Vertical),
+    vec![
+      stitch,
+      part(8.0, 2.0, PartStitchDirection::Backward, PartStitchKind::Quarter)
+    ]
+  );
+}
+
+#[test]
+fn mirrors_lines_and_nodes() {
+  let pattern = create_pattern(10, 10);
+
+  let line = Stitch::Line(Line {
+    x: (coord(1.0), coord(3.0)),
+    y: (coord(1.0), coord(2.5)),
+    palindex: 0,
+    kind: LineKind::Back,
+  });
+  assert_eq!(
+    pattern.symmetric_stitches(line, Symmetry::Vertical),
+    vec![
+      line,
+      Stitch::Line(Line {
+        x: (coord(9.0), coord(7.0)),
+        y: (coord(1.0), coord(2.5)),
+        palindex: 0,
+        kind: LineKind::Back,
+      })
+    ]
+  );
+
+  let node = Stitch::Node(Node {
+    x: coord(2.0),
+    y: coord(3.5),
+    rotated: false,
+    palindex: 0,
+    kind: NodeKind::Bead,
+  });
+  let stitches = pattern.symmetric_stitches(node, Symmetry::Radial { order: 4 });
+  assert_eq!(stitches.len(), 4);
+  assert_eq!(
+    stitches[1],
+    Stitch::Node(Node {
+      x: coord(6.5),
+      y: coord(2.0),
+      rotated: true,
+      palindex: 0,
+      kind: NodeKind::Bead,
+    })
+  );
+}
+
+#[test]
+fn skips_stitches_that_do_not_fit() {
+  // The full stitches rotated by a quarter turn would start in the middle of the cells.
+  let pattern = create_pattern(10, 9);
+  let stitch = full(1.0, 2.0, FullStitchKind::Full);
+  assert_eq!(
+    pattern.symmetric_stitches(stitch, Symmetry::Radial { order: 4 }),
+    vec![stitch, full(8.0, 6.0, FullStitchKind::Full)]
+  );
+
+  // The rotated stitches that fall outside the pattern are skipped.
+  let pattern = create_pattern(10, 4);
+  let stitch = full(0.0, 0.0, FullStitchKind::Full);
+  assert_eq!(
+    pattern.symmetric_stitches(stitch, Symmetry::Radial { order: 8 }),
+    vec![
+      stitch,
+      full(9.0, 3.0, FullStitchKind::Full),
+      full(9.0, 0.0, FullStitchKind::Full),
+      full(0.0, 3.0, FullStitchKind::Full),
+    ]
+  );
+}
+
+#[test]
+fn deserializes_symmetry() {
+  let symmetry: Symmetry = serde_json::from_str(r#"{"mode":"radial","order":8}"#).unwrap();
+  assert_eq!(symmetry, Symmetry::Radial { order: 8 });
+  let symmetry: Symmetry = serde_json::from_str(r#"{"mode":"both"}"#).unwrap();
+  assert_eq!(symmetry, Symmetry::Both);
+}
@@ -0,0 +1,221 @@
+//! The rendering of the text into the stitches.
+//!
+//! The text is rasterized into the cells either with the bundled cross-stitch alphabet or with a font rasterized at the given height.
+//! The cells are then filled with the stitches, outlined or stroked with the backstitches.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use super::draw::Cell;
+use super::fill::FillStitchKind;
+use super::{Pattern, Stitch};
+use crate::core::fonts::{FontRegistry, RasterGlyph};
+
+#[cfg(test)]
+#[path = "text.test.rs"]
+mod tests;
+
+/// The largest line height of the text rasterized with a font, in cells.
+/// The larger heights are clamped, so a typo can't make the glyphs rasterize into millions of cells.
+pub const MAX_FONT_HEIGHT: u16 = 200;
+
+/// The bundled cross-stitch alphabet.
+static BUNDLED_ALPHABET: LazyLock<Alphabet> = LazyLock::new(|| {
+  Alphabet::parse(include_str!("../../../resources/alphabets/dot-matrix-5x7.json"))
+    .expect("The bundled alphabet must be valid")
+});
+
+/// A bitmap alphabet for the cross-stitch lettering.
+///
+/// The alphabets are stored as JSON files that describe every glyph as the rows of `#` (filled) and `.` (empty) cells.
+/// The files also have the `name`, `source` and `license` fields that credit the origin of the glyph shapes.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Alphabet {
+  /// The height of the glyphs in cells.
+  pub height: u16,
+  /// The advance of the space and the characters missing in the alphabet.
+  pub space_advance: u16,
+  glyphs: HashMap<char, Vec<String>>,
+}
+
+impl Alphabet {
+  /// Parses the alphabet file and checks that all its glyphs have the declared height.
+  pub fn parse(data: &str) -> Result<Self> {
+    let alphabet: Alphabet = serde_json::from_str(data).context("The alphabet file is invalid")?;
+    for (symbol, rows) in alphabet.glyphs.iter() {
+      anyhow::ensure!(
+        rows.len() == usize::from(alphabet.height),
+        "The glyph {symbol:?} must have {} rows",
+        alphabet.height
+      );
+      anyhow::ensure!(
+        rows
+          .iter()
+          .all(|row| row.chars().all(|cell| cell == '#' || cell == '.')),
+        "The glyph {symbol:?} must consist of `#` and `.`"
+      );
+    }
+    Ok(alphabet)
+  }
+
+  /// Returns the glyph of the character.
+  /// The empty columns are trimmed, so the glyphs are proportional.
+  /// The characters that are not in the alphabet are rendered as spaces.
+  pub fn glyph(&self, symbol: char) -> RasterGlyph {
+    let cells: Vec<(i32, i32)> = self
+      .glyphs
+      .get(&symbol)
+      .into_iter()
+      .flatten()
+      .enumerate()
+      .flat_map(|(y, row)| {
+        row
+          .chars()
+          .enumerate()
+          .filter(|&(_, cell)| cell == '#')
+          .map(move |(x, _)| (x as i32, y as i32))
+      })
+      .collect();
+    let (Some(first), Some(last)) = (cells.iter().map(|&(x, _)| x).min(), cells.iter().map(|&(x, _)| x).max()) else {
+      return RasterGlyph {
+        advance: self.space_advance,
+        cells: Vec::new(),
+      };
+    };
+    RasterGlyph {
+      // The glyphs are separated by a single empty column.
+      advance: (last - first + 2) as u16,
+      cells: cells.into_iter().map(|(x, y)| (x - first, y)).collect(),
+    }
+  }
+}
+
+/// The source of the glyphs.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum TextFont {
+  /// The bundled 5x7 cross-stitch alphabet.
+  Alphabet,
+  /// The font rasterized so the line of text is `height` cells high.
+  /// The height is clamped to `1..=MAX_FONT_HEIGHT`.
+  Font { name: String, height: u16 },
+}
+
+/// How the glyphs are stitched.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextStyle {
+  /// The glyph cells are filled with the stitches.
+  #[default]
+  Filled,
+  /// The glyph cells are outlined with the backstitches, cutting the steps diagonally.
+  Outline,
+  /// The glyphs are stitched with the single-stroke backstitches through the cell centers.
+  /// It suits the alphabet glyphs, whose strokes are a single cell wide.
+  Backstitch,
+}
+
+/// The alignment of the text lines relative to the widest one.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextAlign {
+  #[default]
+  Left,
+  Center,
+  Right,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TextOptions {
+  pub text: String,
+  /// The position of the top-left corner of the text in cells.
+  pub x: i32,
+  pub y: i32,
+  pub font: TextFont,
+  #[serde(default)]
+  pub style: TextStyle,
+  /// The extra space between the glyphs in cells. It can be negative to tighten the text.
+  #[serde(default)]
+  pub letter_spacing: i16,
+  /// The extra space between the lines in cells.
+  #[serde(default)]
+  pub line_spacing: i16,
+  #[serde(default)]
+  pub align: TextAlign,
+}
+
+impl Pattern {
+  /// Returns the stitches that render the text.
+  /// The stitch kind is used only for the filled text, while the other styles are always drawn with the backstitches.
+  pub fn render_text(
+    &self,
+    options: &TextOptions,
+    palindex: u8,
+    kind: FillStitchKind,
+    fonts: &FontRegistry,
+  ) -> Result<Vec<Stitch>> {
+    let cells = layout_text(options, fonts)?;
+    Ok(match options.style {
+      TextStyle::Filled => self.fill_cells(cells, palindex, kind),
+      TextStyle::Outline => self.outline_cells(cells, palindex),
+      TextStyle::Backstitch => self.stroke_cells(cells, palindex),
+    })
+  }
+}
+
+/// Places the glyphs of the text and returns their cells in the pattern coordinates.
+pub fn layout_text(options: &TextOptions, fonts: &FontRegistry) -> Result<Vec<Cell>> {
+  let (rasterize, line_height): (Box<dyn Fn(char) -> RasterGlyph + '_>, u16) = match &options.font {
+    TextFont::Alphabet => (
+      Box::new(|symbol| BUNDLED_ALPHABET.glyph(symbol)),
+      BUNDLED_ALPHABET.height,
+    ),
+    TextFont::Font { name, height } => {
+      let font = fonts
+        .get(name)
+        .with_context(|| format!("The font {name} is not available"))?;
+      let height = (*height).clamp(1, MAX_FONT_HEIGHT);
+      (Box::new(move |symbol| font.rasterize(symbol, height)), height)
+    }
+  };
+
+  // The lines are laid out first to find the widest one for the alignment.
+  let lines: Vec<(i32, Vec<Cell>)> = options
+    .text
+    .lines()
+    .map(|line| {
+      let mut cells = Vec::new();
+      let mut pen: i32 = 0;
+      for (index, symbol) in line.chars().enumerate() {
+        if index > 0 {
+          pen = pen.saturating_add(i32::from(options.letter_spacing));
+        }
+        let glyph = rasterize(symbol);
+        cells.extend(glyph.cells.iter().map(|&(x, y)| (pen.saturating_add(x), y)));
+        pen = pen.saturating_add(i32::from(glyph.advance));
+      }
+      (pen.max(0), cells)
+    })
+    .collect();
+  let block_width = lines.iter().map(|(width, _)| *width).max().unwrap_or_default();
+
+  let mut cells = Vec::new();
+  for (index, (width, line_cells)) in lines.into_iter().enumerate() {
+    let offset_x = match options.align {
+      TextAlign::Left => 0,
+      TextAlign::Center => (block_width - width) / 2,
+      TextAlign::Right => block_width - width,
+    };
+    let offset_y = (index as i32).saturating_mul(i32::from(line_height) + i32::from(options.line_spacing));
+    cells.extend(line_cells.into_iter().map(|(x, y)| {
+      (
+        options.x.saturating_add(offset_x).saturating_add(x),
+        options.y.saturating_add(offset_y).saturating_add(y),
+      )
+    }));
+  }
+  Ok(cells)
+}
@@ -0,0 +1,259 @@
+use super::*;
+use crate::core::pattern::{LineKind, PatternProperties};
+
+fn create_pattern() -> Pattern {
+  Pattern {
+    properties: PatternProperties { width: 50, height: 30 },
+    ..Pattern::default()
+  }
+}
+
+fn options(text: &str) -> TextOptions {
+  TextOptions {
+    text: String::from(text),
+    x: 1,
+    y: 1,
+    font: TextFont::Alphabet,
+    style: TextStyle::Filled,
+    letter_spacing: 0,
+    line_spacing: 0,
+    align: TextAlign::Left,
+  }
+}
+
+fn bounds(cells: &[Cell]) -> (i32, i32, i32, i32) {
+  let min_x = cells.iter().map(|(x, _)| *x).min().unwrap();
+  let max_x = cells.iter().map(|(x, _)| *x).max().unwrap();
+  let min_y = cells.iter().map(|(_, y)| *y).min().unwrap();
+  let max_y = cells.iter().map(|(_, y)| *y).max().unwrap();
+  (min_x, min_y, max_x, max_y)
+}
+
+fn alphabet_glyph(symbol: char) -> RasterGlyph {
+  BUNDLED_ALPHABET.glyph(symbol)
+}
+
+#[test]
+fn alphabet_glyphs_are_proportional() {
+  assert_eq!(BUNDLED_ALPHABET.height, 7);
+  assert_eq!(BUNDLED_ALPHABET.glyphs.len(), 94);
+
+  let glyph = alphabet_glyph('H');
+  assert_eq!(glyph.advance, 6);
+  assert_eq!(glyph.cells.len(), 7 * 2 + 3);
+
+  let glyph = alphabet_glyph('i');
+  assert_eq!(glyph.advance, 4);
+  assert!(glyph.cells.iter().all(|&(x, _)| x < 3));
+
+  // The unknown characters are rendered as spaces.
+  assert_eq!(alphabet_glyph('é'), alphabet_glyph(' '));
+  assert!(alphabet_glyph(' ').cells.is_empty());
+}
+
+#[test]
+fn lays_out_text() {
+  let fonts = FontRegistry::new();
+  let cells = layout_text(&options("HI"), &fonts).unwrap();
+  // `H` is 5 cells wide, followed by a gap and `I`, which is 3 cells wide.
+  assert_eq!(bounds(&cells), (1, 1, 1 + 5 + 1 + 3 - 1, 7));
+
+  let cells = layout_text(
+    &TextOptions {
+      letter_spacing: 2,
+      ..options("HI")
+    },
+    &fonts,
+  )
+  .unwrap();
+  assert_eq!(bounds(&cells).2, 11);
+}
+
+#[test]
+fn aligns_lines() {
+  let fonts = FontRegistry::new();
+  let text = "HH\nI";
+  let left = layout_text(&options(text), &fonts).unwrap();
+  let right = layout_text(
+    &TextOptions {
+      align: TextAlign::Right,
+      ..options(text)
+    },
+    &fonts,
+  )
+  .unwrap();
+  let center = layout_text(
+    &TextOptions {
+      align: TextAlign::Center,
+      line_spacing: 1,
+      ..options(text)
+    },
+    &fonts,
+  )
+  .unwrap();
+
+  let second_line = |cells: &[Cell], top: i32| {
+    let line: Vec<Cell> = cells.iter().copied().filter(|&(_, y)| y >= top).collect();
+    bounds(&line)
+  };
+  // The second line starts right after the first one.
+  assert_eq!(second_line(&left, 8).0, 1);
+  // The widest line is 12 cells wide, while `I` has the advance of 4 cells.
+  assert_eq!(second_line(&right, 8).0, 1 + 12 - 4);
+  assert_eq!(second_line(&center, 9), (1 + 4, 9, 1 + 4 + 2, 15));
+}
+
+#[test]
+fn renders_text_with_fonts() {
+  let fonts = FontRegistry::new();
+  let pattern = create_pattern();
+  let font = TextFont::Font {
+    name: String::from("Noto Sans"),
+    height: 12,
+  };
+  let stitches = pattern
+    .render_text(
+      &TextOptions {
+        font: font.clone(),
+        ..options("Ab")
+      },
+      0,
+      FillStitchKind::Full,
+      &fonts,
+    )
+    .unwrap();
+  assert!(!stitches.is_empty());
+  assert!(stitches.iter().all(|stitch| matches!(stitch, Stitch::Full(_))));
+
+  let missing = TextFont::Font {
+    name: String::from("Missing"),
+    height: 12,
+  };
+  let result = pattern.render_text(
+    &TextOptions { font: missing, ..options("Ab") },
+    0,
+    FillStitchKind::Full,
+    &fonts,
+  );
+  assert!(result.is_err());
+}
+
+#[test]
+fn renders_outlined_text() {
+  let fonts = FontRegistry::new();
+  let pattern = create_pattern();
+  let stitches = pattern
+    .render_text(
+      &TextOptions {
+        style: TextStyle::Outline,
+        ..options("I")
+      },
+      0,
+      FillStitchKind::Full,
+      &fonts,
+    )
+    .unwrap();
+  assert!(!stitches.is_empty());
+  assert!(stitches
+    .iter()
+    .all(|stitch| matches!(stitch, Stitch::Line(line) if line.kind == LineKind::Back)));
+}
+
+#[test]
+fn outlines_slopes_with_diagonals() {
+  let fonts = FontRegistry::new();
+  let pattern = create_pattern();
+  let stitches = pattern
+    .render_text(
+      &TextOptions {
+        style: TextStyle::Outline,
+        ..options("V")
+      },
+      0,
+      FillStitchKind::Full,
+      &fonts,
+    )
+    .unwrap();
+  assert!(stitches
+    .iter()
+    .any(|stitch| matches!(stitch, Stitch::Line(line) if line.x.0 != line.x.1 && line.y.0 != line.y.1)));
+}
+
+#[test]
+fn renders_backstitched_text() {
+  let fonts = FontRegistry::new();
+  let pattern = create_pattern();
+  let render = |text: &str| {
+    pattern
+      .render_text(
+        &TextOptions {
+          style: TextStyle::Backstitch,
+          ..options(text)
+        },
+        0,
+        FillStitchKind::Full,
+        &fonts,
+      )
+      .unwrap()
+  };
+
+  // `I` is a vertical bar with the serifs: three straight backstitches through the cell centers.
+  let stitches = render("I");
+  assert_eq!(stitches.len(), 3);
+  assert!(stitches.iter().all(|stitch| matches!(
+    stitch,
+    Stitch::Line(line) if line.kind == LineKind::Back && line.x.0.fract() == 0.5 && line.y.0.fract() == 0.5
+  )));
+
+  // The slopes of `V` are stitched diagonally.
+  assert!(render("V")
+    .iter()
+    .any(|stitch| matches!(stitch, Stitch::Line(line) if line.x.0 != line.x.1 && line.y.0 != line.y.1)));
+
+  // The dot of `!` becomes a french knot.
+  assert!(render("!").iter().any(|stitch| matches!(stitch, Stitch::Node(_))));
+}
+
+#[test]
+fn clamps_font_height() {
+  let fonts = FontRegistry::new();
+  let options = |height: u16| TextOptions {
+    font: TextFont::Font {
+      name: String::from("Noto Sans"),
+      height,
+    },
+    ..options("I")
+  };
+  let huge = layout_text(&options(u16::MAX), &fonts).unwrap();
+  let largest = layout_text(&options(MAX_FONT_HEIGHT), &fonts).unwrap();
+  assert_eq!(huge, largest);
+}
+
+#[test]
+fn rejects_invalid_alphabets() {
+  let alphabet = |glyphs: &str| {
+    Alphabet::parse(&format!(
+      r#"{{"name":"Test","source":"Test","license":"CC0","height":2,"space_advance":2,"glyphs":{glyphs}}}"#
+    ))
+  };
+  assert!(alphabet(r###"{"a":["#.","##"]}"###).is_ok());
+  assert!(alphabet(r###"{"a":["#."]}"###).is_err());
+  assert!(alphabet(r###"{"a":["#x","##"]}"###).is_err());
+}
+
+#[test]
+fn deserializes_text_options() {
+  let options: TextOptions = serde_json::from_str(
+    r#"{"text":"Anna","x":2,"y":3,"font":{"source":"font","name":"Noto Sans","height":10},"align":"center"}"#,
+  )
+  .unwrap();
+  assert_eq!(
+    options.font,
+    TextFont::Font {
+      name: String::from("Noto Sans"),
+      height: 10
+    }
+  );
+  assert_eq!(options.style, TextStyle::Filled);
+  assert_eq!(options.align, TextAlign::Center);
+}
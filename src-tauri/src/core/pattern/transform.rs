@@ -0,0 +1,144 @@
+//! The affine transformations of the stitches, i.e. the mirroring, the quarter turns and the translations.
+
+use ordered_float::NotNan;
+
+use super::{Coord, FullStitchKind, Line, Node, PartStitch, PartStitchDirection, PartStitchKind, Stitch};
+
+/// A linear transformation of the grid.
+pub type Matrix = [[f32; 2]; 2];
+
+pub const IDENTITY: Matrix = [[1.0, 0.0], [0.0, 1.0]];
+pub const MIRROR_X: Matrix = [[-1.0, 0.0], [0.0, 1.0]];
+pub const MIRROR_Y: Matrix = [[1.0, 0.0], [0.0, -1.0]];
+pub const ROTATE_90: Matrix = [[0.0, -1.0], [1.0, 0.0]];
+pub const ROTATE_180: Matrix = [[-1.0, 0.0], [0.0, -1.0]];
+pub const ROTATE_270: Matrix = [[0.0, 1.0], [-1.0, 0.0]];
+pub const MIRROR_DIAGONAL: Matrix = [[0.0, 1.0], [1.0, 0.0]];
+pub const MIRROR_ANTIDIAGONAL: Matrix = [[0.0, -1.0], [-1.0, 0.0]];
+
+/// Maps the point `p` to `matrix * p + offset`.
+/// The stitches that don't fit the grid or the bounds after the transformation are skipped.
+pub struct Transform {
+  pub matrix: Matrix,
+  pub offset: (f32, f32),
+  /// The bounds that the transformed stitches must lie in.
+  pub bounds: (f32, f32, f32, f32),
+}
+
+impl Transform {
+  /// Creates a transformation around the given center, e.g. to mirror the stitches across the pattern axes.
+  pub fn around(matrix: Matrix, (cx, cy): (f32, f32), bounds: (f32, f32, f32, f32)) -> Self {
+    let [[a, b], [c, d]] = matrix;
+    Self {
+      matrix,
+      offset: (cx - a * cx - b * cy, cy - c * cx - d * cy),
+      bounds,
+    }
+  }
+
+  /// Creates a transformation that moves the box with the top-left corner at `from` and the given size,
+  /// transformed by the matrix, so its new top-left corner lies at `to`.
+  pub fn placing(
+    matrix: Matrix,
+    from: (f32, f32),
+    size: (f32, f32),
+    to: (f32, f32),
+    bounds: (f32, f32, f32, f32),
+  ) -> Self {
+    let transform = Self {
+      matrix,
+      offset: (0.0, 0.0),
+      bounds,
+    };
+    let (x1, y1) = transform.apply(from);
+    let (x2, y2) = transform.apply((from.0 + size.0, from.1 + size.1));
+    Self {
+      offset: (to.0 - x1.min(x2), to.1 - y1.min(y2)),
+      ..transform
+    }
+  }
+
+  fn apply(&self, (x, y): (f32, f32)) -> (f32, f32) {
+    let [[a, b], [c, d]] = self.matrix;
+    (a * x + b * y + self.offset.0, c * x + d * y + self.offset.1)
+  }
+
+  /// Whether the transformation swaps the axes, e.g. the quarter turns.
+  fn swaps_axes(&self) -> bool {
+    self.matrix[0][0] == 0.0
+  }
+
+  fn point(&self, x: Coord, y: Coord) -> Option<(Coord, Coord)> {
+    let (x, y) = self.apply((x.into_inner(), y.into_inner()));
+    let (left, top, right, bottom) = self.bounds;
+    if x < left || y < top || x > right || y > bottom {
+      return None;
+    }
+    Some((NotNan::new(x).ok()?, NotNan::new(y).ok()?))
+  }
+
+  /// Transforms the square of the given size and returns its new top-left corner.
+  fn square(&self, x: Coord, y: Coord, size: f32) -> Option<(Coord, Coord)> {
+    let (x1, y1) = self.apply((x.into_inner(), y.into_inner()));
+    let (x2, y2) = self.apply((x.into_inner() + size, y.into_inner() + size));
+    let (x, y) = (x1.min(x2), y1.min(y2));
+    let (left, top, right, bottom) = self.bounds;
+    // The squares must stay aligned to the grid of their size, e.g. the full stitches can't start in the middle of a cell.
+    if x < left || y < top || x + size > right || y + size > bottom || (x % size) != 0.0 || (y % size) != 0.0 {
+      return None;
+    }
+    Some((NotNan::new(x).ok()?, NotNan::new(y).ok()?))
+  }
+
+  fn direction(&self, partstitch: &PartStitch, size: f32) -> PartStitchDirection {
+    let (x, y) = (partstitch.x.into_inner(), partstitch.y.into_inner());
+    // The backward stitch goes from the top-left corner to the bottom-right one, while the forward one goes from the bottom-left corner to the top-right one.
+    let (start, end) = match partstitch.direction {
+      PartStitchDirection::Backward => ((x, y), (x + size, y + size)),
+      PartStitchDirection::Forward => ((x, y + size), (x + size, y)),
+    };
+    let (start, end) = (self.apply(start), self.apply(end));
+    if (end.0 - start.0) * (end.1 - start.1) > 0.0 {
+      PartStitchDirection::Backward
+    } else {
+      PartStitchDirection::Forward
+    }
+  }
+
+  /// Returns the transformed stitch or `None` if it doesn't fit the grid or the bounds.
+  pub fn stitch(&self, stitch: Stitch) -> Option<Stitch> {
+    match stitch {
+      Stitch::Full(mut fullstitch) => {
+        let size = match fullstitch.kind {
+          FullStitchKind::Full => 1.0,
+          FullStitchKind::Petite => 0.5,
+        };
+        (fullstitch.x, fullstitch.y) = self.square(fullstitch.x, fullstitch.y, size)?;
+        Some(Stitch::Full(fullstitch))
+      }
+      Stitch::Part(partstitch) => {
+        let size = match partstitch.kind {
+          PartStitchKind::Half => 1.0,
+          PartStitchKind::Quarter => 0.5,
+        };
+        let (x, y) = self.square(partstitch.x, partstitch.y, size)?;
+        let direction = self.direction(&partstitch, size);
+        Some(Stitch::Part(PartStitch { x, y, direction, ..partstitch }))
+      }
+      Stitch::Node(node) => {
+        let (x, y) = self.point(node.x, node.y)?;
+        Some(Stitch::Node(Node {
+          x,
+          y,
+          rotated: node.rotated ^ self.swaps_axes(),
+          ..node
+        }))
+      }
+      Stitch::Line(line) => {
+        let (x1, y1) = self.point(line.x.0, line.y.0)?;
+        let (x2, y2) = self.point(line.x.1, line.y.1)?;
+        Some(Stitch::Line(Line { x: (x1, x2), y: (y1, y2), ..line }))
+      }
+    }
+  }
+}
@@ -0,0 +1,51 @@
+/// An RGB color as it is stored in the pattern (a hex string like `FF00AA`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+  pub const BLACK: Rgb = Rgb(0, 0, 0);
+  pub const WHITE: Rgb = Rgb(255, 255, 255);
+
+  /// Parses a hex color. The leading `#` is optional.
+  pub fn from_hex(hex: &str) -> Option<Self> {
+    let hex = hex.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+      return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(Rgb(channel(0)?, channel(2)?, channel(4)?))
+  }
+
+  /// Parses a hex color, falling back to black if it is invalid.
+  pub fn from_hex_or_black(hex: &str) -> Self {
+    Self::from_hex(hex).unwrap_or(Rgb::BLACK)
+  }
+
+  pub fn to_hex(self) -> String {
+    format!("{:02X}{:02X}{:02X}", self.0, self.1, self.2)
+  }
+
+  /// Darkens the color to the given percentage of its brightness.
+  pub fn darken(self, percentage: u8) -> Self {
+    let factor = f32::from(percentage.min(100)) / 100.0;
+    let channel = |value: u8| (f32::from(value) * factor).round() as u8;
+    Rgb(channel(self.0), channel(self.1), channel(self.2))
+  }
+
+  /// Lightens the color by mixing it with the given percentage of white.
+  pub fn lighten(self, percentage: u8) -> Self {
+    let factor = f32::from(percentage.min(100)) / 100.0;
+    let channel = |value: u8| (f32::from(value) + (255.0 - f32::from(value)) * factor).round() as u8;
+    Rgb(channel(self.0), channel(self.1), channel(self.2))
+  }
+
+  /// Returns black or white, whichever is better readable on top of this color.
+  pub fn contrasting(self) -> Self {
+    let luminance = 0.299 * f32::from(self.0) + 0.587 * f32::from(self.1) + 0.114 * f32::from(self.2);
+    if luminance > 140.0 {
+      Rgb::BLACK
+    } else {
+      Rgb::WHITE
+    }
+  }
+}
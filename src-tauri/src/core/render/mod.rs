@@ -0,0 +1,105 @@
+//! Headless rendering of the patterns.
+//! It is used to export the patterns to images without the frontend.
+
+use anyhow::{bail, Result};
+
+use super::pattern::display::View;
+use super::pattern::{Bounds, PatternProject};
+
+mod color;
+mod painter;
+mod preview;
+mod raster;
+mod shapes;
+mod svg;
+
+pub use preview::render_preview_png;
+pub use raster::render_png;
+pub use svg::render_svg;
+
+// The region lives in the pattern model, since the pattern queries and edits use it too.
+pub use super::pattern::Region;
+
+/// The size of a single cell (stitch) in pixels at 100% zoom.
+pub const PIXELS_PER_CELL: f32 = 10.0;
+
+/// The maximum size of the rendered image side in pixels.
+pub const MAX_IMAGE_SIZE: u32 = 16384;
+
+/// Returns the area the stitches are drawn in.
+/// It is a bit larger than the region, so the threads and the nodes crossing its border are drawn too.
+pub fn drawn_area(region: &Region) -> Bounds {
+  region.bounds().grown(1.0)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderOptions {
+  /// The zoom in percents.
+  pub zoom: u16,
+  /// The part of the pattern to render. The whole pattern is rendered if it is not specified.
+  pub region: Option<Region>,
+  pub view: View,
+}
+
+impl RenderOptions {
+  /// Creates the options that match the current view of the pattern.
+  pub fn from_display_settings(patproj: &PatternProject) -> Self {
+    Self {
+      zoom: patproj.display_settings.zoom,
+      region: None,
+      view: patproj.display_settings.view.clone(),
+    }
+  }
+
+  /// Returns the size of a single cell in pixels.
+  pub fn cell_size(&self) -> f32 {
+    PIXELS_PER_CELL * f32::from(self.zoom) / 100.0
+  }
+
+  /// Returns the region to render, clipped to the pattern bounds.
+  pub fn region(&self, patproj: &PatternProject) -> Result<Region> {
+    let properties = &patproj.pattern.properties;
+    let region = self.region.unwrap_or(Region {
+      x: 0,
+      y: 0,
+      width: properties.width,
+      height: properties.height,
+    });
+
+    let x = region.x.min(properties.width);
+    let y = region.y.min(properties.height);
+    let region = Region {
+      x,
+      y,
+      width: region.width.min(properties.width - x),
+      height: region.height.min(properties.height - y),
+    };
+
+    if region.width == 0 || region.height == 0 {
+      bail!("The region to render is empty");
+    }
+    Ok(region)
+  }
+
+  /// Returns the size of the rendered image in pixels.
+  /// The size is limited, since the raster image is kept in memory.
+  pub fn image_size(&self, region: &Region) -> Result<(u32, u32)> {
+    let (width, height) = self.scaled_size(region)?;
+    if width > MAX_IMAGE_SIZE || height > MAX_IMAGE_SIZE {
+      bail!("The image is too large ({width}x{height} pixels), decrease the zoom or the region to render");
+    }
+    Ok((width, height))
+  }
+
+  /// Returns the size of the region in pixels at the zoom.
+  pub fn scaled_size(&self, region: &Region) -> Result<(u32, u32)> {
+    if self.zoom == 0 {
+      bail!("The zoom must be greater than zero");
+    }
+
+    let cell_size = self.cell_size();
+    let width = (f32::from(region.width) * cell_size).ceil() as u32;
+    let height = (f32::from(region.height) * cell_size).ceil() as u32;
+    Ok((width.max(1), height.max(1)))
+  }
+}
@@ -0,0 +1,627 @@
+//! Walks through the pattern and draws it on a surface.
+//! The surfaces only know how to draw primitive shapes, so all the output formats look the same.
+
+use super::color::Rgb;
+use super::shapes::{self, LinePattern, Point};
+use super::{drawn_area, Region, PIXELS_PER_CELL};
+use crate::core::pattern::display::*;
+use crate::core::pattern::*;
+
+/// The width of a thread per unit of the stitch thickness in cells.
+const THREAD_THICKNESS_UNIT: f32 = 0.2;
+
+/// The diameter of a french knot per unit of the stitch thickness in cells.
+const KNOT_THICKNESS_UNIT: f32 = 0.15;
+
+/// The part of the symbol box that is left empty around the symbol.
+pub const SYMBOL_PADDING: f32 = 0.1;
+
+const MM_PER_INCH: f32 = 25.4;
+const POINTS_PER_INCH: f32 = 72.0;
+const PIXELS_PER_INCH: f32 = 96.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrokeStyle {
+  pub color: Rgb,
+  /// The width in cells.
+  pub width: f32,
+  pub pattern: LinePattern,
+}
+
+impl StrokeStyle {
+  pub fn solid(color: Rgb, width: f32) -> Self {
+    Self {
+      color,
+      width,
+      pattern: shapes::line_pattern(&LineStyle::Solid, width),
+    }
+  }
+
+  pub fn styled(color: Rgb, width: f32, style: &LineStyle) -> Self {
+    Self {
+      color,
+      width,
+      pattern: shapes::line_pattern(style, width),
+    }
+  }
+}
+
+/// The transformation of the special stitch model.
+/// It is applied in the following order: scale, rotate, translate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroupTransform {
+  pub translate: Point,
+  /// The rotation in degrees clockwise.
+  pub rotation: f32,
+  pub scale: (f32, f32),
+}
+
+/// A target to draw the pattern on.
+/// All the coordinates and sizes are in cells unless otherwise noted.
+pub trait Surface {
+  /// Fills the whole surface with the color.
+  fn fill(&mut self, color: Rgb);
+  fn fill_polygon(&mut self, points: &[Point], color: Rgb);
+  fn stroke_polyline(&mut self, points: &[Point], closed: bool, stroke: &StrokeStyle);
+  fn fill_circle(&mut self, center: Point, radius: f32, color: Rgb);
+  fn stroke_circle(&mut self, center: Point, radius: f32, stroke: &StrokeStyle);
+  fn fill_rounded_rect(&mut self, center: Point, size: (f32, f32), radius: f32, color: Rgb);
+  fn stroke_rounded_rect(&mut self, center: Point, size: (f32, f32), radius: f32, stroke: &StrokeStyle);
+
+  /// Draws the grid lines. Their width is in pixels at 100% zoom.
+  fn draw_grid_lines(&mut self, lines: &[(Point, Point)], color: Rgb, width: f32);
+
+  /// Checks if the symbol can be drawn, so the stitch is drawn as is otherwise.
+  fn has_symbol(&self, symbol: char, font: &str) -> bool;
+  /// Draws the symbol centered in the square of the given size.
+  fn draw_symbol(&mut self, symbol: char, font: &str, center: Point, size: f32, color: Rgb);
+
+  fn push_transform(&mut self, transform: GroupTransform);
+  fn pop_transform(&mut self);
+}
+
+/// Draws the pattern region on the surface in the given view.
+pub fn paint<S: Surface>(surface: &mut S, patproj: &PatternProject, region: Region, view: &View) {
+  let mut painter = Painter {
+    surface,
+    patproj,
+    region,
+    default_symbols: &Symbols::default(),
+    default_formats: &Formats::default(),
+  };
+
+  match view {
+    View::Stitches => painter.paint_stitches_view(),
+    View::Symbols => painter.paint_symbols_view(),
+    View::Solid | View::Information | View::MachineEmbInfo => painter.paint_solid_view(),
+  }
+}
+
+struct Painter<'a, S: Surface> {
+  surface: &'a mut S,
+  patproj: &'a PatternProject,
+  region: Region,
+  /// The symbols and formats of the palette items that have no display settings.
+  default_symbols: &'a Symbols,
+  default_formats: &'a Formats,
+}
+
+impl<'a, S: Surface> Painter<'a, S> {
+  fn paint_solid_view(&mut self) {
+    let patproj = self.patproj;
+    self.surface.fill(Rgb::from_hex_or_black(&patproj.pattern.fabric.color));
+
+    for fullstitch in patproj.pattern.fullstitches.intersecting(&drawn_area(&self.region)) {
+      if let Some(palitem) = self.palitem(fullstitch.palindex) {
+        self.paint_solid_shape(&shapes::fullstitch_shape(fullstitch), palitem);
+      }
+    }
+    for partstitch in patproj.pattern.partstitches.intersecting(&drawn_area(&self.region)) {
+      if let Some(palitem) = self.palitem(partstitch.palindex) {
+        self.paint_solid_shape(&shapes::partstitch_shape(partstitch), palitem);
+      }
+    }
+
+    self.paint_grid();
+    self.paint_special_stitches();
+    self.paint_lines(false);
+    self.paint_nodes(false);
+  }
+
+  fn paint_stitches_view(&mut self) {
+    let patproj = self.patproj;
+    self.surface.fill(Rgb::from_hex_or_black(&patproj.pattern.fabric.color));
+
+    let defaults = &patproj.display_settings.stitch_settings.default_strands;
+    for fullstitch in patproj.pattern.fullstitches.intersecting(&drawn_area(&self.region)) {
+      if let Some(palitem) = self.palitem(fullstitch.palindex) {
+        let strands = palitem.strands.as_ref();
+        let width = match fullstitch.kind {
+          FullStitchKind::Full => self.thread_width(strands.and_then(|s| s.full), defaults.full),
+          FullStitchKind::Petite => self.thread_width(strands.and_then(|s| s.petite), defaults.petite),
+        };
+        for thread in shapes::fullstitch_threads(fullstitch) {
+          self.paint_thread(thread, palitem, width);
+        }
+      }
+    }
+    for partstitch in patproj.pattern.partstitches.intersecting(&drawn_area(&self.region)) {
+      if let Some(palitem) = self.palitem(partstitch.palindex) {
+        let strands = palitem.strands.as_ref();
+        let width = match partstitch.kind {
+          PartStitchKind::Half => self.thread_width(strands.and_then(|s| s.half), defaults.half),
+          PartStitchKind::Quarter => self.thread_width(strands.and_then(|s| s.quarter), defaults.quarter),
+        };
+        self.paint_thread(shapes::partstitch_thread(partstitch), palitem, width);
+      }
+    }
+
+    self.paint_grid();
+    self.paint_special_stitches();
+    self.paint_lines(true);
+    self.paint_nodes(true);
+  }
+
+  fn paint_symbols_view(&mut self) {
+    let patproj = self.patproj;
+    let fabric_color = if patproj.display_settings.show_fabric_colors_with_symbols {
+      Rgb::from_hex_or_black(&patproj.pattern.fabric.color)
+    } else {
+      Rgb::WHITE
+    };
+    self.surface.fill(fabric_color);
+
+    // The symbol backgrounds are drawn first, so the grid is visible over them.
+    let symbols = self.collect_symbols();
+    for symbol in symbols.iter() {
+      if let Some((points, color)) = &symbol.background {
+        self.surface.fill_polygon(points, *color);
+      }
+    }
+
+    self.paint_grid();
+
+    let symbols_over_lines = patproj.display_settings.symbol_settings.draw_symbols_over_backstitches;
+    if !symbols_over_lines {
+      self.paint_symbols(&symbols);
+    }
+    self.paint_special_stitches();
+    self.paint_lines(false);
+    self.paint_nodes(false);
+    if symbols_over_lines {
+      self.paint_symbols(&symbols);
+    }
+  }
+
+  /// Resolves the symbols of the full and part stitches and their backgrounds.
+  fn collect_symbols(&self) -> Vec<StitchSymbol<'a>> {
+    let patproj = self.patproj;
+    let settings = &patproj.display_settings.symbol_settings;
+    let stitch_size = percentage(settings.stitch_size);
+    let small_stitch_size = percentage(settings.small_stitch_size);
+
+    let mut symbols = Vec::new();
+    let mut push =
+      |palindex: u8, shape: Vec<Point>, triangle: Option<Vec<Point>>, symbol: Option<u16>, center: Point, size: f32| {
+        let Some(palitem) = self.palitem(palindex) else {
+          return;
+        };
+        let formats = self.formats(palindex);
+        let palitem_color = Rgb::from_hex_or_black(&palitem.color);
+
+        let font = formats
+          .font
+          .font_name
+          .as_deref()
+          .unwrap_or(&patproj.display_settings.default_stitch_font);
+        let symbol = symbol
+          .and_then(|symbol| char::from_u32(u32::from(symbol)))
+          .filter(|&symbol| self.surface.has_symbol(symbol, font));
+        let Some(symbol) = symbol else {
+          // The stitch is drawn as is if it has no symbol.
+          symbols.push(StitchSymbol {
+            background: Some((shape, palitem_color)),
+            symbol: None,
+          });
+          return;
+        };
+
+        let format = &formats.symbol;
+        let (background, color) = if settings.show_stitch_color {
+          (Some((shape, palitem_color)), palitem_color.contrasting())
+        } else if format.use_alt_bg_color {
+          let background = Rgb::from_hex_or_black(&format.bg_color);
+          (Some((shape, background)), Rgb::from_hex_or_black(&format.fg_color))
+        } else {
+          let background = triangle.map(|triangle| (triangle, palitem_color));
+          (background, Rgb::from_hex_or_black(&format.fg_color))
+        };
+        symbols.push(StitchSymbol {
+          background,
+          symbol: Some((symbol, font, center, size, color)),
+        });
+      };
+
+    for fullstitch in patproj.pattern.fullstitches.intersecting(&drawn_area(&self.region)) {
+      let font = &self.formats(fullstitch.palindex).font;
+      let symbols = self.symbols(fullstitch.palindex);
+      let (x, y) = (fullstitch.x.into_inner(), fullstitch.y.into_inner());
+      let shape = shapes::fullstitch_shape(fullstitch);
+      match fullstitch.kind {
+        FullStitchKind::Full => {
+          let size = stitch_size * percentage(font.stitch_size);
+          push(fullstitch.palindex, shape, None, symbols.full, (x + 0.5, y + 0.5), size);
+        }
+        FullStitchKind::Petite => {
+          let size = 0.5 * small_stitch_size * percentage(font.small_stitch_size);
+          push(
+            fullstitch.palindex,
+            shape,
+            None,
+            symbols.petite,
+            (x + 0.25, y + 0.25),
+            size,
+          );
+        }
+      }
+    }
+
+    for partstitch in patproj.pattern.partstitches.intersecting(&drawn_area(&self.region)) {
+      let font = &self.formats(partstitch.palindex).font;
+      let symbols = self.symbols(partstitch.palindex);
+      let (x, y) = (partstitch.x.into_inner(), partstitch.y.into_inner());
+      let shape = shapes::partstitch_shape(partstitch);
+      match partstitch.kind {
+        PartStitchKind::Half => {
+          let size = if settings.use_large_half_stitch_symbol {
+            stitch_size * percentage(font.stitch_size)
+          } else {
+            small_stitch_size * percentage(font.small_stitch_size)
+          };
+          push(partstitch.palindex, shape, None, symbols.half, (x + 0.5, y + 0.5), size);
+        }
+        PartStitchKind::Quarter => {
+          let triangle = settings
+            .use_triangles_behind_quarter_stitches
+            .then(|| shapes::quarter_triangle(partstitch));
+          let size = 0.5 * small_stitch_size * percentage(font.small_stitch_size);
+          push(
+            partstitch.palindex,
+            shape,
+            triangle,
+            symbols.quarter,
+            (x + 0.25, y + 0.25),
+            size,
+          );
+        }
+      }
+    }
+
+    symbols
+  }
+
+  fn paint_symbols(&mut self, symbols: &[StitchSymbol]) {
+    for symbol in symbols {
+      if let Some((symbol, font, center, size, color)) = symbol.symbol {
+        self.surface.draw_symbol(symbol, font, center, size, color);
+      }
+    }
+  }
+
+  fn paint_solid_shape(&mut self, points: &[Point], palitem: &PaletteItem) {
+    let display_settings = &self.patproj.display_settings;
+    let color = Rgb::from_hex_or_black(&palitem.color);
+    let points = if display_settings.gaps_between_stitches {
+      shapes::inset(points, shapes::STITCH_GAP)
+    } else {
+      points.to_vec()
+    };
+
+    self.surface.fill_polygon(&points, color);
+    if display_settings.outlined_stitches {
+      let stroke = StrokeStyle::solid(self.outline_color(color), self.outline_width());
+      self.surface.stroke_polyline(&points, true, &stroke);
+    }
+  }
+
+  /// Draws a single thread of the full or part stitch as it looks on the fabric.
+  fn paint_thread(&mut self, thread: (Point, Point), palitem: &PaletteItem, width: f32) {
+    let display_settings = &self.patproj.display_settings;
+
+    // The round caps should not go beyond the cell.
+    let mut inset = width / 2.0;
+    if display_settings.gaps_between_stitches {
+      inset += shapes::STITCH_GAP;
+    }
+    let (start, end) = shapes::shorten(thread, inset);
+
+    let color = Rgb::from_hex_or_black(&palitem.color);
+    if display_settings.outlined_stitches {
+      let stroke = StrokeStyle::solid(self.outline_color(color), width + self.outline_width() * 2.0);
+      self.surface.stroke_polyline(&[start, end], false, &stroke);
+    }
+    self
+      .surface
+      .stroke_polyline(&[start, end], false, &StrokeStyle::solid(color, width));
+  }
+
+  fn paint_grid(&mut self) {
+    let display_settings = &self.patproj.display_settings;
+    if !display_settings.show_grid {
+      return;
+    }
+
+    let grid = &display_settings.grid;
+    let major_every = grid.major_line_every_stitches.max(1);
+    let Region { x, y, width, height } = self.region;
+    let (left, top) = (f32::from(x), f32::from(y));
+    let (right, bottom) = (f32::from(x + width), f32::from(y + height));
+
+    for (style, is_major) in [(&grid.minor_screen_lines, false), (&grid.major_screen_lines, true)] {
+      let mut lines = Vec::new();
+      for column in x..=(x + width) {
+        if (column % major_every == 0) == is_major {
+          lines.push(((f32::from(column), top), (f32::from(column), bottom)));
+        }
+      }
+      for row in y..=(y + height) {
+        if (row % major_every == 0) == is_major {
+          lines.push(((left, f32::from(row)), (right, f32::from(row))));
+        }
+      }
+
+      // The grid thickness is set in points.
+      let width = style.thickness * PIXELS_PER_INCH / POINTS_PER_INCH;
+      self
+        .surface
+        .draw_grid_lines(&lines, Rgb::from_hex_or_black(&style.color), width);
+    }
+  }
+
+  /// Draws the lines.
+  /// If `realistic` is true, the lines are drawn as threads, otherwise, they are drawn using their line formats.
+  fn paint_lines(&mut self, realistic: bool) {
+    let patproj = self.patproj;
+    let defaults = &patproj.display_settings.stitch_settings.default_strands;
+    for line in patproj.pattern.lines.intersecting(&drawn_area(&self.region)) {
+      let Some(palitem) = self.palitem(line.palindex) else {
+        continue;
+      };
+      let start = (line.x.0.into_inner(), line.y.0.into_inner());
+      let end = (line.x.1.into_inner(), line.y.1.into_inner());
+
+      let strands = palitem.strands.as_ref();
+      let formats = self.formats(line.palindex);
+      let (width, format) = match line.kind {
+        LineKind::Back => (
+          self.thread_width(strands.and_then(|s| s.back), defaults.back),
+          &formats.back,
+        ),
+        LineKind::Straight => (
+          self.thread_width(strands.and_then(|s| s.straight), defaults.straight),
+          &formats.straight,
+        ),
+      };
+
+      if realistic {
+        self.paint_thread_line(&[start, end], Rgb::from_hex_or_black(&palitem.color), width);
+      } else {
+        self.paint_formatted_line(start, end, palitem, width, format);
+      }
+    }
+  }
+
+  /// Draws a polyline as a thread with a dark border, the same way the editor does.
+  fn paint_thread_line(&mut self, points: &[Point], color: Rgb, width: f32) {
+    self
+      .surface
+      .stroke_polyline(points, false, &StrokeStyle::solid(Rgb::BLACK, width * 1.125));
+    self
+      .surface
+      .stroke_polyline(points, false, &StrokeStyle::solid(color, width));
+  }
+
+  /// Draws a line using its line format.
+  /// The format thickness scales the thread width.
+  fn paint_formatted_line(&mut self, start: Point, end: Point, palitem: &PaletteItem, width: f32, format: &LineFormat) {
+    let color = if format.use_alt_color {
+      Rgb::from_hex_or_black(&format.color)
+    } else {
+      Rgb::from_hex_or_black(&palitem.color)
+    };
+    let width = width * format.thickness.into_inner();
+    let stroke = StrokeStyle::styled(color, width, &format.style);
+
+    match format.style {
+      LineStyle::ZigZag => {
+        let points = shapes::zigzag(start, end, width * 1.5, width * 4.0);
+        self.surface.stroke_polyline(&points, false, &stroke);
+      }
+      LineStyle::Outlined => {
+        let outline = StrokeStyle::solid(color.darken(50), width * 2.0);
+        self.surface.stroke_polyline(&[start, end], false, &outline);
+        self.surface.stroke_polyline(&[start, end], false, &stroke);
+      }
+      LineStyle::Zebra => {
+        let base = StrokeStyle::solid(color.darken(50), width);
+        self.surface.stroke_polyline(&[start, end], false, &base);
+        self.surface.stroke_polyline(&[start, end], false, &stroke);
+      }
+      LineStyle::Barred => {
+        self.surface.stroke_polyline(&[start, end], false, &stroke);
+        let bar = StrokeStyle::solid(color, width / 2.0);
+        for (bar_start, bar_end) in shapes::bars(start, end, width * 4.0, width * 4.0) {
+          self.surface.stroke_polyline(&[bar_start, bar_end], false, &bar);
+        }
+      }
+      _ => self.surface.stroke_polyline(&[start, end], false, &stroke),
+    }
+  }
+
+  /// Draws the french knots and beads.
+  /// If `realistic` is true, the nodes are drawn as they look on the fabric, otherwise, they are drawn using their node formats.
+  fn paint_nodes(&mut self, realistic: bool) {
+    let patproj = self.patproj;
+    for node in patproj.pattern.nodes.intersecting(&drawn_area(&self.region)) {
+      let Some(palitem) = self.palitem(node.palindex) else {
+        continue;
+      };
+      let center = (node.x.into_inner(), node.y.into_inner());
+      let formats = self.formats(node.palindex);
+      let format = match node.kind {
+        NodeKind::FrenchKnot => &formats.french,
+        NodeKind::Bead => &formats.bead,
+      };
+      let color = if !realistic && format.use_alt_color {
+        Rgb::from_hex_or_black(&format.color)
+      } else {
+        Rgb::from_hex_or_black(&palitem.color)
+      };
+
+      match node.kind {
+        NodeKind::FrenchKnot => self.paint_french_knot(center, color, realistic || format.use_dot_style, format),
+        NodeKind::Bead => self.paint_bead(center, node.rotated, palitem, color),
+      }
+    }
+  }
+
+  /// Draws the french knot as a dot or, if it is not `filled`, as a ring.
+  fn paint_french_knot(&mut self, center: Point, color: Rgb, filled: bool, format: &NodeFormat) {
+    let display_thickness = &self.patproj.display_settings.stitch_settings.display_thickness;
+    let radius = display_thickness[12].into_inner() * KNOT_THICKNESS_UNIT / 2.0;
+
+    if filled {
+      self.surface.fill_circle(center, radius, color);
+      let border = StrokeStyle::solid(Rgb::BLACK, 1.0 / PIXELS_PER_CELL);
+      self.surface.stroke_circle(center, radius, &border);
+    } else {
+      let ring = StrokeStyle::solid(color, format.thickness.into_inner() * THREAD_THICKNESS_UNIT / 2.0);
+      self.surface.stroke_circle(center, radius, &ring);
+    }
+  }
+
+  fn paint_bead(&mut self, center: Point, rotated: bool, palitem: &PaletteItem, color: Rgb) {
+    let (length, diameter) = shapes::bead_size(Some(palitem), self.patproj.pattern.fabric.spi);
+    let size = if rotated {
+      (diameter, length)
+    } else {
+      (length, diameter)
+    };
+    let radius = size.0.min(size.1) / 3.0;
+
+    self.surface.fill_rounded_rect(center, size, radius, color);
+    let border = StrokeStyle::solid(Rgb::BLACK, 1.0 / PIXELS_PER_CELL);
+    self.surface.stroke_rounded_rect(center, size, radius, &border);
+  }
+
+  fn paint_special_stitches(&mut self) {
+    let patproj = self.patproj;
+    let defaults = &patproj.display_settings.stitch_settings.default_strands;
+    for specialstitch in patproj.pattern.specialstitches.iter() {
+      let Some(palitem) = self.palitem(specialstitch.palindex) else {
+        continue;
+      };
+      let Some(model) = patproj
+        .pattern
+        .special_stitch_models
+        .get(specialstitch.modindex as usize)
+      else {
+        continue;
+      };
+
+      let formats = self.formats(specialstitch.palindex);
+      let color = if formats.special.use_alt_color {
+        Rgb::from_hex_or_black(&formats.special.color)
+      } else {
+        Rgb::from_hex_or_black(&palitem.color)
+      };
+      let strands = palitem.strands.as_ref().and_then(|s| s.special);
+      let width = self.thread_width(strands, defaults.special) * formats.special.thickness.into_inner();
+
+      let (flip_x, flip_y) = specialstitch.flip;
+      self.surface.push_transform(GroupTransform {
+        translate: (specialstitch.x.into_inner(), specialstitch.y.into_inner()),
+        rotation: f32::from(specialstitch.rotation.into_inner()),
+        scale: (if flip_x { -1.0 } else { 1.0 }, if flip_y { -1.0 } else { 1.0 }),
+      });
+
+      for line in model.lines.iter() {
+        let start = (line.x.0.into_inner(), line.y.0.into_inner());
+        let end = (line.x.1.into_inner(), line.y.1.into_inner());
+        self.paint_thread_line(&[start, end], color, width);
+      }
+      for curve in model.curves.iter() {
+        let points: Vec<Point> = curve
+          .points
+          .iter()
+          .map(|(x, y)| (x.into_inner(), y.into_inner()))
+          .collect();
+        self.paint_thread_line(&points, color, width);
+      }
+      for node in model.nodes.iter() {
+        let center = (node.x.into_inner(), node.y.into_inner());
+        self.paint_french_knot(center, color, true, &formats.french);
+      }
+
+      self.surface.pop_transform();
+    }
+  }
+
+  fn palitem(&self, palindex: u8) -> Option<&'a PaletteItem> {
+    self.patproj.pattern.palette.get(palindex as usize)
+  }
+
+  fn symbols(&self, palindex: u8) -> &'a Symbols {
+    self
+      .patproj
+      .display_settings
+      .symbols
+      .get(palindex as usize)
+      .unwrap_or(self.default_symbols)
+  }
+
+  fn formats(&self, palindex: u8) -> &'a Formats {
+    self
+      .patproj
+      .display_settings
+      .formats
+      .get(palindex as usize)
+      .unwrap_or(self.default_formats)
+  }
+
+  /// Returns the width of the thread in cells.
+  /// The palette item strands take precedence over the default ones.
+  fn thread_width(&self, strands: Option<StitchStrands>, default: StitchStrands) -> f32 {
+    let display_thickness = &self.patproj.display_settings.stitch_settings.display_thickness;
+    let strands = strands.unwrap_or(default).into_inner() as usize;
+    display_thickness[strands.clamp(1, 12) - 1].into_inner() * THREAD_THICKNESS_UNIT
+  }
+
+  /// Returns the stitch outline width in cells.
+  /// The outline thickness is set in millimeters, so it depends on the fabric count.
+  fn outline_width(&self) -> f32 {
+    let thickness = self.patproj.display_settings.stitch_outline.thickness.into_inner();
+    let spi = self.patproj.pattern.fabric.spi.0.max(1);
+    thickness * f32::from(spi) / MM_PER_INCH
+  }
+
+  fn outline_color(&self, stitch_color: Rgb) -> Rgb {
+    let outline = &self.patproj.display_settings.stitch_outline;
+    match outline.color.as_deref().and_then(Rgb::from_hex) {
+      Some(color) => color,
+      None => stitch_color.darken(outline.color_percentage.into_inner()),
+    }
+  }
+}
+
+/// The symbol of a full or part stitch that is ready to be drawn.
+struct StitchSymbol<'a> {
+  /// The shape and the color to fill behind the symbol.
+  background: Option<(Vec<Point>, Rgb)>,
+  /// The symbol, its font, center, size and color.
+  symbol: Option<(char, &'a str, Point, f32, Rgb)>,
+}
+
+fn percentage(percentage: Percentage) -> f32 {
+  f32::from(percentage.into_inner()) / 100.0
+}
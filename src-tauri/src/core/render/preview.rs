@@ -0,0 +1,492 @@
+//! The realistic preview of the finished piece.
+//! Unlike the chart views, it simulates the fabric weave and the threads, so the customer can see how the pattern looks when stitched.
+
+use anyhow::{Context, Result};
+use tiny_skia::{Color, FillRule, LineCap, Paint, Path, PathBuilder, Pixmap, Stroke, StrokeDash, Transform};
+
+use super::color::Rgb;
+use super::raster::{polyline_path, push_rounded_rect, rounded_rect_path};
+use super::shapes::{self, Point};
+use super::{drawn_area, Region, RenderOptions};
+use crate::core::pattern::*;
+
+#[cfg(test)]
+#[path = "preview.test.rs"]
+mod tests;
+
+/// The width of a single strand of the stranded cotton in millimeters.
+/// The strands flatten a little when they are stitched, so it is slightly wider than the real diameter.
+const STRAND_WIDTH_MM: f32 = 0.45;
+
+/// How much the neighbouring strands of a thread overlap.
+const STRAND_OVERLAP: f32 = 0.2;
+
+/// The number of the fabric threads per cell of the evenweave and linen fabrics.
+const THREADS_PER_CELL: u16 = 2;
+
+const MM_PER_INCH: f32 = 25.4;
+
+/// Renders the preview of the stitched pattern and encodes it as a PNG image.
+pub fn render_preview_png(patproj: &PatternProject, options: &RenderOptions) -> Result<Vec<u8>> {
+  let pixmap = render_preview(patproj, options)?;
+  pixmap.encode_png().context("Failed to encode the image")
+}
+
+/// Renders the preview of the stitched pattern to a bitmap.
+pub fn render_preview(patproj: &PatternProject, options: &RenderOptions) -> Result<Pixmap> {
+  log::trace!("Rendering the pattern preview");
+  let region = options.region(patproj)?;
+  let (width, height) = options.image_size(&region)?;
+
+  let cell_size = options.cell_size();
+  let mut preview = Preview {
+    pixmap: Pixmap::new(width, height).context("Failed to allocate the image")?,
+    transform: Transform::from_scale(cell_size, cell_size).pre_translate(-f32::from(region.x), -f32::from(region.y)),
+    patproj,
+    area: drawn_area(&region),
+    strand_width: strand_width(patproj.pattern.fabric.spi),
+  };
+
+  preview.draw_fabric(region);
+  preview.draw_fullstitches();
+  preview.draw_partstitches();
+  preview.draw_special_stitches();
+  preview.draw_lines();
+  preview.draw_nodes();
+
+  log::trace!("Pattern preview rendered");
+  Ok(preview.pixmap)
+}
+
+/// Returns the width of a single strand in cells.
+/// The finer the fabric, the thicker the threads look relative to the cells.
+fn strand_width((spi_x, spi_y): StitchesPerInch) -> f32 {
+  let spi = f32::from(spi_x.max(spi_y).max(1));
+  STRAND_WIDTH_MM * spi / MM_PER_INCH
+}
+
+/// The kind of the fabric weave.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Weave {
+  /// The threads are woven in blocks, with the distinct holes at the cell corners.
+  Aida,
+  /// The single threads are woven one over one, and the stitches go over two threads.
+  Evenweave,
+}
+
+impl Weave {
+  fn from_fabric_kind(kind: &str) -> Self {
+    let kind = kind.to_lowercase();
+    if ["linen", "evenweave", "lugana", "jobelan", "cashel"]
+      .iter()
+      .any(|name| kind.contains(name))
+    {
+      Weave::Evenweave
+    } else {
+      Weave::Aida
+    }
+  }
+}
+
+struct Preview<'a> {
+  pixmap: Pixmap,
+  /// Maps the pattern coordinates (in cells) to the image coordinates (in pixels).
+  transform: Transform,
+  patproj: &'a PatternProject,
+  /// Only the stitches in this area are drawn.
+  area: Bounds,
+  /// The width of a single strand in cells.
+  strand_width: f32,
+}
+
+impl Preview<'_> {
+  fn draw_fabric(&mut self, region: Region) {
+    let fabric = &self.patproj.pattern.fabric;
+    let color = Rgb::from_hex_or_black(&fabric.color);
+    let Region { x, y, width, height } = region;
+    let (left, top) = (f32::from(x), f32::from(y));
+    let (right, bottom) = (f32::from(x + width), f32::from(y + height));
+
+    match Weave::from_fabric_kind(&fabric.kind) {
+      Weave::Aida => {
+        self.pixmap.fill(to_color(color.darken(85), 1.0));
+
+        // The blocks of the threads.
+        let mut blocks = PathBuilder::new();
+        for row in y..(y + height) {
+          for column in x..(x + width) {
+            push_rounded_rect(
+              &mut blocks,
+              (f32::from(column) + 0.5, f32::from(row) + 0.5),
+              (0.84, 0.84),
+              0.25,
+            );
+          }
+        }
+        self.fill(blocks.finish(), color, 1.0);
+
+        // The texture of the threads in the blocks.
+        let mut texture = PathBuilder::new();
+        for row in y..(y + height) {
+          for column in x..(x + width) {
+            let (cx, cy) = (f32::from(column) + 0.5, f32::from(row) + 0.5);
+            texture.move_to(cx - 0.3, cy);
+            texture.line_to(cx + 0.3, cy);
+            texture.move_to(cx, cy - 0.3);
+            texture.line_to(cx, cy + 0.3);
+          }
+        }
+        self.stroke(texture.finish(), color.lighten(25), 0.5, 0.12, LineCap::Round, None);
+      }
+      Weave::Evenweave => {
+        self.pixmap.fill(to_color(color.darken(80), 1.0));
+
+        // The warp and weft threads go over and under each other.
+        let spacing = 1.0 / f32::from(THREADS_PER_CELL);
+        let dash = StrokeDash::new(vec![spacing, spacing], 0.0);
+        let mut warp = PathBuilder::new();
+        let mut weft = PathBuilder::new();
+        for thread in 0..((width + 1) * THREADS_PER_CELL) {
+          let position = left + f32::from(thread) * spacing + spacing / 2.0;
+          warp.move_to(position, top);
+          warp.line_to(position, bottom);
+        }
+        for thread in 0..((height + 1) * THREADS_PER_CELL) {
+          let position = top + f32::from(thread) * spacing + spacing / 2.0;
+          weft.move_to(left, position);
+          weft.line_to(right, position);
+        }
+        let (warp, weft) = (warp.finish(), weft.finish());
+
+        // The threads are drawn fully, then the dashes go over the crossings to make the weave.
+        self.stroke(warp.clone(), color.darken(95), 1.0, spacing * 0.7, LineCap::Butt, None);
+        self.stroke(weft.clone(), color, 1.0, spacing * 0.7, LineCap::Butt, None);
+        self.stroke(warp, color, 1.0, spacing * 0.7, LineCap::Butt, dash);
+      }
+    }
+  }
+
+  fn draw_fullstitches(&mut self) {
+    let patproj = self.patproj;
+    let defaults = &patproj.display_settings.stitch_settings.default_strands;
+    for fullstitch in patproj.pattern.fullstitches.intersecting(&self.area) {
+      let Some(palitem) = patproj.pattern.palette.get(fullstitch.palindex as usize) else {
+        continue;
+      };
+      let strands = palitem.strands.as_ref();
+      let strands = match fullstitch.kind {
+        FullStitchKind::Full => strands.and_then(|s| s.full).unwrap_or(defaults.full),
+        FullStitchKind::Petite => strands.and_then(|s| s.petite).unwrap_or(defaults.petite),
+      };
+      // The bottom thread goes first.
+      for thread in shapes::fullstitch_threads(fullstitch) {
+        self.draw_thread(&[thread.0, thread.1], palitem, strands);
+      }
+    }
+  }
+
+  fn draw_partstitches(&mut self) {
+    let patproj = self.patproj;
+    let defaults = &patproj.display_settings.stitch_settings.default_strands;
+    for partstitch in patproj.pattern.partstitches.intersecting(&self.area) {
+      let Some(palitem) = patproj.pattern.palette.get(partstitch.palindex as usize) else {
+        continue;
+      };
+      let strands = palitem.strands.as_ref();
+      let strands = match partstitch.kind {
+        PartStitchKind::Half => strands.and_then(|s| s.half).unwrap_or(defaults.half),
+        PartStitchKind::Quarter => strands.and_then(|s| s.quarter).unwrap_or(defaults.quarter),
+      };
+      let (start, end) = shapes::partstitch_thread(partstitch);
+      self.draw_thread(&[start, end], palitem, strands);
+    }
+  }
+
+  fn draw_lines(&mut self) {
+    let patproj = self.patproj;
+    let defaults = &patproj.display_settings.stitch_settings.default_strands;
+    for line in patproj.pattern.lines.intersecting(&self.area) {
+      let Some(palitem) = patproj.pattern.palette.get(line.palindex as usize) else {
+        continue;
+      };
+      let strands = palitem.strands.as_ref();
+      let strands = match line.kind {
+        LineKind::Back => strands.and_then(|s| s.back).unwrap_or(defaults.back),
+        LineKind::Straight => strands.and_then(|s| s.straight).unwrap_or(defaults.straight),
+      };
+      let start = (line.x.0.into_inner(), line.y.0.into_inner());
+      let end = (line.x.1.into_inner(), line.y.1.into_inner());
+      self.draw_thread(&[start, end], palitem, strands);
+    }
+  }
+
+  fn draw_nodes(&mut self) {
+    let patproj = self.patproj;
+    let defaults = &patproj.display_settings.stitch_settings.default_strands;
+    for node in patproj.pattern.nodes.intersecting(&self.area) {
+      let Some(palitem) = patproj.pattern.palette.get(node.palindex as usize) else {
+        continue;
+      };
+      let center = (node.x.into_inner(), node.y.into_inner());
+      match node.kind {
+        NodeKind::FrenchKnot => {
+          let strands = palitem.strands.as_ref().and_then(|s| s.french_knot);
+          self.draw_french_knot(center, palitem, strands.unwrap_or(defaults.french_knot));
+        }
+        NodeKind::Bead => self.draw_bead(center, node.rotated, palitem),
+      }
+    }
+  }
+
+  fn draw_special_stitches(&mut self) {
+    let patproj = self.patproj;
+    let defaults = &patproj.display_settings.stitch_settings.default_strands;
+    let base_transform = self.transform;
+    for specialstitch in patproj.pattern.specialstitches.iter() {
+      let Some(palitem) = patproj.pattern.palette.get(specialstitch.palindex as usize) else {
+        continue;
+      };
+      let Some(model) = patproj
+        .pattern
+        .special_stitch_models
+        .get(specialstitch.modindex as usize)
+      else {
+        continue;
+      };
+      let strands = palitem
+        .strands
+        .as_ref()
+        .and_then(|s| s.special)
+        .unwrap_or(defaults.special);
+
+      let (flip_x, flip_y) = specialstitch.flip;
+      self.transform = base_transform
+        .pre_translate(specialstitch.x.into_inner(), specialstitch.y.into_inner())
+        .pre_rotate(f32::from(specialstitch.rotation.into_inner()))
+        .pre_scale(if flip_x { -1.0 } else { 1.0 }, if flip_y { -1.0 } else { 1.0 });
+
+      for line in model.lines.iter() {
+        let start = (line.x.0.into_inner(), line.y.0.into_inner());
+        let end = (line.x.1.into_inner(), line.y.1.into_inner());
+        self.draw_thread(&[start, end], palitem, strands);
+      }
+      for curve in model.curves.iter() {
+        let points: Vec<Point> = curve
+          .points
+          .iter()
+          .map(|(x, y)| (x.into_inner(), y.into_inner()))
+          .collect();
+        self.draw_thread(&points, palitem, strands);
+      }
+      for node in model.nodes.iter() {
+        self.draw_french_knot((node.x.into_inner(), node.y.into_inner()), palitem, strands);
+      }
+    }
+    self.transform = base_transform;
+  }
+
+  /// Draws a thread that goes through the points.
+  /// Every strand is drawn separately with its own shading and twist, so the thread looks like a real one.
+  fn draw_thread(&mut self, points: &[Point], palitem: &PaletteItem, strands: StitchStrands) {
+    let color = Rgb::from_hex_or_black(&palitem.color);
+    let strands = strands.into_inner();
+    let strand_width = self.strand_width;
+    let spread = strand_width * (1.0 - STRAND_OVERLAP);
+    let width = strand_width + spread * f32::from(strands - 1);
+
+    // The threads go into the fabric holes, so their ends are hidden a bit.
+    let points = shorten_polyline(points, strand_width / 2.0);
+
+    // The shadow that the thread casts on the fabric.
+    let shadow = offset_polyline(&points, (width * 0.15, width * 0.2));
+    self.stroke(
+      polyline_path(&shadow, false),
+      Rgb::BLACK,
+      0.25,
+      width,
+      LineCap::Round,
+      None,
+    );
+
+    for strand in 0..strands {
+      let offset = (f32::from(strand) - f32::from(strands - 1) / 2.0) * spread;
+      let Some(normal) = polyline_normal(&points) else {
+        continue;
+      };
+      let strand_points = offset_polyline(&points, (normal.0 * offset, normal.1 * offset));
+
+      // The edges of the strand are in the shadow.
+      self.stroke(
+        polyline_path(&strand_points, false),
+        color.darken(70),
+        1.0,
+        strand_width,
+        LineCap::Round,
+        None,
+      );
+      self.stroke(
+        polyline_path(&strand_points, false),
+        color,
+        1.0,
+        strand_width * 0.7,
+        LineCap::Round,
+        None,
+      );
+
+      // The light falls from the top left corner.
+      let highlight = offset_polyline(&strand_points, (-strand_width * 0.12, -strand_width * 0.12));
+      self.stroke(
+        polyline_path(&highlight, false),
+        color.lighten(35),
+        0.8,
+        strand_width * 0.25,
+        LineCap::Round,
+        None,
+      );
+
+      // The plies of the strand are twisted, which is seen as the short slanted stripes.
+      let twist = StrokeDash::new(
+        vec![strand_width * 0.3, strand_width * 0.5],
+        f32::from(strand) * strand_width * 0.4,
+      );
+      self.stroke(
+        polyline_path(&strand_points, false),
+        color.darken(75),
+        0.5,
+        strand_width * 0.8,
+        LineCap::Butt,
+        twist,
+      );
+    }
+  }
+
+  fn draw_french_knot(&mut self, center: Point, palitem: &PaletteItem, strands: StitchStrands) {
+    let color = Rgb::from_hex_or_black(&palitem.color);
+    let strands = f32::from(strands.into_inner());
+    // A knot is made of two wraps, so it is about twice as thick as the thread.
+    let radius = (self.strand_width * strands).max(self.strand_width * 2.0);
+
+    let shadow = (center.0 + radius * 0.2, center.1 + radius * 0.25);
+    self.fill(PathBuilder::from_circle(shadow.0, shadow.1, radius), Rgb::BLACK, 0.3);
+    self.fill(
+      PathBuilder::from_circle(center.0, center.1, radius),
+      color.darken(65),
+      1.0,
+    );
+
+    // The wraps of the thread around the center.
+    const WRAPS: usize = 6;
+    for wrap in 0..WRAPS {
+      let angle = std::f32::consts::TAU * wrap as f32 / WRAPS as f32;
+      let (x, y) = (
+        center.0 + angle.cos() * radius * 0.45,
+        center.1 + angle.sin() * radius * 0.45,
+      );
+      self.fill(PathBuilder::from_circle(x, y, radius * 0.4), color, 1.0);
+    }
+    self.fill(
+      PathBuilder::from_circle(center.0 - radius * 0.2, center.1 - radius * 0.2, radius * 0.25),
+      color.lighten(35),
+      0.8,
+    );
+  }
+
+  fn draw_bead(&mut self, center: Point, rotated: bool, palitem: &PaletteItem) {
+    let color = Rgb::from_hex_or_black(&palitem.color);
+    let (length, diameter) = shapes::bead_size(Some(palitem), self.patproj.pattern.fabric.spi);
+    let size = if rotated {
+      (diameter, length)
+    } else {
+      (length, diameter)
+    };
+    let radius = size.0.min(size.1) / 2.0;
+
+    let shadow = (center.0 + radius * 0.2, center.1 + radius * 0.25);
+    self.fill(rounded_rect_path(shadow, size, radius), Rgb::BLACK, 0.3);
+    self.fill(rounded_rect_path(center, size, radius), color.darken(70), 1.0);
+    let body = (size.0 * 0.85, size.1 * 0.85);
+    self.fill(rounded_rect_path(center, body, radius * 0.85), color, 1.0);
+
+    // The glass glare.
+    let glare = (center.0 - size.0 * 0.2, center.1 - size.1 * 0.2);
+    self.fill(
+      PathBuilder::from_circle(glare.0, glare.1, radius * 0.25),
+      Rgb::WHITE,
+      0.7,
+    );
+  }
+
+  fn fill(&mut self, path: Option<Path>, color: Rgb, opacity: f32) {
+    if let Some(path) = path {
+      let paint = paint(color, opacity);
+      self
+        .pixmap
+        .fill_path(&path, &paint, FillRule::Winding, self.transform, None);
+    }
+  }
+
+  fn stroke(
+    &mut self,
+    path: Option<Path>,
+    color: Rgb,
+    opacity: f32,
+    width: f32,
+    line_cap: LineCap,
+    dash: Option<StrokeDash>,
+  ) {
+    if let Some(path) = path {
+      let stroke = Stroke {
+        width,
+        line_cap,
+        dash,
+        ..Stroke::default()
+      };
+      let paint = paint(color, opacity);
+      self.pixmap.stroke_path(&path, &paint, &stroke, self.transform, None);
+    }
+  }
+}
+
+fn to_color(color: Rgb, opacity: f32) -> Color {
+  Color::from_rgba8(
+    color.0,
+    color.1,
+    color.2,
+    (opacity.clamp(0.0, 1.0) * 255.0).round() as u8,
+  )
+}
+
+fn paint(color: Rgb, opacity: f32) -> Paint<'static> {
+  let mut paint = Paint::default();
+  paint.set_color(to_color(color, opacity));
+  paint.anti_alias = true;
+  paint
+}
+
+/// Moves the first and the last points of the polyline inwards by the given amount.
+fn shorten_polyline(points: &[Point], amount: f32) -> Vec<Point> {
+  let mut points = points.to_vec();
+  if let [first, second, ..] = points[..] {
+    points[0] = shapes::shorten((first, second), amount).0;
+  }
+  if let [.., before_last, last] = points[..] {
+    let index = points.len() - 1;
+    points[index] = shapes::shorten((before_last, last), amount).1;
+  }
+  points
+}
+
+fn offset_polyline(points: &[Point], (dx, dy): Point) -> Vec<Point> {
+  points.iter().map(|&(x, y)| (x + dx, y + dy)).collect()
+}
+
+/// Returns the unit normal of the polyline direction from its first point to the last one.
+fn polyline_normal(points: &[Point]) -> Option<Point> {
+  let (first, last) = (points.first()?, points.last()?);
+  let (dx, dy) = (last.0 - first.0, last.1 - first.1);
+  let length = (dx * dx + dy * dy).sqrt();
+  if length == 0.0 {
+    return None;
+  }
+  Some((-dy / length, dx / length))
+}
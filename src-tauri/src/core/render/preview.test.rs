@@ -0,0 +1,159 @@
+use ordered_float::NotNan;
+
+use super::*;
+use crate::core::pattern::display::*;
+use crate::core::render::Region;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+fn create_pattern_project(fabric_kind: &str) -> PatternProject {
+  let mut pattern = Pattern {
+    properties: PatternProperties { width: 10, height: 5 },
+    fabric: Fabric {
+      kind: String::from(fabric_kind),
+      color: String::from("FFFFFF"),
+      ..Fabric::default()
+    },
+    palette: vec![PaletteItem {
+      brand: String::from("DMC"),
+      number: String::from("321"),
+      name: String::from("Red"),
+      color: String::from("FF0000"),
+      blends: None,
+      bead: None,
+      strands: None,
+    }],
+    ..Pattern::default()
+  };
+  pattern.add_stitches(vec![
+    Stitch::Full(FullStitch {
+      x: NotNan::new(1.0).unwrap(),
+      y: NotNan::new(1.0).unwrap(),
+      palindex: 0,
+      kind: FullStitchKind::Full,
+    }),
+    Stitch::Line(Line {
+      x: (NotNan::new(0.0).unwrap(), NotNan::new(5.0).unwrap()),
+      y: (NotNan::new(4.0).unwrap(), NotNan::new(4.0).unwrap()),
+      palindex: 0,
+      kind: LineKind::Back,
+    }),
+    Stitch::Node(Node {
+      x: NotNan::new(7.0).unwrap(),
+      y: NotNan::new(2.0).unwrap(),
+      rotated: false,
+      palindex: 0,
+      kind: NodeKind::FrenchKnot,
+    }),
+    Stitch::Node(Node {
+      x: NotNan::new(8.0).unwrap(),
+      y: NotNan::new(2.0).unwrap(),
+      rotated: true,
+      palindex: 0,
+      kind: NodeKind::Bead,
+    }),
+  ]);
+
+  PatternProject {
+    pattern,
+    display_settings: DisplaySettings::new(1),
+    ..PatternProject::default()
+  }
+}
+
+fn options(zoom: u16) -> RenderOptions {
+  RenderOptions {
+    zoom,
+    region: None,
+    view: View::Solid,
+  }
+}
+
+fn pixel(pixmap: &Pixmap, x: u32, y: u32) -> (u8, u8, u8) {
+  let pixel = pixmap.pixel(x, y).unwrap();
+  (pixel.red(), pixel.green(), pixel.blue())
+}
+
+/// Returns whether the pixel is reddish, that is, it belongs to the thread.
+fn is_thread(pixel: (u8, u8, u8)) -> bool {
+  let (red, green, blue) = pixel;
+  red > 100 && green < red / 2 && blue < red / 2
+}
+
+#[test]
+fn renders_png() {
+  let patproj = create_pattern_project("Aida");
+  let png = render_preview_png(&patproj, &options(100)).unwrap();
+  assert!(png.starts_with(PNG_SIGNATURE));
+
+  let options = RenderOptions {
+    zoom: 200,
+    region: Some(Region { x: 0, y: 0, width: 4, height: 2 }),
+    view: View::Solid,
+  };
+  let pixmap = render_preview(&patproj, &options).unwrap();
+  assert_eq!((pixmap.width(), pixmap.height()), (80, 40));
+}
+
+#[test]
+fn renders_fabric() {
+  let patproj = create_pattern_project("Aida");
+  let pixmap = render_preview(&patproj, &options(400)).unwrap();
+
+  // The middle of an empty Aida block is close to the fabric color, while its corners are the darker holes.
+  let (red, green, blue) = pixel(&pixmap, 140 + 6, 20 + 6);
+  assert!(red > 200 && green > 200 && blue > 200);
+  let (red, green, blue) = pixel(&pixmap, 200, 40);
+  assert!(red < 255 && red == green && green == blue);
+}
+
+#[test]
+fn renders_weaves_differently() {
+  let aida = render_preview(&create_pattern_project("Aida"), &options(400)).unwrap();
+  let linen = render_preview(&create_pattern_project("Linen"), &options(400)).unwrap();
+  assert_ne!(aida.data(), linen.data());
+}
+
+#[test]
+fn renders_stitches_as_threads() {
+  let patproj = create_pattern_project("Aida");
+  let pixmap = render_preview(&patproj, &options(400)).unwrap();
+
+  // The center of the cross stitch.
+  assert!(is_thread(pixel(&pixmap, 60, 60)));
+  // The backstitch.
+  assert!(is_thread(pixel(&pixmap, 100, 160)));
+  // The french knot and the bead.
+  assert!(is_thread(pixel(&pixmap, 280, 80)));
+  assert!(is_thread(pixel(&pixmap, 320 + 2, 80 + 2)));
+}
+
+#[test]
+fn scales_threads_with_strands() {
+  let mut patproj = create_pattern_project("Aida");
+  let count_thread_pixels = |patproj: &PatternProject| {
+    let pixmap = render_preview(patproj, &options(400)).unwrap();
+    (0..40).filter(|&x| is_thread(pixel(&pixmap, 40 + x, 60))).count()
+  };
+
+  patproj.display_settings.stitch_settings.default_strands.full = StitchStrands::new(1);
+  let thin = count_thread_pixels(&patproj);
+  patproj.display_settings.stitch_settings.default_strands.full = StitchStrands::new(4);
+  let thick = count_thread_pixels(&patproj);
+  assert!(thick > thin);
+}
+
+#[test]
+fn renders_region() {
+  let patproj = create_pattern_project("Aida");
+  let options = RenderOptions {
+    region: Some(Region { x: 5, y: 0, width: 5, height: 5 }),
+    ..options(400)
+  };
+  let pixmap = render_preview(&patproj, &options).unwrap();
+
+  // The french knot and the bead are shifted with the region, while the cross stitch is outside it.
+  assert!(is_thread(pixel(&pixmap, 80, 80)));
+  assert!(is_thread(pixel(&pixmap, 120 + 2, 80 + 2)));
+  assert!(!is_thread(pixel(&pixmap, 10, 60)));
+}
@@ -0,0 +1,233 @@
+use anyhow::{Context, Result};
+use tiny_skia::{Color, FillRule, LineCap, LineJoin, Paint, Path, PathBuilder, Pixmap, Stroke, StrokeDash, Transform};
+
+use super::color::Rgb;
+use super::painter::{self, GroupTransform, StrokeStyle, Surface, SYMBOL_PADDING};
+use super::shapes::Point;
+use super::RenderOptions;
+use crate::core::fonts::{FontRegistry, GlyphSegment};
+use crate::core::pattern::PatternProject;
+
+#[cfg(test)]
+#[path = "raster.test.rs"]
+mod tests;
+
+/// Renders the pattern and encodes it as a PNG image.
+pub fn render_png(patproj: &PatternProject, options: &RenderOptions, fonts: &FontRegistry) -> Result<Vec<u8>> {
+  let pixmap = render(patproj, options, fonts)?;
+  pixmap.encode_png().context("Failed to encode the image")
+}
+
+/// Renders the pattern to a bitmap.
+pub fn render(patproj: &PatternProject, options: &RenderOptions, fonts: &FontRegistry) -> Result<Pixmap> {
+  log::trace!("Rendering the pattern");
+  let region = options.region(patproj)?;
+  let (width, height) = options.image_size(&region)?;
+
+  let cell_size = options.cell_size();
+  let mut surface = RasterSurface {
+    pixmap: Pixmap::new(width, height).context("Failed to allocate the image")?,
+    transform: Transform::from_scale(cell_size, cell_size).pre_translate(-f32::from(region.x), -f32::from(region.y)),
+    transforms: Vec::new(),
+    cell_size,
+    fonts,
+  };
+  painter::paint(&mut surface, patproj, region, &options.view);
+
+  log::trace!("Pattern rendered");
+  Ok(surface.pixmap)
+}
+
+struct RasterSurface<'a> {
+  pixmap: Pixmap,
+  /// Maps the pattern coordinates (in cells) to the image coordinates (in pixels).
+  transform: Transform,
+  /// The transforms that were active before the current group.
+  transforms: Vec<Transform>,
+  /// The size of a cell in pixels.
+  cell_size: f32,
+  fonts: &'a FontRegistry,
+}
+
+impl RasterSurface<'_> {
+  fn fill_path(&mut self, path: Option<Path>, color: Rgb) {
+    if let Some(path) = path {
+      self
+        .pixmap
+        .fill_path(&path, &paint(color), FillRule::Winding, self.transform, None);
+    }
+  }
+
+  /// Strokes the path. The stroke is never thinner than a pixel.
+  fn stroke_path(&mut self, path: Option<Path>, stroke: &StrokeStyle) {
+    let Some(path) = path else {
+      return;
+    };
+    let (color, pattern) = (stroke.color, &stroke.pattern);
+    let stroke = Stroke {
+      width: stroke.width.max(1.0 / self.cell_size),
+      line_cap: if pattern.round_caps {
+        LineCap::Round
+      } else {
+        LineCap::Butt
+      },
+      line_join: LineJoin::Round,
+      dash: pattern.dash.clone().and_then(|dash| StrokeDash::new(dash, 0.0)),
+      ..Stroke::default()
+    };
+    self
+      .pixmap
+      .stroke_path(&path, &paint(color), &stroke, self.transform, None);
+  }
+
+  /// Builds the path of the symbol glyph that fits the square of the given size.
+  fn glyph_path(&self, symbol: char, font: &str, center: Point, size: f32) -> Option<Path> {
+    let glyph = self.fonts.get_or_default(font).glyph(symbol)?;
+    let size = size * (1.0 - SYMBOL_PADDING * 2.0);
+    let map = |(x, y): (f32, f32)| (center.0 + x * size, center.1 + y * size);
+
+    let mut builder = PathBuilder::new();
+    for segment in glyph {
+      match segment {
+        GlyphSegment::MoveTo(point) => {
+          let (x, y) = map(point);
+          builder.move_to(x, y);
+        }
+        GlyphSegment::LineTo(point) => {
+          let (x, y) = map(point);
+          builder.line_to(x, y);
+        }
+        GlyphSegment::QuadTo(control, point) => {
+          let ((x1, y1), (x, y)) = (map(control), map(point));
+          builder.quad_to(x1, y1, x, y);
+        }
+        GlyphSegment::CubicTo(control1, control2, point) => {
+          let ((x1, y1), (x2, y2), (x, y)) = (map(control1), map(control2), map(point));
+          builder.cubic_to(x1, y1, x2, y2, x, y);
+        }
+        GlyphSegment::Close => builder.close(),
+      }
+    }
+    builder.finish()
+  }
+}
+
+impl Surface for RasterSurface<'_> {
+  fn fill(&mut self, color: Rgb) {
+    self.pixmap.fill(Color::from_rgba8(color.0, color.1, color.2, 255));
+  }
+
+  fn fill_polygon(&mut self, points: &[Point], color: Rgb) {
+    self.fill_path(polyline_path(points, true), color);
+  }
+
+  fn stroke_polyline(&mut self, points: &[Point], closed: bool, stroke: &StrokeStyle) {
+    self.stroke_path(polyline_path(points, closed), stroke);
+  }
+
+  fn fill_circle(&mut self, center: Point, radius: f32, color: Rgb) {
+    self.fill_path(PathBuilder::from_circle(center.0, center.1, radius), color);
+  }
+
+  fn stroke_circle(&mut self, center: Point, radius: f32, stroke: &StrokeStyle) {
+    self.stroke_path(PathBuilder::from_circle(center.0, center.1, radius), stroke);
+  }
+
+  fn fill_rounded_rect(&mut self, center: Point, size: (f32, f32), radius: f32, color: Rgb) {
+    self.fill_path(rounded_rect_path(center, size, radius), color);
+  }
+
+  fn stroke_rounded_rect(&mut self, center: Point, size: (f32, f32), radius: f32, stroke: &StrokeStyle) {
+    self.stroke_path(rounded_rect_path(center, size, radius), stroke);
+  }
+
+  fn draw_grid_lines(&mut self, lines: &[(Point, Point)], color: Rgb, width: f32) {
+    // The grid is drawn in the image coordinates, so its lines are crisp.
+    let width = (width * self.cell_size / super::PIXELS_PER_CELL).max(1.0).round();
+    // The odd-width lines are centered on the pixel centers, and the border lines are kept inside the image.
+    let offset = if width as u32 % 2 == 1 { 0.5 } else { 0.0 };
+    let snap = |value: f32, size: u32| (value.round() + offset).clamp(width / 2.0, size as f32 - width / 2.0);
+    let (image_width, image_height) = (self.pixmap.width(), self.pixmap.height());
+
+    let mut builder = PathBuilder::new();
+    for &(start, end) in lines {
+      let mut points = [start.into(), end.into()];
+      self.transform.map_points(&mut points);
+      builder.move_to(snap(points[0].x, image_width), snap(points[0].y, image_height));
+      builder.line_to(snap(points[1].x, image_width), snap(points[1].y, image_height));
+    }
+
+    if let Some(path) = builder.finish() {
+      let stroke = Stroke { width, ..Stroke::default() };
+      self
+        .pixmap
+        .stroke_path(&path, &paint(color), &stroke, Transform::identity(), None);
+    }
+  }
+
+  fn has_symbol(&self, symbol: char, font: &str) -> bool {
+    self.fonts.get_or_default(font).has_glyph(symbol)
+  }
+
+  fn draw_symbol(&mut self, symbol: char, font: &str, center: Point, size: f32, color: Rgb) {
+    let path = self.glyph_path(symbol, font, center, size);
+    self.fill_path(path, color);
+  }
+
+  fn push_transform(&mut self, transform: GroupTransform) {
+    self.transforms.push(self.transform);
+    self.transform = self
+      .transform
+      .pre_translate(transform.translate.0, transform.translate.1)
+      .pre_rotate(transform.rotation)
+      .pre_scale(transform.scale.0, transform.scale.1);
+  }
+
+  fn pop_transform(&mut self) {
+    if let Some(transform) = self.transforms.pop() {
+      self.transform = transform;
+    }
+  }
+}
+
+fn paint(color: Rgb) -> Paint<'static> {
+  let mut paint = Paint::default();
+  paint.set_color_rgba8(color.0, color.1, color.2, 255);
+  paint.anti_alias = true;
+  paint
+}
+
+pub fn polyline_path(points: &[Point], closed: bool) -> Option<Path> {
+  let (&(x, y), rest) = points.split_first()?;
+  let mut builder = PathBuilder::new();
+  builder.move_to(x, y);
+  for &(x, y) in rest {
+    builder.line_to(x, y);
+  }
+  if closed {
+    builder.close();
+  }
+  builder.finish()
+}
+
+pub fn rounded_rect_path(center: Point, size: (f32, f32), radius: f32) -> Option<Path> {
+  let mut builder = PathBuilder::new();
+  push_rounded_rect(&mut builder, center, size, radius);
+  builder.finish()
+}
+
+/// Adds the rounded rectangle to the path, so many of them can be drawn at once.
+pub fn push_rounded_rect(builder: &mut PathBuilder, center: Point, (width, height): (f32, f32), radius: f32) {
+  let (left, top) = (center.0 - width / 2.0, center.1 - height / 2.0);
+  let (right, bottom) = (left + width, top + height);
+  builder.move_to(left + radius, top);
+  builder.line_to(right - radius, top);
+  builder.quad_to(right, top, right, top + radius);
+  builder.line_to(right, bottom - radius);
+  builder.quad_to(right, bottom, right - radius, bottom);
+  builder.line_to(left + radius, bottom);
+  builder.quad_to(left, bottom, left, bottom - radius);
+  builder.line_to(left, top + radius);
+  builder.quad_to(left, top, left + radius, top);
+  builder.close();
+}
@@ -0,0 +1,210 @@
+use ordered_float::NotNan;
+
+use super::*;
+use crate::core::fonts::FontRegistry;
+use crate::core::pattern::display::*;
+use crate::core::pattern::*;
+use crate::core::render::{Region, PIXELS_PER_CELL};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+fn create_pattern_project() -> PatternProject {
+  let mut pattern = Pattern {
+    properties: PatternProperties { width: 10, height: 5 },
+    fabric: Fabric {
+      color: String::from("FFFFFF"),
+      ..Fabric::default()
+    },
+    palette: vec![PaletteItem {
+      brand: String::from("DMC"),
+      number: String::from("310"),
+      name: String::from("Black"),
+      color: String::from("FF0000"),
+      blends: None,
+      bead: None,
+      strands: None,
+    }],
+    ..Pattern::default()
+  };
+  pattern.add_stitches(vec![
+    Stitch::Full(FullStitch {
+      x: NotNan::new(1.0).unwrap(),
+      y: NotNan::new(1.0).unwrap(),
+      palindex: 0,
+      kind: FullStitchKind::Full,
+    }),
+    Stitch::Part(PartStitch {
+      x: NotNan::new(2.0).unwrap(),
+      y: NotNan::new(1.0).unwrap(),
+      palindex: 0,
+      direction: PartStitchDirection::Forward,
+      kind: PartStitchKind::Half,
+    }),
+    Stitch::Line(Line {
+      x: (NotNan::new(0.0).unwrap(), NotNan::new(5.0).unwrap()),
+      y: (NotNan::new(4.0).unwrap(), NotNan::new(4.0).unwrap()),
+      palindex: 0,
+      kind: LineKind::Back,
+    }),
+    Stitch::Node(Node {
+      x: NotNan::new(7.0).unwrap(),
+      y: NotNan::new(2.0).unwrap(),
+      rotated: false,
+      palindex: 0,
+      kind: NodeKind::FrenchKnot,
+    }),
+    // This stitch refers to a missing palette item, so it should be skipped.
+    Stitch::Full(FullStitch {
+      x: NotNan::new(8.0).unwrap(),
+      y: NotNan::new(3.0).unwrap(),
+      palindex: 5,
+      kind: FullStitchKind::Full,
+    }),
+  ]);
+
+  let mut display_settings = DisplaySettings::new(1);
+  display_settings.symbols[0].full = Some(u16::from(b'A'));
+  PatternProject {
+    pattern,
+    display_settings,
+    ..PatternProject::default()
+  }
+}
+
+fn options(view: View) -> RenderOptions {
+  RenderOptions { zoom: 100, region: None, view }
+}
+
+fn pixel(pixmap: &Pixmap, x: u32, y: u32) -> (u8, u8, u8) {
+  let pixel = pixmap.pixel(x, y).unwrap();
+  (pixel.red(), pixel.green(), pixel.blue())
+}
+
+#[test]
+fn renders_png() {
+  let patproj = create_pattern_project();
+  let png = render_png(&patproj, &options(View::Solid), &FontRegistry::new()).unwrap();
+  assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+#[test]
+fn respects_zoom_and_region() {
+  let patproj = create_pattern_project();
+
+  let pixmap = render(&patproj, &options(View::Solid), &FontRegistry::new()).unwrap();
+  assert_eq!((pixmap.width(), pixmap.height()), (100, 50));
+
+  let options = RenderOptions {
+    zoom: 200,
+    region: Some(Region {
+      x: 1,
+      y: 1,
+      width: 3,
+      height: 100,
+    }),
+    view: View::Solid,
+  };
+  let pixmap = render(&patproj, &options, &FontRegistry::new()).unwrap();
+  // The region is clipped to the pattern bounds.
+  let cell_size = (PIXELS_PER_CELL * 2.0) as u32;
+  assert_eq!((pixmap.width(), pixmap.height()), (3 * cell_size, 4 * cell_size));
+  // The full stitch is in the top-left corner of the region now.
+  assert_eq!(pixel(&pixmap, cell_size / 2, cell_size / 2), (255, 0, 0));
+}
+
+#[test]
+fn rejects_invalid_options() {
+  let patproj = create_pattern_project();
+
+  let mut invalid = options(View::Solid);
+  invalid.zoom = 0;
+  assert!(render(&patproj, &invalid, &FontRegistry::new()).is_err());
+
+  let mut invalid = options(View::Solid);
+  invalid.region = Some(Region { x: 10, y: 0, width: 5, height: 5 });
+  assert!(render(&patproj, &invalid, &FontRegistry::new()).is_err());
+
+  let mut invalid = options(View::Solid);
+  invalid.zoom = u16::MAX;
+  assert!(render(&patproj, &invalid, &FontRegistry::new()).is_err());
+}
+
+#[test]
+fn renders_solid_view() {
+  let patproj = create_pattern_project();
+  let pixmap = render(&patproj, &options(View::Solid), &FontRegistry::new()).unwrap();
+
+  // The center of the full stitch.
+  assert_eq!(pixel(&pixmap, 15, 15), (255, 0, 0));
+  // The fabric.
+  assert_eq!(pixel(&pixmap, 45, 25), (255, 255, 255));
+  // The stitch with the missing palette item.
+  assert_eq!(pixel(&pixmap, 85, 35), (255, 255, 255));
+}
+
+#[test]
+fn renders_gaps_between_stitches() {
+  let mut patproj = create_pattern_project();
+  patproj.display_settings.gaps_between_stitches = true;
+  patproj.display_settings.outlined_stitches = false;
+  patproj.display_settings.show_grid = false;
+  let options = RenderOptions {
+    zoom: 400,
+    ..options(View::Solid)
+  };
+  let pixmap = render(&patproj, &options, &FontRegistry::new()).unwrap();
+
+  assert_eq!(pixel(&pixmap, 60, 60), (255, 0, 0));
+  // The gap is two pixels wide at this zoom.
+  assert_eq!(pixel(&pixmap, 40, 60), (255, 255, 255));
+}
+
+#[test]
+fn renders_symbols_view() {
+  let mut patproj = create_pattern_project();
+  patproj.display_settings.show_grid = false;
+  let options = RenderOptions {
+    zoom: 400,
+    ..options(View::Symbols)
+  };
+  let pixmap = render(&patproj, &options, &FontRegistry::new()).unwrap();
+
+  // The full stitch has a symbol, so it is drawn over the white background.
+  let cell: Vec<_> = (40..80)
+    .flat_map(|y| (40..80).map(move |x| (x, y)))
+    .map(|(x, y)| pixel(&pixmap, x, y))
+    .collect();
+  assert!(cell.contains(&(0, 0, 0)));
+  assert!(cell.contains(&(255, 255, 255)));
+  assert!(!cell.contains(&(255, 0, 0)));
+
+  // The half stitch has no symbol, so it is drawn as is.
+  assert_eq!(pixel(&pixmap, 100, 60), (255, 0, 0));
+
+  patproj.display_settings.symbol_settings.show_stitch_color = true;
+  let pixmap = render(&patproj, &options, &FontRegistry::new()).unwrap();
+  assert_eq!(pixel(&pixmap, 42, 42), (255, 0, 0));
+}
+
+#[test]
+fn renders_all_views() {
+  let mut patproj = create_pattern_project();
+  for style in [
+    LineStyle::Solid,
+    LineStyle::Barred,
+    LineStyle::Dotted,
+    LineStyle::ChainDotted,
+    LineStyle::Dashed,
+    LineStyle::Outlined,
+    LineStyle::Zebra,
+    LineStyle::ZigZag,
+    LineStyle::Morse,
+  ] {
+    patproj.display_settings.formats[0].back.style = style;
+    for view in [View::Stitches, View::Symbols, View::Solid, View::Information] {
+      let pixmap = render(&patproj, &options(view), &FontRegistry::new()).unwrap();
+      // The backstitch.
+      assert_ne!(pixel(&pixmap, 5, 40), (255, 255, 255));
+    }
+  }
+}
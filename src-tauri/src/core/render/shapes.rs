@@ -0,0 +1,205 @@
+//! The geometry of the pattern elements in cell units.
+//! It is shared by all renderers, so the pattern looks the same regardless of the output format.
+
+use crate::core::pattern::display::LineStyle;
+use crate::core::pattern::*;
+
+pub type Point = (f32, f32);
+
+/// The gap between the neighbouring stitches if the `gaps_between_stitches` option is enabled.
+pub const STITCH_GAP: f32 = 0.05;
+
+/// The outline of the full or petite stitch in the solid view.
+pub fn fullstitch_shape(fullstitch: &FullStitch) -> Vec<Point> {
+  let (x, y) = (fullstitch.x.into_inner(), fullstitch.y.into_inner());
+  let size = match fullstitch.kind {
+    FullStitchKind::Full => 1.0,
+    FullStitchKind::Petite => 0.5,
+  };
+  vec![(x, y), (x + size, y), (x + size, y + size), (x, y + size)]
+}
+
+/// The outline of the half or quarter stitch in the solid view.
+pub fn partstitch_shape(partstitch: &PartStitch) -> Vec<Point> {
+  let (x, y) = (partstitch.x.into_inner(), partstitch.y.into_inner());
+  let size = match partstitch.kind {
+    PartStitchKind::Half => 1.0,
+    PartStitchKind::Quarter => 0.5,
+  };
+  let points: [Point; 6] = match partstitch.direction {
+    PartStitchDirection::Forward => [
+      (1.0, 0.0),
+      (1.0, 0.25),
+      (0.25, 1.0),
+      (0.0, 1.0),
+      (0.0, 0.75),
+      (0.75, 0.0),
+    ],
+    PartStitchDirection::Backward => [
+      (0.0, 0.0),
+      (0.25, 0.0),
+      (1.0, 0.75),
+      (1.0, 1.0),
+      (0.75, 1.0),
+      (0.0, 0.25),
+    ],
+  };
+  points
+    .into_iter()
+    .map(|(px, py)| (x + px * size, y + py * size))
+    .collect()
+}
+
+/// The thread segments of the full or petite stitch in the stitches view.
+/// The bottom thread goes first.
+pub fn fullstitch_threads(fullstitch: &FullStitch) -> [(Point, Point); 2] {
+  let (x, y) = (fullstitch.x.into_inner(), fullstitch.y.into_inner());
+  let size = match fullstitch.kind {
+    FullStitchKind::Full => 1.0,
+    FullStitchKind::Petite => 0.5,
+  };
+  [((x, y + size), (x + size, y)), ((x, y), (x + size, y + size))]
+}
+
+/// The thread segment of the half or quarter stitch in the stitches view.
+pub fn partstitch_thread(partstitch: &PartStitch) -> (Point, Point) {
+  let (x, y) = (partstitch.x.into_inner(), partstitch.y.into_inner());
+  let size = match partstitch.kind {
+    PartStitchKind::Half => 1.0,
+    PartStitchKind::Quarter => 0.5,
+  };
+  match partstitch.direction {
+    PartStitchDirection::Forward => ((x, y + size), (x + size, y)),
+    PartStitchDirection::Backward => ((x, y), (x + size, y + size)),
+  }
+}
+
+/// The triangle drawn behind the quarter stitch symbol.
+/// It is the half of the quarter cell that is adjacent to the cell center.
+pub fn quarter_triangle(partstitch: &PartStitch) -> Vec<Point> {
+  let (x, y) = (partstitch.x.into_inner(), partstitch.y.into_inner());
+  let center = (x.floor() + 0.5, y.floor() + 0.5);
+  let mut corners = vec![(x, y), (x + 0.5, y), (x + 0.5, y + 0.5), (x, y + 0.5)];
+  let distance = |&(px, py): &Point| (px - center.0).powi(2) + (py - center.1).powi(2);
+  if let Some(farthest) = (0..corners.len()).max_by(|&a, &b| distance(&corners[a]).total_cmp(&distance(&corners[b]))) {
+    corners.remove(farthest);
+  }
+  corners
+}
+
+/// Moves both ends of the segment towards each other by the given amount.
+pub fn shorten((start, end): (Point, Point), amount: f32) -> (Point, Point) {
+  let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+  let length = (dx * dx + dy * dy).sqrt();
+  if length <= amount * 2.0 {
+    let middle = ((start.0 + end.0) / 2.0, (start.1 + end.1) / 2.0);
+    return (middle, middle);
+  }
+  let (ux, uy) = (dx / length * amount, dy / length * amount);
+  ((start.0 + ux, start.1 + uy), (end.0 - ux, end.1 - uy))
+}
+
+/// Returns the bounding box of the points as `(min, max)`.
+pub fn bounds(points: &[Point]) -> (Point, Point) {
+  points.iter().fold(
+    ((f32::INFINITY, f32::INFINITY), (f32::NEG_INFINITY, f32::NEG_INFINITY)),
+    |((min_x, min_y), (max_x, max_y)), &(x, y)| ((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y))),
+  )
+}
+
+/// Shrinks the shape towards the center of its bounding box, so there is a gap of the given size around it.
+pub fn inset(points: &[Point], gap: f32) -> Vec<Point> {
+  let ((min_x, min_y), (max_x, max_y)) = bounds(points);
+  let (width, height) = (max_x - min_x, max_y - min_y);
+  let (cx, cy) = ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+  let scale_x = ((width - 2.0 * gap) / width).max(0.0);
+  let scale_y = ((height - 2.0 * gap) / height).max(0.0);
+  points
+    .iter()
+    .map(|&(x, y)| (cx + (x - cx) * scale_x, cy + (y - cy) * scale_y))
+    .collect()
+}
+
+/// Returns the size of the bead in cells.
+pub fn bead_size(palette_item: Option<&PaletteItem>, spi: StitchesPerInch) -> (f32, f32) {
+  const MM_PER_INCH: f32 = 25.4;
+  let (length, diameter) = palette_item
+    .and_then(|palitem| palitem.bead.as_ref())
+    .map(|bead| (bead.length, bead.diameter))
+    .unwrap_or((1.5, 2.5));
+  let spi = (f32::from(spi.0.max(1)), f32::from(spi.1.max(1)));
+  (length * spi.0 / MM_PER_INCH, diameter * spi.1 / MM_PER_INCH)
+}
+
+/// Describes how to stroke a line of the given style.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinePattern {
+  /// The lengths of the alternating dashes and gaps, if the line is not continuous.
+  pub dash: Option<Vec<f32>>,
+  /// Whether the line has round caps, which is required to draw dots.
+  pub round_caps: bool,
+}
+
+/// Returns the dash pattern of the line of the given style and width.
+/// The styles that need extra decorations (bars, outlines, stripes or zigzags) are stroked as solid lines,
+/// and the decorations are drawn on top of them.
+pub fn line_pattern(style: &LineStyle, width: f32) -> LinePattern {
+  // Dashes should be visible even for very thin lines.
+  let unit = width.max(0.05);
+  // A tiny dash with round caps is drawn as a dot.
+  let dot = unit * 0.01;
+  let (dash, round_caps) = match style {
+    LineStyle::Dotted => (Some(vec![dot, unit * 2.0]), true),
+    LineStyle::Dashed => (Some(vec![unit * 5.0, unit * 2.5]), false),
+    LineStyle::ChainDotted => (Some(vec![unit * 5.0, unit * 2.0, dot, unit * 2.0]), true),
+    LineStyle::Morse => (Some(vec![unit * 5.0, unit * 2.0, unit * 1.5, unit * 2.0]), false),
+    LineStyle::Zebra => (Some(vec![unit * 2.0, unit * 2.0]), false),
+    LineStyle::Solid | LineStyle::Barred | LineStyle::Outlined | LineStyle::ZigZag => (None, true),
+  };
+  LinePattern { dash, round_caps }
+}
+
+/// Returns the points of the zigzag that goes along the line.
+pub fn zigzag(start: Point, end: Point, amplitude: f32, period: f32) -> Vec<Point> {
+  let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+  let length = (dx * dx + dy * dy).sqrt();
+  if length == 0.0 || period <= 0.0 {
+    return vec![start, end];
+  }
+
+  let (ux, uy) = (dx / length, dy / length);
+  let (nx, ny) = (-uy, ux);
+  let steps = ((length / (period / 2.0)).round() as usize).max(1);
+  let step = length / steps as f32;
+
+  let mut points = vec![start];
+  for i in 1..steps {
+    let along = step * i as f32;
+    let side = if i % 2 == 1 { amplitude } else { -amplitude };
+    points.push((start.0 + ux * along + nx * side, start.1 + uy * along + ny * side));
+  }
+  points.push(end);
+  points
+}
+
+/// Returns the bars that cross the line at regular intervals.
+pub fn bars(start: Point, end: Point, length: f32, spacing: f32) -> Vec<(Point, Point)> {
+  let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+  let line_length = (dx * dx + dy * dy).sqrt();
+  if line_length == 0.0 || spacing <= 0.0 {
+    return Vec::new();
+  }
+
+  let (ux, uy) = (dx / line_length, dy / line_length);
+  let (nx, ny) = (-uy * length / 2.0, ux * length / 2.0);
+  let count = (line_length / spacing).floor() as usize;
+  let offset = (line_length - spacing * count as f32 + spacing) / 2.0;
+
+  (0..count)
+    .map(|i| {
+      let along = offset + spacing * i as f32;
+      let (cx, cy) = (start.0 + ux * along, start.1 + uy * along);
+      ((cx - nx, cy - ny), (cx + nx, cy + ny))
+    })
+    .collect()
+}
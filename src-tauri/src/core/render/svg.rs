@@ -0,0 +1,306 @@
+use anyhow::Result;
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, Event};
+use quick_xml::Writer;
+
+use super::color::Rgb;
+use super::painter::{self, GroupTransform, StrokeStyle, Surface, SYMBOL_PADDING};
+use super::shapes::Point;
+use super::{RenderOptions, PIXELS_PER_CELL};
+use crate::core::fonts::{FontRegistry, GlyphSegment};
+use crate::core::pattern::PatternProject;
+
+#[cfg(test)]
+#[path = "svg.test.rs"]
+mod tests;
+
+/// Renders the pattern as an SVG document.
+/// The document uses the cell units, so it can be scaled without losing quality.
+/// The glyphs of the symbols are embedded as paths, so the document doesn't depend on the installed fonts.
+pub fn render_svg(patproj: &PatternProject, options: &RenderOptions, fonts: &FontRegistry) -> Result<String> {
+  log::trace!("Rendering the pattern to SVG");
+  let region = options.region(patproj)?;
+  // The vector image is not limited by the raster image size.
+  let (width, height) = options.scaled_size(&region)?;
+
+  let mut surface = SvgSurface {
+    writer: Writer::new(Vec::new()),
+    size: (f32::from(region.width), f32::from(region.height)),
+    origin: (f32::from(region.x), f32::from(region.y)),
+    fonts,
+    glyphs: Vec::new(),
+  };
+  surface.write(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)));
+  surface.write(Event::Start(BytesStart::new("svg").with_attributes([
+    ("xmlns", "http://www.w3.org/2000/svg"),
+    ("width", width.to_string().as_str()),
+    ("height", height.to_string().as_str()),
+    (
+      "viewBox",
+      format!("{} {} {} {}", region.x, region.y, region.width, region.height).as_str(),
+    ),
+  ])));
+  painter::paint(&mut surface, patproj, region, &options.view);
+  surface.write_glyphs();
+  surface.write(Event::End(BytesEnd::new("svg")));
+
+  log::trace!("Pattern rendered to SVG");
+  Ok(String::from_utf8(surface.writer.into_inner())?)
+}
+
+struct SvgSurface<'a> {
+  writer: Writer<Vec<u8>>,
+  /// The origin and the size of the rendered region in cells.
+  origin: Point,
+  size: Point,
+  fonts: &'a FontRegistry,
+  /// The glyphs used in the document, identified by their font names and symbols.
+  /// Their indices are used in the identifiers of the glyph definitions.
+  glyphs: Vec<(String, char)>,
+}
+
+impl SvgSurface<'_> {
+  fn write(&mut self, event: Event) {
+    // Writing to a vector never fails.
+    let _ = self.writer.write_event(event);
+  }
+
+  fn write_element(&mut self, name: &str, attributes: Vec<(&str, String)>) {
+    let mut element = BytesStart::new(name);
+    for (key, value) in attributes.iter() {
+      element.push_attribute((*key, value.as_str()));
+    }
+    self.write(Event::Empty(element));
+  }
+
+  /// Returns the index of the glyph definition, registering it if the glyph is used for the first time.
+  fn glyph_index(&mut self, symbol: char, font: &str) -> usize {
+    let font = self.fonts.get_or_default(font).name();
+    match self
+      .glyphs
+      .iter()
+      .position(|(name, glyph)| name == font && *glyph == symbol)
+    {
+      Some(index) => index,
+      None => {
+        self.glyphs.push((font.to_owned(), symbol));
+        self.glyphs.len() - 1
+      }
+    }
+  }
+
+  /// Writes the outlines of the used glyphs, so they can be referenced by the symbols.
+  fn write_glyphs(&mut self) {
+    if self.glyphs.is_empty() {
+      return;
+    }
+    self.write(Event::Start(BytesStart::new("defs")));
+    for (index, (font, symbol)) in std::mem::take(&mut self.glyphs).into_iter().enumerate() {
+      let glyph = self.fonts.get_or_default(&font).glyph(symbol).unwrap_or_default();
+      let attributes = vec![("id", glyph_id(index)), ("d", glyph_path(&glyph))];
+      self.write_element("path", attributes);
+    }
+    self.write(Event::End(BytesEnd::new("defs")));
+  }
+}
+
+impl Surface for SvgSurface<'_> {
+  fn fill(&mut self, color: Rgb) {
+    let attributes = vec![
+      ("x", number(self.origin.0)),
+      ("y", number(self.origin.1)),
+      ("width", number(self.size.0)),
+      ("height", number(self.size.1)),
+      ("fill", hex(color)),
+    ];
+    self.write_element("rect", attributes);
+  }
+
+  fn fill_polygon(&mut self, points: &[Point], color: Rgb) {
+    self.write_element("polygon", vec![("points", points_list(points)), ("fill", hex(color))]);
+  }
+
+  fn stroke_polyline(&mut self, points: &[Point], closed: bool, stroke: &StrokeStyle) {
+    let (name, mut attributes) = match (points, closed) {
+      ([(x1, y1), (x2, y2)], false) => (
+        "line",
+        vec![
+          ("x1", number(*x1)),
+          ("y1", number(*y1)),
+          ("x2", number(*x2)),
+          ("y2", number(*y2)),
+        ],
+      ),
+      (_, true) => ("polygon", vec![("points", points_list(points))]),
+      (_, false) => ("polyline", vec![("points", points_list(points))]),
+    };
+    attributes.push(("fill", String::from("none")));
+    attributes.extend(stroke_attributes(stroke));
+    self.write_element(name, attributes);
+  }
+
+  fn fill_circle(&mut self, center: Point, radius: f32, color: Rgb) {
+    let attributes = vec![
+      ("cx", number(center.0)),
+      ("cy", number(center.1)),
+      ("r", number(radius)),
+      ("fill", hex(color)),
+    ];
+    self.write_element("circle", attributes);
+  }
+
+  fn stroke_circle(&mut self, center: Point, radius: f32, stroke: &StrokeStyle) {
+    let mut attributes = vec![
+      ("cx", number(center.0)),
+      ("cy", number(center.1)),
+      ("r", number(radius)),
+      ("fill", String::from("none")),
+    ];
+    attributes.extend(stroke_attributes(stroke));
+    self.write_element("circle", attributes);
+  }
+
+  fn fill_rounded_rect(&mut self, center: Point, size: (f32, f32), radius: f32, color: Rgb) {
+    let mut attributes = rect_attributes(center, size, radius);
+    attributes.push(("fill", hex(color)));
+    self.write_element("rect", attributes);
+  }
+
+  fn stroke_rounded_rect(&mut self, center: Point, size: (f32, f32), radius: f32, stroke: &StrokeStyle) {
+    let mut attributes = rect_attributes(center, size, radius);
+    attributes.push(("fill", String::from("none")));
+    attributes.extend(stroke_attributes(stroke));
+    self.write_element("rect", attributes);
+  }
+
+  fn draw_grid_lines(&mut self, lines: &[(Point, Point)], color: Rgb, width: f32) {
+    let path = lines
+      .iter()
+      .map(|((x1, y1), (x2, y2))| format!("M{} {}L{} {}", number(*x1), number(*y1), number(*x2), number(*y2)))
+      .collect::<String>();
+    let attributes = vec![
+      ("d", path),
+      ("stroke", hex(color)),
+      ("stroke-width", number(width / PIXELS_PER_CELL)),
+    ];
+    self.write_element("path", attributes);
+  }
+
+  fn has_symbol(&self, symbol: char, font: &str) -> bool {
+    self.fonts.get_or_default(font).has_glyph(symbol)
+  }
+
+  fn draw_symbol(&mut self, symbol: char, font: &str, center: Point, size: f32, color: Rgb) {
+    let index = self.glyph_index(symbol, font);
+    let transform = format!(
+      "translate({} {}) scale({})",
+      number(center.0),
+      number(center.1),
+      number(size * (1.0 - SYMBOL_PADDING * 2.0))
+    );
+    let attributes = vec![
+      ("href", format!("#{}", glyph_id(index))),
+      ("transform", transform),
+      ("fill", hex(color)),
+    ];
+    self.write_element("use", attributes);
+  }
+
+  fn push_transform(&mut self, transform: GroupTransform) {
+    let GroupTransform {
+      translate: (x, y),
+      rotation,
+      scale: (sx, sy),
+    } = transform;
+    let transform = format!(
+      "translate({} {}) rotate({}) scale({} {})",
+      number(x),
+      number(y),
+      number(rotation),
+      number(sx),
+      number(sy)
+    );
+    self.write(Event::Start(
+      BytesStart::new("g").with_attributes([("transform", transform.as_str())]),
+    ));
+  }
+
+  fn pop_transform(&mut self) {
+    self.write(Event::End(BytesEnd::new("g")));
+  }
+}
+
+/// Formats the number with up to three decimal places.
+fn number(value: f32) -> String {
+  trim_number(format!("{value:.3}"))
+}
+
+/// Formats the number with up to five decimal places.
+fn precise_number(value: f32) -> String {
+  trim_number(format!("{value:.5}"))
+}
+
+fn trim_number(value: String) -> String {
+  let value = value.trim_end_matches('0').trim_end_matches('.');
+  match value {
+    "-0" | "" => String::from("0"),
+    value => value.to_owned(),
+  }
+}
+
+fn glyph_id(index: usize) -> String {
+  format!("glyph-{index}")
+}
+
+/// Builds the path data of the glyph outline.
+/// The outline fits the unit square, so more precision is kept than for the other numbers.
+fn glyph_path(glyph: &[GlyphSegment]) -> String {
+  let point = |(x, y): (f32, f32)| format!("{} {}", precise_number(x), precise_number(y));
+  glyph
+    .iter()
+    .map(|segment| match *segment {
+      GlyphSegment::MoveTo(p) => format!("M{}", point(p)),
+      GlyphSegment::LineTo(p) => format!("L{}", point(p)),
+      GlyphSegment::QuadTo(p1, p) => format!("Q{} {}", point(p1), point(p)),
+      GlyphSegment::CubicTo(p1, p2, p) => format!("C{} {} {}", point(p1), point(p2), point(p)),
+      GlyphSegment::Close => String::from("Z"),
+    })
+    .collect()
+}
+
+fn hex(color: Rgb) -> String {
+  format!("#{}", color.to_hex())
+}
+
+fn points_list(points: &[Point]) -> String {
+  points
+    .iter()
+    .map(|(x, y)| format!("{},{}", number(*x), number(*y)))
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+fn stroke_attributes(stroke: &StrokeStyle) -> Vec<(&'static str, String)> {
+  let mut attributes = vec![
+    ("stroke", hex(stroke.color)),
+    ("stroke-width", number(stroke.width)),
+    ("stroke-linejoin", String::from("round")),
+  ];
+  if stroke.pattern.round_caps {
+    attributes.push(("stroke-linecap", String::from("round")));
+  }
+  if let Some(dash) = &stroke.pattern.dash {
+    let dash = dash.iter().map(|length| number(*length)).collect::<Vec<_>>().join(" ");
+    attributes.push(("stroke-dasharray", dash));
+  }
+  attributes
+}
+
+fn rect_attributes(center: Point, (width, height): (f32, f32), radius: f32) -> Vec<(&'static str, String)> {
+  vec![
+    ("x", number(center.0 - width / 2.0)),
+    ("y", number(center.1 - height / 2.0)),
+    ("width", number(width)),
+    ("height", number(height)),
+    ("rx", number(radius)),
+  ]
+}
@@ -0,0 +1,248 @@
+use ordered_float::NotNan;
+use quick_xml::events::Event;
+
+use super::*;
+use crate::core::fonts::FontRegistry;
+use crate::core::pattern::display::*;
+use crate::core::pattern::*;
+use crate::core::render::Region;
+
+fn coord(value: f32) -> Coord {
+  NotNan::new(value).unwrap()
+}
+
+fn create_pattern_project() -> PatternProject {
+  let mut pattern = Pattern {
+    properties: PatternProperties { width: 10, height: 5 },
+    palette: vec![PaletteItem {
+      brand: String::from("DMC"),
+      number: String::from("321"),
+      name: String::from("Red"),
+      color: String::from("FF0000"),
+      blends: None,
+      bead: None,
+      strands: None,
+    }],
+    special_stitch_models: vec![SpecialStitchModel {
+      unique_name: String::from("Lazy Daisy"),
+      name: String::from("Lazy Daisy"),
+      nodes: Vec::new(),
+      lines: vec![Line {
+        x: (coord(0.0), coord(1.0)),
+        y: (coord(0.0), coord(1.0)),
+        palindex: 0,
+        kind: LineKind::Straight,
+      }],
+      curves: Vec::new(),
+    }],
+    ..Pattern::default()
+  };
+  pattern.add_stitches(vec![
+    Stitch::Full(FullStitch {
+      x: coord(1.0),
+      y: coord(1.0),
+      palindex: 0,
+      kind: FullStitchKind::Full,
+    }),
+    Stitch::Line(Line {
+      x: (coord(0.0), coord(5.0)),
+      y: (coord(4.0), coord(4.0)),
+      palindex: 0,
+      kind: LineKind::Back,
+    }),
+    Stitch::Node(Node {
+      x: coord(7.0),
+      y: coord(2.0),
+      rotated: false,
+      palindex: 0,
+      kind: NodeKind::FrenchKnot,
+    }),
+    Stitch::Node(Node {
+      x: coord(8.0),
+      y: coord(2.0),
+      rotated: true,
+      palindex: 0,
+      kind: NodeKind::Bead,
+    }),
+  ]);
+  pattern.specialstitches.insert(SpecialStitch {
+    x: coord(3.0),
+    y: coord(3.0),
+    rotation: Degree::new(90),
+    flip: (true, false),
+    palindex: 0,
+    modindex: 0,
+  });
+
+  PatternProject {
+    pattern,
+    display_settings: DisplaySettings::new(1),
+    ..PatternProject::default()
+  }
+}
+
+fn options(view: View) -> RenderOptions {
+  RenderOptions { zoom: 100, region: None, view }
+}
+
+/// Reads the SVG document and returns the names and attributes of its elements.
+fn read_elements(svg: &str) -> Vec<(String, Vec<(String, String)>)> {
+  let mut reader = quick_xml::Reader::from_str(svg);
+  let mut elements = Vec::new();
+  loop {
+    match reader.read_event().unwrap() {
+      Event::Start(e) | Event::Empty(e) => {
+        let name = String::from_utf8(e.name().as_ref().to_vec()).unwrap();
+        let attributes = e
+          .attributes()
+          .map(|attr| {
+            let attr = attr.unwrap();
+            let key = String::from_utf8(attr.key.as_ref().to_vec()).unwrap();
+            (key, attr.unescape_value().unwrap().to_string())
+          })
+          .collect();
+        elements.push((name, attributes));
+      }
+      Event::Eof => break,
+      _ => {}
+    }
+  }
+  elements
+}
+
+fn find<'a>(elements: &'a [(String, Vec<(String, String)>)], name: &str) -> Vec<&'a [(String, String)]> {
+  elements
+    .iter()
+    .filter(|(element, _)| element == name)
+    .map(|(_, attributes)| attributes.as_slice())
+    .collect()
+}
+
+fn attribute<'a>(attributes: &'a [(String, String)], key: &str) -> Option<&'a str> {
+  attributes
+    .iter()
+    .find(|(attribute, _)| attribute == key)
+    .map(|(_, value)| value.as_str())
+}
+
+#[test]
+fn renders_svg_document() {
+  let patproj = create_pattern_project();
+  let options = RenderOptions {
+    zoom: 200,
+    region: Some(Region { x: 1, y: 1, width: 4, height: 4 }),
+    view: View::Solid,
+  };
+  let elements = read_elements(&render_svg(&patproj, &options, &FontRegistry::new()).unwrap());
+
+  let (name, svg) = &elements[0];
+  assert_eq!(name, "svg");
+  assert_eq!(attribute(svg, "viewBox"), Some("1 1 4 4"));
+  assert_eq!(attribute(svg, "width"), Some("80"));
+  assert_eq!(attribute(svg, "height"), Some("80"));
+}
+
+#[test]
+fn renders_stitches_as_shapes() {
+  let patproj = create_pattern_project();
+  let elements = read_elements(&render_svg(&patproj, &options(View::Solid), &FontRegistry::new()).unwrap());
+
+  let polygons = find(&elements, "polygon");
+  assert!(polygons
+    .iter()
+    .any(|polygon| attribute(polygon, "points") == Some("1,1 2,1 2,2 1,2")
+      && attribute(polygon, "fill") == Some("#FF0000")));
+
+  let circles = find(&elements, "circle");
+  assert!(circles.iter().any(|circle| attribute(circle, "cx") == Some("7")
+    && attribute(circle, "cy") == Some("2")
+    && attribute(circle, "fill") == Some("#FF0000")));
+
+  // The bead is rotated, so its diameter goes along the X axis.
+  let rects = find(&elements, "rect");
+  assert!(rects.iter().any(|rect| {
+    let width: f32 = attribute(rect, "width").unwrap().parse().unwrap();
+    let height: f32 = attribute(rect, "height").unwrap().parse().unwrap();
+    attribute(rect, "fill") == Some("#FF0000") && width > height
+  }));
+
+  let groups = find(&elements, "g");
+  assert_eq!(groups.len(), 1);
+  assert_eq!(
+    attribute(groups[0], "transform"),
+    Some("translate(3 3) rotate(90) scale(-1 1)")
+  );
+}
+
+#[test]
+fn renders_lines_with_styles_and_thickness() {
+  let mut patproj = create_pattern_project();
+  patproj.display_settings.formats[0].back.style = LineStyle::Dashed;
+  patproj.display_settings.stitch_settings.display_thickness[0] = StitchThickness::new(2.0);
+  let elements = read_elements(&render_svg(&patproj, &options(View::Solid), &FontRegistry::new()).unwrap());
+
+  let backstitch = find(&elements, "line")
+    .into_iter()
+    .find(|line| attribute(line, "y1") == Some("4") && attribute(line, "x2") == Some("5"))
+    .unwrap();
+  assert_eq!(attribute(backstitch, "stroke"), Some("#FF0000"));
+  assert_eq!(attribute(backstitch, "stroke-width"), Some("0.4"));
+  assert_eq!(attribute(backstitch, "stroke-dasharray"), Some("2 1"));
+
+  patproj.display_settings.formats[0].back.style = LineStyle::ZigZag;
+  let elements = read_elements(&render_svg(&patproj, &options(View::Solid), &FontRegistry::new()).unwrap());
+  assert!(!find(&elements, "polyline").is_empty());
+}
+
+#[test]
+fn renders_grid() {
+  let mut patproj = create_pattern_project();
+  let elements = read_elements(&render_svg(&patproj, &options(View::Solid), &FontRegistry::new()).unwrap());
+  let paths = find(&elements, "path");
+  assert_eq!(paths.len(), 2);
+  assert_eq!(attribute(paths[0], "stroke"), Some("#C8C8C8"));
+  assert_eq!(attribute(paths[1], "stroke"), Some("#646464"));
+
+  patproj.display_settings.show_grid = false;
+  let elements = read_elements(&render_svg(&patproj, &options(View::Solid), &FontRegistry::new()).unwrap());
+  assert!(find(&elements, "path").is_empty());
+}
+
+#[test]
+fn embeds_symbol_glyphs() {
+  let mut patproj = create_pattern_project();
+  patproj.display_settings.symbols[0].full = Some(u16::from(b'&'));
+  patproj.pattern.add_stitches(vec![Stitch::Full(FullStitch {
+    x: coord(2.0),
+    y: coord(1.0),
+    palindex: 0,
+    kind: FullStitchKind::Full,
+  })]);
+  let elements = read_elements(&render_svg(&patproj, &options(View::Symbols), &FontRegistry::new()).unwrap());
+
+  // Both stitches refer to the same glyph definition.
+  let uses = find(&elements, "use");
+  assert_eq!(uses.len(), 2);
+  assert!(uses.iter().all(|symbol| attribute(symbol, "href") == Some("#glyph-0")));
+  assert!(attribute(uses[0], "transform")
+    .unwrap()
+    .starts_with("translate(1.5 1.5) scale("));
+
+  let glyphs: Vec<_> = find(&elements, "path")
+    .into_iter()
+    .filter(|path| attribute(path, "id").is_some())
+    .collect();
+  assert_eq!(glyphs.len(), 1);
+  assert_eq!(attribute(glyphs[0], "id"), Some("glyph-0"));
+  assert!(attribute(glyphs[0], "d").unwrap().starts_with('M'));
+}
+
+#[test]
+fn renders_large_patterns() {
+  let mut patproj = create_pattern_project();
+  patproj.pattern.properties = PatternProperties { width: 2000, height: 2000 };
+  let elements = read_elements(&render_svg(&patproj, &options(View::Solid), &FontRegistry::new()).unwrap());
+  let svg = &find(&elements, "svg")[0];
+  assert_eq!(attribute(svg, "width"), Some("20000"));
+  assert_eq!(attribute(svg, "viewBox"), Some("0 0 2000 2000"));
+}
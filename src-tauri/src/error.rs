@@ -1,38 +1,149 @@
 //! This is a set of utilities to make working with `anyhow` and `tauri` easier.
 //! It is based on https://github.com/TDiblik/anyhow-tauri.
+//!
+//! The errors are serialized as objects with a code, so the frontend can handle different kinds of errors differently.
+
+use std::path::{Path, PathBuf};
+
+use crate::core::parser::oxs::OxsError;
+use crate::core::parser::xsd::XsdError;
+use crate::core::parser::UnsupportedFormatError;
+use crate::core::pattern::sessions::SessionError;
+use crate::state::PatternKey;
+
+/// The kind of the error that is reported to the frontend.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+  /// The pattern with the given key is not opened.
+  PatternNotFound,
+  /// The font with the given name is neither bundled nor installed.
+  FontNotFound,
+  /// The motif with the given name is not in the library.
+  MotifNotFound,
+  /// The stitching session is started while another one is running.
+  SessionAlreadyStarted,
+  /// The stitching session is stopped while none is running.
+  SessionNotStarted,
+  /// The file format is not supported.
+  UnsupportedFormat,
+  /// The pattern file is corrupted or cannot be interpreted.
+  InvalidPattern,
+  /// The file cannot be read or written.
+  Io,
+  /// Any other error.
+  Unknown,
+}
 
 #[derive(Debug)]
-pub struct CommandError(anyhow::Error);
+pub struct CommandError {
+  code: ErrorCode,
+  error: anyhow::Error,
+  file_path: Option<PathBuf>,
+}
+
+impl CommandError {
+  pub fn pattern_not_found(pattern_key: &PatternKey) -> Self {
+    Self {
+      code: ErrorCode::PatternNotFound,
+      error: anyhow::anyhow!("The pattern {pattern_key} is not opened"),
+      file_path: None,
+    }
+  }
+
+  pub fn font_not_found(font_name: &str) -> Self {
+    Self {
+      code: ErrorCode::FontNotFound,
+      error: anyhow::anyhow!("The font {font_name} is not available"),
+      file_path: None,
+    }
+  }
+
+  pub fn motif_not_found(motif_name: &str) -> Self {
+    Self {
+      code: ErrorCode::MotifNotFound,
+      error: anyhow::anyhow!("The motif {motif_name} is not in the library"),
+      file_path: None,
+    }
+  }
+
+  /// Attaches the path of the file the command was working with.
+  pub fn with_file_path<P: AsRef<Path>>(mut self, file_path: P) -> Self {
+    self.file_path = Some(file_path.as_ref().to_path_buf());
+    self
+  }
+
+  /// Returns the byte offset in the XSD file the error occurred at.
+  fn offset(&self) -> Option<u64> {
+    self.error.downcast_ref::<XsdError>().map(|error| error.offset)
+  }
+
+  /// Returns the position in the OXS document the error occurred at.
+  fn position(&self) -> Option<u64> {
+    self.error.downcast_ref::<OxsError>().and_then(OxsError::position)
+  }
+}
 
 impl std::error::Error for CommandError {}
 
 impl std::fmt::Display for CommandError {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    write!(f, "{:#}", self.0)
+    write!(f, "{:#}", self.error)
   }
 }
 
 impl From<anyhow::Error> for CommandError {
   fn from(error: anyhow::Error) -> Self {
-    Self(error)
+    let code = if error.is::<UnsupportedFormatError>() {
+      ErrorCode::UnsupportedFormat
+    } else if error.is::<OxsError>() || error.is::<XsdError>() {
+      ErrorCode::InvalidPattern
+    } else if error.is::<std::io::Error>() {
+      ErrorCode::Io
+    } else {
+      ErrorCode::Unknown
+    };
+    Self { code, error, file_path: None }
+  }
+}
+
+impl From<SessionError> for CommandError {
+  fn from(error: SessionError) -> Self {
+    let code = match error {
+      SessionError::AlreadyStarted => ErrorCode::SessionAlreadyStarted,
+      SessionError::NotStarted => ErrorCode::SessionNotStarted,
+    };
+    Self {
+      code,
+      error: anyhow::Error::from(error),
+      file_path: None,
+    }
   }
 }
 
 impl From<std::io::Error> for CommandError {
   fn from(error: std::io::Error) -> Self {
-    Self(anyhow::Error::from(error))
+    Self::from(anyhow::Error::from(error))
   }
 }
 
 impl From<tauri::Error> for CommandError {
   fn from(error: tauri::Error) -> Self {
-    Self(anyhow::Error::from(error))
+    Self::from(anyhow::Error::from(error))
   }
 }
 
 impl serde::Serialize for CommandError {
   fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
-    serializer.serialize_str(&format!("{:#}", self.0))
+    use serde::ser::SerializeStruct;
+
+    let mut state = serializer.serialize_struct("CommandError", 5)?;
+    state.serialize_field("code", &self.code)?;
+    state.serialize_field("message", &self.to_string())?;
+    state.serialize_field("filePath", &self.file_path)?;
+    state.serialize_field("offset", &self.offset())?;
+    state.serialize_field("position", &self.position())?;
+    state.end()
   }
 }
 
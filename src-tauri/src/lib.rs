@@ -13,6 +13,12 @@ mod utils;
 mod error;
 mod logger;
 
+/// Entry points for the fuzz targets located in the `fuzz/` directory.
+#[cfg(fuzzing)]
+pub mod fuzzing {
+  pub use crate::core::parser::oxs::parse_pattern_from_slice as parse_oxs;
+}
+
 pub fn setup_app<R: tauri::Runtime>(builder: tauri::Builder<R>) -> tauri::App<R> {
   builder
     .setup(|app| {
@@ -29,12 +35,27 @@ pub fn setup_app<R: tauri::Runtime>(builder: tauri::Builder<R>) -> tauri::App<R>
           std::fs::copy(pattern.clone(), app_document_dir.join(pattern.file_name().unwrap()))?;
         }
       }
+
+      let fonts_dir = utils::path::app_fonts_dir(app.handle())?;
+      let fonts = app.state::<state::FontsState>();
+      if let Err(error) = fonts.write().unwrap().load_dir(&fonts_dir) {
+        // The bundled fonts are still available, so the app can work without the user ones.
+        log::warn!("Failed to load the user fonts: {error:#}");
+      }
+
+      let motifs_dir = utils::path::app_motifs_dir(app.handle())?;
+      let motifs = app.state::<state::MotifsState>();
+      if let Err(error) = motifs.write().unwrap().load_dir(&motifs_dir) {
+        log::warn!("Failed to load the motif library: {error:#}");
+      }
       Ok(())
     })
     .manage(RwLock::new(
       HashMap::<state::PatternKey, core::pattern::PatternProject>::new(),
     ))
     .manage(RwLock::new(HistoryStateInner::<R>::default()))
+    .manage(RwLock::new(core::fonts::FontRegistry::new()))
+    .manage(RwLock::new(core::motifs::MotifLibrary::new()))
     .plugin(logger::setup_logger().build())
     .plugin(tauri_plugin_dialog::init())
     .plugin(tauri_plugin_fs::init())
@@ -45,10 +66,52 @@ pub fn setup_app<R: tauri::Runtime>(builder: tauri::Builder<R>) -> tauri::App<R>
       commands::pattern::save_pattern,
       commands::pattern::close_pattern,
       commands::pattern::get_pattern_file_path,
+      commands::pattern::update_pattern_info,
+      commands::pattern::update_pattern_properties,
+      commands::pattern::update_fabric,
+      commands::render::render_pattern,
+      commands::render::render_pattern_preview,
+      commands::render::export_pattern_svg,
       commands::palette::add_palette_item,
       commands::palette::remove_palette_item,
+      commands::palette::update_palette_item,
+      commands::palette::move_palette_item,
+      commands::palette::replace_palette_index,
+      commands::palette::analyze_palette,
+      commands::palette::cleanup_palette,
+      commands::palette::reassign_symbols,
+      commands::fonts::get_symbol_fonts,
+      commands::fonts::get_font_codepoints,
+      commands::fonts::install_font,
+      commands::motifs::get_motifs,
+      commands::motifs::get_motif_tags,
+      commands::motifs::save_motif,
+      commands::motifs::remove_motif,
+      commands::motifs::insert_motif,
+      commands::settings::update_display_settings,
+      commands::settings::update_print_settings,
       commands::stitches::add_stitch,
+      commands::stitches::fill_stitches,
+      commands::stitches::draw_shape,
+      commands::stitches::repeat_motif,
+      commands::stitches::add_text,
       commands::stitches::remove_stitch,
+      commands::stitches::get_stitches_in_viewport,
+      commands::stitches::select_stitches,
+      commands::stitches::hit_test_stitches,
+      commands::layers::get_layers,
+      commands::layers::add_layer,
+      commands::layers::remove_layer,
+      commands::layers::move_layer,
+      commands::layers::update_layer,
+      commands::layers::set_active_layer,
+      commands::layers::merge_layer_down,
+      commands::progress::get_progress,
+      commands::progress::mark_progress,
+      commands::progress::get_progress_stats,
+      commands::progress::start_stitching_session,
+      commands::progress::stop_stitching_session,
+      commands::progress::get_session_stats,
       commands::history::undo,
       commands::history::redo,
     ])
@@ -4,7 +4,9 @@ use std::path::PathBuf;
 use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 
+use crate::core::fonts::FontRegistry;
 use crate::core::history::History;
+use crate::core::motifs::MotifLibrary;
 use crate::core::pattern::PatternProject;
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
@@ -17,6 +19,12 @@ impl From<&PathBuf> for PatternKey {
   }
 }
 
+impl std::fmt::Display for PatternKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.0)
+  }
+}
+
 pub struct HistoryStateInner<R: tauri::Runtime> {
   inner: HashMap<PatternKey, History<R>>,
 }
@@ -39,3 +47,5 @@ impl<R: tauri::Runtime> Default for HistoryStateInner<R> {
 
 pub type PatternsState = std::sync::RwLock<HashMap<PatternKey, PatternProject>>;
 pub type HistoryState<R> = std::sync::RwLock<HistoryStateInner<R>>;
+pub type FontsState = std::sync::RwLock<FontRegistry>;
+pub type MotifsState = std::sync::RwLock<MotifLibrary>;
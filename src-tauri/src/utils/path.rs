@@ -15,3 +15,13 @@ pub fn app_document_dir<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) ->
   };
   Ok(dir_path.join(app_name))
 }
+
+/// Returns the directory where the user installs the symbol fonts.
+pub fn app_fonts_dir<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> anyhow::Result<PathBuf> {
+  Ok(app_document_dir(app_handle)?.join("fonts"))
+}
+
+/// Returns the directory where the motif library is stored.
+pub fn app_motifs_dir<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> anyhow::Result<PathBuf> {
+  Ok(app_document_dir(app_handle)?.join("motifs"))
+}
@@ -81,3 +81,47 @@ fn closes_pattern() {
   commands::pattern::close_pattern(pattern_key, patterns_state.clone());
   assert!(patterns_state.read().unwrap().is_empty());
 }
+
+#[test]
+fn reports_structured_errors() {
+  let app = setup_app::<MockRuntime>(mock_builder());
+  let app_handle = app.handle();
+  let patterns_state = app_handle.state::<PatternsState>();
+
+  let file_path = std::env::temp_dir().join("pattern.txt");
+  std::fs::write(&file_path, "Hello, world!").unwrap();
+  let error = commands::pattern::load_pattern(file_path.clone(), patterns_state.clone()).unwrap_err();
+  let error = serde_json::to_value(error).unwrap();
+  assert_eq!(error["code"], "unsupported_format");
+  assert_eq!(error["filePath"], file_path.to_string_lossy().as_ref());
+
+  let file_path = std::env::temp_dir().join("corrupted.oxs");
+  std::fs::write(
+    &file_path,
+    r#"<chart><properties oxsversion="1.0" chartwidth="1"/></chart>"#,
+  )
+  .unwrap();
+  let error = commands::pattern::load_pattern(file_path, patterns_state.clone()).unwrap_err();
+  let error = serde_json::to_value(error).unwrap();
+  assert_eq!(error["code"], "invalid_pattern");
+  assert!(error["position"].is_u64());
+
+  let pattern_key = PatternKey::from(&std::path::PathBuf::from("unknown.embproj"));
+  let error = commands::pattern::get_pattern_file_path(pattern_key, patterns_state.clone()).unwrap_err();
+  let error = serde_json::to_value(error).unwrap();
+  assert_eq!(error["code"], "pattern_not_found");
+}
+
+#[test]
+fn loads_patterns_with_wrong_extensions() {
+  let app = setup_app::<MockRuntime>(mock_builder());
+  let app_handle = app.handle();
+  let patterns_state = app_handle.state::<PatternsState>();
+
+  let sample_patterns = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/patterns");
+  for (source, target) in [("piggies.oxs", "piggies-oxs.xsd"), ("piggies.xsd", "piggies-xsd")] {
+    let file_path = std::env::temp_dir().join(target);
+    std::fs::copy(sample_patterns.join(source), &file_path).unwrap();
+    assert!(commands::pattern::load_pattern(file_path.clone(), patterns_state.clone()).is_ok());
+  }
+}
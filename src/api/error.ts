@@ -0,0 +1,28 @@
+/** A code of the error returned by the backend commands. */
+export type CommandErrorCode =
+  | "pattern_not_found"
+  | "font_not_found"
+  | "motif_not_found"
+  | "session_already_started"
+  | "session_not_started"
+  | "unsupported_format"
+  | "invalid_pattern"
+  | "io"
+  | "unknown";
+
+/** An error returned by the backend commands. */
+export interface CommandError {
+  code: CommandErrorCode;
+  /** A user-presentable description of the error. */
+  message: string;
+  /** A path of the file the command was working with. */
+  filePath: string | null;
+  /** A byte offset in the XSD file the error occurred at. */
+  offset: number | null;
+  /** A position in the OXS document the error occurred at. */
+  position: number | null;
+}
+
+export function isCommandError(err: unknown): err is CommandError {
+  return typeof err === "object" && err !== null && "code" in err && "message" in err;
+}
@@ -0,0 +1,10 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface FontInfo {
+  name: string;
+  bundled: boolean;
+}
+
+export const getSymbolFonts = () => invoke<FontInfo[]>("get_symbol_fonts");
+export const getFontCodepoints = (fontName: string) => invoke<number[]>("get_font_codepoints", { fontName });
+export const installFont = (filePath: string) => invoke<FontInfo>("install_font", { filePath });
@@ -2,3 +2,8 @@ export * as PatternApi from "./pattern";
 export * as StitchesApi from "./stitches";
 export * as HistoryApi from "./history";
 export * as PathApi from "./path";
+export * as FontsApi from "./fonts";
+export * as SettingsApi from "./settings";
+export * as MotifsApi from "./motifs";
+export * as LayersApi from "./layers";
+export * as ProgressApi from "./progress";
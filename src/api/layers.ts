@@ -0,0 +1,39 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { PatternKey } from "#/schemas/pattern";
+
+export interface LayerProperties {
+  name: string;
+  /** Whether the layer stitches are shown and exported. */
+  visible: boolean;
+  /** Whether the layer stitches can be edited. */
+  locked: boolean;
+}
+
+export interface LayerInfo extends LayerProperties {
+  active: boolean;
+}
+
+/** Returns the layers of the pattern, from the bottom to the top one. */
+export const getLayers = (patternKey: PatternKey) => invoke<LayerInfo[]>("get_layers", { patternKey });
+
+/** Adds an empty layer above the active one. */
+export const addLayer = (patternKey: PatternKey, name?: string) => invoke<void>("add_layer", { patternKey, name });
+export const removeLayer = (patternKey: PatternKey, index: number) => {
+  return invoke<void>("remove_layer", { patternKey, index });
+};
+export const moveLayer = (patternKey: PatternKey, from: number, to: number) => {
+  return invoke<void>("move_layer", { patternKey, from, to });
+};
+export const updateLayer = (patternKey: PatternKey, index: number, properties: LayerProperties) => {
+  return invoke<void>("update_layer", { patternKey, index, properties });
+};
+
+/** Makes the layer active, so the stitches are added to and removed from it. */
+export const setActiveLayer = (patternKey: PatternKey, index: number) => {
+  return invoke<void>("set_active_layer", { patternKey, index });
+};
+
+/** Merges the layer into the one below it. */
+export const mergeLayerDown = (patternKey: PatternKey, index: number) => {
+  return invoke<void>("merge_layer_down", { patternKey, index });
+};
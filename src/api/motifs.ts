@@ -0,0 +1,28 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { PaletteItem, PatternKey, Stitch } from "#/schemas/pattern";
+import type { Region } from "#/api/pattern";
+
+/** A pattern fragment with its own palette. The stitches are placed relative to its top-left corner. */
+export interface Motif {
+  name: string;
+  tags: string[];
+  width: number;
+  height: number;
+  palette: PaletteItem[];
+  stitches: Stitch[];
+}
+
+/** Returns the motifs whose name or tags contain the query and that have all the given tags. */
+export const getMotifs = (query?: string, tags?: string[]) => invoke<Motif[]>("get_motifs", { query, tags });
+export const getMotifTags = () => invoke<string[]>("get_motif_tags");
+
+/** Saves the region of the pattern to the motif library. The motif with the same name is replaced. */
+export const saveMotif = (patternKey: PatternKey, region: Region, name: string, tags: string[]) => {
+  return invoke<Motif>("save_motif", { patternKey, region, name, tags });
+};
+export const removeMotif = (name: string) => invoke<void>("remove_motif", { name });
+
+/** Inserts the motif at the given cell. The motif colours missing in the pattern palette are appended to it. */
+export const insertMotif = (patternKey: PatternKey, name: string, x: number, y: number) => {
+  return invoke<void>("insert_motif", { patternKey, name, x, y });
+};
@@ -1,5 +1,12 @@
 import { invoke } from "@tauri-apps/api/core";
-import { PatternProject, PaletteItem, type PatternKey } from "#/schemas/pattern";
+import {
+  PatternProject,
+  PaletteItem,
+  type Fabric,
+  type PatternInfo,
+  type PatternKey,
+  type PatternProperties,
+} from "#/schemas/pattern";
 
 export const loadPattern = async (filePath: string) => {
   const bytes = await invoke<number[]>("load_pattern", { filePath });
@@ -19,9 +26,97 @@ export const closePattern = (patternKey: PatternKey) => invoke<void>("close_patt
 
 export const getPatternFilePath = (patternKey: PatternKey) => invoke<string>("get_pattern_file_path", { patternKey });
 
+export const updatePatternInfo = (patternKey: PatternKey, info: PatternInfo) => {
+  return invoke<void>("update_pattern_info", { patternKey, info });
+};
+/** Resizes the pattern. The stitches that don't fit the new size are removed. */
+export const updatePatternProperties = (patternKey: PatternKey, properties: PatternProperties) => {
+  return invoke<void>("update_pattern_properties", { patternKey, properties });
+};
+export const updateFabric = (patternKey: PatternKey, fabric: Fabric) => {
+  return invoke<void>("update_fabric", { patternKey, fabric });
+};
+
 export const addPaletteItem = (patternKey: PatternKey, paletteItem: PaletteItem) => {
   return invoke<void>("add_palette_item", { patternKey, paletteItem });
 };
 export const removePaletteItem = (patternKey: PatternKey, paletteItem: PaletteItem) => {
   return invoke<void>("remove_palette_item", { patternKey, paletteItem });
 };
+/** Replaces the palette item at the given index keeping its stitches. */
+export const updatePaletteItem = (patternKey: PatternKey, palindex: number, paletteItem: PaletteItem) => {
+  return invoke<void>("update_palette_item", { patternKey, palindex, paletteItem });
+};
+/** Moves the palette item to the new position. */
+export const movePaletteItem = (patternKey: PatternKey, from: number, to: number) => {
+  return invoke<void>("move_palette_item", { patternKey, from, to });
+};
+/** Assigns the new distinct symbols to all the palette items. */
+export const reassignSymbols = (patternKey: PatternKey) => invoke<void>("reassign_symbols", { patternKey });
+
+/** A rectangular part of the pattern in cells. */
+export interface Region {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+/** Renders the pattern to a PNG image using its current display settings. */
+export const renderPattern = async (patternKey: PatternKey, zoom?: number, region?: Region) => {
+  const bytes = await invoke<ArrayBuffer>("render_pattern", { patternKey, zoom, region });
+  return new Blob([bytes], { type: "image/png" });
+};
+
+/** Renders the realistic preview of the stitched pattern to a PNG image. */
+export const renderPatternPreview = async (patternKey: PatternKey, zoom?: number, region?: Region) => {
+  const bytes = await invoke<ArrayBuffer>("render_pattern_preview", { patternKey, zoom, region });
+  return new Blob([bytes], { type: "image/png" });
+};
+
+/** Exports the pattern to an SVG file using its current display settings. */
+export const exportPatternSvg = (patternKey: PatternKey, filePath: string, region?: Region) => {
+  return invoke<void>("export_pattern_svg", { patternKey, filePath, region });
+};
+
+export type StitchKind =
+  | "full"
+  | "petite"
+  | "half"
+  | "quarter"
+  | "back"
+  | "straight"
+  | "french_knot"
+  | "bead"
+  | "special";
+
+/** The stitches affected by the palette index replacement. */
+export type ReplacementScope =
+  | { scope: "all" }
+  | { scope: "stitch_kind"; value: StitchKind }
+  | { scope: "region"; value: Region };
+
+/**
+ * Makes the stitches of the `from` palette item use the `to` one, e.g. to merge similar colours.
+ * If `removeUnused` is set and the `from` palette item is no longer used, it is removed from the palette.
+ */
+export const replacePaletteIndex = (
+  patternKey: PatternKey,
+  from: number,
+  to: number,
+  scope: ReplacementScope = { scope: "all" },
+  removeUnused = false,
+) => invoke<void>("replace_palette_index", { patternKey, from, to, scope, removeUnused });
+
+export interface PaletteAnalysis {
+  /** The indices of the palette items that no stitch uses. */
+  unused: number[];
+  /** The groups of indices of the palette items with the same brand and number. The first one is kept when merging. */
+  duplicates: number[][];
+}
+
+export const analyzePalette = (patternKey: PatternKey) => invoke<PaletteAnalysis>("analyze_palette", { patternKey });
+/** Merges the duplicate palette items and removes the unused ones. */
+export const cleanupPalette = (patternKey: PatternKey, removeUnused: boolean, mergeDuplicates: boolean) => {
+  return invoke<void>("cleanup_palette", { patternKey, removeUnused, mergeDuplicates });
+};
@@ -0,0 +1,76 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { PatternKey, SpecialStitch, Stitch } from "#/schemas/pattern";
+import type { Region } from "#/api/pattern";
+
+/** The tracked stitch. The progress is tracked per stitch, so a stitch recolored by hand is not completed anymore. */
+export type ProgressStitch = Stitch | { special: SpecialStitch };
+
+export interface ProgressEntry {
+  stitch: ProgressStitch;
+  /** The time the stitch was completed at, in seconds since the Unix epoch, or `null` if it is not completed. */
+  completedAt: number | null;
+}
+
+/** The stitches to mark. A single cell is the region of one cell. */
+export type ProgressTarget =
+  | { target: "stitches"; stitches: ProgressStitch[] }
+  | { target: "region"; region: Region }
+  | { target: "color"; palindex: number };
+
+export interface Completion {
+  total: number;
+  completed: number;
+}
+
+export interface ProgressStats extends Completion {
+  colors: (Completion & { palindex: number })[];
+  pages: (Completion & { region: Region })[];
+}
+
+/** Returns the completed stitches of all the layers. */
+export const getProgress = (patternKey: PatternKey) => invoke<ProgressEntry[]>("get_progress", { patternKey });
+
+/** Marks the stitches as completed now or as not completed. */
+export const markProgress = (patternKey: PatternKey, target: ProgressTarget, completed: boolean) => {
+  return invoke<void>("mark_progress", { patternKey, target, completed });
+};
+
+/** Returns the completion of the whole pattern, each palette item and each page of the given size in cells. */
+export const getProgressStats = (patternKey: PatternKey, pageWidth: number, pageHeight: number) => {
+  return invoke<ProgressStats>("get_progress_stats", { patternKey, pageWidth, pageHeight });
+};
+
+/** A stitching session. The times are in seconds since the Unix epoch. */
+export interface StitchingSession {
+  startedAt: number;
+  /** The time the session was stopped at or `null` if it is still running. */
+  endedAt: number | null;
+}
+
+export interface SessionSummary extends StitchingSession {
+  /** The duration of the session in seconds. */
+  duration: number;
+  stitches: number;
+  stitchesPerHour: number;
+}
+
+export interface SessionStats {
+  sessions: SessionSummary[];
+  /** The total duration of all the sessions in seconds. */
+  duration: number;
+  stitches: number;
+  stitchesPerHour: number;
+  remaining: number;
+  /** The projected completion time in seconds since the Unix epoch or `null` if there is not enough data yet. */
+  projectedCompletion: number | null;
+}
+
+export const startStitchingSession = (patternKey: PatternKey) => {
+  return invoke<StitchingSession>("start_stitching_session", { patternKey });
+};
+export const stopStitchingSession = (patternKey: PatternKey) => {
+  return invoke<StitchingSession>("stop_stitching_session", { patternKey });
+};
+
+/** Returns the statistics of the stitching sessions and the projected completion date of the pattern. */
+export const getSessionStats = (patternKey: PatternKey) => invoke<SessionStats>("get_session_stats", { patternKey });
@@ -0,0 +1,13 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { PatternKey } from "#/schemas/pattern";
+
+/** A change of a single section of the settings, e.g. `{ section: "show_grid", value: false }`. */
+export interface SettingsUpdate {
+  section: string;
+  value: unknown;
+}
+
+export const updateDisplaySettings = (patternKey: PatternKey, update: SettingsUpdate) =>
+  invoke<void>("update_display_settings", { patternKey, update });
+export const updatePrintSettings = (patternKey: PatternKey, update: SettingsUpdate) =>
+  invoke<void>("update_print_settings", { patternKey, update });
@@ -1,6 +1,85 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { PatternKey, Stitch } from "#/schemas/pattern";
+import type { PartStitchDirection, PatternKey, Stitch } from "#/schemas/pattern";
+import type { Region } from "#/api/pattern";
 
-export const addStitch = (patternKey: PatternKey, stitch: Stitch) => invoke<void>("add_stitch", { patternKey, stitch });
-export const removeStitch = (patternKey: PatternKey, stitch: Stitch) =>
-  invoke<void>("remove_stitch", { patternKey, stitch });
+/**
+ * The symmetry mode. The stitches are mirrored or rotated around the center of the pattern.
+ * Only the radial orders 2, 4 and 8 fit the grid, the order 8 also mirrors the stitches across the diagonals.
+ */
+export type Symmetry =
+  | { mode: "vertical" }
+  | { mode: "horizontal" }
+  | { mode: "both" }
+  | { mode: "radial"; order: number };
+
+/** Adds the stitch and, if the symmetry is set, its symmetric copies in one undoable step. */
+export const addStitch = (patternKey: PatternKey, stitch: Stitch, symmetry?: Symmetry) =>
+  invoke<void>("add_stitch", { patternKey, stitch, symmetry });
+/** Removes the stitch and, if the symmetry is set, its symmetric copies in one undoable step. */
+export const removeStitch = (patternKey: PatternKey, stitch: Stitch, symmetry?: Symmetry) =>
+  invoke<void>("remove_stitch", { patternKey, stitch, symmetry });
+
+/** The kind of stitches used to fill the cells. The petite fill puts four petite stitches into each cell. */
+export type FillStitchKind = { kind: "full" } | { kind: "petite" } | { kind: "half"; direction: PartStitchDirection };
+
+/** Fills the area connected to the cell with the stitches. */
+export const fillStitches = (
+  patternKey: PatternKey,
+  x: number,
+  y: number,
+  palindex: number,
+  kind: FillStitchKind,
+  respectLines = true,
+) => invoke<void>("fill_stitches", { patternKey, x, y, palindex, kind, respectLines });
+
+/** A shape to draw. The cells may lie outside the pattern, the shapes are clipped. */
+export type Shape =
+  | { shape: "line"; start: [number, number]; end: [number, number] }
+  | { shape: "rectangle"; x: number; y: number; width: number; height: number; filled: boolean }
+  | { shape: "ellipse"; x: number; y: number; width: number; height: number; filled: boolean }
+  | { shape: "polyline"; points: [number, number][]; closed: boolean };
+
+/** Draws the shape with the stitches. The polylines are always drawn with the backstitches. */
+export const drawShape = (patternKey: PatternKey, shape: Shape, palindex: number, kind: FillStitchKind) =>
+  invoke<void>("draw_shape", { patternKey, shape, palindex, kind });
+
+/** The corners of the border. The motif is designed for the top-left corner and turned clockwise for the others. */
+export type BorderCorners = { kind: "empty" } | { kind: "motif"; region: Region };
+
+/** How the motif is repeated. The border motif is turned so its top faces the edge. */
+export type RepeatLayout =
+  | { layout: "tile"; spacing_x?: number; spacing_y?: number; offset?: "none" | "brick" | "half_drop" }
+  | { layout: "border"; spacing?: number; margin?: number; corners?: BorderCorners };
+
+/** Repeats the stitches of the motif across the pattern or along its edges. */
+export const repeatMotif = (patternKey: PatternKey, motif: Region, layout: RepeatLayout) =>
+  invoke<void>("repeat_motif", { patternKey, motif, layout });
+
+/** The source of the glyphs: the bundled 5x7 alphabet or a font rasterized at the given height in cells. */
+export type TextFont = { source: "alphabet" } | { source: "font"; name: string; height: number };
+
+export interface TextOptions {
+  text: string;
+  x: number;
+  y: number;
+  font: TextFont;
+  /** The filled text uses the stitches of the given kind, the outlined and backstitched ones use the backstitches. */
+  style?: "filled" | "outline" | "backstitch";
+  letter_spacing?: number;
+  line_spacing?: number;
+  align?: "left" | "center" | "right";
+}
+
+/** Renders the text with the stitches. */
+export const addText = (patternKey: PatternKey, options: TextOptions, palindex: number, kind: FillStitchKind) =>
+  invoke<void>("add_text", { patternKey, options, palindex, kind });
+
+/** Returns the stitches of the visible layers to draw in the viewport, including the ones crossing its border. */
+export const getStitchesInViewport = (patternKey: PatternKey, viewport: Region) =>
+  invoke<Stitch[]>("get_stitches_in_viewport", { patternKey, viewport });
+/** Returns the stitches of the active layer lying inside the region. */
+export const selectStitches = (patternKey: PatternKey, region: Region) =>
+  invoke<Stitch[]>("select_stitches", { patternKey, region });
+/** Returns the stitches of the active layer under the point in cells, from the topmost one. The tolerance is at most 2 cells. */
+export const hitTestStitches = (patternKey: PatternKey, x: number, y: number, tolerance?: number) =>
+  invoke<Stitch[]>("hit_test_stitches", { patternKey, x, y, tolerance });
@@ -366,9 +366,20 @@ export class CanvasService extends EventTarget {
     graphics.angle = rotation;
     if (flip[0]) graphics.scale.x = -1;
     if (flip[1]) graphics.scale.y = -1;
+    graphics.label = this.#specialStitchKey(specialStitch);
     this.#stages.specialstitches.addChild(graphics);
   }
 
+  removeSpecialStitch(specialStitch: SpecialStitch) {
+    const key = this.#specialStitchKey(specialStitch);
+    const graphics = this.#stages.specialstitches.getChildByName(key);
+    if (graphics) this.#stages.specialstitches.removeChild(graphics);
+  }
+
+  #specialStitchKey({ x, y, modindex }: SpecialStitch) {
+    return [x, y, modindex].toString();
+  }
+
   #clearHint() {
     const hint = this.#stages.hint.clear().restore();
     hint.angle = 0;
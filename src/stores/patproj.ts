@@ -3,6 +3,7 @@ import { defineStore } from "pinia";
 import { useConfirm } from "primevue";
 import { useAppStateStore } from "./state";
 import { PatternApi } from "#/api";
+import { isCommandError } from "#/api/error";
 import type { PatternKey, PatternProject, PaletteItem } from "#/schemas/pattern";
 
 export const usePatternProjectStore = defineStore("pattern-project", () => {
@@ -25,7 +26,7 @@ export const usePatternProjectStore = defineStore("pattern-project", () => {
     } catch (err) {
       confirm.require({
         header: "Error",
-        message: err as string,
+        message: isCommandError(err) ? err.message : String(err),
         icon: "pi pi-info-circle",
         acceptLabel: "OK",
         acceptProps: { outlined: true },
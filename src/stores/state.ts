@@ -1,6 +1,7 @@
 import { reactive } from "vue";
 import { defineStore } from "pinia";
 import { FullStitchKind, type StitchKind, type PatternKey } from "#/schemas/pattern";
+import type { Symmetry } from "#/api/stitches";
 
 interface OpenedPattern {
   title: string;
@@ -9,6 +10,8 @@ interface OpenedPattern {
 
 export interface AppState {
   selectedStitchTool: StitchKind;
+  /** The symmetry mode used when adding and removing the stitches. */
+  symmetry?: Symmetry;
   selectedPaletteItemIndex?: number;
   openedPatterns?: OpenedPattern[];
   currentPattern?: OpenedPattern;